@@ -8,6 +8,8 @@ use std::os::unix::io::RawFd;
 use crate::sys::stat::Mode;
 use crate::{NixPath, Result};
 
+#[cfg(target_os = "linux")]
+use std::mem; // For openat2 and dedupe_file_range
 #[cfg(any(target_os = "android", target_os = "linux"))]
 use std::ptr; // For splice and copy_file_range
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -186,6 +188,79 @@ pub fn openat<P: ?Sized + NixPath>(
     Errno::result(fd)
 }
 
+#[cfg(target_os = "linux")]
+bitflags::bitflags! {
+    /// Restrictions on path resolution for [`openat2`](fn.openat2.html).
+    // From <linux/openat2.h>; not yet in libc
+    pub struct ResolveFlag: u64 {
+        /// Do not cross mount points, including bind mounts.
+        const RESOLVE_NO_XDEV = 0x01;
+        /// Do not follow "magic links" such as `/proc/self/fd/*`.
+        const RESOLVE_NO_MAGICLINKS = 0x02;
+        /// Do not follow any symbolic links, magic links included.
+        const RESOLVE_NO_SYMLINKS = 0x04;
+        /// Fail with `EXDEV` if resolution would leave the directory `dirfd`,
+        /// whether through `..`, an absolute path or a symbolic link.
+        const RESOLVE_BENEATH = 0x08;
+        /// Resolve as if `dirfd` were the root directory, so that `..`,
+        /// absolute paths and symbolic links cannot escape it.
+        const RESOLVE_IN_ROOT = 0x10;
+    }
+}
+
+/// How [`openat2`](fn.openat2.html) should open a file (`struct open_how`).
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OpenHow {
+    flags: u64,
+    mode: u64,
+    resolve: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl OpenHow {
+    /// Open with the given flags and, when creating a file, permissions.
+    pub fn new(oflag: OFlag, mode: Mode) -> OpenHow {
+        OpenHow {
+            flags: oflag.bits() as u64,
+            mode: u64::from(mode.bits()),
+            resolve: 0,
+        }
+    }
+
+    /// Restrict how the path is resolved.
+    pub fn resolve(mut self, resolve: ResolveFlag) -> OpenHow {
+        self.resolve = resolve.bits();
+        self
+    }
+}
+
+/// Open a file relative to the directory `dirfd`, like
+/// [`openat`](fn.openat.html), with additional control over how `path` is
+/// resolved.
+///
+/// Unlike `openat`, unknown flags and a mode without `O_CREAT` or
+/// `O_TMPFILE` are rejected with `EINVAL`.  Requires Linux 5.6 or later;
+/// older kernels fail with `ENOSYS`.
+///
+/// # References
+///
+/// [openat2(2)](http://man7.org/linux/man-pages/man2/openat2.2.html)
+#[cfg(target_os = "linux")]
+pub fn openat2<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, how: OpenHow) -> Result<RawFd> {
+    let fd = path.with_nix_path(|cstr| unsafe {
+        libc::syscall(
+            libc::SYS_openat2,
+            dirfd,
+            cstr.as_ptr(),
+            &how as *const OpenHow,
+            mem::size_of::<OpenHow>(),
+        )
+    })?;
+    Errno::result(fd).map(|fd| fd as RawFd)
+}
+
 #[cfg(not(target_os = "redox"))]
 pub fn renameat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(
     old_dirfd: Option<RawFd>,
@@ -206,6 +281,52 @@ pub fn renameat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(
     Errno::result(res).map(drop)
 }
 
+#[cfg(target_os = "linux")]
+libc_bitflags! {
+    /// Flags for [`renameat2`](fn.renameat2.html).
+    pub struct RenameFlags: c_uint {
+        /// Atomically exchange the two paths, which must both exist.
+        RENAME_EXCHANGE;
+        /// Fail with `EEXIST` instead of replacing an existing `new_path`.
+        RENAME_NOREPLACE;
+        /// Leave a whiteout object at `old_path`, for overlay filesystems.
+        /// Requires `CAP_MKNOD`.
+        RENAME_WHITEOUT;
+    }
+}
+
+/// Rename a file like [`renameat`](fn.renameat.html), with additional
+/// `flags`.
+///
+/// Not all filesystems support every flag; those that don't fail with
+/// `EINVAL`.  Kernels older than Linux 3.15 fail with `ENOSYS`.
+///
+/// # References
+///
+/// [rename(2)](http://man7.org/linux/man-pages/man2/rename.2.html)
+#[cfg(target_os = "linux")]
+pub fn renameat2<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(
+    old_dirfd: Option<RawFd>,
+    old_path: &P1,
+    new_dirfd: Option<RawFd>,
+    new_path: &P2,
+    flags: RenameFlags,
+) -> Result<()> {
+    let res = old_path.with_nix_path(|old_cstr| {
+        new_path.with_nix_path(|new_cstr| unsafe {
+            libc::syscall(
+                libc::SYS_renameat2,
+                at_rawfd(old_dirfd),
+                old_cstr.as_ptr(),
+                at_rawfd(new_dirfd),
+                new_cstr.as_ptr(),
+                flags.bits(),
+            )
+        })
+    })??;
+    Errno::result(res).map(drop)
+}
+
 fn wrap_readlink_result(mut v: Vec<u8>, len: ssize_t) -> Result<OsString> {
     unsafe { v.set_len(len as usize) }
     v.shrink_to_fit();
@@ -401,16 +522,33 @@ pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
     Errno::result(res)
 }
 
+/// The operation performed by [`flock`](fn.flock.html).
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum FlockArg {
+    /// Take a shared lock, waiting for any exclusive lock to be released.
     LockShared,
+    /// Take an exclusive lock, waiting for any other lock to be released.
     LockExclusive,
+    /// Release the lock held on the file.
     Unlock,
+    /// Take a shared lock, failing with `EWOULDBLOCK` instead of waiting.
     LockSharedNonblock,
+    /// Take an exclusive lock, failing with `EWOULDBLOCK` instead of waiting.
     LockExclusiveNonblock,
+    /// Release the lock held on the file.
     UnlockNonblock,
 }
 
+/// Apply or remove an advisory lock on the whole of the open file `fd`.
+///
+/// Unlike the record locks taken with `fcntl`, these locks belong to the open
+/// file description: they are shared by `dup`ed and inherited descriptors and
+/// only released once all of them are closed, or with `FlockArg::Unlock`.
+/// Opening the same file again yields an independent lock.
+///
+/// # References
+///
+/// [flock(2)](http://man7.org/linux/man-pages/man2/flock.2.html)
 #[cfg(not(target_os = "redox"))]
 pub fn flock(fd: RawFd, arg: FlockArg) -> Result<()> {
     use self::FlockArg::*;
@@ -466,6 +604,13 @@ libc_bitflags! {
 ///
 /// On successful completion the number of bytes actually copied will be
 /// returned.
+///
+/// The system call's `flags` argument is not exposed, because no flags are
+/// defined yet and the kernel requires it to be `0`.
+///
+/// # References
+///
+/// [copy_file_range(2)](http://man7.org/linux/man-pages/man2/copy_file_range.2.html)
 #[cfg(any(target_os = "android", target_os = "linux"))]
 pub fn copy_file_range(
     fd_in: RawFd,
@@ -495,6 +640,174 @@ pub fn copy_file_range(
     Errno::result(ret).map(|r| r as usize)
 }
 
+#[cfg(target_os = "linux")]
+mod ffi {
+    use crate::{ioctl_readwrite, ioctl_write_int_bad, ioctl_write_ptr, request_code_write};
+
+    // From <linux/fs.h>; not yet in libc
+    #[repr(C)]
+    pub struct FileCloneRange {
+        pub src_fd: i64,
+        pub src_offset: u64,
+        pub src_length: u64,
+        pub dest_offset: u64,
+    }
+
+    #[repr(C)]
+    pub struct FileDedupeRange {
+        pub src_offset: u64,
+        pub src_length: u64,
+        pub dest_count: u16,
+        pub reserved1: u16,
+        pub reserved2: u32,
+        // Followed by dest_count FileDedupeRangeInfo
+    }
+
+    #[repr(C)]
+    pub struct FileDedupeRangeInfo {
+        pub dest_fd: i64,
+        pub dest_offset: u64,
+        pub bytes_deduped: u64,
+        pub status: i32,
+        pub reserved: u32,
+    }
+
+    pub const FILE_DEDUPE_RANGE_SAME: i32 = 0;
+    pub const FILE_DEDUPE_RANGE_DIFFERS: i32 = 1;
+
+    // The argument is the source file descriptor itself, not a pointer to it
+    ioctl_write_int_bad!(ficlone, request_code_write!(0x94, 9, ::std::mem::size_of::<libc::c_int>()));
+    ioctl_write_ptr!(ficlonerange, 0x94, 13, FileCloneRange);
+    ioctl_readwrite!(fideduperange, 0x94, 54, FileDedupeRange);
+}
+
+/// Make `dest_fd` a copy of `src_fd` that shares its storage (a "reflink"),
+/// using the `FICLONE` ioctl.
+///
+/// The copy is made instantly and takes no space until either file is
+/// modified.  Both files must be on the same filesystem, and the filesystem,
+/// such as Btrfs or XFS, must support sharing extents; otherwise this fails
+/// with `EXDEV` or `EOPNOTSUPP`.
+///
+/// # References
+///
+/// [ioctl_ficlone(2)](http://man7.org/linux/man-pages/man2/ioctl_ficlone.2.html)
+#[cfg(target_os = "linux")]
+pub fn clone_file(src_fd: RawFd, dest_fd: RawFd) -> Result<()> {
+    unsafe { ffi::ficlone(dest_fd, src_fd) }.map(drop)
+}
+
+/// Share `len` bytes of `src_fd` starting at `src_offset` with `dest_fd` at
+/// `dest_offset`, using the `FICLONERANGE` ioctl.
+///
+/// Like [`clone_file`](fn.clone_file.html), but for part of a file.  A `len`
+/// of `0` means up to the end of `src_fd`.  Offsets and lengths usually need
+/// to be multiples of the filesystem block size.
+///
+/// # References
+///
+/// [ioctl_ficlonerange(2)](http://man7.org/linux/man-pages/man2/ioctl_ficlonerange.2.html)
+#[cfg(target_os = "linux")]
+pub fn clone_file_range(
+    src_fd: RawFd,
+    src_offset: u64,
+    len: u64,
+    dest_fd: RawFd,
+    dest_offset: u64,
+) -> Result<()> {
+    let range = ffi::FileCloneRange {
+        src_fd: i64::from(src_fd),
+        src_offset,
+        src_length: len,
+        dest_offset,
+    };
+    unsafe { ffi::ficlonerange(dest_fd, &range) }.map(drop)
+}
+
+/// The outcome of [`dedupe_file_range`](fn.dedupe_file_range.html) for one
+/// destination.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DedupeResult {
+    /// The data was identical, and this many bytes now share storage.
+    Same(u64),
+    /// The data differed, so nothing was changed.
+    Differs,
+    /// Deduplicating this destination failed.
+    Error(Errno),
+}
+
+/// Make ranges of other files share the storage of `len` bytes of `src_fd`
+/// starting at `src_offset`, where their contents are identical, using the
+/// `FIDEDUPERANGE` ioctl.
+///
+/// Each destination is given as a file descriptor and an offset, and gets
+/// its own [`DedupeResult`](enum.DedupeResult.html), in the same order.  The
+/// call as a whole only fails for problems with the source or the
+/// arguments.  The kernel compares and shares the data atomically, so
+/// concurrent writers cannot cause a destination to lose data.
+///
+/// # References
+///
+/// [ioctl_fideduperange(2)](http://man7.org/linux/man-pages/man2/ioctl_fideduperange.2.html)
+#[cfg(target_os = "linux")]
+pub fn dedupe_file_range(
+    src_fd: RawFd,
+    src_offset: u64,
+    len: u64,
+    dests: &[(RawFd, u64)],
+) -> Result<Vec<DedupeResult>> {
+    use self::ffi::{FileDedupeRange, FileDedupeRangeInfo};
+    use std::convert::TryFrom;
+
+    let dest_count = u16::try_from(dests.len())
+        .map_err(|_| crate::Error::Sys(Errno::EINVAL))?;
+    let header_size = mem::size_of::<FileDedupeRange>();
+    let info_size = mem::size_of::<FileDedupeRangeInfo>();
+
+    // Backed by u64s to get the alignment of the structures
+    let mut buf = vec![0u64; (header_size + info_size * dests.len()) / 8];
+    let header = buf.as_mut_ptr() as *mut FileDedupeRange;
+    let infos = unsafe { (header as *mut u8).add(header_size) as *mut FileDedupeRangeInfo };
+
+    unsafe {
+        (*header).src_offset = src_offset;
+        (*header).src_length = len;
+        (*header).dest_count = dest_count;
+        for (i, &(fd, offset)) in dests.iter().enumerate() {
+            let info = &mut *infos.add(i);
+            info.dest_fd = i64::from(fd);
+            info.dest_offset = offset;
+        }
+
+        ffi::fideduperange(src_fd, header)?;
+    }
+
+    let results = (0..dests.len()).map(|i| {
+        let info = unsafe { &*infos.add(i) };
+        match info.status {
+            ffi::FILE_DEDUPE_RANGE_SAME => DedupeResult::Same(info.bytes_deduped),
+            ffi::FILE_DEDUPE_RANGE_DIFFERS => DedupeResult::Differs,
+            e => DedupeResult::Error(Errno::from_i32(-e)),
+        }
+    }).collect();
+    Ok(results)
+}
+
+/// Move up to `len` bytes from `fd_in` to `fd_out` without copying them
+/// through userspace, returning the number of bytes moved.
+///
+/// At least one of the two descriptors must refer to a pipe.  For the other
+/// one, `off_in` or `off_out` selects where to read or write: with `None` the
+/// file offset is used and updated, as with `read` and `write`; with `Some`
+/// the given offset is used and advanced instead, leaving the file offset
+/// alone.  Offsets must be `None` for pipes and sockets.
+///
+/// A return value of `0` means there was nothing left to read from `fd_in`.
+///
+/// # References
+///
+/// [splice(2)](http://man7.org/linux/man-pages/man2/splice.2.html)
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn splice(
     fd_in: RawFd,
@@ -515,12 +828,32 @@ pub fn splice(
     Errno::result(ret).map(|r| r as usize)
 }
 
+/// Duplicate up to `len` bytes from the pipe `fd_in` into the pipe `fd_out`,
+/// returning the number of bytes duplicated.
+///
+/// The data is not consumed from `fd_in`, so it can still be read or
+/// `splice`d elsewhere afterwards.
+///
+/// # References
+///
+/// [tee(2)](http://man7.org/linux/man-pages/man2/tee.2.html)
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn tee(fd_in: RawFd, fd_out: RawFd, len: usize, flags: SpliceFFlags) -> Result<usize> {
     let ret = unsafe { libc::tee(fd_in, fd_out, len, flags.bits()) };
     Errno::result(ret).map(|r| r as usize)
 }
 
+/// Splice user memory into the pipe `fd`, returning the number of bytes
+/// transferred.
+///
+/// Without `SPLICE_F_GIFT` the data is copied, so the buffers may be reused
+/// as soon as the call returns.  With it, the pages themselves may be handed
+/// to the pipe: the buffers must then be page-aligned and must not be
+/// modified or freed afterwards, as the reader may still see the changes.
+///
+/// # References
+///
+/// [vmsplice(2)](http://man7.org/linux/man-pages/man2/vmsplice.2.html)
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn vmsplice(fd: RawFd, iov: &[IoVec<&[u8]>], flags: SpliceFFlags) -> Result<usize> {
     let ret = unsafe {
@@ -558,9 +891,9 @@ libc_bitflags!(
         ///
         /// Does not overwrite existing data. Hole starts at offset and continues for len bytes.
         FALLOC_FL_INSERT_RANGE;
-        /// Shared file data extants are made private to the file.
+        /// Shared file data extents are made private to the file.
         ///
-        /// Gaurantees that a subsequent write will not fail due to lack of space.
+        /// Guarantees that a subsequent write will not fail due to lack of space.
         FALLOC_FL_UNSHARE_RANGE;
     }
 );
@@ -568,7 +901,12 @@ libc_bitflags!(
 /// Manipulates file space.
 ///
 /// Allows the caller to directly manipulate the allocated disk space for the
-/// file referred to by fd.
+/// file referred to by fd.  Not every filesystem supports every mode; those
+/// that don't fail with `EOPNOTSUPP`.
+///
+/// # References
+///
+/// [fallocate(2)](http://man7.org/linux/man-pages/man2/fallocate.2.html)
 #[cfg(any(target_os = "linux"))]
 pub fn fallocate(
     fd: RawFd,
@@ -144,4 +144,21 @@ mod tests {
     fn test_getifaddrs() {
         let _ = getifaddrs();
     }
+
+    // The loopback interface should always be listed, with its IPv4 address
+    #[test]
+    fn test_getifaddrs_loopback() {
+        use crate::sys::socket::{InetAddr, IpAddr};
+
+        let (lo, inet) = getifaddrs().unwrap()
+            .filter(|ifaddr| ifaddr.flags.contains(InterfaceFlags::IFF_LOOPBACK))
+            .find_map(|ifaddr| match ifaddr.address {
+                Some(SockAddr::Inet(inet @ InetAddr::V4(_))) => Some((ifaddr, inet)),
+                _ => None,
+            })
+            .expect("no IPv4 loopback address");
+        assert_eq!(inet.ip(), IpAddr::new_v4(127, 0, 0, 1));
+        assert!(lo.netmask.is_some());
+        assert!(lo.destination.is_none());
+    }
 }
@@ -209,10 +209,11 @@ macro_rules! libc_enum {
 #[cfg(not(target_os = "redox"))]
 macro_rules! offset_of {
     ($ty:ty, $field:ident) => {{
-        // Safe because we don't actually read from the dereferenced pointer
+        // Measure on a real value; dereferencing a null pointer, even without
+        // reading from it, is undefined behavior.  Only used with libc
+        // structures, for which all zeroes is valid.
         #[allow(unused_unsafe)] // for when the macro is used in an unsafe block
-        unsafe {
-            &(*(ptr::null() as *const $ty)).$field as *const _ as usize
-        }
+        let base: $ty = unsafe { ::std::mem::zeroed() };
+        &base.$field as *const _ as usize - &base as *const $ty as usize
     }}
 }
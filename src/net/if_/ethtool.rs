@@ -0,0 +1,336 @@
+//! Network device driver and hardware queries through the `SIOCETHTOOL`
+//! `ioctl`.
+//!
+//! These are the requests behind the `ethtool` utility.  As with
+//! [`ioctl`](../ioctl/index.html), any socket may be used as `fd`.  Drivers
+//! only implement some of the requests, and fail the others with
+//! `EOPNOTSUPP`.
+//!
+//! # Examples
+//!
+//! ```
+//! use nix::net::if_::ethtool::get_link;
+//! use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+//!
+//! let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+//!                 None).unwrap();
+//! assert!(get_link(fd, "lo").unwrap());
+//! ```
+
+use libc::{self, c_char, c_void};
+use std::ffi::CStr;
+use std::mem;
+use std::os::unix::io::RawFd;
+use crate::{NixPath, Result};
+use super::ioctl::IfReq;
+
+// From <linux/ethtool.h>; not yet in libc
+const ETHTOOL_GSET: u32 = 0x0000_0001;
+const ETHTOOL_GDRVINFO: u32 = 0x0000_0003;
+const ETHTOOL_GLINK: u32 = 0x0000_000a;
+const ETHTOOL_GRINGPARAM: u32 = 0x0000_0010;
+const ETHTOOL_GRXCSUM: u32 = 0x0000_0014;
+const ETHTOOL_GTXCSUM: u32 = 0x0000_0016;
+const ETHTOOL_GSG: u32 = 0x0000_0018;
+const ETHTOOL_GTSO: u32 = 0x0000_001e;
+const ETHTOOL_GGSO: u32 = 0x0000_0023;
+const ETHTOOL_GGRO: u32 = 0x0000_002b;
+const ETHTOOL_GCHANNELS: u32 = 0x0000_003c;
+const SPEED_UNKNOWN: u32 = 0xffff_ffff;
+const DUPLEX_HALF: u8 = 0x00;
+const DUPLEX_FULL: u8 = 0x01;
+
+mod ffi {
+    use crate::ioctl_readwrite_bad;
+
+    ioctl_readwrite_bad!(siocethtool, libc::SIOCETHTOOL, libc::ifreq);
+}
+
+/// Issue an ethtool command.  `data` is both the request and the response,
+/// and must start with the `u32` command number.
+fn ethtool<P: ?Sized + NixPath, T>(fd: RawFd, name: &P, data: &mut T)
+    -> Result<()>
+{
+    let mut req = IfReq::new(name)?.with_data(data as *mut T as *mut c_void);
+    unsafe { ffi::siocethtool(fd, req.as_mut()) }.map(drop)
+}
+
+/// `struct ethtool_value`, used by the simple get and set commands.
+#[repr(C)]
+struct EthtoolValue {
+    cmd: u32,
+    data: u32,
+}
+
+fn get_value<P: ?Sized + NixPath>(fd: RawFd, name: &P, cmd: u32) -> Result<u32> {
+    let mut value = EthtoolValue { cmd, data: 0 };
+    ethtool(fd, name, &mut value)?;
+    Ok(value.data)
+}
+
+/// Identification of the driver behind an interface
+/// (`ETHTOOL_GDRVINFO`).  See [`get_driver_info`](fn.get_driver_info.html).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DriverInfo {
+    cmd: u32,
+    driver: [c_char; 32],
+    version: [c_char; 32],
+    fw_version: [c_char; 32],
+    bus_info: [c_char; 32],
+    erom_version: [c_char; 32],
+    reserved2: [c_char; 12],
+    n_priv_flags: u32,
+    n_stats: u32,
+    testinfo_len: u32,
+    eedump_len: u32,
+    regdump_len: u32,
+}
+
+// The strings are always null-terminated by the kernel
+fn cstr(s: &[c_char]) -> &CStr {
+    unsafe { CStr::from_ptr(s.as_ptr()) }
+}
+
+impl DriverInfo {
+    /// Name of the driver, such as `"e1000e"`
+    pub fn driver(&self) -> &CStr {
+        cstr(&self.driver)
+    }
+
+    /// Version of the driver
+    pub fn version(&self) -> &CStr {
+        cstr(&self.version)
+    }
+
+    /// Version of the device firmware, if any
+    pub fn fw_version(&self) -> &CStr {
+        cstr(&self.fw_version)
+    }
+
+    /// Location of the device on its bus, such as a PCI address
+    pub fn bus_info(&self) -> &CStr {
+        cstr(&self.bus_info)
+    }
+
+    /// Version of the expansion ROM, if any
+    pub fn erom_version(&self) -> &CStr {
+        cstr(&self.erom_version)
+    }
+
+    /// Number of driver-specific flags
+    pub fn n_priv_flags(&self) -> u32 {
+        self.n_priv_flags
+    }
+
+    /// Number of device statistics
+    pub fn n_stats(&self) -> u32 {
+        self.n_stats
+    }
+}
+
+/// Duplex mode of a link.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Duplex {
+    /// Half duplex
+    Half,
+    /// Full duplex
+    Full,
+    /// Unknown, such as when the link is down
+    Unknown,
+}
+
+/// Speed, duplex and autonegotiation settings of a link (`ETHTOOL_GSET`).
+/// See [`get_link_settings`](fn.get_link_settings.html).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LinkSettings {
+    cmd: u32,
+    supported: u32,
+    advertising: u32,
+    speed: u16,
+    duplex: u8,
+    port: u8,
+    phy_address: u8,
+    transceiver: u8,
+    autoneg: u8,
+    mdio_support: u8,
+    maxtxpkt: u32,
+    maxrxpkt: u32,
+    speed_hi: u16,
+    eth_tp_mdix: u8,
+    eth_tp_mdix_ctrl: u8,
+    lp_advertising: u32,
+    reserved: [u32; 2],
+}
+
+impl LinkSettings {
+    /// Link speed in Mb/s, or `None` if unknown, such as when the link is
+    /// down
+    pub fn speed(&self) -> Option<u32> {
+        let speed = u32::from(self.speed_hi) << 16 | u32::from(self.speed);
+        if speed == SPEED_UNKNOWN {
+            None
+        } else {
+            Some(speed)
+        }
+    }
+
+    /// Duplex mode
+    pub fn duplex(&self) -> Duplex {
+        match self.duplex {
+            DUPLEX_HALF => Duplex::Half,
+            DUPLEX_FULL => Duplex::Full,
+            _ => Duplex::Unknown,
+        }
+    }
+
+    /// Physical connector type, one of the `PORT_*` values of
+    /// `<linux/ethtool.h>`
+    pub fn port(&self) -> u8 {
+        self.port
+    }
+
+    /// Whether autonegotiation is enabled
+    pub fn autoneg(&self) -> bool {
+        self.autoneg != 0
+    }
+
+    /// Link modes supported by the device, as a mask of the
+    /// `SUPPORTED_*` values of `<linux/ethtool.h>`
+    pub fn supported(&self) -> u32 {
+        self.supported
+    }
+
+    /// Link modes advertised during autonegotiation, as a mask of the
+    /// `ADVERTISED_*` values of `<linux/ethtool.h>`
+    pub fn advertising(&self) -> u32 {
+        self.advertising
+    }
+
+    /// Link modes advertised by the link partner
+    pub fn lp_advertising(&self) -> u32 {
+        self.lp_advertising
+    }
+}
+
+/// Sizes of the device's RX and TX rings (`ETHTOOL_GRINGPARAM`).  See
+/// [`get_ring_params`](fn.get_ring_params.html).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RingParams {
+    cmd: u32,
+    /// Largest supported number of pending frames in the RX ring
+    pub rx_max_pending: u32,
+    /// Largest supported number of pending frames in the RX mini ring
+    pub rx_mini_max_pending: u32,
+    /// Largest supported number of pending frames in the RX jumbo ring
+    pub rx_jumbo_max_pending: u32,
+    /// Largest supported number of pending frames in the TX ring
+    pub tx_max_pending: u32,
+    /// Current number of pending frames in the RX ring
+    pub rx_pending: u32,
+    /// Current number of pending frames in the RX mini ring
+    pub rx_mini_pending: u32,
+    /// Current number of pending frames in the RX jumbo ring
+    pub rx_jumbo_pending: u32,
+    /// Current number of pending frames in the TX ring
+    pub tx_pending: u32,
+}
+
+/// Number of queues, or channels, of the device (`ETHTOOL_GCHANNELS`).  See
+/// [`get_channels`](fn.get_channels.html).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Channels {
+    cmd: u32,
+    /// Largest supported number of RX-only channels
+    pub max_rx: u32,
+    /// Largest supported number of TX-only channels
+    pub max_tx: u32,
+    /// Largest supported number of other channels, such as for link
+    /// interrupts
+    pub max_other: u32,
+    /// Largest supported number of combined RX and TX channels
+    pub max_combined: u32,
+    /// Current number of RX-only channels
+    pub rx_count: u32,
+    /// Current number of TX-only channels
+    pub tx_count: u32,
+    /// Current number of other channels
+    pub other_count: u32,
+    /// Current number of combined channels
+    pub combined_count: u32,
+}
+
+/// Offloads that can be queried with [`get_offload`](fn.get_offload.html).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Offload {
+    /// RX checksumming (`ETHTOOL_GRXCSUM`)
+    RxChecksum,
+    /// TX checksumming (`ETHTOOL_GTXCSUM`)
+    TxChecksum,
+    /// Scatter-gather (`ETHTOOL_GSG`)
+    ScatterGather,
+    /// TCP segmentation offload (`ETHTOOL_GTSO`)
+    TcpSegmentation,
+    /// Generic segmentation offload (`ETHTOOL_GGSO`)
+    GenericSegmentation,
+    /// Generic receive offload (`ETHTOOL_GGRO`)
+    GenericReceive,
+}
+
+impl Offload {
+    fn get_cmd(self) -> u32 {
+        match self {
+            Offload::RxChecksum => ETHTOOL_GRXCSUM,
+            Offload::TxChecksum => ETHTOOL_GTXCSUM,
+            Offload::ScatterGather => ETHTOOL_GSG,
+            Offload::TcpSegmentation => ETHTOOL_GTSO,
+            Offload::GenericSegmentation => ETHTOOL_GGSO,
+            Offload::GenericReceive => ETHTOOL_GGRO,
+        }
+    }
+}
+
+/// Get the driver information of an interface.
+pub fn get_driver_info<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<DriverInfo> {
+    let mut info: DriverInfo = unsafe { mem::zeroed() };
+    info.cmd = ETHTOOL_GDRVINFO;
+    ethtool(fd, name, &mut info)?;
+    Ok(info)
+}
+
+/// Get the speed, duplex and autonegotiation settings of an interface.
+pub fn get_link_settings<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<LinkSettings> {
+    let mut settings: LinkSettings = unsafe { mem::zeroed() };
+    settings.cmd = ETHTOOL_GSET;
+    ethtool(fd, name, &mut settings)?;
+    Ok(settings)
+}
+
+/// Whether the link of an interface is up (`ETHTOOL_GLINK`).
+pub fn get_link<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<bool> {
+    get_value(fd, name, ETHTOOL_GLINK).map(|link| link != 0)
+}
+
+/// Get the RX and TX ring sizes of an interface.
+pub fn get_ring_params<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<RingParams> {
+    let mut params = RingParams { cmd: ETHTOOL_GRINGPARAM, ..Default::default() };
+    ethtool(fd, name, &mut params)?;
+    Ok(params)
+}
+
+/// Get the number of channels of an interface.
+pub fn get_channels<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<Channels> {
+    let mut channels = Channels { cmd: ETHTOOL_GCHANNELS, ..Default::default() };
+    ethtool(fd, name, &mut channels)?;
+    Ok(channels)
+}
+
+/// Whether an offload is enabled on an interface.
+pub fn get_offload<P: ?Sized + NixPath>(fd: RawFd, name: &P, offload: Offload)
+    -> Result<bool>
+{
+    get_value(fd, name, offload.get_cmd()).map(|on| on != 0)
+}
@@ -0,0 +1,290 @@
+//! Interface configuration through `ioctl`s on a socket.
+//!
+//! Each function builds an [`IfReq`](struct.IfReq.html) naming the interface,
+//! and issues one of the `SIOCGIF*` or `SIOCSIF*` requests described in
+//! [`netdevice(7)`](http://man7.org/linux/man-pages/man7/netdevice.7.html).
+//! Any socket may be used as `fd`, typically an `AF_INET` datagram socket.
+//! Changing the configuration requires `CAP_NET_ADMIN`.
+//!
+//! # Examples
+//!
+//! ```
+//! use nix::net::if_::InterfaceFlags;
+//! use nix::net::if_::ioctl::get_flags;
+//! use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+//!
+//! let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+//!                 None).unwrap();
+//! let flags = get_flags(fd, "lo").unwrap();
+//! assert!(flags.contains(InterfaceFlags::IFF_LOOPBACK));
+//! ```
+
+use libc::{self, c_int, c_short};
+use std::ffi::CStr;
+use std::{fmt, mem, ptr};
+use std::os::unix::io::RawFd;
+use crate::{Error, Result, NixPath};
+use crate::errno::Errno;
+use crate::sys::socket::{AddressFamily, Ipv4Addr, MacAddr};
+use super::InterfaceFlags;
+
+mod ffi {
+    use crate::{ioctl_read_bad, ioctl_write_ptr_bad};
+
+    ioctl_read_bad!(siocgifaddr, libc::SIOCGIFADDR, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifaddr, libc::SIOCSIFADDR, libc::ifreq);
+    ioctl_read_bad!(siocgifnetmask, libc::SIOCGIFNETMASK, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifnetmask, libc::SIOCSIFNETMASK, libc::ifreq);
+    ioctl_read_bad!(siocgifbrdaddr, libc::SIOCGIFBRDADDR, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifbrdaddr, libc::SIOCSIFBRDADDR, libc::ifreq);
+    ioctl_read_bad!(siocgifdstaddr, libc::SIOCGIFDSTADDR, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifdstaddr, libc::SIOCSIFDSTADDR, libc::ifreq);
+    ioctl_read_bad!(siocgifflags, libc::SIOCGIFFLAGS, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifflags, libc::SIOCSIFFLAGS, libc::ifreq);
+    ioctl_read_bad!(siocgifmtu, libc::SIOCGIFMTU, libc::ifreq);
+    ioctl_write_ptr_bad!(siocsifmtu, libc::SIOCSIFMTU, libc::ifreq);
+    ioctl_read_bad!(siocgifhwaddr, libc::SIOCGIFHWADDR, libc::ifreq);
+}
+
+/// A typed `struct ifreq`, the argument of interface configuration
+/// `ioctl`s.
+///
+/// The request is created for an interface name, and the value to set, if
+/// any, is filled in with one of the `with_*` methods.  Only one value can be
+/// held at a time, so each `with_*` method replaces the previous one.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct IfReq(libc::ifreq);
+
+impl IfReq {
+    /// Create a request for the named interface.
+    ///
+    /// Fails with `ENAMETOOLONG` if the name doesn't fit in `IFNAMSIZ`
+    /// bytes, including the terminating null byte.
+    pub fn new<P: ?Sized + NixPath>(name: &P) -> Result<IfReq> {
+        name.with_nix_path(|cstr| {
+            let mut req: libc::ifreq = unsafe { mem::zeroed() };
+            let bytes = cstr.to_bytes();
+            if bytes.len() >= req.ifr_name.len() {
+                return Err(Error::Sys(Errno::ENAMETOOLONG));
+            }
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr(),
+                                         req.ifr_name.as_mut_ptr() as *mut u8,
+                                         bytes.len());
+            }
+            Ok(IfReq(req))
+        })?
+    }
+
+    /// Name of the interface this request is for.
+    pub fn name(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.0.ifr_name.as_ptr()) }
+    }
+
+    /// Set the IPv4 address carried by the request.  Used for the address,
+    /// netmask, broadcast and destination requests.
+    pub fn with_addr(mut self, addr: Ipv4Addr) -> IfReq {
+        let sin = libc::sockaddr_in {
+            sin_family: AddressFamily::Inet as libc::sa_family_t,
+            sin_addr: addr.0,
+            .. unsafe { mem::zeroed() }
+        };
+        // sockaddr_in has the same size as the sockaddr in the union
+        unsafe {
+            ptr::write_unaligned(
+                &mut self.0.ifr_ifru.ifru_addr as *mut _ as *mut libc::sockaddr_in,
+                sin);
+        }
+        self
+    }
+
+    /// Set the interface flags carried by the request.
+    pub fn with_flags(mut self, flags: InterfaceFlags) -> IfReq {
+        // The kernel only has room for the low 16 flag bits here
+        self.0.ifr_ifru.ifru_flags = flags.bits() as c_short;
+        self
+    }
+
+    /// Set the MTU carried by the request.
+    pub fn with_mtu(mut self, mtu: c_int) -> IfReq {
+        self.0.ifr_ifru.ifru_mtu = mtu;
+        self
+    }
+
+    /// Point the request at a command-specific buffer, as `SIOCETHTOOL`
+    /// expects.
+    pub(crate) fn with_data(mut self, data: *mut libc::c_void) -> IfReq {
+        self.0.ifr_ifru.ifru_data = data as *mut libc::c_char;
+        self
+    }
+
+    /// The IPv4 address held by the request, if it holds one.
+    pub fn addr(&self) -> Option<Ipv4Addr> {
+        let sin: libc::sockaddr_in = unsafe {
+            ptr::read_unaligned(
+                &self.0.ifr_ifru.ifru_addr as *const _ as *const libc::sockaddr_in)
+        };
+        if c_int::from(sin.sin_family) == libc::AF_INET {
+            Some(Ipv4Addr(sin.sin_addr))
+        } else {
+            None
+        }
+    }
+
+    /// The hardware address held by the request, as returned by
+    /// `SIOCGIFHWADDR`.  Only the first six bytes are used, so this is only
+    /// meaningful for Ethernet-like interfaces.
+    pub fn hwaddr(&self) -> MacAddr {
+        let sa = unsafe { self.0.ifr_ifru.ifru_hwaddr };
+        let mut octets = [0u8; 6];
+        for (o, &b) in octets.iter_mut().zip(sa.sa_data.iter()) {
+            *o = b as u8;
+        }
+        MacAddr::new(octets)
+    }
+
+    /// The interface flags held by the request.
+    pub fn flags(&self) -> InterfaceFlags {
+        let flags = unsafe { self.0.ifr_ifru.ifru_flags };
+        // Don't sign-extend IFF_DYNAMIC
+        InterfaceFlags::from_bits_truncate(c_int::from(flags as u16))
+    }
+
+    /// The MTU held by the request.
+    pub fn mtu(&self) -> c_int {
+        unsafe { self.0.ifr_ifru.ifru_mtu }
+    }
+}
+
+impl AsRef<libc::ifreq> for IfReq {
+    fn as_ref(&self) -> &libc::ifreq {
+        &self.0
+    }
+}
+
+impl AsMut<libc::ifreq> for IfReq {
+    fn as_mut(&mut self) -> &mut libc::ifreq {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for IfReq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IfReq")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// Issue a request that reads an IPv4 address back into `req`.
+fn get_addr_with<P: ?Sized + NixPath>(
+    fd: RawFd,
+    name: &P,
+    ioctl: unsafe fn(c_int, *mut libc::ifreq) -> Result<c_int>
+) -> Result<Ipv4Addr> {
+    let mut req = IfReq::new(name)?;
+    unsafe { ioctl(fd, &mut req.0) }?;
+    req.addr().ok_or(Error::Sys(Errno::EAFNOSUPPORT))
+}
+
+fn set_addr_with<P: ?Sized + NixPath>(
+    fd: RawFd,
+    name: &P,
+    addr: Ipv4Addr,
+    ioctl: unsafe fn(c_int, *const libc::ifreq) -> Result<c_int>
+) -> Result<()> {
+    let req = IfReq::new(name)?.with_addr(addr);
+    unsafe { ioctl(fd, &req.0) }.map(drop)
+}
+
+/// Get the IPv4 address of an interface (`SIOCGIFADDR`).
+pub fn get_addr<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<Ipv4Addr> {
+    get_addr_with(fd, name, ffi::siocgifaddr)
+}
+
+/// Set the IPv4 address of an interface (`SIOCSIFADDR`).
+pub fn set_addr<P: ?Sized + NixPath>(fd: RawFd, name: &P, addr: Ipv4Addr)
+    -> Result<()>
+{
+    set_addr_with(fd, name, addr, ffi::siocsifaddr)
+}
+
+/// Get the IPv4 netmask of an interface (`SIOCGIFNETMASK`).
+pub fn get_netmask<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<Ipv4Addr> {
+    get_addr_with(fd, name, ffi::siocgifnetmask)
+}
+
+/// Set the IPv4 netmask of an interface (`SIOCSIFNETMASK`).
+pub fn set_netmask<P: ?Sized + NixPath>(fd: RawFd, name: &P, mask: Ipv4Addr)
+    -> Result<()>
+{
+    set_addr_with(fd, name, mask, ffi::siocsifnetmask)
+}
+
+/// Get the IPv4 broadcast address of an interface (`SIOCGIFBRDADDR`).
+pub fn get_broadcast<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<Ipv4Addr> {
+    get_addr_with(fd, name, ffi::siocgifbrdaddr)
+}
+
+/// Set the IPv4 broadcast address of an interface (`SIOCSIFBRDADDR`).
+pub fn set_broadcast<P: ?Sized + NixPath>(fd: RawFd, name: &P, addr: Ipv4Addr)
+    -> Result<()>
+{
+    set_addr_with(fd, name, addr, ffi::siocsifbrdaddr)
+}
+
+/// Get the IPv4 address of the peer of a point-to-point interface
+/// (`SIOCGIFDSTADDR`).
+pub fn get_destination<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<Ipv4Addr> {
+    get_addr_with(fd, name, ffi::siocgifdstaddr)
+}
+
+/// Set the IPv4 address of the peer of a point-to-point interface
+/// (`SIOCSIFDSTADDR`).
+pub fn set_destination<P: ?Sized + NixPath>(fd: RawFd, name: &P, addr: Ipv4Addr)
+    -> Result<()>
+{
+    set_addr_with(fd, name, addr, ffi::siocsifdstaddr)
+}
+
+/// Get the active flags of an interface (`SIOCGIFFLAGS`).
+pub fn get_flags<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<InterfaceFlags> {
+    let mut req = IfReq::new(name)?;
+    unsafe { ffi::siocgifflags(fd, &mut req.0) }?;
+    Ok(req.flags())
+}
+
+/// Set the active flags of an interface (`SIOCSIFFLAGS`).  For example,
+/// adding or removing `IFF_UP` brings the interface up or down.
+///
+/// Only the flags that fit in 16 bits can be changed this way.
+pub fn set_flags<P: ?Sized + NixPath>(fd: RawFd, name: &P, flags: InterfaceFlags)
+    -> Result<()>
+{
+    let req = IfReq::new(name)?.with_flags(flags);
+    unsafe { ffi::siocsifflags(fd, &req.0) }.map(drop)
+}
+
+/// Get the MTU of an interface (`SIOCGIFMTU`).
+pub fn get_mtu<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<c_int> {
+    let mut req = IfReq::new(name)?;
+    unsafe { ffi::siocgifmtu(fd, &mut req.0) }?;
+    Ok(req.mtu())
+}
+
+/// Set the MTU of an interface (`SIOCSIFMTU`).
+pub fn set_mtu<P: ?Sized + NixPath>(fd: RawFd, name: &P, mtu: c_int) -> Result<()> {
+    let req = IfReq::new(name)?.with_mtu(mtu);
+    unsafe { ffi::siocsifmtu(fd, &req.0) }.map(drop)
+}
+
+/// Get the hardware (MAC) address of an interface (`SIOCGIFHWADDR`).
+///
+/// Interfaces without a hardware address, such as the loopback interface,
+/// report all zeroes.
+pub fn get_hwaddr<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<MacAddr> {
+    let mut req = IfReq::new(name)?;
+    unsafe { ffi::siocgifhwaddr(fd, &mut req.0) }?;
+    Ok(req.hwaddr())
+}
@@ -2,3 +2,7 @@
 // To avoid clashing with the keyword "if", we use "if_" as the module name.
 // The original header is called "net/if.h".
 pub mod if_;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod netlink;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod pfkey;
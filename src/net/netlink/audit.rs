@@ -0,0 +1,227 @@
+//! The kernel audit interface, through `NETLINK_AUDIT`.
+//!
+//! Requests are sent on a `NetlinkProtocol::Audit` socket, and fail with
+//! `ETIMEDOUT` if the kernel doesn't answer within 5 seconds.  Querying and
+//! changing the audit configuration requires `CAP_AUDIT_CONTROL`, and sending
+//! user-space records `CAP_AUDIT_WRITE`.  Audit records reach the
+//! process registered as the audit daemon with the `pid` field of
+//! [`AuditStatus`](struct.AuditStatus.html), and, read-only, every socket
+//! that joined the [`NLGRP_READLOG`](constant.NLGRP_READLOG.html) multicast
+//! group, which requires `CAP_AUDIT_READ`.  Each record is a netlink message
+//! of the record's type, holding the record's text.
+//!
+//! [Further reading](https://man7.org/linux/man-pages/man8/auditd.8.html)
+//!
+//! # Examples
+//!
+//! Follow the audit log:
+//!
+//! ```no_run
+//! use nix::net::netlink::audit::{AuditRecord, NLGRP_READLOG};
+//! use nix::net::netlink::messages;
+//! use nix::sys::socket::*;
+//!
+//! let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+//!                 NetlinkProtocol::Audit).unwrap();
+//! bind(&fd, &SockAddr::Netlink(NetlinkAddr::new(0, 1 << (NLGRP_READLOG - 1))))
+//!     .unwrap();
+//! let mut buf = vec![0u8; 16 * 1024];
+//! loop {
+//!     let len = recv(&fd, &mut buf, MsgFlags::empty()).unwrap();
+//!     for msg in messages(&buf[..len]) {
+//!         let record = AuditRecord::parse(&msg);
+//!         println!("type={} {}", record.record_type(),
+//!                  String::from_utf8_lossy(record.text()));
+//!     }
+//! }
+//! ```
+
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::{Error, Result};
+use crate::errno::Errno;
+use super::{NlMsg, NlMsgBuilder, NlMsgFlags, REQUEST_TIMEOUT};
+
+// From <linux/audit.h>; not yet in libc
+const AUDIT_GET: u16 = 1000;
+const AUDIT_SET: u16 = 1001;
+const AUDIT_FIRST_USER_MSG: u16 = 1100;
+const AUDIT_LAST_USER_MSG: u16 = 1199;
+const AUDIT_FIRST_USER_MSG2: u16 = 2100;
+const AUDIT_LAST_USER_MSG2: u16 = 2999;
+
+/// Multicast group carrying a read-only copy of every audit record.
+pub const NLGRP_READLOG: u32 = 1;
+
+/// Sequence numbers for requests, to skip stale replies
+static AUDIT_SEQ: AtomicUsize = AtomicUsize::new(1);
+
+bitflags::bitflags! {
+    /// Fields of [`AuditStatus`](struct.AuditStatus.html) to change with
+    /// [`set_status`](fn.set_status.html).
+    pub struct AuditStatusMask: u32 {
+        /// [`AuditStatus::enabled`](struct.AuditStatus.html#structfield.enabled)
+        const AUDIT_STATUS_ENABLED = 0x01;
+        /// [`AuditStatus::failure`](struct.AuditStatus.html#structfield.failure)
+        const AUDIT_STATUS_FAILURE = 0x02;
+        /// [`AuditStatus::pid`](struct.AuditStatus.html#structfield.pid)
+        const AUDIT_STATUS_PID = 0x04;
+        /// [`AuditStatus::rate_limit`](struct.AuditStatus.html#structfield.rate_limit)
+        const AUDIT_STATUS_RATE_LIMIT = 0x08;
+        /// [`AuditStatus::backlog_limit`](struct.AuditStatus.html#structfield.backlog_limit)
+        const AUDIT_STATUS_BACKLOG_LIMIT = 0x10;
+        /// [`AuditStatus::backlog_wait_time`](struct.AuditStatus.html#structfield.backlog_wait_time)
+        const AUDIT_STATUS_BACKLOG_WAIT_TIME = 0x20;
+        /// [`AuditStatus::lost`](struct.AuditStatus.html#structfield.lost),
+        /// which is reset to zero
+        const AUDIT_STATUS_LOST = 0x40;
+        /// [`AuditStatus::backlog_wait_time_actual`](struct.AuditStatus.html#structfield.backlog_wait_time_actual),
+        /// which is reset to zero
+        const AUDIT_STATUS_BACKLOG_WAIT_TIME_ACTUAL = 0x80;
+    }
+}
+
+/// Configuration and counters of the audit subsystem.
+///
+/// This mirrors the kernel's `struct audit_status`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AuditStatus {
+    /// Fields to change, when passed to [`set_status`](fn.set_status.html)
+    pub mask: u32,
+    /// 0 if auditing is disabled, 1 if enabled, and 2 if enabled and locked
+    /// until the next reboot
+    pub enabled: u32,
+    /// What to do on critical errors: 0 to stay silent, 1 to log a message
+    /// and 2 to panic
+    pub failure: u32,
+    /// Process ID of the audit daemon, or zero if none is registered
+    pub pid: u32,
+    /// Maximum number of records per second, or zero for no limit
+    pub rate_limit: u32,
+    /// Maximum number of records queued for the audit daemon
+    pub backlog_limit: u32,
+    /// Number of records lost
+    pub lost: u32,
+    /// Number of records currently queued
+    pub backlog: u32,
+    /// Bitmap of the audit features supported by the kernel
+    pub feature_bitmap: u32,
+    /// How long, in jiffies, a process waits when the backlog is full
+    pub backlog_wait_time: u32,
+    /// Total time, in jiffies, processes spent waiting on a full backlog
+    pub backlog_wait_time_actual: u32,
+}
+
+impl AuditStatus {
+    fn from_msg(msg: &NlMsg) -> AuditStatus {
+        // Older kernels send a shorter structure; the rest stays zero
+        let payload = msg.payload();
+        let mut status = AuditStatus::default();
+        let len = std::cmp::min(payload.len(), mem::size_of::<AuditStatus>());
+        unsafe {
+            ptr::copy_nonoverlapping(payload.as_ptr(),
+                                     &mut status as *mut _ as *mut u8, len);
+        }
+        status
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        // AuditStatus only has u32 fields, so no padding
+        unsafe {
+            std::slice::from_raw_parts(self as *const _ as *const u8,
+                                       mem::size_of::<AuditStatus>())
+        }
+    }
+}
+
+/// An audit record, or any other message received on an audit socket.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AuditRecord<'a> {
+    record_type: u16,
+    text: &'a [u8],
+}
+
+impl<'a> AuditRecord<'a> {
+    /// Interpret a received message as an audit record.
+    pub fn parse(msg: &NlMsg<'a>) -> AuditRecord<'a> {
+        let payload = msg.payload();
+        let end = payload.iter().position(|&b| b == 0).unwrap_or(payload.len());
+        AuditRecord {
+            record_type: msg.msg_type(),
+            text: &payload[..end],
+        }
+    }
+
+    /// The type of the record, such as 1300 for `AUDIT_SYSCALL`.
+    pub fn record_type(&self) -> u16 {
+        self.record_type
+    }
+
+    /// The text of the record, such as `audit(1600000000.123:42):
+    /// arch=c000003e syscall=59 ...`, without any terminating null byte.
+    pub fn text(&self) -> &'a [u8] {
+        self.text
+    }
+}
+
+/// Log a user-space audit record of type `record_type`, such as 1112 for
+/// `AUDIT_USER_LOGIN`.
+///
+/// Fails with `EINVAL` unless `record_type` lies within one of the ranges
+/// reserved for user-space records, 1100 to 1199 and 2100 to 2999, and with
+/// `EPERM` without `CAP_AUDIT_WRITE`.
+pub fn log_user_record(fd: RawFd, record_type: u16, text: &str) -> Result<()> {
+    if !(AUDIT_FIRST_USER_MSG..=AUDIT_LAST_USER_MSG).contains(&record_type) &&
+        !(AUDIT_FIRST_USER_MSG2..=AUDIT_LAST_USER_MSG2).contains(&record_type)
+    {
+        return Err(Error::Sys(Errno::EINVAL));
+    }
+    let mut request = NlMsgBuilder::new(record_type,
+                                        NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_ACK);
+    request.push(text.as_bytes()).push(&[0]);
+    transact(fd, &mut request, |msg| msg.error().transpose())
+}
+
+/// Send `request` with a fresh sequence number and pass the replies to `f`
+/// until it returns a value.
+fn transact<T, F>(fd: RawFd, request: &mut NlMsgBuilder, f: F) -> Result<T>
+    where F: FnMut(NlMsg) -> Result<Option<T>>
+{
+    let seq = AUDIT_SEQ.fetch_add(1, Ordering::Relaxed) as u32;
+    request.seq(seq);
+    super::transact(fd, request, Some(REQUEST_TIMEOUT), f)
+}
+
+/// Query the configuration and counters of the audit subsystem.
+///
+/// `fd` must be a `NetlinkProtocol::Audit` socket.  Fails with `EPERM`
+/// without `CAP_AUDIT_CONTROL`, and with `ECONNREFUSED` outside the initial
+/// user and PID namespaces.
+pub fn get_status(fd: RawFd) -> Result<AuditStatus> {
+    let mut request = NlMsgBuilder::new(AUDIT_GET, NlMsgFlags::NLM_F_REQUEST);
+    transact(fd, &mut request, |msg| {
+        if let Some(result) = msg.error() {
+            result?;
+            Ok(None)
+        } else if msg.msg_type() == AUDIT_GET {
+            Ok(Some(AuditStatus::from_msg(&msg)))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+/// Change the fields of the audit configuration selected by `mask`.
+///
+/// Setting `pid` registers the audit daemon, which then receives the audit
+/// records on `fd`.  The other fields of `status` are ignored.
+pub fn set_status(fd: RawFd, mask: AuditStatusMask, status: &AuditStatus) -> Result<()> {
+    let status = AuditStatus { mask: mask.bits(), ..*status };
+    let mut request = NlMsgBuilder::new(AUDIT_SET,
+                                        NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_ACK);
+    request.push(status.as_bytes());
+    transact(fd, &mut request, |msg| msg.error().transpose())
+}
@@ -0,0 +1,237 @@
+//! Generic netlink.
+//!
+//! Generic netlink multiplexes many kernel families, such as `nl80211` or
+//! `TASKSTATS`, over `NetlinkProtocol::Generic` sockets.  Families have
+//! dynamically assigned IDs, which are looked up by name with
+//! [`get_family`](fn.get_family.html).  Each message starts with a
+//! `struct genlmsghdr` naming the command, followed by attributes.
+//!
+//! [Further reading](https://www.kernel.org/doc/html/latest/userspace-api/netlink/intro.html)
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use nix::net::netlink::genl::{get_family, GenlMsg};
+//! use nix::net::netlink::{messages, NlMsgFlags};
+//! use nix::sys::socket::*;
+//!
+//! let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+//!                 NetlinkProtocol::Generic).unwrap();
+//! let family = get_family(fd, "nl80211").unwrap();
+//!
+//! // NL80211_CMD_GET_INTERFACE, dumping all wireless interfaces
+//! let msg = GenlMsg::builder(family.id(), 5, 1,
+//!                            NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_DUMP);
+//! send(&fd, msg.as_bytes(), MsgFlags::empty()).unwrap();
+//! ```
+
+use libc;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::{Error, Result};
+use crate::errno::Errno;
+use super::{transact, NlAttrs, NlMsg, NlMsgBuilder, NlMsgFlags, REQUEST_TIMEOUT};
+
+/// Length of `struct genlmsghdr`
+const GENL_HDRLEN: usize = 4;
+/// Version of the controller interface spoken by `get_family`
+const CTRL_VERSION: u8 = 1;
+
+/// Sequence numbers for controller requests, to skip stale replies
+static CTRL_SEQ: AtomicUsize = AtomicUsize::new(1);
+
+/// A generic netlink message parsed from a received buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GenlMsg<'a> {
+    family: u16,
+    cmd: u8,
+    version: u8,
+    attrs: NlAttrs<'a>,
+}
+
+impl<'a> GenlMsg<'a> {
+    /// Start a generic netlink message for command `cmd` of `family`.
+    /// Attributes may be appended to the returned builder.
+    pub fn builder(family: u16, cmd: u8, version: u8, flags: NlMsgFlags) -> NlMsgBuilder {
+        let mut builder = NlMsgBuilder::new(family, flags);
+        builder.push(&[cmd, version, 0, 0]);
+        builder
+    }
+
+    /// Parse the generic netlink header of `msg`.
+    ///
+    /// Fails with `EINVAL` if the payload is too short to hold one.
+    pub fn parse(msg: &NlMsg<'a>) -> Result<GenlMsg<'a>> {
+        let payload = msg.payload();
+        if payload.len() < GENL_HDRLEN {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        Ok(GenlMsg {
+            family: msg.msg_type(),
+            cmd: payload[0],
+            version: payload[1],
+            attrs: msg.attrs(GENL_HDRLEN),
+        })
+    }
+
+    /// The family ID, which is the netlink message type.
+    pub fn family(&self) -> u16 {
+        self.family
+    }
+
+    /// The family-specific command.
+    pub fn cmd(&self) -> u8 {
+        self.cmd
+    }
+
+    /// The version of the family's interface.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The attributes following the generic netlink header.
+    pub fn attrs(&self) -> NlAttrs<'a> {
+        self.attrs
+    }
+}
+
+/// A multicast group of a generic netlink family.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct McastGroup {
+    name: String,
+    id: u32,
+}
+
+impl McastGroup {
+    /// The name of the group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The group ID, as passed to
+    /// [`NetlinkAddMembership`](../../../sys/socket/sockopt/struct.NetlinkAddMembership.html).
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+/// A generic netlink family, as described by the controller.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Family {
+    id: u16,
+    name: String,
+    version: u32,
+    header_size: u32,
+    max_attr: u32,
+    mcast_groups: Vec<McastGroup>,
+}
+
+impl Family {
+    /// The ID of the family, used as the type of its messages.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// The name of the family.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version of the family's interface.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The size of the family-specific header between the generic netlink
+    /// header and the attributes.  Usually zero.
+    pub fn header_size(&self) -> u32 {
+        self.header_size
+    }
+
+    /// The highest attribute type understood by the family.
+    pub fn max_attr(&self) -> u32 {
+        self.max_attr
+    }
+
+    /// The multicast groups of the family.
+    pub fn mcast_groups(&self) -> &[McastGroup] {
+        &self.mcast_groups
+    }
+
+    /// Look up a multicast group by name.
+    pub fn mcast_group(&self, name: &str) -> Option<&McastGroup> {
+        self.mcast_groups.iter().find(|group| group.name == name)
+    }
+
+    fn from_attrs(attrs: NlAttrs) -> Result<Family> {
+        let mut id = None;
+        let mut family = Family {
+            id: 0,
+            name: String::new(),
+            version: 0,
+            header_size: 0,
+            max_attr: 0,
+            mcast_groups: Vec::new(),
+        };
+        for attr in attrs {
+            match i32::from(attr.kind()) {
+                libc::CTRL_ATTR_FAMILY_ID => id = attr.get_u16(),
+                libc::CTRL_ATTR_FAMILY_NAME => {
+                    family.name = attr.get_str().unwrap_or_default().to_owned();
+                },
+                libc::CTRL_ATTR_VERSION => family.version = attr.get_u32().unwrap_or(0),
+                libc::CTRL_ATTR_HDRSIZE => family.header_size = attr.get_u32().unwrap_or(0),
+                libc::CTRL_ATTR_MAXATTR => family.max_attr = attr.get_u32().unwrap_or(0),
+                libc::CTRL_ATTR_MCAST_GROUPS => {
+                    for group in attr.nested() {
+                        let mut name = None;
+                        let mut group_id = None;
+                        for a in group.nested() {
+                            match i32::from(a.kind()) {
+                                libc::CTRL_ATTR_MCAST_GRP_NAME => name = a.get_str(),
+                                libc::CTRL_ATTR_MCAST_GRP_ID => group_id = a.get_u32(),
+                                _ => (),
+                            }
+                        }
+                        if let (Some(name), Some(id)) = (name, group_id) {
+                            family.mcast_groups.push(McastGroup {
+                                name: name.to_owned(),
+                                id,
+                            });
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+        family.id = id.ok_or(Error::Sys(Errno::EINVAL))?;
+        Ok(family)
+    }
+}
+
+/// Resolve the generic netlink family called `name` with a
+/// `CTRL_CMD_GETFAMILY` request to the controller.
+///
+/// `fd` must be a `NetlinkProtocol::Generic` socket.  Fails with `ENOENT`
+/// if no such family is registered, for instance because its kernel module
+/// isn't loaded, with `EINVAL` if `name` is longer than the 15 bytes allowed
+/// for family names, and with `ETIMEDOUT` if the controller doesn't answer
+/// within 5 seconds.
+pub fn get_family(fd: RawFd, name: &str) -> Result<Family> {
+    let seq = CTRL_SEQ.fetch_add(1, Ordering::Relaxed) as u32;
+    let mut request = GenlMsg::builder(libc::GENL_ID_CTRL as u16,
+                                       libc::CTRL_CMD_GETFAMILY as u8,
+                                       CTRL_VERSION,
+                                       NlMsgFlags::NLM_F_REQUEST);
+    request.seq(seq).attr_str(libc::CTRL_ATTR_FAMILY_NAME as u16, name);
+    transact(fd, &request, Some(REQUEST_TIMEOUT), |msg| {
+        if let Some(result) = msg.error() {
+            result?;
+            Ok(None)
+        } else if msg.msg_type() == libc::GENL_ID_CTRL as u16 {
+            Family::from_attrs(GenlMsg::parse(&msg)?.attrs()).map(Some)
+        } else {
+            Ok(None)
+        }
+    })
+}
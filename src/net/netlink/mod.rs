@@ -0,0 +1,530 @@
+//! Netlink message and attribute encoding.
+//!
+//! Netlink sockets are created with [`socket`](../../sys/socket/fn.socket.html)
+//! using `AddressFamily::Netlink`.  This module builds the messages sent over
+//! them and parses the replies: [`NlMsgBuilder`](struct.NlMsgBuilder.html)
+//! writes a message header, an optional family-specific header and a list of
+//! attributes; [`messages`](fn.messages.html) and
+//! [`NlAttrs`](struct.NlAttrs.html) walk a received buffer.
+//!
+//! All values use the host byte order, as the kernel does unless an attribute
+//! has the `NLA_F_NET_BYTEORDER` flag.
+//!
+//! [Further reading](https://man7.org/linux/man-pages/man7/netlink.7.html)
+//!
+//! # Examples
+//!
+//! ```
+//! use nix::net::netlink::{messages, NlMsgBuilder, NlMsgFlags};
+//!
+//! let mut msg = NlMsgBuilder::new(0x10, NlMsgFlags::NLM_F_REQUEST);
+//! msg.seq(1).attr_u32(1, 42).attr_str(2, "eth0");
+//!
+//! let parsed = messages(msg.as_bytes()).next().unwrap();
+//! assert_eq!(parsed.msg_type(), 0x10);
+//! let mut attrs = parsed.attrs(0);
+//! assert_eq!(attrs.next().unwrap().get_u32(), Some(42));
+//! assert_eq!(attrs.next().unwrap().get_str(), Some("eth0"));
+//! assert!(attrs.next().is_none());
+//! ```
+
+use libc::c_int;
+use std::convert::TryFrom;
+use std::os::unix::io::RawFd;
+use std::time::{Duration, Instant};
+use std::{mem, str};
+use crate::{Error, Result};
+use crate::errno::Errno;
+use crate::poll::{poll, PollFd, PollFlags};
+use crate::sys::socket::{recv_trunc, sendto, MsgFlags, NetlinkAddr, SockAddr};
+
+pub mod audit;
+pub mod genl;
+pub mod sock_diag;
+
+/// Netlink messages and attributes are padded to multiples of this
+const NL_ALIGNTO: usize = 4;
+/// Length of `struct nlmsghdr`
+const NLMSG_HDRLEN: usize = 16;
+/// Length of `struct nlattr`
+const NLA_HDRLEN: usize = 4;
+/// Largest attribute that `nla_len` can describe
+const NLA_MAXLEN: usize = 0xffff;
+/// Large enough for any message of a reply
+const REPLY_BUF_SIZE: usize = 32 * 1024;
+/// How long to wait for the answer to a request that the kernel handles as
+/// soon as it is sent, so that a missing answer doesn't block forever
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn align(len: usize) -> usize {
+    (len + NL_ALIGNTO - 1) & !(NL_ALIGNTO - 1)
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes(<[u8; 2]>::try_from(&buf[offset..offset + 2]).unwrap())
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(<[u8; 4]>::try_from(&buf[offset..offset + 4]).unwrap())
+}
+
+libc_bitflags! {
+    /// Flags for the `nlmsg_flags` field of a netlink message header.
+    pub struct NlMsgFlags: u16 {
+        /// The message is a request.  Must be set on all requests.
+        NLM_F_REQUEST as u16;
+        /// The message is part of a multipart reply, terminated by a message
+        /// of type `NLMSG_DONE`.
+        NLM_F_MULTI as u16;
+        /// Request an acknowledgement on success.
+        NLM_F_ACK as u16;
+        /// Echo this request back to the sender.
+        NLM_F_ECHO as u16;
+        /// Return the complete table instead of a single entry.
+        NLM_F_ROOT as u16;
+        /// Return all entries matching the criteria in the request.
+        NLM_F_MATCH as u16;
+        /// Return an atomic snapshot of the table.
+        NLM_F_ATOMIC as u16;
+        /// Shorthand for `NLM_F_ROOT | NLM_F_MATCH`.
+        NLM_F_DUMP as u16;
+        /// Replace an existing object.
+        NLM_F_REPLACE as u16;
+        /// Don't replace an object if it already exists.
+        NLM_F_EXCL as u16;
+        /// Create the object if it doesn't exist yet.
+        NLM_F_CREATE as u16;
+        /// Add to the end of the object list.
+        NLM_F_APPEND as u16;
+    }
+}
+
+/// Position of a nested attribute started with
+/// [`NlMsgBuilder::begin_nested`](struct.NlMsgBuilder.html#method.begin_nested).
+// Not Copy, so that each nest can only be ended once
+#[allow(missing_copy_implementations)]
+#[derive(Debug, Eq, PartialEq)]
+pub struct Nest(usize);
+
+/// Builder for a single netlink message.
+///
+/// The `nlmsg_len` field of the header is kept up to date as data is added,
+/// so [`as_bytes`](#method.as_bytes) may be passed to
+/// [`send`](../../sys/socket/fn.send.html) at any point.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NlMsgBuilder {
+    buf: Vec<u8>,
+}
+
+impl NlMsgBuilder {
+    /// Start a message of type `msg_type` with the given header flags.  The
+    /// sequence number and port ID are zero.
+    pub fn new(msg_type: u16, flags: NlMsgFlags) -> NlMsgBuilder {
+        let mut buf = Vec::with_capacity(64);
+        buf.extend_from_slice(&(NLMSG_HDRLEN as u32).to_ne_bytes());
+        buf.extend_from_slice(&msg_type.to_ne_bytes());
+        buf.extend_from_slice(&flags.bits().to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        NlMsgBuilder { buf }
+    }
+
+    /// Set the sequence number, used to match replies to requests.
+    pub fn seq(&mut self, seq: u32) -> &mut Self {
+        self.buf[8..12].copy_from_slice(&seq.to_ne_bytes());
+        self
+    }
+
+    /// Set the port ID of the sender.  Zero lets the kernel fill it in.
+    pub fn pid(&mut self, pid: u32) -> &mut Self {
+        self.buf[12..16].copy_from_slice(&pid.to_ne_bytes());
+        self
+    }
+
+    /// Append raw data, padded to the netlink alignment.  This is used for
+    /// family-specific headers such as `struct genlmsghdr`, which precede the
+    /// attributes.
+    pub fn push(&mut self, data: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(data);
+        self.pad();
+        self
+    }
+
+    /// Append an attribute with an arbitrary payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload is too long to fit in an attribute.
+    pub fn attr(&mut self, kind: u16, payload: &[u8]) -> &mut Self {
+        let len = NLA_HDRLEN + payload.len();
+        assert!(len <= NLA_MAXLEN, "netlink attribute payload too long");
+        self.buf.extend_from_slice(&(len as u16).to_ne_bytes());
+        self.buf.extend_from_slice(&kind.to_ne_bytes());
+        self.push(payload)
+    }
+
+    /// Append an attribute holding a `u8`.
+    pub fn attr_u8(&mut self, kind: u16, value: u8) -> &mut Self {
+        self.attr(kind, &[value])
+    }
+
+    /// Append an attribute holding a `u16`.
+    pub fn attr_u16(&mut self, kind: u16, value: u16) -> &mut Self {
+        self.attr(kind, &value.to_ne_bytes())
+    }
+
+    /// Append an attribute holding a `u32`.
+    pub fn attr_u32(&mut self, kind: u16, value: u32) -> &mut Self {
+        self.attr(kind, &value.to_ne_bytes())
+    }
+
+    /// Append an attribute holding a `u64`.
+    pub fn attr_u64(&mut self, kind: u16, value: u64) -> &mut Self {
+        self.attr(kind, &value.to_ne_bytes())
+    }
+
+    /// Append an attribute holding a NUL-terminated string.
+    pub fn attr_str(&mut self, kind: u16, value: &str) -> &mut Self {
+        let mut payload = Vec::with_capacity(value.len() + 1);
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(0);
+        self.attr(kind, &payload)
+    }
+
+    /// Start a nested attribute.  Attributes appended until the matching call
+    /// to [`end_nested`](#method.end_nested) become its payload.
+    pub fn begin_nested(&mut self, kind: u16) -> Nest {
+        let start = self.buf.len();
+        self.attr(kind | libc::NLA_F_NESTED as u16, &[]);
+        Nest(start)
+    }
+
+    /// Finish a nested attribute started with
+    /// [`begin_nested`](#method.begin_nested).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the nested attributes are too long to fit in an attribute.
+    pub fn end_nested(&mut self, nest: Nest) -> &mut Self {
+        let len = self.buf.len() - nest.0;
+        assert!(len <= NLA_MAXLEN, "nested netlink attribute too long");
+        self.buf[nest.0..nest.0 + 2].copy_from_slice(&(len as u16).to_ne_bytes());
+        self
+    }
+
+    /// The encoded message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// The sequence number of the message.
+    fn get_seq(&self) -> u32 {
+        read_u32(&self.buf, 8)
+    }
+
+    /// Consume the builder, returning the encoded message.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn pad(&mut self) {
+        let len = align(self.buf.len());
+        self.buf.resize(len, 0);
+        self.buf[0..4].copy_from_slice(&(len as u32).to_ne_bytes());
+    }
+}
+
+/// A netlink message parsed from a received buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NlMsg<'a> {
+    msg_type: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+    payload: &'a [u8],
+}
+
+impl<'a> NlMsg<'a> {
+    /// The message type, such as `NLMSG_DONE` or a family-specific type.
+    pub fn msg_type(&self) -> u16 {
+        self.msg_type
+    }
+
+    /// The header flags.
+    pub fn flags(&self) -> NlMsgFlags {
+        NlMsgFlags::from_bits_truncate(self.flags)
+    }
+
+    /// The sequence number, copied from the request by the kernel.
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// The port ID of the sender.  Zero for messages from the kernel.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// The data following the message header.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// The attributes of the message, which start `header_len` bytes (before
+    /// padding) into the payload.  `header_len` is the size of the
+    /// family-specific header, if any.
+    pub fn attrs(&self, header_len: usize) -> NlAttrs<'a> {
+        let offset = align(header_len);
+        if offset > self.payload.len() {
+            NlAttrs::new(&[])
+        } else {
+            NlAttrs::new(&self.payload[offset..])
+        }
+    }
+
+    /// Whether this message ends a multipart reply (`NLMSG_DONE`).
+    pub fn is_done(&self) -> bool {
+        self.msg_type == libc::NLMSG_DONE as u16
+    }
+
+    /// Decode an `NLMSG_ERROR` message.
+    ///
+    /// Returns `None` for other message types, `Some(Ok(()))` for an
+    /// acknowledgement and `Some(Err(_))` if the kernel rejected the request.
+    pub fn error(&self) -> Option<Result<()>> {
+        if self.msg_type != libc::NLMSG_ERROR as u16 {
+            return None;
+        }
+        if self.payload.len() < mem::size_of::<i32>() {
+            return Some(Err(Error::Sys(Errno::EINVAL)));
+        }
+        match read_u32(self.payload, 0) as i32 {
+            0 => Some(Ok(())),
+            e => Some(Err(Error::Sys(Errno::from_i32(-e)))),
+        }
+    }
+}
+
+/// Iterate over the netlink messages in `buf`, as filled in by
+/// [`recv`](../../sys/socket/fn.recv.html).
+///
+/// Iteration stops at the end of the buffer or at the first malformed
+/// message.
+pub fn messages(buf: &[u8]) -> NlMsgIter<'_> {
+    NlMsgIter { buf }
+}
+
+/// Send `request` to the kernel and pass each message of the reply to `f`,
+/// until it returns a value.
+///
+/// Messages whose sequence number differs from the request's are skipped.
+/// Fails with `EMSGSIZE` if a message is larger than 32 KiB, or with the
+/// first error returned by `f`.  With a `timeout`, fails with `ETIMEDOUT` if
+/// `f` hasn't returned a value by then.  Without one, each receive waits as
+/// long as the receive timeout of the socket, failing with `EAGAIN` when it
+/// expires, or forever if the socket has none.
+pub fn transact<T, F>(fd: RawFd, request: &NlMsgBuilder, timeout: Option<Duration>, mut f: F)
+    -> Result<T>
+    where F: FnMut(NlMsg) -> Result<Option<T>>
+{
+    let seq = request.get_seq();
+    let kernel = SockAddr::Netlink(NetlinkAddr::new(0, 0));
+    sendto(&fd, request.as_bytes(), &kernel, MsgFlags::empty())?;
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut buf = vec![0u8; REPLY_BUF_SIZE];
+    loop {
+        if let Some(deadline) = deadline {
+            wait_readable(fd, deadline)?;
+        }
+        let (len, real_len) = recv_trunc(&fd, &mut buf, MsgFlags::empty())?;
+        if len < real_len {
+            return Err(Error::Sys(Errno::EMSGSIZE));
+        }
+        for msg in messages(&buf[..len]).filter(|msg| msg.seq() == seq) {
+            if let Some(value) = f(msg)? {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+/// Wait until `fd` has something to receive, failing with `ETIMEDOUT` if that
+/// doesn't happen before `deadline`.
+fn wait_readable(fd: RawFd, deadline: Instant) -> Result<()> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    // Round up, so as not to spin through the last millisecond
+    let ms = (left + Duration::from_nanos(999_999)).as_millis();
+    let ms = c_int::try_from(ms).unwrap_or(c_int::MAX);
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match poll(&mut fds, ms)? {
+        0 => Err(Error::Sys(Errno::ETIMEDOUT)),
+        _ => Ok(()),
+    }
+}
+
+/// Send a dump request to the kernel and call `f` on each message of the
+/// reply, until the final `NLMSG_DONE`.
+///
+/// `request` should have the `NLM_F_DUMP` flag.  If the kernel replies with a
+/// single message without `NLM_F_MULTI` instead, or with an acknowledgement,
+/// that ends the reply.  Messages whose sequence number differs from the
+/// request's are skipped.  Fails with the error reported by the kernel, if
+/// any, or the first error returned by `f`.  Each receive waits as long as
+/// the receive timeout of the socket, as with [`transact`](fn.transact.html).
+pub fn dump<F>(fd: RawFd, request: &NlMsgBuilder, mut f: F) -> Result<()>
+    where F: FnMut(NlMsg) -> Result<()>
+{
+    transact(fd, request, None, |msg| {
+        if let Some(result) = msg.error() {
+            result.map(Some)
+        } else if msg.is_done() {
+            // Some families report dump errors in the NLMSG_DONE payload
+            if msg.payload.len() >= mem::size_of::<i32>() {
+                let status = read_u32(msg.payload, 0) as i32;
+                if status < 0 {
+                    return Err(Error::Sys(Errno::from_i32(-status)));
+                }
+            }
+            Ok(Some(()))
+        } else {
+            let multi = msg.flags().contains(NlMsgFlags::NLM_F_MULTI);
+            f(msg)?;
+            Ok(if multi { None } else { Some(()) })
+        }
+    })
+}
+
+/// Iterator returned by [`messages`](fn.messages.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NlMsgIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for NlMsgIter<'a> {
+    type Item = NlMsg<'a>;
+
+    fn next(&mut self) -> Option<NlMsg<'a>> {
+        if self.buf.len() < NLMSG_HDRLEN {
+            return None;
+        }
+        let len = read_u32(self.buf, 0) as usize;
+        if len < NLMSG_HDRLEN || len > self.buf.len() {
+            self.buf = &[];
+            return None;
+        }
+        let msg = NlMsg {
+            msg_type: read_u16(self.buf, 4),
+            flags: read_u16(self.buf, 6),
+            seq: read_u32(self.buf, 8),
+            pid: read_u32(self.buf, 12),
+            payload: &self.buf[NLMSG_HDRLEN..len],
+        };
+        let next = align(len);
+        self.buf = if next < self.buf.len() { &self.buf[next..] } else { &[] };
+        Some(msg)
+    }
+}
+
+/// A netlink attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NlAttr<'a> {
+    kind: u16,
+    payload: &'a [u8],
+}
+
+impl<'a> NlAttr<'a> {
+    /// The attribute type, without the `NLA_F_NESTED` and
+    /// `NLA_F_NET_BYTEORDER` flags.
+    pub fn kind(&self) -> u16 {
+        self.kind & libc::NLA_TYPE_MASK as u16
+    }
+
+    /// Whether the kernel marked this attribute as holding nested attributes.
+    /// Many older families nest attributes without setting the flag.
+    pub fn is_nested(&self) -> bool {
+        self.kind & libc::NLA_F_NESTED as u16 != 0
+    }
+
+    /// The raw payload.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// The payload as a `u8`, if it has the right size.
+    pub fn get_u8(&self) -> Option<u8> {
+        if self.payload.len() == 1 {
+            Some(self.payload[0])
+        } else {
+            None
+        }
+    }
+
+    /// The payload as a `u16`, if it has the right size.
+    pub fn get_u16(&self) -> Option<u16> {
+        <[u8; 2]>::try_from(self.payload).ok().map(u16::from_ne_bytes)
+    }
+
+    /// The payload as a `u32`, if it has the right size.
+    pub fn get_u32(&self) -> Option<u32> {
+        <[u8; 4]>::try_from(self.payload).ok().map(u32::from_ne_bytes)
+    }
+
+    /// The payload as a `u64`, if it has the right size.
+    pub fn get_u64(&self) -> Option<u64> {
+        <[u8; 8]>::try_from(self.payload).ok().map(u64::from_ne_bytes)
+    }
+
+    /// The payload as a string, without the terminating NUL.  Returns `None`
+    /// if it isn't valid UTF-8.
+    pub fn get_str(&self) -> Option<&'a str> {
+        let bytes = match self.payload.iter().position(|&b| b == 0) {
+            Some(nul) => &self.payload[..nul],
+            None => self.payload,
+        };
+        str::from_utf8(bytes).ok()
+    }
+
+    /// The attributes nested in the payload.
+    pub fn nested(&self) -> NlAttrs<'a> {
+        NlAttrs::new(self.payload)
+    }
+}
+
+/// Iterator over a list of netlink attributes.
+///
+/// Iteration stops at the end of the buffer or at the first malformed
+/// attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NlAttrs<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> NlAttrs<'a> {
+    /// Iterate over the attributes encoded in `buf`.
+    pub fn new(buf: &'a [u8]) -> NlAttrs<'a> {
+        NlAttrs { buf }
+    }
+}
+
+impl<'a> Iterator for NlAttrs<'a> {
+    type Item = NlAttr<'a>;
+
+    fn next(&mut self) -> Option<NlAttr<'a>> {
+        if self.buf.len() < NLA_HDRLEN {
+            return None;
+        }
+        let len = usize::from(read_u16(self.buf, 0));
+        if len < NLA_HDRLEN || len > self.buf.len() {
+            self.buf = &[];
+            return None;
+        }
+        let attr = NlAttr {
+            kind: read_u16(self.buf, 2),
+            payload: &self.buf[NLA_HDRLEN..len],
+        };
+        let next = align(len);
+        self.buf = if next < self.buf.len() { &self.buf[next..] } else { &[] };
+        Some(attr)
+    }
+}
@@ -0,0 +1,634 @@
+//! Socket monitoring through `NETLINK_SOCK_DIAG`.
+//!
+//! This is the interface behind the `ss` utility.  Requests are sent on a
+//! `NetlinkProtocol::SockDiag` socket, usually with
+//! [`dump`](../fn.dump.html), and the kernel answers with one message per
+//! matching socket: a fixed header followed by attributes selected by the
+//! request.
+//!
+//! [Further reading](https://man7.org/linux/man-pages/man7/sock_diag.7.html)
+//!
+//! # Examples
+//!
+//! List the listening TCP sockets:
+//!
+//! ```
+//! use nix::net::netlink::dump;
+//! use nix::net::netlink::sock_diag::*;
+//! use nix::sys::socket::*;
+//!
+//! let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+//!                 NetlinkProtocol::SockDiag).unwrap();
+//! let req = InetDiagReq::new(AddressFamily::Inet, SockProtocol::Tcp,
+//!                            TcpStates::LISTEN).unwrap();
+//! dump(fd, &req.message(), |msg| {
+//!     let (diag, _attrs) = InetDiagMsg::parse(&msg)?;
+//!     println!("listening on port {}", diag.id().src_port());
+//!     Ok(())
+//! }).unwrap();
+//! ```
+
+use std::convert::TryFrom;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ptr;
+use crate::{Error, Result};
+use crate::errno::Errno;
+use crate::sys::socket::{AddressFamily, SockProtocol};
+use super::{NlAttrs, NlMsg, NlMsgBuilder, NlMsgFlags};
+
+// From <linux/sock_diag.h>; not yet in libc
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+// From <linux/inet_diag.h>; not yet in libc
+const INET_DIAG_NOCOOKIE: u32 = !0;
+
+/// Read a `T` from the start of `msg`'s payload, returning it with the
+/// attributes that follow.
+fn parse_header<'a, T: Copy>(msg: &NlMsg<'a>) -> Result<(T, NlAttrs<'a>)> {
+    let payload = msg.payload();
+    if payload.len() < mem::size_of::<T>() {
+        return Err(Error::Sys(Errno::EINVAL));
+    }
+    // The length was checked above, and T is a plain integer struct
+    let header = unsafe { ptr::read_unaligned(payload.as_ptr() as *const T) };
+    Ok((header, msg.attrs(mem::size_of::<T>())))
+}
+
+/// Build a `SOCK_DIAG_BY_FAMILY` dump request carrying `req`.
+fn message<T>(req: &T) -> NlMsgBuilder {
+    // T is a plain integer struct without padding
+    let bytes = unsafe {
+        std::slice::from_raw_parts(req as *const T as *const u8, mem::size_of::<T>())
+    };
+    let mut builder = NlMsgBuilder::new(SOCK_DIAG_BY_FAMILY,
+                                        NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_DUMP);
+    builder.push(bytes);
+    builder
+}
+
+fn cookie(cookie: [u32; 2]) -> Option<u64> {
+    if cookie == [INET_DIAG_NOCOOKIE; 2] {
+        None
+    } else {
+        Some(u64::from(cookie[0]) | u64::from(cookie[1]) << 32)
+    }
+}
+
+/// State of a TCP socket.  UNIX sockets use `Established`, `Listen` and
+/// `Close`, and UDP sockets `Established` and `Close`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TcpState {
+    /// Connection established
+    Established = 1,
+    /// SYN sent, waiting for the SYN-ACK
+    SynSent = 2,
+    /// SYN received, waiting for the final ACK
+    SynRecv = 3,
+    /// Closed locally, waiting for the peer to acknowledge the FIN
+    FinWait1 = 4,
+    /// Closed locally, waiting for the peer's FIN
+    FinWait2 = 5,
+    /// Waiting for stray segments after closing
+    TimeWait = 6,
+    /// Closed, or an unconnected UNIX or UDP socket
+    Close = 7,
+    /// Closed by the peer, waiting for the local close
+    CloseWait = 8,
+    /// Closed on both ends, waiting for the last ACK
+    LastAck = 9,
+    /// Listening for connections
+    Listen = 10,
+    /// Both ends closed simultaneously
+    Closing = 11,
+    /// Request socket of a listener, before the handshake completes
+    NewSynRecv = 12,
+}
+
+impl TcpState {
+    fn from_u8(state: u8) -> Option<TcpState> {
+        use self::TcpState::*;
+        let state = match state {
+            1 => Established,
+            2 => SynSent,
+            3 => SynRecv,
+            4 => FinWait1,
+            5 => FinWait2,
+            6 => TimeWait,
+            7 => Close,
+            8 => CloseWait,
+            9 => LastAck,
+            10 => Listen,
+            11 => Closing,
+            12 => NewSynRecv,
+            _ => return None,
+        };
+        Some(state)
+    }
+}
+
+bitflags::bitflags! {
+    /// Set of socket states to match in a request.
+    pub struct TcpStates: u32 {
+        /// [`TcpState::Established`](enum.TcpState.html)
+        const ESTABLISHED = 1 << 1;
+        /// [`TcpState::SynSent`](enum.TcpState.html)
+        const SYN_SENT = 1 << 2;
+        /// [`TcpState::SynRecv`](enum.TcpState.html)
+        const SYN_RECV = 1 << 3;
+        /// [`TcpState::FinWait1`](enum.TcpState.html)
+        const FIN_WAIT1 = 1 << 4;
+        /// [`TcpState::FinWait2`](enum.TcpState.html)
+        const FIN_WAIT2 = 1 << 5;
+        /// [`TcpState::TimeWait`](enum.TcpState.html)
+        const TIME_WAIT = 1 << 6;
+        /// [`TcpState::Close`](enum.TcpState.html)
+        const CLOSE = 1 << 7;
+        /// [`TcpState::CloseWait`](enum.TcpState.html)
+        const CLOSE_WAIT = 1 << 8;
+        /// [`TcpState::LastAck`](enum.TcpState.html)
+        const LAST_ACK = 1 << 9;
+        /// [`TcpState::Listen`](enum.TcpState.html)
+        const LISTEN = 1 << 10;
+        /// [`TcpState::Closing`](enum.TcpState.html)
+        const CLOSING = 1 << 11;
+        /// [`TcpState::NewSynRecv`](enum.TcpState.html)
+        const NEW_SYN_RECV = 1 << 12;
+    }
+}
+
+impl From<TcpState> for TcpStates {
+    fn from(state: TcpState) -> TcpStates {
+        TcpStates::from_bits_truncate(1 << state as u32)
+    }
+}
+
+bitflags::bitflags! {
+    /// Optional attributes to include in the replies to an
+    /// [`InetDiagReq`](struct.InetDiagReq.html).
+    pub struct InetDiagExt: u8 {
+        /// `INET_DIAG_MEMINFO`: queue memory usage
+        const MEMINFO = 1 << 0;
+        /// `INET_DIAG_INFO`: protocol information, such as a `struct tcp_info`
+        const INFO = 1 << 1;
+        /// `INET_DIAG_VEGASINFO`: TCP Vegas congestion control information
+        const VEGASINFO = 1 << 2;
+        /// `INET_DIAG_CONG`: name of the congestion control algorithm
+        const CONG = 1 << 3;
+        /// `INET_DIAG_TOS`: IPv4 type of service
+        const TOS = 1 << 4;
+        /// `INET_DIAG_TCLASS`: IPv6 traffic class
+        const TCLASS = 1 << 5;
+        /// `INET_DIAG_SKMEMINFO`: socket memory usage
+        const SKMEMINFO = 1 << 6;
+        /// `INET_DIAG_SHUTDOWN`: shutdown state
+        const SHUTDOWN = 1 << 7;
+    }
+}
+
+/// Attribute types of the replies to an
+/// [`InetDiagReq`](struct.InetDiagReq.html).
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InetDiagAttr {
+    /// `struct inet_diag_meminfo`
+    MemInfo = 1,
+    /// Protocol information, such as a `struct tcp_info`
+    Info = 2,
+    /// `struct tcpvegas_info`
+    VegasInfo = 3,
+    /// Name of the congestion control algorithm
+    Cong = 4,
+    /// IPv4 type of service, as a `u8`
+    Tos = 5,
+    /// IPv6 traffic class, as a `u8`
+    Tclass = 6,
+    /// Socket memory usage, as an array of `u32`
+    SkMemInfo = 7,
+    /// Shutdown state, as a `u8`
+    Shutdown = 8,
+}
+
+/// Identity of an IPv4 or IPv6 socket (`struct inet_diag_sockid`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct InetDiagSockId {
+    sport: u16,
+    dport: u16,
+    src: [u32; 4],
+    dst: [u32; 4],
+    if_: u32,
+    cookie: [u32; 2],
+}
+
+impl InetDiagSockId {
+    /// The local port.
+    pub fn src_port(&self) -> u16 {
+        u16::from_be(self.sport)
+    }
+
+    /// The remote port.
+    pub fn dst_port(&self) -> u16 {
+        u16::from_be(self.dport)
+    }
+
+    /// The index of the interface the socket is bound to, or zero.
+    pub fn interface(&self) -> u32 {
+        self.if_
+    }
+
+    /// The kernel's cookie for the socket, as returned by
+    /// [`SocketCookie`](../../../sys/socket/sockopt/struct.SocketCookie.html).
+    pub fn cookie(&self) -> Option<u64> {
+        cookie(self.cookie)
+    }
+
+    fn addr(family: u8, words: &[u32; 4]) -> IpAddr {
+        // The words are in network byte order, so their in-memory bytes are
+        // the address
+        let mut octets = [0u8; 16];
+        for (chunk, word) in octets.chunks_mut(4).zip(words.iter()) {
+            chunk.copy_from_slice(&word.to_ne_bytes());
+        }
+        if i32::from(family) == libc::AF_INET {
+            IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+    }
+}
+
+/// Request for IPv4 or IPv6 sockets (`struct inet_diag_req_v2`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InetDiagReq {
+    family: u8,
+    protocol: u8,
+    ext: u8,
+    pad: u8,
+    states: u32,
+    id: InetDiagSockId,
+}
+
+impl InetDiagReq {
+    /// Request the sockets of `family` (`Inet` or `Inet6`) and `protocol`
+    /// (such as `Tcp` or `Udp`) that are in one of `states`.
+    ///
+    /// Fails with `EINVAL` for other families, and for protocols whose number
+    /// doesn't fit in the request, such as `MptcpTcp`.
+    pub fn new(family: AddressFamily, protocol: SockProtocol, states: TcpStates)
+        -> Result<InetDiagReq>
+    {
+        match family {
+            AddressFamily::Inet | AddressFamily::Inet6 => (),
+            _ => return Err(Error::Sys(Errno::EINVAL)),
+        }
+        let protocol = u8::try_from(protocol as i32)
+            .map_err(|_| Error::Sys(Errno::EINVAL))?;
+        Ok(InetDiagReq {
+            family: family as u8,
+            protocol,
+            ext: 0,
+            pad: 0,
+            states: states.bits(),
+            id: InetDiagSockId::default(),
+        })
+    }
+
+    /// Include the given optional attributes in the replies.
+    pub fn extensions(mut self, ext: InetDiagExt) -> InetDiagReq {
+        self.ext = ext.bits();
+        self
+    }
+
+    /// Build a dump request, to be sent with [`dump`](../fn.dump.html).
+    pub fn message(&self) -> NlMsgBuilder {
+        message(self)
+    }
+}
+
+/// Reply describing an IPv4 or IPv6 socket (`struct inet_diag_msg`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InetDiagMsg {
+    family: u8,
+    state: u8,
+    timer: u8,
+    retrans: u8,
+    id: InetDiagSockId,
+    expires: u32,
+    rqueue: u32,
+    wqueue: u32,
+    uid: u32,
+    inode: u32,
+}
+
+impl InetDiagMsg {
+    /// Decode a reply to an [`InetDiagReq`](struct.InetDiagReq.html),
+    /// returning it with its attributes (see
+    /// [`InetDiagAttr`](enum.InetDiagAttr.html)).
+    ///
+    /// Fails with `EINVAL` if the message is too short.
+    pub fn parse<'a>(msg: &NlMsg<'a>) -> Result<(InetDiagMsg, NlAttrs<'a>)> {
+        parse_header(msg)
+    }
+
+    /// The address family of the socket.
+    pub fn family(&self) -> Option<AddressFamily> {
+        AddressFamily::from_i32(i32::from(self.family))
+    }
+
+    /// The state of the socket.
+    pub fn state(&self) -> Option<TcpState> {
+        TcpState::from_u8(self.state)
+    }
+
+    /// The identity of the socket, including its ports.
+    pub fn id(&self) -> &InetDiagSockId {
+        &self.id
+    }
+
+    /// The local address.
+    pub fn src_addr(&self) -> IpAddr {
+        InetDiagSockId::addr(self.family, &self.id.src)
+    }
+
+    /// The remote address.
+    pub fn dst_addr(&self) -> IpAddr {
+        InetDiagSockId::addr(self.family, &self.id.dst)
+    }
+
+    /// The kind of pending TCP timer, or zero if none.
+    pub fn timer(&self) -> u8 {
+        self.timer
+    }
+
+    /// The number of retransmissions or keepalive probes sent so far.
+    pub fn retransmits(&self) -> u8 {
+        self.retrans
+    }
+
+    /// Milliseconds until the pending timer expires.
+    pub fn expires(&self) -> u32 {
+        self.expires
+    }
+
+    /// For listening sockets, the number of pending connections; otherwise
+    /// the amount of unread data.
+    pub fn rqueue(&self) -> u32 {
+        self.rqueue
+    }
+
+    /// For listening sockets, the backlog length; otherwise the amount of
+    /// unsent or unacknowledged data.
+    pub fn wqueue(&self) -> u32 {
+        self.wqueue
+    }
+
+    /// The user ID of the socket's owner.
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// The inode number of the socket.
+    pub fn inode(&self) -> u32 {
+        self.inode
+    }
+}
+
+bitflags::bitflags! {
+    /// Optional attributes to include in the replies to a
+    /// [`UnixDiagReq`](struct.UnixDiagReq.html).
+    pub struct UnixDiagShow: u32 {
+        /// `UNIX_DIAG_NAME`: the bound address
+        const NAME = 0x01;
+        /// `UNIX_DIAG_VFS`: device and inode of a socket file
+        const VFS = 0x02;
+        /// `UNIX_DIAG_PEER`: inode of the peer
+        const PEER = 0x04;
+        /// `UNIX_DIAG_ICONS`: inodes of pending connections
+        const ICONS = 0x08;
+        /// `UNIX_DIAG_RQLEN`: queue lengths
+        const RQLEN = 0x10;
+        /// `UNIX_DIAG_MEMINFO`: socket memory usage
+        const MEMINFO = 0x20;
+        /// `UNIX_DIAG_UID`: user ID of the owner
+        const UID = 0x40;
+    }
+}
+
+/// Attribute types of the replies to a
+/// [`UnixDiagReq`](struct.UnixDiagReq.html).
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnixDiagAttr {
+    /// The bound address, without the terminating NUL
+    Name = 0,
+    /// `struct unix_diag_vfs`
+    Vfs = 1,
+    /// Inode of the peer, as a `u32`
+    Peer = 2,
+    /// Inodes of pending connections, as an array of `u32`
+    Icons = 3,
+    /// `struct unix_diag_rqlen`
+    RqLen = 4,
+    /// Socket memory usage, as an array of `u32`
+    MemInfo = 5,
+    /// Shutdown state, as a `u8`
+    Shutdown = 6,
+    /// User ID of the owner, as a `u32`
+    Uid = 7,
+}
+
+/// Request for UNIX domain sockets (`struct unix_diag_req`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct UnixDiagReq {
+    family: u8,
+    protocol: u8,
+    pad: u16,
+    states: u32,
+    ino: u32,
+    show: u32,
+    cookie: [u32; 2],
+}
+
+impl UnixDiagReq {
+    /// Request the UNIX domain sockets that are in one of `states`, with the
+    /// attributes selected by `show`.
+    pub fn new(states: TcpStates, show: UnixDiagShow) -> UnixDiagReq {
+        UnixDiagReq {
+            family: AddressFamily::Unix as u8,
+            protocol: 0,
+            pad: 0,
+            states: states.bits(),
+            ino: 0,
+            show: show.bits(),
+            cookie: [INET_DIAG_NOCOOKIE; 2],
+        }
+    }
+
+    /// Build a dump request, to be sent with [`dump`](../fn.dump.html).
+    pub fn message(&self) -> NlMsgBuilder {
+        message(self)
+    }
+}
+
+/// Reply describing a UNIX domain socket (`struct unix_diag_msg`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct UnixDiagMsg {
+    family: u8,
+    type_: u8,
+    state: u8,
+    pad: u8,
+    ino: u32,
+    cookie: [u32; 2],
+}
+
+impl UnixDiagMsg {
+    /// Decode a reply to a [`UnixDiagReq`](struct.UnixDiagReq.html),
+    /// returning it with its attributes (see
+    /// [`UnixDiagAttr`](enum.UnixDiagAttr.html)).
+    ///
+    /// Fails with `EINVAL` if the message is too short.
+    pub fn parse<'a>(msg: &NlMsg<'a>) -> Result<(UnixDiagMsg, NlAttrs<'a>)> {
+        parse_header(msg)
+    }
+
+    /// The socket type, such as `libc::SOCK_STREAM`.
+    pub fn sock_type(&self) -> u8 {
+        self.type_
+    }
+
+    /// The state of the socket.
+    pub fn state(&self) -> Option<TcpState> {
+        TcpState::from_u8(self.state)
+    }
+
+    /// The inode number of the socket.
+    pub fn inode(&self) -> u32 {
+        self.ino
+    }
+
+    /// The kernel's cookie for the socket.
+    pub fn cookie(&self) -> Option<u64> {
+        cookie(self.cookie)
+    }
+}
+
+bitflags::bitflags! {
+    /// Optional attributes to include in the replies to a
+    /// [`PacketDiagReq`](struct.PacketDiagReq.html).
+    pub struct PacketDiagShow: u32 {
+        /// `PACKET_DIAG_INFO`: basic information
+        const INFO = 0x01;
+        /// `PACKET_DIAG_MCLIST`: multicast memberships
+        const MCLIST = 0x02;
+        /// `PACKET_DIAG_RX_RING` and `PACKET_DIAG_TX_RING`: ring parameters
+        const RING_CFG = 0x04;
+        /// `PACKET_DIAG_FANOUT`: fanout group
+        const FANOUT = 0x08;
+        /// `PACKET_DIAG_MEMINFO`: socket memory usage
+        const MEMINFO = 0x10;
+        /// `PACKET_DIAG_FILTER`: attached socket filter
+        const FILTER = 0x20;
+    }
+}
+
+/// Attribute types of the replies to a
+/// [`PacketDiagReq`](struct.PacketDiagReq.html).
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PacketDiagAttr {
+    /// `struct packet_diag_info`
+    Info = 0,
+    /// Array of `struct packet_diag_mclist`
+    McList = 1,
+    /// `struct packet_diag_ring` of the receive ring
+    RxRing = 2,
+    /// `struct packet_diag_ring` of the transmit ring
+    TxRing = 3,
+    /// Fanout group, as a `u32`
+    Fanout = 4,
+    /// User ID of the owner, as a `u32`
+    Uid = 5,
+    /// Socket memory usage, as an array of `u32`
+    MemInfo = 6,
+    /// Attached socket filter, as an array of `struct sock_filter`
+    Filter = 7,
+}
+
+/// Request for packet sockets (`struct packet_diag_req`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PacketDiagReq {
+    family: u8,
+    protocol: u8,
+    pad: u16,
+    ino: u32,
+    show: u32,
+    cookie: [u32; 2],
+}
+
+impl PacketDiagReq {
+    /// Request all packet sockets, with the attributes selected by `show`.
+    pub fn new(show: PacketDiagShow) -> PacketDiagReq {
+        PacketDiagReq {
+            family: AddressFamily::Packet as u8,
+            protocol: 0,
+            pad: 0,
+            ino: 0,
+            show: show.bits(),
+            cookie: [INET_DIAG_NOCOOKIE; 2],
+        }
+    }
+
+    /// Build a dump request, to be sent with [`dump`](../fn.dump.html).
+    pub fn message(&self) -> NlMsgBuilder {
+        message(self)
+    }
+}
+
+/// Reply describing a packet socket (`struct packet_diag_msg`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PacketDiagMsg {
+    family: u8,
+    type_: u8,
+    num: u16,
+    ino: u32,
+    cookie: [u32; 2],
+}
+
+impl PacketDiagMsg {
+    /// Decode a reply to a [`PacketDiagReq`](struct.PacketDiagReq.html),
+    /// returning it with its attributes (see
+    /// [`PacketDiagAttr`](enum.PacketDiagAttr.html)).
+    ///
+    /// Fails with `EINVAL` if the message is too short.
+    pub fn parse<'a>(msg: &NlMsg<'a>) -> Result<(PacketDiagMsg, NlAttrs<'a>)> {
+        parse_header(msg)
+    }
+
+    /// The socket type, `libc::SOCK_RAW` or `libc::SOCK_DGRAM`.
+    pub fn sock_type(&self) -> u8 {
+        self.type_
+    }
+
+    /// The protocol the socket is bound to, in network byte order as given
+    /// to [`socket`](../../../sys/socket/fn.socket.html).
+    pub fn protocol(&self) -> u16 {
+        self.num
+    }
+
+    /// The inode number of the socket.
+    pub fn inode(&self) -> u32 {
+        self.ino
+    }
+
+    /// The kernel's cookie for the socket.
+    pub fn cookie(&self) -> Option<u64> {
+        cookie(self.cookie)
+    }
+}
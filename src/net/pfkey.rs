@@ -0,0 +1,499 @@
+//! IPsec key management through PF_KEY version 2 sockets.
+//!
+//! A PF_KEY socket exchanges messages with the kernel's security association
+//! database: each starts with a `struct sadb_msg` header, followed by
+//! extensions that each start with a `struct sadb_ext`.  All lengths are in
+//! units of 8 bytes.  Using PF_KEY requires `CAP_NET_ADMIN` and the `af_key`
+//! kernel module.
+//!
+//! [Further reading](https://tools.ietf.org/html/rfc2367)
+//!
+//! # Examples
+//!
+//! Dump the ESP security associations:
+//!
+//! ```no_run
+//! use nix::net::pfkey::*;
+//! use nix::sys::socket::{recv, send, MsgFlags, SockFlag};
+//!
+//! let fd = socket(SockFlag::SOCK_CLOEXEC).unwrap();
+//! let request = SadbMsgBuilder::new(SadbMsgType::Dump, SaType::Esp, 1);
+//! send(&fd, request.as_bytes(), MsgFlags::empty()).unwrap();
+//! let mut buf = vec![0u8; 64 * 1024];
+//! loop {
+//!     let len = recv(&fd, &mut buf, MsgFlags::empty()).unwrap();
+//!     let (msg, exts) = SadbMsg::parse(&buf[..len]).unwrap();
+//!     for ext in exts {
+//!         if let Some(sa) = ext.sa() {
+//!             println!("SPI {:#x}", sa.spi());
+//!         }
+//!     }
+//!     // The last message of a dump has sequence number zero
+//!     if msg.seq() == 0 {
+//!         break;
+//!     }
+//! }
+//! ```
+
+use libc::{self, c_int};
+use std::convert::TryFrom;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use crate::{Error, Result};
+use crate::errno::Errno;
+use crate::sys::socket::{self, sockaddr_storage_to_addr, AddressFamily, SockAddr, SockFlag,
+                         SockType};
+
+// From <linux/pfkeyv2.h>; not yet in libc
+const PF_KEY_V2: c_int = 2;
+
+/// Lengths in PF_KEY messages count multiples of this
+const SADB_ALIGN: usize = 8;
+/// Length of `struct sadb_ext`
+const SADB_EXT_HDRLEN: usize = 4;
+/// Length of `struct sadb_address` and `struct sadb_supported`
+const SADB_EXT_HDRLEN_LONG: usize = 8;
+
+fn align(len: usize) -> usize {
+    (len + SADB_ALIGN - 1) & !(SADB_ALIGN - 1)
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes(<[u8; 2]>::try_from(&buf[offset..offset + 2]).unwrap())
+}
+
+/// Create a PF_KEY version 2 socket.
+///
+/// Fails with `EAFNOSUPPORT` if the kernel lacks PF_KEY support.
+pub fn socket(flags: SockFlag) -> Result<RawFd> {
+    socket::socket(AddressFamily::Key, SockType::Raw, flags, PF_KEY_V2)
+}
+
+/// Type of a PF_KEY message.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SadbMsgType {
+    /// Allocate an SPI for a larval security association
+    GetSpi = 1,
+    /// Complete a larval security association
+    Update = 2,
+    /// Add a complete security association
+    Add = 3,
+    /// Delete a security association
+    Delete = 4,
+    /// Get a security association
+    Get = 5,
+    /// Request from the kernel to negotiate a security association
+    Acquire = 6,
+    /// Register to receive `Acquire` messages for an SA type
+    Register = 7,
+    /// Notification that a security association expired
+    Expire = 8,
+    /// Delete all security associations of an SA type
+    Flush = 9,
+    /// List all security associations of an SA type
+    Dump = 10,
+}
+
+impl SadbMsgType {
+    fn from_u8(msg_type: u8) -> Option<SadbMsgType> {
+        use self::SadbMsgType::*;
+        let msg_type = match msg_type {
+            1 => GetSpi,
+            2 => Update,
+            3 => Add,
+            4 => Delete,
+            5 => Get,
+            6 => Acquire,
+            7 => Register,
+            8 => Expire,
+            9 => Flush,
+            10 => Dump,
+            _ => return None,
+        };
+        Some(msg_type)
+    }
+}
+
+/// Type of security association.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SaType {
+    /// All types, for `Register`, `Flush` and `Dump`
+    Unspec = 0,
+    /// IPsec Authentication Header
+    Ah = 2,
+    /// IPsec Encapsulating Security Payload
+    Esp = 3,
+    /// RSVP authentication
+    Rsvp = 5,
+    /// OSPFv2 authentication
+    OspfV2 = 6,
+    /// RIPv2 authentication
+    RipV2 = 7,
+    /// Mobile IP authentication
+    Mip = 8,
+    /// IP payload compression
+    IpComp = 9,
+}
+
+/// Type of a PF_KEY extension.
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SadbExtType {
+    /// `struct sadb_sa`
+    Sa = 1,
+    /// `struct sadb_lifetime` with the current usage
+    LifetimeCurrent = 2,
+    /// `struct sadb_lifetime` with the hard limits
+    LifetimeHard = 3,
+    /// `struct sadb_lifetime` with the soft limits
+    LifetimeSoft = 4,
+    /// `struct sadb_address` with the source address
+    AddressSrc = 5,
+    /// `struct sadb_address` with the destination address
+    AddressDst = 6,
+    /// `struct sadb_address` with the proxy address
+    AddressProxy = 7,
+    /// `struct sadb_key` with the authentication key
+    KeyAuth = 8,
+    /// `struct sadb_key` with the encryption key
+    KeyEncrypt = 9,
+    /// `struct sadb_ident` with the source identity
+    IdentitySrc = 10,
+    /// `struct sadb_ident` with the destination identity
+    IdentityDst = 11,
+    /// `struct sadb_sens`
+    Sensitivity = 12,
+    /// `struct sadb_prop`
+    Proposal = 13,
+    /// `struct sadb_supported` with the authentication algorithms
+    SupportedAuth = 14,
+    /// `struct sadb_supported` with the encryption algorithms
+    SupportedEncrypt = 15,
+    /// `struct sadb_spirange`
+    SpiRange = 16,
+}
+
+/// Builder for a PF_KEY message.
+///
+/// The length field of the header is kept up to date as extensions are
+/// added, so [`as_bytes`](#method.as_bytes) may be sent at any point.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SadbMsgBuilder {
+    buf: Vec<u8>,
+}
+
+impl SadbMsgBuilder {
+    /// Start a message with the given type, SA type and sequence number.
+    /// The process ID is filled in from [`getpid`](../../unistd/fn.getpid.html).
+    pub fn new(msg_type: SadbMsgType, sa_type: SaType, seq: u32) -> SadbMsgBuilder {
+        let header = SadbMsg {
+            version: PF_KEY_V2 as u8,
+            msg_type: msg_type as u8,
+            errno: 0,
+            sa_type: sa_type as u8,
+            len: (mem::size_of::<SadbMsg>() / SADB_ALIGN) as u16,
+            reserved: 0,
+            seq,
+            pid: crate::unistd::getpid().as_raw() as u32,
+        };
+        // SadbMsg is a plain integer struct without padding
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&header as *const SadbMsg as *const u8,
+                                       mem::size_of::<SadbMsg>())
+        };
+        SadbMsgBuilder { buf: bytes.to_vec() }
+    }
+
+    /// Append an extension.  `data` is everything after the 4-byte
+    /// `struct sadb_ext` header, which is written by this method.  Padding
+    /// is added to reach a multiple of 8 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message grows beyond the 512 KiB a PF_KEY message can
+    /// describe.
+    pub fn ext(&mut self, ext_type: SadbExtType, data: &[u8]) -> &mut Self {
+        let len = align(SADB_EXT_HDRLEN + data.len());
+        self.buf.extend_from_slice(&((len / SADB_ALIGN) as u16).to_ne_bytes());
+        self.buf.extend_from_slice(&(ext_type as u16).to_ne_bytes());
+        self.buf.extend_from_slice(data);
+        let total = align(self.buf.len());
+        assert!(total / SADB_ALIGN <= 0xffff, "PF_KEY message too long");
+        self.buf.resize(total, 0);
+        let words = (total / SADB_ALIGN) as u16;
+        self.buf[4..6].copy_from_slice(&words.to_ne_bytes());
+        self
+    }
+
+    /// Append a `struct sadb_address` extension holding `addr`.  `proto` is
+    /// the upper layer protocol, or zero for any.
+    pub fn address(&mut self, ext_type: SadbExtType, proto: u8, prefix_len: u8,
+                   addr: &SockAddr) -> &mut Self {
+        let (ptr, len) = addr.as_ffi_pair();
+        // as_ffi_pair returns a valid pointer to len bytes
+        let sa = unsafe {
+            std::slice::from_raw_parts(ptr as *const libc::sockaddr as *const u8, len as usize)
+        };
+        let mut data = Vec::with_capacity(4 + sa.len());
+        data.extend_from_slice(&[proto, prefix_len, 0, 0]);
+        data.extend_from_slice(sa);
+        self.ext(ext_type, &data)
+    }
+
+    /// The encoded message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Consume the builder, returning the encoded message.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Header of a PF_KEY message (`struct sadb_msg`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SadbMsg {
+    version: u8,
+    msg_type: u8,
+    errno: u8,
+    sa_type: u8,
+    len: u16,
+    reserved: u16,
+    seq: u32,
+    pid: u32,
+}
+
+impl SadbMsg {
+    /// Parse a message received from a PF_KEY socket, returning the header
+    /// and an iterator over the extensions.
+    ///
+    /// Fails with `EINVAL` if `buf` is shorter than the header or than the
+    /// length it announces.
+    pub fn parse(buf: &[u8]) -> Result<(SadbMsg, SadbExts)> {
+        if buf.len() < mem::size_of::<SadbMsg>() {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        // The length was checked above, and SadbMsg is a plain integer struct
+        let header = unsafe { ptr::read_unaligned(buf.as_ptr() as *const SadbMsg) };
+        let len = usize::from(header.len) * SADB_ALIGN;
+        if len < mem::size_of::<SadbMsg>() || len > buf.len() {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        Ok((header, SadbExts { buf: &buf[mem::size_of::<SadbMsg>()..len] }))
+    }
+
+    /// The PF_KEY protocol version, 2.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The message type.
+    pub fn msg_type(&self) -> Option<SadbMsgType> {
+        SadbMsgType::from_u8(self.msg_type)
+    }
+
+    /// The error reported by the kernel in a reply, if any.
+    pub fn error(&self) -> Option<Errno> {
+        if self.errno == 0 {
+            None
+        } else {
+            Some(Errno::from_i32(i32::from(self.errno)))
+        }
+    }
+
+    /// The SA type, as a raw [`SaType`](enum.SaType.html) value.
+    pub fn sa_type(&self) -> u8 {
+        self.sa_type
+    }
+
+    /// The sequence number.  The kernel copies it from requests, and sets it
+    /// to zero in the last message of a dump.
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// The process ID of the sender, or zero for kernel messages.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+/// Iterator over the extensions of a PF_KEY message.
+///
+/// Iteration stops at the end of the message or at the first malformed
+/// extension.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SadbExts<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for SadbExts<'a> {
+    type Item = SadbExt<'a>;
+
+    fn next(&mut self) -> Option<SadbExt<'a>> {
+        if self.buf.len() < SADB_EXT_HDRLEN {
+            return None;
+        }
+        let len = usize::from(read_u16(self.buf, 0)) * SADB_ALIGN;
+        if len < SADB_EXT_HDRLEN || len > self.buf.len() {
+            self.buf = &[];
+            return None;
+        }
+        let ext = SadbExt { buf: &self.buf[..len] };
+        self.buf = &self.buf[len..];
+        Some(ext)
+    }
+}
+
+/// An extension of a PF_KEY message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SadbExt<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> SadbExt<'a> {
+    /// The raw extension type.
+    pub fn ext_type(&self) -> u16 {
+        read_u16(self.buf, 2)
+    }
+
+    /// The data following the `struct sadb_ext` header, including padding.
+    pub fn data(&self) -> &'a [u8] {
+        &self.buf[SADB_EXT_HDRLEN..]
+    }
+
+    /// Decode a `Sa` extension.
+    pub fn sa(&self) -> Option<SadbSa> {
+        if self.ext_type() != SadbExtType::Sa as u16
+            || self.buf.len() < mem::size_of::<SadbSa>()
+        {
+            return None;
+        }
+        // The length was checked above, and SadbSa is a plain integer struct
+        Some(unsafe { ptr::read_unaligned(self.buf.as_ptr() as *const SadbSa) })
+    }
+
+    /// Decode an `AddressSrc`, `AddressDst` or `AddressProxy` extension.
+    pub fn address(&self) -> Option<SockAddr> {
+        let ext_type = self.ext_type();
+        if ext_type < SadbExtType::AddressSrc as u16
+            || ext_type > SadbExtType::AddressProxy as u16
+            || self.buf.len() <= SADB_EXT_HDRLEN_LONG
+        {
+            return None;
+        }
+        let sa = &self.buf[SADB_EXT_HDRLEN_LONG..];
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let len = sa.len().min(mem::size_of::<libc::sockaddr_storage>());
+        // The length is limited to the size of the storage
+        unsafe {
+            ptr::copy_nonoverlapping(sa.as_ptr(),
+                                     &mut storage as *mut _ as *mut u8,
+                                     len);
+        }
+        sockaddr_storage_to_addr(&storage, len).ok()
+    }
+
+    /// Decode a `SupportedAuth` or `SupportedEncrypt` extension.
+    pub fn algorithms(&self) -> Option<Vec<SadbAlg>> {
+        let ext_type = self.ext_type();
+        if ext_type != SadbExtType::SupportedAuth as u16
+            && ext_type != SadbExtType::SupportedEncrypt as u16
+        {
+            return None;
+        }
+        let algs = self.buf[SADB_EXT_HDRLEN_LONG.min(self.buf.len())..]
+            .chunks(mem::size_of::<SadbAlg>())
+            .filter(|chunk| chunk.len() == mem::size_of::<SadbAlg>())
+            // Each chunk holds a whole SadbAlg, a plain integer struct
+            .map(|chunk| unsafe { ptr::read_unaligned(chunk.as_ptr() as *const SadbAlg) })
+            .collect();
+        Some(algs)
+    }
+}
+
+/// Security association extension (`struct sadb_sa`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SadbSa {
+    len: u16,
+    exttype: u16,
+    spi: u32,
+    replay: u8,
+    state: u8,
+    auth: u8,
+    encrypt: u8,
+    flags: u32,
+}
+
+impl SadbSa {
+    /// The Security Parameters Index.
+    pub fn spi(&self) -> u32 {
+        u32::from_be(self.spi)
+    }
+
+    /// The size of the replay window.
+    pub fn replay_window(&self) -> u8 {
+        self.replay
+    }
+
+    /// The state of the association: 0 while larval, 1 when mature, 2 when
+    /// dying and 3 when dead.
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// The authentication algorithm.
+    pub fn auth_alg(&self) -> u8 {
+        self.auth
+    }
+
+    /// The encryption algorithm.
+    pub fn encrypt_alg(&self) -> u8 {
+        self.encrypt
+    }
+
+    /// The `SADB_SAFLAGS_*` flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// An algorithm supported by the kernel (`struct sadb_alg`), as listed in the
+/// reply to a `Register` message.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SadbAlg {
+    id: u8,
+    ivlen: u8,
+    minbits: u16,
+    maxbits: u16,
+    reserved: u16,
+}
+
+impl SadbAlg {
+    /// The algorithm identifier.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The length of the initialization vector, in bits.
+    pub fn iv_len(&self) -> u8 {
+        self.ivlen
+    }
+
+    /// The smallest supported key length, in bits.
+    pub fn min_bits(&self) -> u16 {
+        self.minbits
+    }
+
+    /// The largest supported key length, in bits.
+    pub fn max_bits(&self) -> u16 {
+        self.maxbits
+    }
+}
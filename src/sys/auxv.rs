@@ -0,0 +1,50 @@
+//! Access to the auxiliary vector passed to the process by the kernel
+//!
+//! See [getauxval(3)](http://man7.org/linux/man-pages/man3/getauxval.3.html)
+use libc::{self, c_ulong};
+
+use crate::errno::Errno;
+
+libc_enum!{
+    /// Keys of the auxiliary vector entries that can be queried with
+    /// [`getauxval`](fn.getauxval.html).
+    #[repr(u32)]
+    pub enum AuxvType {
+        /// System page size in bytes.
+        AT_PAGESZ as u32,
+        /// Bit mask of architecture-specific CPU capabilities.
+        AT_HWCAP as u32,
+        /// Further architecture-specific CPU capabilities.
+        AT_HWCAP2 as u32,
+        /// Non-zero if the program is executed in secure mode, e.g. it is a
+        /// set-user-ID or set-group-ID binary.
+        AT_SECURE as u32,
+        /// Address of sixteen random bytes provided by the kernel.
+        AT_RANDOM as u32,
+    }
+}
+
+/// Retrieve a value from the auxiliary vector.
+///
+/// Returns `None` if the kernel did not supply an entry for `ty`.
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::auxv::{getauxval, AuxvType};
+///
+/// let page_size = getauxval(AuxvType::AT_PAGESZ).unwrap();
+/// assert!(page_size > 0);
+/// ```
+pub fn getauxval(ty: AuxvType) -> Option<c_ulong> {
+    Errno::clear();
+    let res = unsafe { libc::getauxval(ty as c_ulong) };
+
+    // A zero return value is ambiguous: it is also a legitimate value for
+    // several entries.  The C library sets ENOENT when the entry is missing.
+    if res == 0 && Errno::last() == Errno::ENOENT {
+        None
+    } else {
+        Some(res)
+    }
+}
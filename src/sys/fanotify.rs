@@ -0,0 +1,307 @@
+//! Monitoring and interception of filesystem access.
+//!
+//! Fanotify is a Linux-only API to monitor filesystem events.  Unlike
+//! [inotify](../inotify/index.html), every event comes with an open file
+//! descriptor for the accessed file, whole mounts or filesystems can be
+//! watched, and listeners can decide whether accesses are allowed.  All of
+//! this requires the `CAP_SYS_ADMIN` capability.
+//!
+//! For more documentation, please read [fanotify(7)](http://man7.org/linux/man-pages/man7/fanotify.7.html).
+//!
+//! # Examples
+//!
+//! Deny opening files below the mount point "/mnt":
+//! ```no_run
+//! # use nix::fcntl::OFlag;
+//! # use nix::sys::fanotify::{Fanotify, FanotifyResponse, InitFlags, MarkFlags, MaskFlags, Response};
+//! #
+//! let group = Fanotify::init(InitFlags::FAN_CLASS_CONTENT, OFlag::O_RDONLY).unwrap();
+//! group.mark(MarkFlags::FAN_MARK_ADD | MarkFlags::FAN_MARK_MOUNT,
+//!            MaskFlags::FAN_OPEN_PERM, None, Some("/mnt")).unwrap();
+//!
+//! loop {
+//!     for event in group.read_events().unwrap() {
+//!         if let Some(fd) = event.fd() {
+//!             // The file descriptor is closed when the event is dropped
+//!             group.write_response(FanotifyResponse::new(fd, Response::Deny)).unwrap();
+//!         }
+//!     }
+//! }
+//! ```
+
+use libc::{c_int, c_uint};
+use std::mem::size_of;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::ptr;
+use crate::errno::Errno;
+use crate::fcntl::{at_rawfd, OFlag};
+use crate::unistd::{close, read, write};
+use crate::{NixPath, Result};
+
+libc_bitflags! {
+    /// Configuration options for [`Fanotify::init`](struct.Fanotify.html#method.init).
+    pub struct InitFlags: c_uint {
+        /// Set the close-on-exec flag on the fanotify file descriptor.
+        FAN_CLOEXEC;
+        /// Make reading events non-blocking.
+        FAN_NONBLOCK;
+        /// Only receive notifications, after files have been accessed.
+        FAN_CLASS_NOTIF;
+        /// Receive permission events after the file contents are available.
+        FAN_CLASS_CONTENT;
+        /// Receive permission events before the file contents are available,
+        /// e.g. for hierarchical storage managers.
+        FAN_CLASS_PRE_CONTENT;
+        /// Remove the limit of 16384 events in the queue.
+        FAN_UNLIMITED_QUEUE;
+        /// Remove the limit of 8192 marks per user.
+        FAN_UNLIMITED_MARKS;
+    }
+}
+
+libc_bitflags! {
+    /// How [`Fanotify::mark`](struct.Fanotify.html#method.mark) changes the
+    /// marks of a fanotify group.
+    pub struct MarkFlags: c_uint {
+        /// Add the events in the mask to the mark.
+        FAN_MARK_ADD;
+        /// Remove the events in the mask from the mark.
+        FAN_MARK_REMOVE;
+        /// Mark a symbolic link itself rather than the file it refers to.
+        FAN_MARK_DONT_FOLLOW;
+        /// Fail with `ENOTDIR` if the path is not a directory.
+        FAN_MARK_ONLYDIR;
+        /// Mark the mount containing the path instead of the file.
+        FAN_MARK_MOUNT;
+        /// Mark the filesystem containing the path instead of the file.
+        FAN_MARK_FILESYSTEM;
+        /// Change the events to ignore rather than the events to report.
+        FAN_MARK_IGNORED_MASK;
+        /// Keep the ignore mask when the file is modified.
+        FAN_MARK_IGNORED_SURV_MODIFY;
+        /// Remove all marks of the kind given by `FAN_MARK_MOUNT` or
+        /// `FAN_MARK_FILESYSTEM`, or all inode marks without either.
+        FAN_MARK_FLUSH;
+    }
+}
+
+libc_bitflags! {
+    /// Events to watch for, and the events reported in a
+    /// [`FanotifyEvent`](struct.FanotifyEvent.html).
+    pub struct MaskFlags: u64 {
+        /// A file was read.
+        FAN_ACCESS;
+        /// A file was modified.
+        FAN_MODIFY;
+        /// A file opened for writing was closed.
+        FAN_CLOSE_WRITE;
+        /// A file opened read-only was closed.
+        FAN_CLOSE_NOWRITE;
+        /// A file was opened.
+        FAN_OPEN;
+        /// A file was opened to be executed.
+        FAN_OPEN_EXEC;
+        /// The event queue overflowed, and events were lost.
+        FAN_Q_OVERFLOW;
+        /// Permission to open a file was requested.
+        FAN_OPEN_PERM;
+        /// Permission to read a file was requested.
+        FAN_ACCESS_PERM;
+        /// Permission to open a file for execution was requested.
+        FAN_OPEN_EXEC_PERM;
+        /// Report events on directories too.
+        FAN_ONDIR;
+        /// Report events on the direct children of a marked directory.
+        FAN_EVENT_ON_CHILD;
+        /// A file was closed.
+        FAN_CLOSE;
+    }
+}
+
+/// The decision on a permission event.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Response {
+    /// Allow the access.
+    Allow = libc::FAN_ALLOW,
+    /// Deny the access, which fails with `EPERM`.
+    Deny = libc::FAN_DENY,
+}
+
+/// A fanotify group.  This is also a file descriptor, you can feed it to
+/// other interfaces consuming file descriptors, epoll for example.
+#[derive(Debug, Clone, Copy)]
+pub struct Fanotify {
+    fd: RawFd,
+}
+
+/// A single fanotify event.
+///
+/// The file descriptor of the accessed file, if any, is owned by the event
+/// and closed when it is dropped, unless the event has a metadata version
+/// this module doesn't know.
+///
+/// For more documentation see, [fanotify(7)](http://man7.org/linux/man-pages/man7/fanotify.7.html).
+#[derive(Debug)]
+pub struct FanotifyEvent(libc::fanotify_event_metadata);
+
+/// The answer to a permission event, sent with
+/// [`Fanotify::write_response`](struct.Fanotify.html#method.write_response).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FanotifyResponse(libc::fanotify_response);
+
+impl Fanotify {
+    /// Create a new fanotify group.
+    ///
+    /// `event_f_flags` are the status flags with which the file descriptors
+    /// of events are opened, such as `O_RDONLY` and `O_CLOEXEC`.
+    ///
+    /// For more information see, [fanotify_init(2)](http://man7.org/linux/man-pages/man2/fanotify_init.2.html).
+    pub fn init(flags: InitFlags, event_f_flags: OFlag) -> Result<Fanotify> {
+        let res = Errno::result(unsafe {
+            libc::fanotify_init(flags.bits(), event_f_flags.bits() as c_uint)
+        });
+
+        res.map(|fd| Fanotify { fd })
+    }
+
+    /// Add, remove or change the mark on a file, mount or filesystem.
+    ///
+    /// The object is given by `path`, relative to `dirfd` like for
+    /// [`openat`](../../fcntl/fn.openat.html), or by `dirfd` itself if `path`
+    /// is `None`.
+    ///
+    /// For more information see, [fanotify_mark(2)](http://man7.org/linux/man-pages/man2/fanotify_mark.2.html).
+    pub fn mark<P: ?Sized + NixPath>(self,
+                                     flags: MarkFlags,
+                                     mask: MaskFlags,
+                                     dirfd: Option<RawFd>,
+                                     path: Option<&P>)
+                                     -> Result<()>
+    {
+        let res = match path {
+            Some(path) => path.with_nix_path(|cstr| unsafe {
+                libc::fanotify_mark(self.fd, flags.bits(), mask.bits(),
+                                    at_rawfd(dirfd), cstr.as_ptr())
+            })?,
+            None => unsafe {
+                libc::fanotify_mark(self.fd, flags.bits(), mask.bits(),
+                                    at_rawfd(dirfd), ptr::null())
+            },
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Reads a collection of events from the fanotify group. This call can
+    /// either be blocking or non blocking depending on whether FAN_NONBLOCK
+    /// was set at initialization.
+    ///
+    /// Returns as many events as available. If the call was non blocking and
+    /// no events could be read then the EAGAIN error is returned.
+    pub fn read_events(self) -> Result<Vec<FanotifyEvent>> {
+        let metadata_size = size_of::<libc::fanotify_event_metadata>();
+        const BUFSIZ: usize = 4096;
+        let mut buffer = [0u8; BUFSIZ];
+        let mut events = Vec::new();
+        let mut offset = 0;
+
+        let nread = read(self.fd, &mut buffer)?;
+
+        while (nread - offset) >= metadata_size {
+            let metadata = unsafe {
+                ptr::read_unaligned(
+                    buffer.as_ptr().add(offset) as *const libc::fanotify_event_metadata
+                )
+            };
+            // event_len covers any information records after the metadata
+            let event_len = metadata.event_len as usize;
+
+            events.push(FanotifyEvent(metadata));
+
+            if event_len < metadata_size {
+                break;
+            }
+            offset += event_len;
+        }
+
+        Ok(events)
+    }
+
+    /// Allow or deny the access reported by a permission event.
+    ///
+    /// Every permission event must be answered, or the process accessing the
+    /// file will wait forever.
+    pub fn write_response(self, response: FanotifyResponse) -> Result<()> {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &response.0 as *const libc::fanotify_response as *const u8,
+                size_of::<libc::fanotify_response>(),
+            )
+        };
+
+        write(self.fd, bytes).map(drop)
+    }
+}
+
+impl AsRawFd for Fanotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl FromRawFd for Fanotify {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Fanotify { fd }
+    }
+}
+
+impl FanotifyEvent {
+    /// Whether the event uses the metadata version this module was written
+    /// for.  If not, the other fields may not be meaningful.
+    pub fn check_version(&self) -> bool {
+        self.0.vers == libc::FANOTIFY_METADATA_VERSION
+    }
+
+    /// The events that occurred.
+    pub fn mask(&self) -> MaskFlags {
+        MaskFlags::from_bits_truncate(self.0.mask)
+    }
+
+    /// An open file descriptor for the accessed file, or `None` for queue
+    /// overflow events.
+    ///
+    /// The file descriptor is closed when the event is dropped.
+    pub fn fd(&self) -> Option<RawFd> {
+        if self.0.fd == libc::FAN_NOFD {
+            None
+        } else {
+            Some(self.0.fd)
+        }
+    }
+
+    /// The process or, with `FAN_REPORT_TID`, the thread that caused the
+    /// event.
+    pub fn pid(&self) -> i32 {
+        self.0.pid
+    }
+}
+
+impl Drop for FanotifyEvent {
+    fn drop(&mut self) {
+        // With another metadata version, fd may not be a descriptor at all
+        if self.check_version() && self.0.fd != libc::FAN_NOFD {
+            let _ = close(self.0.fd);
+        }
+    }
+}
+
+impl FanotifyResponse {
+    /// Answer the permission event whose file descriptor is `fd`.
+    pub fn new(fd: RawFd, response: Response) -> FanotifyResponse {
+        FanotifyResponse(libc::fanotify_response {
+            fd: fd as c_int,
+            response: response as u32,
+        })
+    }
+}
@@ -5,12 +5,29 @@ use crate::errno::Errno;
 use std::ffi::CStr;
 
 libc_bitflags!(
+    /// Options for [`memfd_create`](fn.memfd_create.html).
     pub struct MemFdCreateFlag: libc::c_uint {
+        /// Set the close-on-exec flag on the new file descriptor.
         MFD_CLOEXEC;
+        /// Allow seals to be added to the file with `fcntl`'s `F_ADD_SEALS`.
+        ///
+        /// Without this flag, the file starts out with `F_SEAL_SEAL` set.
         MFD_ALLOW_SEALING;
     }
 );
 
+/// Create an anonymous file that lives in memory and return a file
+/// descriptor for it.
+///
+/// `name` is only used for display, e.g. in `/proc/self/fd`.  The file can be
+/// shared with another process by passing the descriptor over a Unix socket;
+/// sealing it first with
+/// [`FcntlArg::F_ADD_SEALS`](../../fcntl/enum.FcntlArg.html#variant.F_ADD_SEALS)
+/// lets the receiver trust that it will not change under it.
+///
+/// # References
+///
+/// [memfd_create(2)](http://man7.org/linux/man-pages/man2/memfd_create.2.html)
 pub fn memfd_create(name: &CStr, flags: MemFdCreateFlag) -> Result<RawFd> {
     let res = unsafe {
         libc::syscall(libc::SYS_memfd_create, name.as_ptr(), flags.bits())
@@ -6,6 +6,9 @@
           target_os = "netbsd"))]
 pub mod aio;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod auxv;
+
 #[cfg(any(target_os = "android", target_os = "linux"))]
 pub mod epoll;
 
@@ -20,6 +23,9 @@ pub mod event;
 #[cfg(target_os = "linux")]
 pub mod eventfd;
 
+#[cfg(target_os = "linux")]
+pub mod fanotify;
+
 #[cfg(any(target_os = "android",
           target_os = "dragonfly",
           target_os = "freebsd",
@@ -94,6 +100,9 @@ pub mod termios;
 
 pub mod time;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod uevent;
+
 pub mod uio;
 
 pub mod utsname;
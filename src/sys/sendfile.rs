@@ -33,6 +33,29 @@ pub fn sendfile(
     Errno::result(ret).map(|r| r as usize)
 }
 
+/// Copy up to `count` bytes to `out_fd` from `in_fd` starting at `offset`.
+///
+/// Like [`sendfile`](fn.sendfile.html), but always takes a 64-bit offset, so
+/// files larger than 2 GiB can be sent from 32-bit platforms too.
+///
+/// Returns a `Result` with the number of bytes written.  If `offset` is
+/// `Some`, it is updated to point to the byte after the last byte copied.
+///
+/// For more information, see [the sendfile(2) man page.](http://man7.org/linux/man-pages/man2/sendfile.2.html)
+#[cfg(target_os = "linux")]
+pub fn sendfile64(
+    out_fd: RawFd,
+    in_fd: RawFd,
+    offset: Option<&mut libc::off64_t>,
+    count: usize,
+) -> Result<usize> {
+    let offset = offset
+        .map(|offset| offset as *mut _)
+        .unwrap_or(ptr::null_mut());
+    let ret = unsafe { libc::sendfile64(out_fd, in_fd, offset, count) };
+    Errno::result(ret).map(|r| r as usize)
+}
+
 cfg_if! {
     if #[cfg(any(target_os = "freebsd",
                  target_os = "ios",
@@ -1,7 +1,9 @@
 use super::sa_family_t;
 use crate::{Error, Result, NixPath};
 use crate::errno::Errno;
-use std::{fmt, mem, net, ptr, slice};
+use std::{cmp, fmt, mem, net, ptr, slice};
+use std::convert::TryFrom;
+use std::str::FromStr;
 use std::ffi::OsStr;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
@@ -25,6 +27,10 @@ use crate::sys::socket::addr::sys_control::SysControlAddr;
 pub use self::datalink::LinkAddr;
 #[cfg(target_os = "linux")]
 pub use self::vsock::VsockAddr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::tipc::TipcAddr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::bluetooth::{L2capAddr, RfcommAddr};
 
 /// These constants specify the protocol family to be used
 /// in [`socket`](fn.socket.html) and [`socketpair`](fn.socketpair.html)
@@ -222,8 +228,8 @@ impl AddressFamily {
     /// Create a new `AddressFamily` from an integer value retrieved from `libc`, usually from
     /// the `sa_family` field of a `sockaddr`.
     ///
-    /// Currently only supports these address families: Unix, Inet (v4 & v6), Netlink, Link/Packet
-    /// and System. Returns None for unsupported or unknown address families.
+    /// Currently only supports the address families of
+    /// [`SockAddr`](enum.SockAddr.html). Returns None for unsupported or unknown address families.
     pub fn from_i32(family: i32) -> Option<AddressFamily> {
         match family {
             libc::AF_UNIX => Some(AddressFamily::Unix),
@@ -244,12 +250,18 @@ impl AddressFamily {
             libc::AF_LINK => Some(AddressFamily::Link),
             #[cfg(target_os = "linux")]
             libc::AF_VSOCK => Some(AddressFamily::Vsock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            libc::AF_ALG => Some(AddressFamily::Alg),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            libc::AF_TIPC => Some(AddressFamily::Tipc),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            libc::AF_BLUETOOTH => Some(AddressFamily::Bluetooth),
             _ => None
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug)]
 pub enum InetAddr {
     V4(libc::sockaddr_in),
     V6(libc::sockaddr_in6),
@@ -335,6 +347,34 @@ impl InetAddr {
     }
 }
 
+impl PartialOrd for InetAddr {
+    fn partial_cmp(&self, other: &InetAddr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders addresses like `std::net::SocketAddr`: IPv4 before IPv6, then by
+/// address and port.
+impl Ord for InetAddr {
+    fn cmp(&self, other: &InetAddr) -> cmp::Ordering {
+        self.to_std().cmp(&other.to_std())
+    }
+}
+
+impl PartialEq for InetAddr {
+    fn eq(&self, other: &InetAddr) -> bool {
+        self.to_std() == other.to_std()
+    }
+}
+
+impl Eq for InetAddr {}
+
+impl Hash for InetAddr {
+    fn hash<H: Hasher>(&self, s: &mut H) {
+        self.to_std().hash(s)
+    }
+}
+
 impl fmt::Display for InetAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -344,12 +384,69 @@ impl fmt::Display for InetAddr {
     }
 }
 
+/// Parses addresses such as `"1.2.3.4:80"` or `"[::1]:443"`.
+impl FromStr for InetAddr {
+    type Err = net::AddrParseError;
+
+    fn from_str(s: &str) -> std::result::Result<InetAddr, Self::Err> {
+        s.parse().map(|std| InetAddr::from_std(&std))
+    }
+}
+
+impl From<net::SocketAddr> for InetAddr {
+    fn from(std: net::SocketAddr) -> InetAddr {
+        InetAddr::from_std(&std)
+    }
+}
+
+impl From<net::SocketAddrV4> for InetAddr {
+    fn from(std: net::SocketAddrV4) -> InetAddr {
+        InetAddr::from_std(&net::SocketAddr::V4(std))
+    }
+}
+
+impl From<net::SocketAddrV6> for InetAddr {
+    fn from(std: net::SocketAddrV6) -> InetAddr {
+        InetAddr::from_std(&net::SocketAddr::V6(std))
+    }
+}
+
+impl From<InetAddr> for net::SocketAddr {
+    fn from(addr: InetAddr) -> net::SocketAddr {
+        addr.to_std()
+    }
+}
+
+/// Fails with `EAFNOSUPPORT` if the address is IPv6.
+impl TryFrom<InetAddr> for net::SocketAddrV4 {
+    type Error = Error;
+
+    fn try_from(addr: InetAddr) -> Result<net::SocketAddrV4> {
+        match addr.to_std() {
+            net::SocketAddr::V4(std) => Ok(std),
+            net::SocketAddr::V6(_) => Err(Error::Sys(Errno::EAFNOSUPPORT)),
+        }
+    }
+}
+
+/// Fails with `EAFNOSUPPORT` if the address is IPv4.
+impl TryFrom<InetAddr> for net::SocketAddrV6 {
+    type Error = Error;
+
+    fn try_from(addr: InetAddr) -> Result<net::SocketAddrV6> {
+        match addr.to_std() {
+            net::SocketAddr::V6(std) => Ok(std),
+            net::SocketAddr::V4(_) => Err(Error::Sys(Errno::EAFNOSUPPORT)),
+        }
+    }
+}
+
 /*
  *
  * ===== IpAddr =====
  *
  */
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug)]
 pub enum IpAddr {
     V4(Ipv4Addr),
     V6(Ipv6Addr),
@@ -387,6 +484,32 @@ impl IpAddr {
     }
 }
 
+impl PartialOrd for IpAddr {
+    fn partial_cmp(&self, other: &IpAddr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IpAddr {
+    fn cmp(&self, other: &IpAddr) -> cmp::Ordering {
+        self.to_std().cmp(&other.to_std())
+    }
+}
+
+impl PartialEq for IpAddr {
+    fn eq(&self, other: &IpAddr) -> bool {
+        self.to_std() == other.to_std()
+    }
+}
+
+impl Eq for IpAddr {}
+
+impl Hash for IpAddr {
+    fn hash<H: Hasher>(&self, s: &mut H) {
+        self.to_std().hash(s)
+    }
+}
+
 impl fmt::Display for IpAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -396,13 +519,34 @@ impl fmt::Display for IpAddr {
     }
 }
 
+impl FromStr for IpAddr {
+    type Err = net::AddrParseError;
+
+    fn from_str(s: &str) -> std::result::Result<IpAddr, Self::Err> {
+        s.parse().map(|std| IpAddr::from_std(&std))
+    }
+}
+
+impl From<net::IpAddr> for IpAddr {
+    fn from(std: net::IpAddr) -> IpAddr {
+        IpAddr::from_std(&std)
+    }
+}
+
+impl From<IpAddr> for net::IpAddr {
+    fn from(ip: IpAddr) -> net::IpAddr {
+        ip.to_std()
+    }
+}
+
 /*
  *
  * ===== Ipv4Addr =====
  *
  */
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
 pub struct Ipv4Addr(pub libc::in_addr);
 
 impl Ipv4Addr {
@@ -438,6 +582,32 @@ impl Ipv4Addr {
     }
 }
 
+impl PartialOrd for Ipv4Addr {
+    fn partial_cmp(&self, other: &Ipv4Addr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ipv4Addr {
+    fn cmp(&self, other: &Ipv4Addr) -> cmp::Ordering {
+        self.to_std().cmp(&other.to_std())
+    }
+}
+
+impl PartialEq for Ipv4Addr {
+    fn eq(&self, other: &Ipv4Addr) -> bool {
+        self.to_std() == other.to_std()
+    }
+}
+
+impl Eq for Ipv4Addr {}
+
+impl Hash for Ipv4Addr {
+    fn hash<H: Hasher>(&self, s: &mut H) {
+        self.to_std().hash(s)
+    }
+}
+
 impl fmt::Display for Ipv4Addr {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let octets = self.octets();
@@ -445,13 +615,33 @@ impl fmt::Display for Ipv4Addr {
     }
 }
 
+impl FromStr for Ipv4Addr {
+    type Err = net::AddrParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Ipv4Addr, Self::Err> {
+        s.parse().map(|std| Ipv4Addr::from_std(&std))
+    }
+}
+
+impl From<net::Ipv4Addr> for Ipv4Addr {
+    fn from(std: net::Ipv4Addr) -> Ipv4Addr {
+        Ipv4Addr::from_std(&std)
+    }
+}
+
+impl From<Ipv4Addr> for net::Ipv4Addr {
+    fn from(ip: Ipv4Addr) -> net::Ipv4Addr {
+        ip.to_std()
+    }
+}
+
 /*
  *
  * ===== Ipv6Addr =====
  *
  */
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug)]
 pub struct Ipv6Addr(pub libc::in6_addr);
 
 // Note that IPv6 addresses are stored in big endian order on all architectures.
@@ -493,12 +683,121 @@ impl Ipv6Addr {
     }
 }
 
+impl PartialOrd for Ipv6Addr {
+    fn partial_cmp(&self, other: &Ipv6Addr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ipv6Addr {
+    fn cmp(&self, other: &Ipv6Addr) -> cmp::Ordering {
+        self.to_std().cmp(&other.to_std())
+    }
+}
+
+impl PartialEq for Ipv6Addr {
+    fn eq(&self, other: &Ipv6Addr) -> bool {
+        self.to_std() == other.to_std()
+    }
+}
+
+impl Eq for Ipv6Addr {}
+
+impl Hash for Ipv6Addr {
+    fn hash<H: Hasher>(&self, s: &mut H) {
+        self.to_std().hash(s)
+    }
+}
+
 impl fmt::Display for Ipv6Addr {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         self.to_std().fmt(fmt)
     }
 }
 
+impl FromStr for Ipv6Addr {
+    type Err = net::AddrParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Ipv6Addr, Self::Err> {
+        s.parse().map(|std| Ipv6Addr::from_std(&std))
+    }
+}
+
+impl From<net::Ipv6Addr> for Ipv6Addr {
+    fn from(std: net::Ipv6Addr) -> Ipv6Addr {
+        Ipv6Addr::from_std(&std)
+    }
+}
+
+impl From<Ipv6Addr> for net::Ipv6Addr {
+    fn from(ip: Ipv6Addr) -> net::Ipv6Addr {
+        ip.to_std()
+    }
+}
+
+/*
+ *
+ * ===== MacAddr =====
+ *
+ */
+
+/// A 48-bit IEEE 802 MAC address, such as an Ethernet hardware address.
+///
+/// It is displayed and parsed in the usual `"00:11:22:aa:bb:cc"` form.
+/// Parsing also accepts `-` as the separator.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    /// Create a MAC address from its six octets.
+    pub fn new(octets: [u8; 6]) -> MacAddr {
+        MacAddr(octets)
+    }
+
+    /// The six octets of this address.
+    pub fn octets(self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> MacAddr {
+        MacAddr(octets)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let o = self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+               o[0], o[1], o[2], o[3], o[4], o[5])
+    }
+}
+
+/// Fails with `EINVAL` unless given six hexadecimal octets separated by `:`
+/// or `-`.
+impl FromStr for MacAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MacAddr> {
+        let sep = if s.contains('-') { '-' } else { ':' };
+        let mut octets = [0u8; 6];
+        let mut parts = s.split(sep);
+        for octet in octets.iter_mut() {
+            let part = parts.next().ok_or(Error::Sys(Errno::EINVAL))?;
+            if part.is_empty() || part.len() > 2 {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            *octet = u8::from_str_radix(part, 16)
+                .map_err(|_| Error::Sys(Errno::EINVAL))?;
+        }
+        if parts.next().is_some() {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        Ok(MacAddr(octets))
+    }
+}
+
 /// A wrapper around `sockaddr_un`.
 ///
 /// This also tracks the length of `sun_path` address (excluding
@@ -563,6 +862,29 @@ impl UnixAddr {
         }
     }
 
+    /// Create an unnamed address, with an empty `sun_path`.
+    ///
+    /// This is the address of sockets created by `socketpair` and of sockets
+    /// that were never bound.  On Linux and Android, binding a socket to it
+    /// "autobinds" the socket to a unique address in the abstract namespace,
+    /// made of five hexadecimal digits, which `getsockname` then reports.
+    pub fn new_unnamed() -> UnixAddr {
+        unsafe {
+            let ret = libc::sockaddr_un {
+                sun_family: AddressFamily::Unix as sa_family_t,
+                .. mem::zeroed()
+            };
+
+            UnixAddr(ret, 0)
+        }
+    }
+
+    /// Whether this address is unnamed.  See
+    /// [`new_unnamed`](#method.new_unnamed).
+    pub fn is_unnamed(&self) -> bool {
+        self.1 == 0
+    }
+
     fn sun_path(&self) -> &[u8] {
         unsafe { slice::from_raw_parts(self.0.sun_path.as_ptr() as *const u8, self.1) }
     }
@@ -599,6 +921,19 @@ impl UnixAddr {
     }
 }
 
+impl PartialOrd for UnixAddr {
+    fn partial_cmp(&self, other: &UnixAddr) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders addresses by the bytes of their `sun_path`.
+impl Ord for UnixAddr {
+    fn cmp(&self, other: &UnixAddr) -> cmp::Ordering {
+        self.sun_path().cmp(other.sun_path())
+    }
+}
+
 impl fmt::Display for UnixAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.1 == 0 {
@@ -612,6 +947,22 @@ impl fmt::Display for UnixAddr {
     }
 }
 
+/// Parses a filesystem path.  On Android and Linux, a leading `@` denotes an
+/// address in the abstract namespace, matching the `Display` output.
+impl FromStr for UnixAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<UnixAddr> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if s.starts_with('@') {
+                return UnixAddr::new_abstract(&s.as_bytes()[1..]);
+            }
+        }
+        UnixAddr::new(s)
+    }
+}
+
 impl PartialEq for UnixAddr {
     fn eq(&self, other: &UnixAddr) -> bool {
         self.sun_path() == other.sun_path()
@@ -649,6 +1000,12 @@ pub enum SockAddr {
     Link(LinkAddr),
     #[cfg(target_os = "linux")]
     Vsock(VsockAddr),
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Tipc(TipcAddr),
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Rfcomm(RfcommAddr),
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    L2cap(L2capAddr),
 }
 
 impl SockAddr {
@@ -702,6 +1059,10 @@ impl SockAddr {
             SockAddr::Link(..) => AddressFamily::Link,
             #[cfg(target_os = "linux")]
             SockAddr::Vsock(..) => AddressFamily::Vsock,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Tipc(..) => AddressFamily::Tipc,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Rfcomm(..) | SockAddr::L2cap(..) => AddressFamily::Bluetooth,
         }
     }
 
@@ -711,53 +1072,19 @@ impl SockAddr {
 
     /// Creates a `SockAddr` struct from libc's sockaddr.
     ///
-    /// Supports only the following address families: Unix, Inet (v4 & v6), Netlink and System.
-    /// Returns None for unsupported families.
+    /// Supports the families of `SockAddr` other than Unix, whose addresses
+    /// can't be read without their length.  Returns None for other families.
     ///
     /// # Safety
     ///
     /// unsafe because it takes a raw pointer as argument.  The caller must
     /// ensure that the pointer is valid.
     pub(crate) unsafe fn from_libc_sockaddr(addr: *const libc::sockaddr) -> Option<SockAddr> {
-        if addr.is_null() {
+        // Without a length, the length of a UNIX address is unknown
+        if addr.is_null() || i32::from((*addr).sa_family) == libc::AF_UNIX {
             None
         } else {
-            match AddressFamily::from_i32(i32::from((*addr).sa_family)) {
-                Some(AddressFamily::Unix) => None,
-                Some(AddressFamily::Inet) => Some(SockAddr::Inet(
-                    InetAddr::V4(*(addr as *const libc::sockaddr_in)))),
-                Some(AddressFamily::Inet6) => Some(SockAddr::Inet(
-                    InetAddr::V6(*(addr as *const libc::sockaddr_in6)))),
-                #[cfg(any(target_os = "android", target_os = "linux"))]
-                Some(AddressFamily::Netlink) => Some(SockAddr::Netlink(
-                    NetlinkAddr(*(addr as *const libc::sockaddr_nl)))),
-                #[cfg(any(target_os = "ios", target_os = "macos"))]
-                Some(AddressFamily::System) => Some(SockAddr::SysControl(
-                    SysControlAddr(*(addr as *const libc::sockaddr_ctl)))),
-                #[cfg(any(target_os = "android", target_os = "linux"))]
-                Some(AddressFamily::Packet) => Some(SockAddr::Link(
-                    LinkAddr(*(addr as *const libc::sockaddr_ll)))),
-                #[cfg(any(target_os = "dragonfly",
-                          target_os = "freebsd",
-                          target_os = "ios",
-                          target_os = "macos",
-                          target_os = "netbsd",
-                          target_os = "openbsd"))]
-                Some(AddressFamily::Link) => {
-                    let ether_addr = LinkAddr(*(addr as *const libc::sockaddr_dl));
-                    if ether_addr.is_empty() {
-                        None
-                    } else {
-                        Some(SockAddr::Link(ether_addr))
-                    }
-                },
-                #[cfg(target_os = "linux")]
-                Some(AddressFamily::Vsock) => Some(SockAddr::Vsock(
-                    VsockAddr(*(addr as *const libc::sockaddr_vm)))),
-                // Other address families are currently not supported and simply yield a None
-                // entry instead of a proper conversion to a `SockAddr`.
-                Some(_) | None => None,
-            }
+            SockAddr::from_raw(addr, None)
         }
     }
 
@@ -845,6 +1172,30 @@ impl SockAddr {
                 },
                 mem::size_of_val(sa) as libc::socklen_t
             ),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Tipc(ref sa) => (
+                // TipcAddr has the layout of the C sockaddr_tipc
+                unsafe {
+                    &*(sa as *const TipcAddr as *const libc::sockaddr)
+                },
+                mem::size_of_val(sa) as libc::socklen_t
+            ),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Rfcomm(ref sa) => (
+                // RfcommAddr has the layout of the C sockaddr_rc
+                unsafe {
+                    &*(sa as *const RfcommAddr as *const libc::sockaddr)
+                },
+                mem::size_of_val(sa) as libc::socklen_t
+            ),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::L2cap(ref sa) => (
+                // L2capAddr has the layout of the C sockaddr_l2
+                unsafe {
+                    &*(sa as *const L2capAddr as *const libc::sockaddr)
+                },
+                mem::size_of_val(sa) as libc::socklen_t
+            ),
         }
     }
 }
@@ -871,77 +1222,595 @@ impl fmt::Display for SockAddr {
             SockAddr::Link(ref ether_addr) => ether_addr.fmt(f),
             #[cfg(target_os = "linux")]
             SockAddr::Vsock(ref svm) => svm.fmt(f),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Tipc(ref tipc) => tipc.fmt(f),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::Rfcomm(ref rc) => rc.fmt(f),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            SockAddr::L2cap(ref l2) => l2.fmt(f),
         }
     }
 }
 
-#[cfg(any(target_os = "android", target_os = "linux"))]
-pub mod netlink {
-    use crate::sys::socket::addr::AddressFamily;
-    use libc::{sa_family_t, sockaddr_nl};
-    use std::{fmt, mem};
-
-    #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-    pub struct NetlinkAddr(pub sockaddr_nl);
-
-    impl NetlinkAddr {
-        pub fn new(pid: u32, groups: u32) -> NetlinkAddr {
-            let mut addr: sockaddr_nl = unsafe { mem::zeroed() };
-            addr.nl_family = AddressFamily::Netlink as sa_family_t;
-            addr.nl_pid = pid;
-            addr.nl_groups = groups;
+/// Parses an internet address such as `"1.2.3.4:80"` or `"[::1]:443"`, or a
+/// UNIX socket path as accepted by `UnixAddr`.  To tell them apart, paths
+/// must contain a `/` or be abstract; anything else fails with `EINVAL`.
+impl FromStr for SockAddr {
+    type Err = Error;
 
-            NetlinkAddr(addr)
+    fn from_str(s: &str) -> Result<SockAddr> {
+        if let Ok(inet) = s.parse::<InetAddr>() {
+            return Ok(SockAddr::Inet(inet));
         }
-
-        pub fn pid(&self) -> u32 {
-            self.0.nl_pid
+        let is_abstract = cfg!(any(target_os = "android", target_os = "linux"))
+            && s.starts_with('@');
+        if s.contains('/') || is_abstract {
+            s.parse().map(SockAddr::Unix)
+        } else {
+            Err(Error::Sys(Errno::EINVAL))
         }
+    }
+}
 
-        pub fn groups(&self) -> u32 {
-            self.0.nl_groups
-        }
+impl From<net::SocketAddr> for SockAddr {
+    fn from(std: net::SocketAddr) -> SockAddr {
+        SockAddr::Inet(InetAddr::from(std))
     }
+}
 
-    impl fmt::Display for NetlinkAddr {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "pid: {} groups: {}", self.pid(), self.groups())
-        }
+impl From<net::SocketAddrV4> for SockAddr {
+    fn from(std: net::SocketAddrV4) -> SockAddr {
+        SockAddr::Inet(InetAddr::from(std))
     }
 }
 
-#[cfg(any(target_os = "android", target_os = "linux"))]
-pub mod alg {
-    use libc::{AF_ALG, sockaddr_alg, c_char};
-    use std::{fmt, mem, str};
-    use std::hash::{Hash, Hasher};
-    use std::ffi::CStr;
+impl From<net::SocketAddrV6> for SockAddr {
+    fn from(std: net::SocketAddrV6) -> SockAddr {
+        SockAddr::Inet(InetAddr::from(std))
+    }
+}
 
-    #[derive(Copy, Clone)]
-    pub struct AlgAddr(pub sockaddr_alg);
+/// Fails with `EAFNOSUPPORT` if the address is not an internet address.
+impl TryFrom<SockAddr> for net::SocketAddr {
+    type Error = Error;
 
-    // , PartialEq, Eq, Debug, Hash
-    impl PartialEq for AlgAddr {
-        fn eq(&self, other: &Self) -> bool {
-            let (inner, other) = (self.0, other.0);
-            (inner.salg_family, &inner.salg_type[..], inner.salg_feat, inner.salg_mask, &inner.salg_name[..]) ==
-            (other.salg_family, &other.salg_type[..], other.salg_feat, other.salg_mask, &other.salg_name[..])
+    fn try_from(addr: SockAddr) -> Result<net::SocketAddr> {
+        match addr {
+            SockAddr::Inet(inet) => Ok(inet.to_std()),
+            _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
         }
     }
+}
 
-    impl Eq for AlgAddr {}
+/// Fails with `EAFNOSUPPORT` if the address is not an IPv4 address.
+impl TryFrom<SockAddr> for net::SocketAddrV4 {
+    type Error = Error;
 
-    impl Hash for AlgAddr {
-        fn hash<H: Hasher>(&self, s: &mut H) {
-            let inner = self.0;
-            (inner.salg_family, &inner.salg_type[..], inner.salg_feat, inner.salg_mask, &inner.salg_name[..]).hash(s);
+    fn try_from(addr: SockAddr) -> Result<net::SocketAddrV4> {
+        match addr {
+            SockAddr::Inet(inet) => net::SocketAddrV4::try_from(inet),
+            _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
         }
     }
+}
 
-    impl AlgAddr {
-        pub fn new(alg_type: &str, alg_name: &str) -> AlgAddr {
-            let mut addr: sockaddr_alg = unsafe { mem::zeroed() };
-            addr.salg_family = AF_ALG as u16;
+/// Fails with `EAFNOSUPPORT` if the address is not an IPv6 address.
+impl TryFrom<SockAddr> for net::SocketAddrV6 {
+    type Error = Error;
+
+    fn try_from(addr: SockAddr) -> Result<net::SocketAddrV6> {
+        match addr {
+            SockAddr::Inet(inet) => net::SocketAddrV6::try_from(inet),
+            _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
+        }
+    }
+}
+
+/// A socket address of one family, held in the C structure the kernel
+/// expects.
+///
+/// [`bind`](fn.bind.html), [`connect`](fn.connect.html) and
+/// [`sendto`](fn.sendto.html) accept any implementation, and pass it to the
+/// kernel without copying.  Besides [`SockAddr`](enum.SockAddr.html), this
+/// is implemented by the address type of each family, such as
+/// [`UnixAddr`](struct.UnixAddr.html), so supporting a new family only takes
+/// a new type implementing this trait.
+///
+/// # Safety
+///
+/// The kernel reads the address through `as_ptr` and `len`, so `as_ptr`
+/// must point to a `sockaddr` structure, starting with its family, that is
+/// valid for reads of `len` bytes for as long as the value is borrowed.
+#[allow(clippy::len_without_is_empty)]
+pub unsafe trait SockaddrLike: Sized {
+    /// Read an address from raw memory, such as a buffer filled in by the
+    /// kernel.
+    ///
+    /// `len` is the length of the address, as returned by the kernel along
+    /// with it; `None` reads the whole of the underlying structure.  Returns
+    /// `None` if the address is of another family, or shorter than its
+    /// family requires.
+    ///
+    /// # Safety
+    ///
+    /// `addr` must be valid for reads of `len` bytes, or of the size of the
+    /// underlying structure if `len` is `None`.
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<Self>;
+
+    /// Pointer to the underlying structure, as passed to the kernel.
+    fn as_ptr(&self) -> *const libc::sockaddr;
+
+    /// Length of the address in bytes.  For UNIX addresses, this covers only
+    /// the used part of `sun_path`.
+    fn len(&self) -> libc::socklen_t;
+
+    /// The address family, or `None` if `AddressFamily` doesn't know it.
+    fn family(&self) -> Option<AddressFamily> {
+        let family = unsafe { (*self.as_ptr()).sa_family };
+        AddressFamily::from_i32(i32::from(family))
+    }
+}
+
+/// Read an address structure of `family`, for `SockaddrLike::from_raw`.
+///
+/// Addresses shorter than `T`, but of at least `min_len` bytes, are padded
+/// with zeroes.
+unsafe fn read_sockaddr<T: Copy>(addr: *const libc::sockaddr,
+                                 len: Option<libc::socklen_t>,
+                                 family: libc::c_int,
+                                 min_len: usize) -> Option<T> {
+    let size = mem::size_of::<T>();
+    let len = len.map_or(size, |len| cmp::min(len as usize, size));
+    // Check the length first, so that sa_family is known to be readable
+    if addr.is_null() || len < cmp::max(min_len, mem::size_of::<sa_family_t>()) ||
+        i32::from((*addr).sa_family) != family
+    {
+        return None;
+    }
+    let mut sa: T = mem::zeroed();
+    ptr::copy_nonoverlapping(addr as *const u8, &mut sa as *mut T as *mut u8, len);
+    Some(sa)
+}
+
+/// Implement `SockaddrLike` for a newtype around a fixed-size `sockaddr_*`.
+macro_rules! sockaddr_like_newtype {
+    ($ty:ident, $raw:ty, $family:expr) => {
+        sockaddr_like_newtype!($ty, $raw, $family, mem::size_of::<$raw>());
+    };
+    ($ty:ident, $raw:ty, $family:expr, $min_len:expr) => {
+        unsafe impl SockaddrLike for $ty {
+            unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+                -> Option<$ty>
+            {
+                read_sockaddr(addr, len, $family, $min_len).map($ty)
+            }
+
+            fn as_ptr(&self) -> *const libc::sockaddr {
+                &self.0 as *const $raw as *const libc::sockaddr
+            }
+
+            fn len(&self) -> libc::socklen_t {
+                mem::size_of::<$raw>() as libc::socklen_t
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockaddr_like_newtype!(NetlinkAddr, libc::sockaddr_nl, libc::AF_NETLINK);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockaddr_like_newtype!(AlgAddr, libc::sockaddr_alg, libc::AF_ALG);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+// The kernel omits the unused part of sll_addr
+sockaddr_like_newtype!(LinkAddr, libc::sockaddr_ll, libc::AF_PACKET,
+                       offset_of!(libc::sockaddr_ll, sll_addr));
+#[cfg(target_os = "linux")]
+sockaddr_like_newtype!(VsockAddr, libc::sockaddr_vm, libc::AF_VSOCK);
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+sockaddr_like_newtype!(SysControlAddr, libc::sockaddr_ctl, libc::AF_SYSTEM);
+
+#[cfg(any(target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+unsafe impl SockaddrLike for LinkAddr {
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<LinkAddr>
+    {
+        // sockaddr_dl is variable-length; keep what fits in the structure
+        read_sockaddr(addr, len, libc::AF_LINK, offset_of!(libc::sockaddr_dl, sdl_data))
+            .map(LinkAddr)
+    }
+
+    fn as_ptr(&self) -> *const libc::sockaddr {
+        &self.0 as *const libc::sockaddr_dl as *const libc::sockaddr
+    }
+
+    fn len(&self) -> libc::socklen_t {
+        mem::size_of::<libc::sockaddr_dl>() as libc::socklen_t
+    }
+}
+
+/// Implement `SockaddrLike` for an address type that has the layout of its
+/// C `sockaddr_*`.
+macro_rules! sockaddr_like_repr_c {
+    ($ty:ident, $family:expr) => {
+        unsafe impl SockaddrLike for $ty {
+            unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+                -> Option<$ty>
+            {
+                read_sockaddr(addr, len, $family, mem::size_of::<$ty>())
+            }
+
+            fn as_ptr(&self) -> *const libc::sockaddr {
+                self as *const $ty as *const libc::sockaddr
+            }
+
+            fn len(&self) -> libc::socklen_t {
+                mem::size_of::<$ty>() as libc::socklen_t
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockaddr_like_repr_c!(TipcAddr, libc::AF_TIPC);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockaddr_like_repr_c!(RfcommAddr, libc::AF_BLUETOOTH);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockaddr_like_repr_c!(L2capAddr, libc::AF_BLUETOOTH);
+
+unsafe impl SockaddrLike for InetAddr {
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<InetAddr>
+    {
+        let (sin, sin6) = (mem::size_of::<libc::sockaddr_in>(),
+                           mem::size_of::<libc::sockaddr_in6>());
+        read_sockaddr(addr, len, libc::AF_INET, sin).map(InetAddr::V4)
+            .or_else(|| read_sockaddr(addr, len, libc::AF_INET6, sin6).map(InetAddr::V6))
+    }
+
+    fn as_ptr(&self) -> *const libc::sockaddr {
+        match *self {
+            InetAddr::V4(ref sin) => sin as *const libc::sockaddr_in as *const libc::sockaddr,
+            InetAddr::V6(ref sin6) => sin6 as *const libc::sockaddr_in6 as *const libc::sockaddr,
+        }
+    }
+
+    fn len(&self) -> libc::socklen_t {
+        match *self {
+            InetAddr::V4(ref sin) => mem::size_of_val(sin) as libc::socklen_t,
+            InetAddr::V6(ref sin6) => mem::size_of_val(sin6) as libc::socklen_t,
+        }
+    }
+}
+
+unsafe impl SockaddrLike for UnixAddr {
+    /// Only the `len` bytes of `addr` are read, and a filesystem path ends at
+    /// its terminating null byte, if any.  Without `len`, `sun_path` is taken
+    /// to be null-terminated, which is wrong for abstract addresses.
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<UnixAddr>
+    {
+        let path_offset = offset_of!(libc::sockaddr_un, sun_path);
+        let size = mem::size_of::<libc::sockaddr_un>();
+        let len = len.map_or(size, |len| cmp::min(len as usize, size));
+        let sun: libc::sockaddr_un = read_sockaddr(addr, Some(len as libc::socklen_t),
+                                                   libc::AF_UNIX, path_offset)?;
+        let mut path_len = len - path_offset;
+        if path_len > 0 && sun.sun_path[0] != 0 {
+            path_len = libc::strnlen(sun.sun_path.as_ptr(), path_len);
+        }
+        Some(UnixAddr(sun, path_len))
+    }
+
+    fn as_ptr(&self) -> *const libc::sockaddr {
+        &self.0 as *const libc::sockaddr_un as *const libc::sockaddr
+    }
+
+    fn len(&self) -> libc::socklen_t {
+        (offset_of!(libc::sockaddr_un, sun_path) + self.1) as libc::socklen_t
+    }
+}
+
+unsafe impl SockaddrLike for SockAddr {
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<SockAddr>
+    {
+        let family_len = mem::size_of::<sa_family_t>() as libc::socklen_t;
+        if addr.is_null() || len.unwrap_or(family_len) < family_len {
+            return None;
+        }
+        match AddressFamily::from_i32(i32::from((*addr).sa_family))? {
+            AddressFamily::Inet | AddressFamily::Inet6 => {
+                InetAddr::from_raw(addr, len).map(SockAddr::Inet)
+            },
+            AddressFamily::Unix => UnixAddr::from_raw(addr, len).map(SockAddr::Unix),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            AddressFamily::Netlink => NetlinkAddr::from_raw(addr, len).map(SockAddr::Netlink),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            AddressFamily::Alg => AlgAddr::from_raw(addr, len).map(SockAddr::Alg),
+            #[cfg(any(target_os = "ios", target_os = "macos"))]
+            AddressFamily::System => {
+                SysControlAddr::from_raw(addr, len).map(SockAddr::SysControl)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            AddressFamily::Packet => LinkAddr::from_raw(addr, len).map(SockAddr::Link),
+            #[cfg(any(target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "macos",
+                      target_os = "netbsd",
+                      target_os = "openbsd"))]
+            AddressFamily::Link => {
+                LinkAddr::from_raw(addr, len)
+                    .filter(|link| !link.is_empty())
+                    .map(SockAddr::Link)
+            },
+            #[cfg(target_os = "linux")]
+            AddressFamily::Vsock => VsockAddr::from_raw(addr, len).map(SockAddr::Vsock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            AddressFamily::Tipc => TipcAddr::from_raw(addr, len).map(SockAddr::Tipc),
+            // Only the length tells the protocols apart
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            AddressFamily::Bluetooth => match len {
+                Some(len) if len as usize == mem::size_of::<RfcommAddr>() => {
+                    RfcommAddr::from_raw(addr, Some(len)).map(SockAddr::Rfcomm)
+                },
+                Some(len) if len as usize == mem::size_of::<L2capAddr>() => {
+                    L2capAddr::from_raw(addr, Some(len)).map(SockAddr::L2cap)
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn as_ptr(&self) -> *const libc::sockaddr {
+        self.as_ffi_pair().0
+    }
+
+    fn len(&self) -> libc::socklen_t {
+        self.as_ffi_pair().1
+    }
+}
+
+/// Storage for a socket address of any family, such as one returned by a
+/// `libc` function without a nix wrapper.
+///
+/// Create it with [`SockaddrLike::from_raw`](trait.SockaddrLike.html#tymethod.from_raw).
+/// The accessors check the family and length of the address before giving
+/// access to it, and the fixed-size ones borrow it instead of copying.
+#[derive(Clone, Copy, Debug)]
+pub struct SockaddrStorage {
+    ss: libc::sockaddr_storage,
+    len: libc::socklen_t,
+}
+
+impl SockaddrStorage {
+    fn as_ref_of<T>(&self, family: libc::c_int) -> Option<&T> {
+        self.as_short_ref_of(family, mem::size_of::<T>())
+    }
+
+    /// Like `as_ref_of`, but also for addresses of at least `min_len` bytes
+    /// that are shorter than `T`, as for `SockaddrLike::from_raw`.  The rest
+    /// of `T` reads as zeroes.
+    fn as_short_ref_of<T>(&self, family: libc::c_int, min_len: usize) -> Option<&T> {
+        if (self.len as usize) < min_len || i32::from(self.ss.ss_family) != family {
+            return None;
+        }
+        // sockaddr_storage is large and aligned enough for any address, and
+        // from_raw zeroed the bytes past len
+        Some(unsafe { &*(&self.ss as *const libc::sockaddr_storage as *const T) })
+    }
+
+    /// Like `as_ref_of`, but only for an address of exactly the size of `T`,
+    /// for families like Bluetooth where protocols share the family and are
+    /// told apart by their address length.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn as_exact_ref_of<T>(&self, family: libc::c_int) -> Option<&T> {
+        if self.len as usize != mem::size_of::<T>() {
+            return None;
+        }
+        self.as_ref_of(family)
+    }
+
+    /// Borrow an IPv4 address.
+    pub fn as_sockaddr_in(&self) -> Option<&libc::sockaddr_in> {
+        self.as_ref_of(libc::AF_INET)
+    }
+
+    /// Borrow an IPv6 address.
+    pub fn as_sockaddr_in6(&self) -> Option<&libc::sockaddr_in6> {
+        self.as_ref_of(libc::AF_INET6)
+    }
+
+    /// Borrow a netlink address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_netlink_addr(&self) -> Option<&NetlinkAddr> {
+        self.as_ref_of(libc::AF_NETLINK)
+    }
+
+    /// Borrow an `AF_ALG` address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_alg_addr(&self) -> Option<&AlgAddr> {
+        self.as_ref_of(libc::AF_ALG)
+    }
+
+    /// Borrow an `AF_PACKET` address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_link_addr(&self) -> Option<&LinkAddr> {
+        // Like LinkAddr::from_raw, accept addresses without the unused part
+        // of sll_addr
+        self.as_short_ref_of(libc::AF_PACKET, offset_of!(libc::sockaddr_ll, sll_addr))
+    }
+
+    /// Borrow a VSOCK address.
+    #[cfg(target_os = "linux")]
+    pub fn as_vsock_addr(&self) -> Option<&VsockAddr> {
+        self.as_ref_of(libc::AF_VSOCK)
+    }
+
+    /// Borrow a TIPC address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_tipc_addr(&self) -> Option<&TipcAddr> {
+        self.as_ref_of(libc::AF_TIPC)
+    }
+
+    /// Borrow a Bluetooth RFCOMM address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_rfcomm_addr(&self) -> Option<&RfcommAddr> {
+        self.as_exact_ref_of(libc::AF_BLUETOOTH)
+    }
+
+    /// Borrow a Bluetooth L2CAP address.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_l2cap_addr(&self) -> Option<&L2capAddr> {
+        self.as_exact_ref_of(libc::AF_BLUETOOTH)
+    }
+
+    /// Copy out a UNIX address, which also records the length of its path.
+    pub fn to_unix_addr(&self) -> Option<UnixAddr> {
+        unsafe { UnixAddr::from_raw(self.as_ptr(), Some(self.len)) }
+    }
+
+    /// Copy out the address as a `SockAddr`, if nix knows its family.
+    pub fn to_sock_addr(&self) -> Option<SockAddr> {
+        unsafe { SockAddr::from_raw(self.as_ptr(), Some(self.len)) }
+    }
+}
+
+unsafe impl SockaddrLike for SockaddrStorage {
+    /// Without `len`, only addresses of the families known to
+    /// [`SockAddr`](enum.SockAddr.html) can be read, as the length is
+    /// otherwise unknown.
+    unsafe fn from_raw(addr: *const libc::sockaddr, len: Option<libc::socklen_t>)
+        -> Option<SockaddrStorage>
+    {
+        let len = match len {
+            Some(len) => len,
+            None => SockAddr::from_raw(addr, None)?.len(),
+        };
+        let size = cmp::min(len as usize, mem::size_of::<libc::sockaddr_storage>());
+        if addr.is_null() || size < mem::size_of::<sa_family_t>() {
+            return None;
+        }
+        let mut ss: libc::sockaddr_storage = mem::zeroed();
+        ptr::copy_nonoverlapping(addr as *const u8, &mut ss as *mut _ as *mut u8, size);
+        Some(SockaddrStorage { ss, len: size as libc::socklen_t })
+    }
+
+    fn as_ptr(&self) -> *const libc::sockaddr {
+        &self.ss as *const libc::sockaddr_storage as *const libc::sockaddr
+    }
+
+    fn len(&self) -> libc::socklen_t {
+        self.len
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod netlink {
+    use crate::sys::socket::addr::AddressFamily;
+    use libc::{sa_family_t, sockaddr_nl};
+    use std::{fmt, mem};
+
+    #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+    #[repr(transparent)]
+    pub struct NetlinkAddr(pub sockaddr_nl);
+
+    impl NetlinkAddr {
+        pub fn new(pid: u32, groups: u32) -> NetlinkAddr {
+            let mut addr: sockaddr_nl = unsafe { mem::zeroed() };
+            addr.nl_family = AddressFamily::Netlink as sa_family_t;
+            addr.nl_pid = pid;
+            addr.nl_groups = groups;
+
+            NetlinkAddr(addr)
+        }
+
+        pub fn pid(&self) -> u32 {
+            self.0.nl_pid
+        }
+
+        pub fn groups(&self) -> u32 {
+            self.0.nl_groups
+        }
+
+        /// Create an address subscribed to the given multicast groups.
+        ///
+        /// Groups are numbered from 1, like the `RTNLGRP_*` constants.  Only
+        /// groups 1 through 32 can be subscribed at `bind` time; use the
+        /// [`NetlinkAddMembership`](../sockopt/struct.NetlinkAddMembership.html)
+        /// socket option for higher-numbered groups.
+        ///
+        /// # Panics
+        ///
+        /// Panics if any group is 0 or greater than 32.
+        pub fn with_groups(pid: u32, groups: &[u32]) -> NetlinkAddr {
+            let mask = groups.iter().fold(0, |mask, &group| mask | group_bit(group));
+            NetlinkAddr::new(pid, mask)
+        }
+
+        /// Returns whether this address is subscribed to multicast group
+        /// `group`.
+        ///
+        /// Groups above 32 are never part of a `sockaddr_nl`, so `false` is
+        /// returned for them.
+        pub fn has_group(&self, group: u32) -> bool {
+            (1..=32).contains(&group) && self.groups() & group_bit(group) != 0
+        }
+    }
+
+    fn group_bit(group: u32) -> u32 {
+        assert!((1..=32).contains(&group),
+                "netlink multicast group {} can't be used in a sockaddr_nl", group);
+        1 << (group - 1)
+    }
+
+    impl fmt::Display for NetlinkAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "pid: {} groups: {}", self.pid(), self.groups())
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod alg {
+    use libc::{AF_ALG, sockaddr_alg, c_char};
+    use std::{fmt, mem, str};
+    use std::hash::{Hash, Hasher};
+    use std::ffi::CStr;
+
+    #[derive(Copy, Clone)]
+    #[repr(transparent)]
+    pub struct AlgAddr(pub sockaddr_alg);
+
+    // , PartialEq, Eq, Debug, Hash
+    impl PartialEq for AlgAddr {
+        fn eq(&self, other: &Self) -> bool {
+            let (inner, other) = (self.0, other.0);
+            (inner.salg_family, &inner.salg_type[..], inner.salg_feat, inner.salg_mask, &inner.salg_name[..]) ==
+            (other.salg_family, &other.salg_type[..], other.salg_feat, other.salg_mask, &other.salg_name[..])
+        }
+    }
+
+    impl Eq for AlgAddr {}
+
+    impl Hash for AlgAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            let inner = self.0;
+            (inner.salg_family, &inner.salg_type[..], inner.salg_feat, inner.salg_mask, &inner.salg_name[..]).hash(s);
+        }
+    }
+
+    impl AlgAddr {
+        pub fn new(alg_type: &str, alg_name: &str) -> AlgAddr {
+            let mut addr: sockaddr_alg = unsafe { mem::zeroed() };
+            addr.salg_family = AF_ALG as u16;
             addr.salg_type[..alg_type.len()].copy_from_slice(alg_type.to_string().as_bytes());
             addr.salg_name[..alg_name.len()].copy_from_slice(alg_name.to_string().as_bytes());
 
@@ -996,11 +1865,17 @@ pub mod sys_control {
 
     ioctl_readwrite!(ctl_info, CTL_IOC_MAGIC, CTL_IOC_INFO, ctl_ioc_info);
 
+    /// Address of a kernel control, such as `utun`, for sockets of protocol
+    /// `SysProtocol::KextControl`.
+    ///
+    /// See also [`connect_sys_control`](../fn.connect_sys_control.html).
     #[repr(C)]
     #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
     pub struct SysControlAddr(pub libc::sockaddr_ctl);
 
     impl SysControlAddr {
+        /// Address of unit `unit` of the kernel control with ID `id`.  Unit 0
+        /// lets the kernel pick a free unit when connecting.
         pub fn new(id: u32, unit: u32) -> SysControlAddr {
             let addr = libc::sockaddr_ctl {
                 sc_len: mem::size_of::<libc::sockaddr_ctl>() as c_uchar,
@@ -1014,8 +1889,15 @@ pub mod sys_control {
             SysControlAddr(addr)
         }
 
+        /// Address of unit `unit` of the kernel control named `name`, such as
+        /// `com.apple.net.utun_control`.
+        ///
+        /// The ID of the control is looked up with the `CTLIOCGINFO` ioctl on
+        /// `sockfd`, which must be a `SysProtocol::KextControl` socket.
+        /// Fails with `ENOENT` if no such control is registered.
         pub fn from_name(sockfd: RawFd, name: &str, unit: u32) -> Result<SysControlAddr> {
-            if name.len() > MAX_KCTL_NAME {
+            // Leave room for the terminating null byte
+            if name.len() >= MAX_KCTL_NAME {
                 return Err(Error::Sys(Errno::ENAMETOOLONG));
             }
 
@@ -1028,10 +1910,12 @@ pub mod sys_control {
             Ok(SysControlAddr::new(info.ctl_id, unit))
         }
 
+        /// ID of the kernel control
         pub fn id(&self) -> u32 {
             self.0.sc_id
         }
 
+        /// Unit number
         pub fn unit(&self) -> u32 {
             self.0.sc_unit
         }
@@ -1047,13 +1931,27 @@ pub mod sys_control {
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod datalink {
-    use super::{fmt, AddressFamily};
+    use super::{fmt, mem, AddressFamily, MacAddr};
 
     /// Hardware Address
     #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    #[repr(transparent)]
     pub struct LinkAddr(pub libc::sockaddr_ll);
 
     impl LinkAddr {
+        /// Create a new address suitable for binding an `AF_PACKET` socket.
+        ///
+        /// `protocol` is one of the `ETH_P_*` constants in network byte
+        /// order, as returned by [`protocol`](#method.protocol), and `ifindex`
+        /// selects the interface, or `0` for any interface.
+        pub fn new(protocol: u16, ifindex: usize) -> LinkAddr {
+            let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+            addr.sll_family = libc::AF_PACKET as libc::sa_family_t;
+            addr.sll_protocol = protocol;
+            addr.sll_ifindex = ifindex as libc::c_int;
+            LinkAddr(addr)
+        }
+
         /// Always AF_PACKET
         pub fn family(&self) -> AddressFamily {
             assert_eq!(self.0.sll_family as i32, libc::AF_PACKET);
@@ -1085,6 +1983,15 @@ mod datalink {
             self.0.sll_halen as usize
         }
 
+        /// Physical-layer address, if it is a 6-byte MAC address
+        pub fn mac_addr(&self) -> Option<MacAddr> {
+            if self.halen() == 6 {
+                Some(MacAddr::new(self.addr()))
+            } else {
+                None
+            }
+        }
+
         /// Physical-layer address (MAC)
         pub fn addr(&self) -> [u8; 6] {
             [
@@ -1119,7 +2026,7 @@ mod datalink {
           target_os = "netbsd",
           target_os = "openbsd"))]
 mod datalink {
-    use super::{fmt, AddressFamily};
+    use super::{fmt, AddressFamily, MacAddr};
 
     /// Hardware Address
     #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -1172,6 +2079,15 @@ mod datalink {
             alen == 0 || nlen + alen >= data_len
         }
 
+        /// Physical-layer address, if it is a 6-byte MAC address
+        pub fn mac_addr(&self) -> Option<MacAddr> {
+            if !self.is_empty() && self.alen() == 6 {
+                Some(MacAddr::new(self.addr()))
+            } else {
+                None
+            }
+        }
+
         /// Physical-layer address (MAC)
         pub fn addr(&self) -> [u8; 6] {
             let nlen = self.nlen();
@@ -1211,7 +2127,12 @@ pub mod vsock {
     use std::{fmt, mem};
     use std::hash::{Hash, Hasher};
 
+    /// VSOCK Address
+    ///
+    /// The address for AF_VSOCK socket is defined as a combination of a
+    /// 32-bit Context Identifier (CID) and a 32-bit port number.
     #[derive(Copy, Clone)]
+    #[repr(transparent)]
     pub struct VsockAddr(pub sockaddr_vm);
 
     impl PartialEq for VsockAddr {
@@ -1231,11 +2152,11 @@ pub mod vsock {
         }
     }
 
-    /// VSOCK Address
-    ///
-    /// The address for AF_VSOCK socket is defined as a combination of a
-    /// 32-bit Context Identifier (CID) and a 32-bit port number.
     impl VsockAddr {
+        /// Create a new address from a Context Identifier and a port number.
+        ///
+        /// Use `libc::VMADDR_CID_ANY` and `libc::VMADDR_PORT_ANY` to bind to
+        /// any CID or to let the kernel pick a port.
         pub fn new(cid: u32, port: u32) -> VsockAddr {
             let mut addr: sockaddr_vm = unsafe { mem::zeroed() };
             addr.svm_family = AddressFamily::Vsock as sa_family_t;
@@ -1269,6 +2190,431 @@ pub mod vsock {
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod tipc {
+    use crate::sys::socket::addr::AddressFamily;
+    use libc::sa_family_t;
+    use std::fmt;
+
+    // From <linux/tipc.h>; not yet in libc
+    const TIPC_SERVICE_RANGE: u8 = 1;
+    const TIPC_SERVICE_ADDR: u8 = 2;
+    const TIPC_SOCKET_ADDR: u8 = 3;
+
+    /// Visibility of a TIPC service binding
+    #[repr(i8)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum TipcScope {
+        /// Visible throughout the cluster
+        Cluster = 2,
+        /// Visible on the local node only
+        Node = 3,
+    }
+
+    /// The forms a [`TipcAddr`](struct.TipcAddr.html) can take
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum TipcAddrKind {
+        /// A specific socket, identified by its port reference and node
+        Socket { port: u32, node: u32 },
+        /// A service instance, looked up within `domain` (0 for anywhere)
+        Service { service_type: u32, instance: u32, domain: u32 },
+        /// A range of instances of a service
+        ServiceRange { service_type: u32, lower: u32, upper: u32 },
+    }
+
+    /// TIPC Address
+    ///
+    /// This has the layout of the kernel's `struct sockaddr_tipc`.  See
+    /// [tipc.io](http://tipc.io/programming.html) for the meaning of the
+    /// different address forms.
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub struct TipcAddr {
+        family: sa_family_t,
+        addrtype: u8,
+        scope: i8,
+        addr: [u32; 3],
+    }
+
+    impl TipcAddr {
+        fn new(addrtype: u8, scope: i8, addr: [u32; 3]) -> TipcAddr {
+            TipcAddr {
+                family: AddressFamily::Tipc as sa_family_t,
+                addrtype,
+                scope,
+                addr,
+            }
+        }
+
+        /// Address of a specific socket
+        pub fn new_socket(port: u32, node: u32) -> TipcAddr {
+            TipcAddr::new(TIPC_SOCKET_ADDR, 0, [port, node, 0])
+        }
+
+        /// Address of a service instance.  When binding, `scope` sets the
+        /// visibility of the binding.  When connecting or sending, any node
+        /// providing the service may be chosen.
+        pub fn new_service(service_type: u32, instance: u32, scope: TipcScope)
+            -> TipcAddr
+        {
+            TipcAddr::new(TIPC_SERVICE_ADDR, scope as i8,
+                          [service_type, instance, 0])
+        }
+
+        /// Address of a range of service instances, from `lower` to `upper`
+        /// inclusive.  Binding to it makes the socket serve all of them; a
+        /// message sent to it is multicast to every matching socket.
+        pub fn new_service_range(service_type: u32, lower: u32, upper: u32,
+                                 scope: TipcScope) -> TipcAddr
+        {
+            TipcAddr::new(TIPC_SERVICE_RANGE, scope as i8,
+                          [service_type, lower, upper])
+        }
+
+        /// The form of this address, or `None` for an unknown address type.
+        pub fn kind(&self) -> Option<TipcAddrKind> {
+            let [a, b, c] = self.addr;
+            match self.addrtype {
+                TIPC_SOCKET_ADDR => Some(TipcAddrKind::Socket {
+                    port: a,
+                    node: b,
+                }),
+                TIPC_SERVICE_ADDR => Some(TipcAddrKind::Service {
+                    service_type: a,
+                    instance: b,
+                    domain: c,
+                }),
+                TIPC_SERVICE_RANGE => Some(TipcAddrKind::ServiceRange {
+                    service_type: a,
+                    lower: b,
+                    upper: c,
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    impl fmt::Display for TipcAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self.kind() {
+                Some(TipcAddrKind::Socket { port, node }) =>
+                    write!(f, "{:x}:{}", node, port),
+                Some(TipcAddrKind::Service { service_type, instance, .. }) =>
+                    write!(f, "{}:{}", service_type, instance),
+                Some(TipcAddrKind::ServiceRange { service_type, lower, upper }) =>
+                    write!(f, "{}:{}:{}", service_type, lower, upper),
+                None => write!(f, "<unknown TIPC address type {}>", self.addrtype),
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod bluetooth {
+    use crate::sys::socket::addr::{AddressFamily, MacAddr};
+    use libc::{c_int, sa_family_t};
+    use std::fmt;
+
+    // From <bluetooth/bluetooth.h>; not yet in libc
+    /// Logical link control and adaptation protocol, for `SeqPacket`,
+    /// `Stream` and `Datagram` sockets
+    pub const BTPROTO_L2CAP: c_int = 0;
+    /// Host controller interface, for `Raw` sockets
+    pub const BTPROTO_HCI: c_int = 1;
+    /// Synchronous connection-oriented links, for `SeqPacket` sockets
+    pub const BTPROTO_SCO: c_int = 2;
+    /// Serial port emulation, for `Stream` sockets
+    pub const BTPROTO_RFCOMM: c_int = 3;
+
+    /// The kernel stores Bluetooth device addresses with the octets reversed
+    fn to_bdaddr(addr: MacAddr) -> [u8; 6] {
+        let mut octets = addr.octets();
+        octets.reverse();
+        octets
+    }
+
+    fn from_bdaddr(mut bdaddr: [u8; 6]) -> MacAddr {
+        bdaddr.reverse();
+        MacAddr::new(bdaddr)
+    }
+
+    /// Bluetooth RFCOMM address
+    ///
+    /// This has the layout of the kernel's `struct sockaddr_rc`.  Use it with
+    /// `Stream` sockets of protocol [`BTPROTO_RFCOMM`](constant.BTPROTO_RFCOMM.html).
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub struct RfcommAddr {
+        family: sa_family_t,
+        bdaddr: [u8; 6],
+        channel: u8,
+    }
+
+    impl RfcommAddr {
+        /// Address of channel `channel`, from 1 to 30, of a device.  Bind to
+        /// `MacAddr::default()` to listen on all local adapters, and to
+        /// channel 0 to let the kernel pick a free channel.
+        pub fn new(bdaddr: MacAddr, channel: u8) -> RfcommAddr {
+            RfcommAddr {
+                family: AddressFamily::Bluetooth as sa_family_t,
+                bdaddr: to_bdaddr(bdaddr),
+                channel,
+            }
+        }
+
+        /// Device address
+        pub fn bdaddr(&self) -> MacAddr {
+            from_bdaddr(self.bdaddr)
+        }
+
+        /// Channel number
+        pub fn channel(&self) -> u8 {
+            self.channel
+        }
+    }
+
+    impl fmt::Display for RfcommAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} channel {}", self.bdaddr(), self.channel)
+        }
+    }
+
+    /// Bluetooth L2CAP address
+    ///
+    /// This has the layout of the kernel's `struct sockaddr_l2`.  Use it with
+    /// sockets of protocol [`BTPROTO_L2CAP`](constant.BTPROTO_L2CAP.html).
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub struct L2capAddr {
+        family: sa_family_t,
+        psm: u16,
+        bdaddr: [u8; 6],
+        cid: u16,
+        bdaddr_type: u8,
+    }
+
+    impl L2capAddr {
+        /// Address of the Protocol/Service Multiplexer `psm` of a BR/EDR
+        /// device.  Bind to `MacAddr::default()` to listen on all local
+        /// adapters.
+        pub fn new(bdaddr: MacAddr, psm: u16) -> L2capAddr {
+            L2capAddr {
+                family: AddressFamily::Bluetooth as sa_family_t,
+                psm: psm.to_le(),
+                bdaddr: to_bdaddr(bdaddr),
+                cid: 0,
+                bdaddr_type: 0,
+            }
+        }
+
+        /// Device address
+        pub fn bdaddr(&self) -> MacAddr {
+            from_bdaddr(self.bdaddr)
+        }
+
+        /// Protocol/Service Multiplexer
+        pub fn psm(&self) -> u16 {
+            u16::from_le(self.psm)
+        }
+
+        /// Channel identifier, for fixed channels
+        pub fn cid(&self) -> u16 {
+            u16::from_le(self.cid)
+        }
+
+        /// Type of the device address: 0 for BR/EDR, 1 for LE public and 2
+        /// for LE random addresses
+        pub fn bdaddr_type(&self) -> u8 {
+            self.bdaddr_type
+        }
+    }
+
+    impl fmt::Display for L2capAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} psm {}", self.bdaddr(), self.psm())
+        }
+    }
+}
+
+/// `Serialize` and `Deserialize` implementations, using the same text form as
+/// `Display` and `FromStr`.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::*;
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use serde::ser::SerializeMap;
+
+    macro_rules! deserialize_from_str {
+        ($($ty:ty),*) => {
+            $(
+                impl<'de> Deserialize<'de> for $ty {
+                    fn deserialize<D>(deserializer: D) -> std::result::Result<$ty, D::Error>
+                        where D: Deserializer<'de>
+                    {
+                        let s = String::deserialize(deserializer)?;
+                        s.parse().map_err(de::Error::custom)
+                    }
+                }
+            )*
+        };
+    }
+
+    macro_rules! serialize_display {
+        ($($ty:ty),*) => {
+            $(
+                impl Serialize for $ty {
+                    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+                        where S: Serializer
+                    {
+                        serializer.collect_str(self)
+                    }
+                }
+            )*
+        };
+    }
+
+    deserialize_from_str!(InetAddr, IpAddr, Ipv4Addr, Ipv6Addr);
+    serialize_display!(InetAddr, IpAddr, Ipv4Addr, Ipv6Addr);
+
+    /// Text form of a UNIX address, if it parses back into the same address
+    /// with `parse`.  Unnamed addresses are represented by an empty string.
+    fn unix_addr_str<T>(addr: &UnixAddr, parse: impl Fn(&str) -> Option<T>,
+                        expected: &T) -> Option<String>
+        where T: PartialEq
+    {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        let abstract_name = addr.as_abstract();
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        let abstract_name: Option<&[u8]> = None;
+
+        let s = if addr.is_unnamed() {
+            String::new()
+        } else if let Some(path) = addr.path() {
+            path.to_str()?.to_owned()
+        } else {
+            format!("@{}", std::str::from_utf8(abstract_name?).ok()?)
+        };
+        // A path like "@foo" would parse as an abstract address
+        if parse(&s).as_ref() == Some(expected) {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// Structured form of UNIX addresses that have no text form: a map with
+    /// the single key `path` or `abstract`, and the raw name as a sequence of
+    /// bytes.  Unnamed addresses have an empty `path`.
+    fn serialize_unix_addr_map<S>(addr: &UnixAddr, serializer: S)
+        -> std::result::Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if let Some(name) = addr.as_abstract() {
+                map.serialize_entry("abstract", name)?;
+                return map.end();
+            }
+        }
+        let path = addr.path().map_or(&[][..], |path| path.as_os_str().as_bytes());
+        map.serialize_entry("path", path)?;
+        map.end()
+    }
+
+    fn deserialize_unix_addr_map<'de, A>(mut map: A) -> std::result::Result<UnixAddr, A::Error>
+        where A: de::MapAccess<'de>
+    {
+        let (key, name) = match map.next_entry::<String, Vec<u8>>()? {
+            Some(entry) => entry,
+            None => return Err(de::Error::invalid_length(0, &"one entry")),
+        };
+        let addr = match key.as_str() {
+            "path" if name.is_empty() => Ok(UnixAddr::new_unnamed()),
+            "path" => UnixAddr::new(OsStr::from_bytes(&name)),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            "abstract" => UnixAddr::new_abstract(&name),
+            _ => return Err(de::Error::unknown_field(&key, &["path", "abstract"])),
+        };
+        addr.map_err(de::Error::custom)
+    }
+
+    /// Accepts both the text and the structured form of an address, the
+    /// latter converted with the given function.
+    struct AddrVisitor<T>(fn(UnixAddr) -> T);
+
+    impl<'de, T> de::Visitor<'de> for AddrVisitor<T>
+        where T: FromStr, T::Err: fmt::Display
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a socket address")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> std::result::Result<T, E> {
+            s.parse().map_err(de::Error::custom)
+        }
+
+        fn visit_map<A>(self, map: A) -> std::result::Result<T, A::Error>
+            where A: de::MapAccess<'de>
+        {
+            deserialize_unix_addr_map(map).map(self.0)
+        }
+    }
+
+    /// UNIX addresses whose text form wouldn't parse back into the same
+    /// address, such as paths starting with `@` or that aren't valid UTF-8,
+    /// are serialized in a structured form instead.
+    impl Serialize for UnixAddr {
+        fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            match unix_addr_str(self, |s| s.parse::<UnixAddr>().ok(), self) {
+                Some(s) => serializer.serialize_str(&s),
+                None => serialize_unix_addr_map(self, serializer),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for UnixAddr {
+        fn deserialize<D>(deserializer: D) -> std::result::Result<UnixAddr, D::Error>
+            where D: Deserializer<'de>
+        {
+            deserializer.deserialize_any(AddrVisitor(|addr| addr))
+        }
+    }
+
+    /// Only internet and UNIX addresses are supported.  UNIX addresses that
+    /// `SockAddr::from_str` can't tell apart from others, such as relative
+    /// paths, are serialized in the structured form of `UnixAddr`.
+    impl Serialize for SockAddr {
+        fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            match *self {
+                SockAddr::Inet(ref inet) => inet.serialize(serializer),
+                SockAddr::Unix(ref unix) => {
+                    match unix_addr_str(unix, |s| s.parse::<SockAddr>().ok(), self) {
+                        Some(s) => serializer.serialize_str(&s),
+                        None => serialize_unix_addr_map(unix, serializer),
+                    }
+                }
+                _ => Err(ser::Error::custom(
+                    "only internet and UNIX socket addresses can be serialized")),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SockAddr {
+        fn deserialize<D>(deserializer: D) -> std::result::Result<SockAddr, D::Error>
+            where D: Deserializer<'de>
+        {
+            deserializer.deserialize_any(AddrVisitor(SockAddr::Unix))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(any(target_os = "android",
@@ -6,7 +6,9 @@ use crate::{Error, Result, errno::Errno};
 use libc::{self, c_void, c_int, iovec, socklen_t, size_t,
         CMSG_FIRSTHDR, CMSG_NXTHDR, CMSG_DATA, CMSG_LEN};
 use std::{mem, ptr, slice};
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+#[cfg(target_os = "linux")]
+use crate::sys::time::TimeSpec;
 use crate::sys::time::TimeVal;
 use crate::sys::uio::IoVec;
 
@@ -28,6 +30,9 @@ pub use self::addr::{
     Ipv4Addr,
     Ipv6Addr,
     LinkAddr,
+    MacAddr,
+    SockaddrLike,
+    SockaddrStorage,
 };
 #[cfg(any(target_os = "android", target_os = "linux"))]
 pub use crate::sys::socket::addr::netlink::NetlinkAddr;
@@ -35,6 +40,19 @@ pub use crate::sys::socket::addr::netlink::NetlinkAddr;
 pub use crate::sys::socket::addr::alg::AlgAddr;
 #[cfg(target_os = "linux")]
 pub use crate::sys::socket::addr::vsock::VsockAddr;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub use crate::sys::socket::addr::sys_control::SysControlAddr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use crate::sys::socket::addr::tipc::{TipcAddr, TipcAddrKind, TipcScope};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use crate::sys::socket::addr::bluetooth::{
+    L2capAddr,
+    RfcommAddr,
+    BTPROTO_HCI,
+    BTPROTO_L2CAP,
+    BTPROTO_RFCOMM,
+    BTPROTO_SCO,
+};
 
 pub use libc::{
     cmsghdr,
@@ -73,10 +91,16 @@ pub enum SockType {
     /// Provides a reliable datagram layer that does not
     /// guarantee ordering.
     Rdm = libc::SOCK_RDM,
+    /// Provides unreliable, connection-oriented datagrams with congestion
+    /// control, using the Datagram Congestion Control Protocol
+    /// ([dccp(7)](https://man7.org/linux/man-pages/man7/dccp.7.html)).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Dccp = libc::SOCK_DCCP,
 }
 
-/// Constants used in [`socket`](fn.socket.html) and [`socketpair`](fn.socketpair.html)
-/// to specify the protocol to use.
+/// Internet protocols of `AF_INET` and `AF_INET6` sockets, used in
+/// [`socket`](fn.socket.html) and [`socketpair`](fn.socketpair.html) to specify
+/// the protocol to use.
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum SockProtocol {
@@ -84,16 +108,173 @@ pub enum SockProtocol {
     Tcp = libc::IPPROTO_TCP,
     /// UDP protocol ([ip(7)](http://man7.org/linux/man-pages/man7/ip.7.html))
     Udp = libc::IPPROTO_UDP,
+    /// ICMP protocol.  On Linux, a `Datagram` socket with this protocol is an
+    /// unprivileged "ping" socket, subject to the `net.ipv4.ping_group_range`
+    /// sysctl ([icmp(7)](http://man7.org/linux/man-pages/man7/icmp.7.html))
+    Icmp = libc::IPPROTO_ICMP,
+    /// ICMPv6 protocol.  Like `Icmp`, but for IPv6
+    /// ([icmp(7)](http://man7.org/linux/man-pages/man7/icmp.7.html))
+    IcmpV6 = libc::IPPROTO_ICMPV6,
+    /// Multipath TCP.  Use it with a `Stream` socket in place of TCP
+    /// ([mptcp(7)](http://man7.org/linux/man-pages/man7/mptcp.7.html))
+    #[cfg(target_os = "linux")]
+    MptcpTcp = libc::IPPROTO_MPTCP,
+    /// Stream Control Transmission Protocol.  Use it with a `Stream` socket for
+    /// one-to-one style or with a `SeqPacket` socket for one-to-many style
+    /// ([sctp(7)](http://man7.org/linux/man-pages/man7/sctp.7.html))
+    #[cfg(any(target_os = "android",
+              target_os = "freebsd",
+              target_os = "linux"))]
+    Sctp = libc::IPPROTO_SCTP,
+    /// Datagram Congestion Control Protocol, used with
+    /// [`SockType::Dccp`](enum.SockType.html)
+    /// ([dccp(7)](https://man7.org/linux/man-pages/man7/dccp.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Dccp = libc::IPPROTO_DCCP,
+}
+
+/// Protocols of `AF_NETLINK` sockets, used in [`socket`](fn.socket.html) and
+/// [`socketpair`](fn.socketpair.html) to pick the kernel subsystem to talk to.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NetlinkProtocol {
+    /// Receives routing and link updates and may be used to modify the routing
+    /// tables, addresses and link parameters
+    /// ([rtnetlink(7)](http://man7.org/linux/man-pages/man7/rtnetlink.7.html))
+    Route = libc::NETLINK_ROUTE,
+    /// Reserved for user-mode socket protocols
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    UserSock = libc::NETLINK_USERSOCK,
+    /// Query information about sockets of various protocol families from the kernel
+    /// ([sock_diag(7)](http://man7.org/linux/man-pages/man7/sock_diag.7.html))
+    SockDiag = libc::NETLINK_SOCK_DIAG,
+    /// Netfilter/iptables ULOG
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    NFLOG = libc::NETLINK_NFLOG,
+    /// IPsec transformations
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Xfrm = libc::NETLINK_XFRM,
+    /// SELinux event notifications
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    SELinux = libc::NETLINK_SELINUX,
+    /// Open-iSCSI
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Iscsi = libc::NETLINK_ISCSI,
+    /// Auditing
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Audit = libc::NETLINK_AUDIT,
+    /// Access to FIB lookup from user space
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    FIBLookup = libc::NETLINK_FIB_LOOKUP,
+    /// Kernel connector
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Connector = libc::NETLINK_CONNECTOR,
+    /// Netfilter subsystem
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    NetFilter = libc::NETLINK_NETFILTER,
+    /// Kernel messages to user space, e.g. device hotplug events
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    KObjectUEvent = libc::NETLINK_KOBJECT_UEVENT,
+    /// Generic netlink family for simplified netlink usage
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Generic = libc::NETLINK_GENERIC,
+    /// Infiniband RDMA
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    RDMA = libc::NETLINK_RDMA,
+    /// Netlink interface to request information about ciphers registered with
+    /// the kernel crypto API
+    /// ([netlink(7)](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    Crypto = libc::NETLINK_CRYPTO,
+}
+
+/// Ethernet protocols of `AF_PACKET` sockets, used in
+/// [`socket`](fn.socket.html) to pick the packets to receive.  The values are
+/// in network byte order, as `socket` and [`LinkAddr`](struct.LinkAddr.html)
+/// expect.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EthProtocol {
+    /// Receive every Ethernet protocol
+    /// ([packet(7)](http://man7.org/linux/man-pages/man7/packet.7.html))
+    All = (libc::ETH_P_ALL as u16).to_be() as i32,
+    /// IPv4 packets
+    /// ([packet(7)](http://man7.org/linux/man-pages/man7/packet.7.html))
+    Ip = (libc::ETH_P_IP as u16).to_be() as i32,
+    /// ARP packets
+    /// ([packet(7)](http://man7.org/linux/man-pages/man7/packet.7.html))
+    Arp = (libc::ETH_P_ARP as u16).to_be() as i32,
+    /// IPv6 packets
+    /// ([packet(7)](http://man7.org/linux/man-pages/man7/packet.7.html))
+    Ipv6 = (libc::ETH_P_IPV6 as u16).to_be() as i32,
+}
+
+/// Protocols of `AF_SYSTEM` sockets, used in [`socket`](fn.socket.html) to
+/// talk to kernel extensions.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SysProtocol {
     /// Allows applications and other KEXTs to be notified when certain kernel events occur
     /// ([ref](https://developer.apple.com/library/content/documentation/Darwin/Conceptual/NKEConceptual/control/control.html))
-    #[cfg(any(target_os = "ios", target_os = "macos"))]
     KextEvent = libc::SYSPROTO_EVENT,
     /// Allows applications to configure and control a KEXT
     /// ([ref](https://developer.apple.com/library/content/documentation/Darwin/Conceptual/NKEConceptual/control/control.html))
-    #[cfg(any(target_os = "ios", target_os = "macos"))]
     KextControl = libc::SYSPROTO_CONTROL,
 }
 
+/// A protocol number, as passed to [`socket`](fn.socket.html) and
+/// [`socketpair`](fn.socketpair.html).
+///
+/// Those functions accept a [`SockProtocol`](enum.SockProtocol.html),
+/// [`NetlinkProtocol`](enum.NetlinkProtocol.html),
+/// [`EthProtocol`](enum.EthProtocol.html) or `SysProtocol`, `None` for the
+/// default protocol of the socket type, or a raw protocol number for
+/// protocols those lack, such as `libc::IPPROTO_GRE` or a custom netlink
+/// protocol.  Note that `AF_PACKET` protocols are in network byte order.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RawSockProtocol(pub c_int);
+
+impl From<SockProtocol> for RawSockProtocol {
+    fn from(protocol: SockProtocol) -> RawSockProtocol {
+        RawSockProtocol(protocol as c_int)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl From<NetlinkProtocol> for RawSockProtocol {
+    fn from(protocol: NetlinkProtocol) -> RawSockProtocol {
+        RawSockProtocol(protocol as c_int)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl From<EthProtocol> for RawSockProtocol {
+    fn from(protocol: EthProtocol) -> RawSockProtocol {
+        RawSockProtocol(protocol as c_int)
+    }
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+impl From<SysProtocol> for RawSockProtocol {
+    fn from(protocol: SysProtocol) -> RawSockProtocol {
+        RawSockProtocol(protocol as c_int)
+    }
+}
+
+impl From<Option<SockProtocol>> for RawSockProtocol {
+    fn from(protocol: Option<SockProtocol>) -> RawSockProtocol {
+        RawSockProtocol(protocol.map_or(0, |p| p as c_int))
+    }
+}
+
+impl From<c_int> for RawSockProtocol {
+    fn from(protocol: c_int) -> RawSockProtocol {
+        RawSockProtocol(protocol)
+    }
+}
+
 libc_bitflags!{
     /// Additional socket options
     pub struct SockFlag: c_int {
@@ -182,6 +363,59 @@ libc_bitflags!{
                   target_os = "netbsd",
                   target_os = "openbsd"))]
         MSG_CMSG_CLOEXEC;
+        /// Don't generate a `SIGPIPE` signal if the peer on a stream-oriented
+        /// socket has closed the connection.  The `EPIPE` error is still
+        /// returned.
+        ///
+        /// Only used in [`send`](fn.send.html), [`sendto`](fn.sendto.html)
+        /// and [`sendmsg`](fn.sendmsg.html).
+        #[cfg(any(target_os = "android",
+                  target_os = "dragonfly",
+                  target_os = "freebsd",
+                  target_os = "linux",
+                  target_os = "netbsd",
+                  target_os = "openbsd"))]
+        MSG_NOSIGNAL;
+        /// The caller has more data to send.  The kernel holds back partial
+        /// frames (for TCP) or accumulates data into a single datagram (for
+        /// UDP) until a call is made without this flag.
+        /// (For more details, see
+        /// [send(2)](https://man7.org/linux/man-pages/man2/send.2.html))
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        MSG_MORE;
+        /// Tell the link layer that forward progress happened: a successful
+        /// reply was received from the other side.  Only valid on
+        /// `SOCK_DGRAM` and `SOCK_RAW` sockets.
+        /// (For more details, see
+        /// [send(2)](https://man7.org/linux/man-pages/man2/send.2.html))
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        MSG_CONFIRM;
+    }
+}
+
+#[cfg(target_os = "linux")]
+libc_bitflags!{
+    /// Configuration flags for `SO_TIMESTAMPING` interface
+    ///
+    /// For use with [`Timestamping`](sockopt/struct.Timestamping.html).
+    ///
+    /// [Further reading](https://www.kernel.org/doc/html/latest/networking/timestamping.html)
+    #[repr(transparent)]
+    pub struct TimestampingFlag: c_uint {
+        /// Request a timestamp when the network adapter transmits the packet
+        SOF_TIMESTAMPING_TX_HARDWARE;
+        /// Request a timestamp when the packet leaves the kernel's network
+        /// stack
+        SOF_TIMESTAMPING_TX_SOFTWARE;
+        /// Request a timestamp when the network adapter receives the packet
+        SOF_TIMESTAMPING_RX_HARDWARE;
+        /// Request a timestamp when the packet enters the kernel's network
+        /// stack
+        SOF_TIMESTAMPING_RX_SOFTWARE;
+        /// Report any software timestamps when available
+        SOF_TIMESTAMPING_SOFTWARE;
+        /// Report hardware timestamps when available
+        SOF_TIMESTAMPING_RAW_HARDWARE;
     }
 }
 
@@ -189,7 +423,8 @@ cfg_if! {
     if #[cfg(any(target_os = "android", target_os = "linux"))] {
         /// Unix credentials of the sending process.
         ///
-        /// This struct is used with the `SO_PEERCRED` ancillary message
+        /// This struct is used with the `SO_PEERCRED` socket option (see
+        /// [`sockopt::PeerCredentials`](sockopt/struct.PeerCredentials.html))
         /// and the `SCM_CREDENTIALS` control message for UNIX sockets.
         #[repr(transparent)]
         #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -281,144 +516,1152 @@ cfg_if! {
     }
 }
 
-/// Request for multicast socket operations
+/// Credentials of the peer of a UNIX domain socket.
 ///
-/// This is a wrapper type around `ip_mreq`.
+/// This struct is used with the `LOCAL_PEERCRED` socket option (see
+/// [`sockopt::LocalPeerCred`](sockopt/struct.LocalPeerCred.html)).  The
+/// credentials are those the peer had when it called `connect` or `listen`.
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos"))]
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct IpMembershipRequest(libc::ip_mreq);
+pub struct XuCred(libc::xucred);
+
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos"))]
+impl XuCred {
+    /// Returns the effective user identifier
+    pub fn uid(&self) -> libc::uid_t {
+        self.0.cr_uid
+    }
 
-impl IpMembershipRequest {
-    /// Instantiate a new `IpMembershipRequest`
-    ///
-    /// If `interface` is `None`, then `Ipv4Addr::any()` will be used for the interface.
-    pub fn new(group: Ipv4Addr, interface: Option<Ipv4Addr>) -> Self {
-        IpMembershipRequest(libc::ip_mreq {
-            imr_multiaddr: group.0,
-            imr_interface: interface.unwrap_or_else(Ipv4Addr::any).0,
-        })
+    /// Returns a list of group identifiers (the first one being the effective
+    /// GID)
+    pub fn groups(&self) -> &[libc::gid_t] {
+        &self.0.cr_groups[..self.0.cr_ngroups as usize]
     }
 }
 
-/// Request for ipv6 multicast socket operations
-///
-/// This is a wrapper type around `ipv6_mreq`.
-#[repr(transparent)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct Ipv6MembershipRequest(libc::ipv6_mreq);
-
-impl Ipv6MembershipRequest {
-    /// Instantiate a new `Ipv6MembershipRequest`
-    pub fn new(group: Ipv6Addr) -> Self {
-        Ipv6MembershipRequest(libc::ipv6_mreq {
-            ipv6mr_multiaddr: group.0,
-            ipv6mr_interface: 0,
-        })
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos"))]
+impl From<libc::xucred> for XuCred {
+    fn from(cred: libc::xucred) -> Self {
+        XuCred(cred)
     }
 }
 
-/// Create a buffer large enough for storing some control messages as returned
-/// by [`recvmsg`](fn.recvmsg.html).
+/// Statistics of a TCP connection, as returned by the
+/// [`TcpInfo`](sockopt/struct.TcpInfo.html) socket option.
 ///
-/// # Examples
+/// The layout follows the kernel's `struct tcp_info`.  Fields that the running
+/// kernel does not know about are reported as zero.
 ///
-/// ```
-/// # #[macro_use] extern crate nix;
-/// # use nix::sys::time::TimeVal;
-/// # use std::os::unix::io::RawFd;
-/// # fn main() {
-/// // Create a buffer for a `ControlMessageOwned::ScmTimestamp` message
-/// let _ = cmsg_space!(TimeVal);
-/// // Create a buffer big enough for a `ControlMessageOwned::ScmRights` message
-/// // with two file descriptors
-/// let _ = cmsg_space!([RawFd; 2]);
-/// // Create a buffer big enough for a `ControlMessageOwned::ScmRights` message
-/// // and a `ControlMessageOwned::ScmTimestamp` message
-/// let _ = cmsg_space!(RawFd, TimeVal);
-/// # }
-/// ```
-// Unfortunately, CMSG_SPACE isn't a const_fn, or else we could return a
-// stack-allocated array.
-#[macro_export]
-macro_rules! cmsg_space {
-    ( $( $x:ty ),* ) => {
-        {
-            use nix::sys::socket::{c_uint, CMSG_SPACE};
-            use std::mem;
-            let mut space = 0;
-            $(
-                // CMSG_SPACE is always safe
-                space += unsafe {
-                    CMSG_SPACE(mem::size_of::<$x>() as c_uint)
-                } as usize;
-            )*
-            Vec::<u8>::with_capacity(space)
-        }
-    }
+/// For further information, please refer to the
+/// [`tcp(7)`](http://man7.org/linux/man-pages/man7/tcp.7.html) man page.
+// libc's tcp_info differs between C libraries, so mirror the kernel's ABI
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TcpInfo {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_snd_rcv_wscale: u8,
+    _tcpi_delivery_bitfields: u8,
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    _tcpi_fackets: u32,
+    tcpi_last_data_sent: u32,
+    _tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+    tcpi_total_retrans: u32,
+    tcpi_pacing_rate: u64,
+    tcpi_max_pacing_rate: u64,
+    tcpi_bytes_acked: u64,
+    tcpi_bytes_received: u64,
+    tcpi_segs_out: u32,
+    tcpi_segs_in: u32,
+    tcpi_notsent_bytes: u32,
+    tcpi_min_rtt: u32,
+    tcpi_data_segs_in: u32,
+    tcpi_data_segs_out: u32,
+    tcpi_delivery_rate: u64,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct RecvMsg<'a> {
-    pub bytes: usize,
-    cmsghdr: Option<&'a cmsghdr>,
-    pub address: Option<SockAddr>,
-    pub flags: MsgFlags,
-    mhdr: msghdr,
-}
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl TcpInfo {
+    /// TCP state of the connection, as one of the `TCP_ESTABLISHED` family of
+    /// constants from `<netinet/tcp.h>`
+    pub fn state(&self) -> u8 {
+        self.tcpi_state
+    }
 
-impl<'a> RecvMsg<'a> {
-    /// Iterate over the valid control messages pointed to by this
-    /// msghdr.
-    pub fn cmsgs(&self) -> CmsgIterator {
-        CmsgIterator {
-            cmsghdr: self.cmsghdr,
-            mhdr: &self.mhdr
-        }
+    /// State of the congestion avoidance algorithm
+    pub fn ca_state(&self) -> u8 {
+        self.tcpi_ca_state
     }
-}
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct CmsgIterator<'a> {
-    /// Control message buffer to decode from. Must adhere to cmsg alignment.
-    cmsghdr: Option<&'a cmsghdr>,
-    mhdr: &'a msghdr
-}
+    /// Number of unrecovered retransmission timeouts
+    pub fn retransmits(&self) -> u8 {
+        self.tcpi_retransmits
+    }
 
-impl<'a> Iterator for CmsgIterator<'a> {
-    type Item = ControlMessageOwned;
+    /// Number of unanswered zero window probes
+    pub fn probes(&self) -> u8 {
+        self.tcpi_probes
+    }
 
-    fn next(&mut self) -> Option<ControlMessageOwned> {
-        match self.cmsghdr {
-            None => None,   // No more messages
-            Some(hdr) => {
-                // Get the data.
-                // Safe if cmsghdr points to valid data returned by recvmsg(2)
-                let cm = unsafe { Some(ControlMessageOwned::decode_from(hdr))};
-                // Advance the internal pointer.  Safe if mhdr and cmsghdr point
-                // to valid data returned by recvmsg(2)
-                self.cmsghdr = unsafe {
-                    let p = CMSG_NXTHDR(self.mhdr as *const _, hdr as *const _);
-                    p.as_ref()
-                };
-                cm
-            }
+    /// Exponential backoff of the retransmission timer
+    pub fn backoff(&self) -> u8 {
+        self.tcpi_backoff
+    }
+
+    /// Options negotiated on the connection, as `TCPI_OPT_*` flags
+    pub fn options(&self) -> u8 {
+        self.tcpi_options
+    }
+
+    /// Window scale used by the peer
+    pub fn snd_wscale(&self) -> u8 {
+        // C bit-fields are allocated starting at the low bits on
+        // little-endian platforms and at the high bits on big-endian ones
+        if cfg!(target_endian = "little") {
+            self.tcpi_snd_rcv_wscale & 0x0f
+        } else {
+            self.tcpi_snd_rcv_wscale >> 4
+        }
+    }
+
+    /// Window scale used locally
+    pub fn rcv_wscale(&self) -> u8 {
+        if cfg!(target_endian = "little") {
+            self.tcpi_snd_rcv_wscale >> 4
+        } else {
+            self.tcpi_snd_rcv_wscale & 0x0f
         }
     }
+
+    /// Retransmission timeout in microseconds
+    pub fn rto(&self) -> u32 {
+        self.tcpi_rto
+    }
+
+    /// Delayed acknowledgement timeout in microseconds
+    pub fn ato(&self) -> u32 {
+        self.tcpi_ato
+    }
+
+    /// Maximum segment size for sending
+    pub fn snd_mss(&self) -> u32 {
+        self.tcpi_snd_mss
+    }
+
+    /// Estimated maximum segment size of the peer
+    pub fn rcv_mss(&self) -> u32 {
+        self.tcpi_rcv_mss
+    }
+
+    /// Number of segments sent but not yet acknowledged
+    pub fn unacked(&self) -> u32 {
+        self.tcpi_unacked
+    }
+
+    /// Number of segments selectively acknowledged by the peer
+    pub fn sacked(&self) -> u32 {
+        self.tcpi_sacked
+    }
+
+    /// Number of segments deemed lost
+    pub fn lost(&self) -> u32 {
+        self.tcpi_lost
+    }
+
+    /// Number of segments currently being retransmitted
+    pub fn retrans(&self) -> u32 {
+        self.tcpi_retrans
+    }
+
+    /// Milliseconds since data was last sent
+    pub fn last_data_sent(&self) -> u32 {
+        self.tcpi_last_data_sent
+    }
+
+    /// Milliseconds since data was last received
+    pub fn last_data_recv(&self) -> u32 {
+        self.tcpi_last_data_recv
+    }
+
+    /// Milliseconds since an acknowledgement was last received
+    pub fn last_ack_recv(&self) -> u32 {
+        self.tcpi_last_ack_recv
+    }
+
+    /// Path MTU
+    pub fn pmtu(&self) -> u32 {
+        self.tcpi_pmtu
+    }
+
+    /// Receive window slow-start threshold
+    pub fn rcv_ssthresh(&self) -> u32 {
+        self.tcpi_rcv_ssthresh
+    }
+
+    /// Smoothed round trip time in microseconds
+    pub fn rtt(&self) -> u32 {
+        self.tcpi_rtt
+    }
+
+    /// Round trip time variance in microseconds
+    pub fn rttvar(&self) -> u32 {
+        self.tcpi_rttvar
+    }
+
+    /// Slow-start threshold of the congestion window
+    pub fn snd_ssthresh(&self) -> u32 {
+        self.tcpi_snd_ssthresh
+    }
+
+    /// Congestion window, in segments
+    pub fn snd_cwnd(&self) -> u32 {
+        self.tcpi_snd_cwnd
+    }
+
+    /// Maximum segment size advertised to the peer
+    pub fn advmss(&self) -> u32 {
+        self.tcpi_advmss
+    }
+
+    /// Reordering metric, in segments
+    pub fn reordering(&self) -> u32 {
+        self.tcpi_reordering
+    }
+
+    /// Receiver-side round trip time estimate in microseconds
+    pub fn rcv_rtt(&self) -> u32 {
+        self.tcpi_rcv_rtt
+    }
+
+    /// Receive buffer space advertised for auto-tuning
+    pub fn rcv_space(&self) -> u32 {
+        self.tcpi_rcv_space
+    }
+
+    /// Total number of retransmitted segments
+    pub fn total_retrans(&self) -> u32 {
+        self.tcpi_total_retrans
+    }
+
+    /// Current pacing rate in bytes per second
+    pub fn pacing_rate(&self) -> u64 {
+        self.tcpi_pacing_rate
+    }
+
+    /// Maximum pacing rate in bytes per second
+    pub fn max_pacing_rate(&self) -> u64 {
+        self.tcpi_max_pacing_rate
+    }
+
+    /// Number of bytes acknowledged by the peer
+    pub fn bytes_acked(&self) -> u64 {
+        self.tcpi_bytes_acked
+    }
+
+    /// Number of bytes received from the peer
+    pub fn bytes_received(&self) -> u64 {
+        self.tcpi_bytes_received
+    }
+
+    /// Number of segments sent
+    pub fn segs_out(&self) -> u32 {
+        self.tcpi_segs_out
+    }
+
+    /// Number of segments received
+    pub fn segs_in(&self) -> u32 {
+        self.tcpi_segs_in
+    }
+
+    /// Number of bytes queued but not yet sent
+    pub fn notsent_bytes(&self) -> u32 {
+        self.tcpi_notsent_bytes
+    }
+
+    /// Minimum round trip time observed, in microseconds
+    pub fn min_rtt(&self) -> u32 {
+        self.tcpi_min_rtt
+    }
+
+    /// Number of segments received carrying data
+    pub fn data_segs_in(&self) -> u32 {
+        self.tcpi_data_segs_in
+    }
+
+    /// Number of segments sent carrying data
+    pub fn data_segs_out(&self) -> u32 {
+        self.tcpi_data_segs_out
+    }
+
+    /// Most recent delivery rate in bytes per second
+    pub fn delivery_rate(&self) -> u64 {
+        self.tcpi_delivery_rate
+    }
 }
 
-/// A type-safe wrapper around a single control message, as used with
-/// [`recvmsg`](#fn.recvmsg).
+/// State of a multipath TCP connection, as returned by the
+/// [`MptcpInfo`](sockopt/struct.MptcpInfo.html) socket option.
 ///
-/// [Further reading](http://man7.org/linux/man-pages/man3/cmsg.3.html)
-//  Nix version 0.13.0 and earlier used ControlMessage for both recvmsg and
-//  sendmsg.  However, on some platforms the messages returned by recvmsg may be
-//  unaligned.  ControlMessageOwned takes those messages by copy, obviating any
-//  alignment issues.
-//
-//  See https://github.com/nix-rust/nix/issues/999
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum ControlMessageOwned {
-    /// Received version of
+/// The layout follows the kernel's `struct mptcp_info`.  Fields that the
+/// running kernel does not know about are reported as zero.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MptcpInfo {
+    mptcpi_subflows: u8,
+    mptcpi_add_addr_signal: u8,
+    mptcpi_add_addr_accepted: u8,
+    mptcpi_subflows_max: u8,
+    mptcpi_add_addr_signal_max: u8,
+    mptcpi_add_addr_accepted_max: u8,
+    mptcpi_flags: u32,
+    mptcpi_token: u32,
+    mptcpi_write_seq: u64,
+    mptcpi_snd_una: u64,
+    mptcpi_rcv_nxt: u64,
+    mptcpi_local_addr_used: u8,
+    mptcpi_local_addr_max: u8,
+    mptcpi_csum_enabled: u8,
+    mptcpi_retransmits: u32,
+    mptcpi_bytes_retrans: u64,
+    mptcpi_bytes_sent: u64,
+    mptcpi_bytes_received: u64,
+    mptcpi_bytes_acked: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl MptcpInfo {
+    /// Number of additional subflows currently established
+    pub fn subflows(&self) -> u8 {
+        self.mptcpi_subflows
+    }
+
+    /// Maximum number of additional subflows allowed
+    pub fn subflows_max(&self) -> u8 {
+        self.mptcpi_subflows_max
+    }
+
+    /// Number of addresses announced to the peer
+    pub fn add_addr_signal(&self) -> u8 {
+        self.mptcpi_add_addr_signal
+    }
+
+    /// Number of addresses announced by the peer and accepted
+    pub fn add_addr_accepted(&self) -> u8 {
+        self.mptcpi_add_addr_accepted
+    }
+
+    /// Whether the connection fell back to regular TCP
+    pub fn fallback(&self) -> bool {
+        self.mptcpi_flags & 1 != 0
+    }
+
+    /// Whether the peer's key has been received
+    pub fn remote_key_received(&self) -> bool {
+        self.mptcpi_flags & 2 != 0
+    }
+
+    /// Token identifying the connection locally
+    pub fn token(&self) -> u32 {
+        self.mptcpi_token
+    }
+
+    /// Next data sequence number to be sent
+    pub fn write_seq(&self) -> u64 {
+        self.mptcpi_write_seq
+    }
+
+    /// Oldest unacknowledged data sequence number
+    pub fn snd_una(&self) -> u64 {
+        self.mptcpi_snd_una
+    }
+
+    /// Next data sequence number expected from the peer
+    pub fn rcv_nxt(&self) -> u64 {
+        self.mptcpi_rcv_nxt
+    }
+
+    /// Number of local addresses used for subflows
+    pub fn local_addr_used(&self) -> u8 {
+        self.mptcpi_local_addr_used
+    }
+
+    /// Maximum number of local addresses usable for subflows
+    pub fn local_addr_max(&self) -> u8 {
+        self.mptcpi_local_addr_max
+    }
+
+    /// Whether data checksums are in use
+    pub fn csum_enabled(&self) -> bool {
+        self.mptcpi_csum_enabled != 0
+    }
+
+    /// Number of data retransmissions
+    pub fn retransmits(&self) -> u32 {
+        self.mptcpi_retransmits
+    }
+
+    /// Number of bytes retransmitted
+    pub fn bytes_retrans(&self) -> u64 {
+        self.mptcpi_bytes_retrans
+    }
+
+    /// Number of bytes sent
+    pub fn bytes_sent(&self) -> u64 {
+        self.mptcpi_bytes_sent
+    }
+
+    /// Number of bytes received
+    pub fn bytes_received(&self) -> u64 {
+        self.mptcpi_bytes_received
+    }
+
+    /// Number of bytes acknowledged by the peer
+    pub fn bytes_acked(&self) -> u64 {
+        self.mptcpi_bytes_acked
+    }
+}
+
+// From <linux/tcp.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCPOPT_MSS: u32 = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCPOPT_WINDOW: u32 = 3;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCPOPT_SACK_PERM: u32 = 4;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCPOPT_TIMESTAMP: u32 = 8;
+
+/// A TCP option negotiated by a connection, to be restored with the
+/// [`TcpRepairOptions`](sockopt/struct.TcpRepairOptions.html) socket option.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TcpRepairOpt {
+    opt_code: u32,
+    opt_val: u32,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl TcpRepairOpt {
+    /// The peer's maximum segment size
+    pub fn mss(mss: u16) -> TcpRepairOpt {
+        TcpRepairOpt { opt_code: TCPOPT_MSS, opt_val: u32::from(mss) }
+    }
+
+    /// Window scaling, with the shift counts for sending and receiving
+    pub fn window_scale(snd_wscale: u8, rcv_wscale: u8) -> TcpRepairOpt {
+        TcpRepairOpt {
+            opt_code: TCPOPT_WINDOW,
+            opt_val: u32::from(snd_wscale) | u32::from(rcv_wscale) << 16,
+        }
+    }
+
+    /// Selective acknowledgements are permitted
+    pub fn sack_permitted() -> TcpRepairOpt {
+        TcpRepairOpt { opt_code: TCPOPT_SACK_PERM, opt_val: 0 }
+    }
+
+    /// Timestamps are in use
+    pub fn timestamp() -> TcpRepairOpt {
+        TcpRepairOpt { opt_code: TCPOPT_TIMESTAMP, opt_val: 0 }
+    }
+}
+
+// From <linux/in6.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_A_GET: u8 = 0;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_A_PUT: u8 = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_A_RENEW: u8 = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_F_CREATE: u16 = 1;
+
+/// Who may share an IPv6 flow label reserved with
+/// [`FlowLabelReq::get`](struct.FlowLabelReq.html#method.get).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FlowLabelShare {
+    /// Only the reserving socket
+    Exclusive = 1,
+    /// Sockets of the same process
+    Process = 2,
+    /// Sockets of the same user
+    User = 3,
+    /// Any socket
+    Any = 255,
+}
+
+/// A request to reserve, renew or release an IPv6 flow label, made with the
+/// [`Ipv6FlowLabelMgr`](sockopt/struct.Ipv6FlowLabelMgr.html) socket option.
+///
+/// This mirrors the kernel's `struct in6_flowlabel_req`.  Flow labels are 20
+/// bits wide.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FlowLabelReq {
+    flr_dst: libc::in6_addr,
+    flr_label: u32,
+    flr_action: u8,
+    flr_share: u8,
+    flr_flags: u16,
+    flr_expires: u16,
+    flr_linger: u16,
+    __flr_pad: u32,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl FlowLabelReq {
+    fn new(label: u32, action: u8) -> FlowLabelReq {
+        FlowLabelReq {
+            flr_dst: libc::in6_addr { s6_addr: [0; 16] },
+            flr_label: label.to_be(),
+            flr_action: action,
+            flr_share: 0,
+            flr_flags: 0,
+            flr_expires: 0,
+            flr_linger: 0,
+            __flr_pad: 0,
+        }
+    }
+
+    /// Reserve `label` for packets sent to `dst`, creating it if it does not
+    /// exist yet.
+    ///
+    /// # Panics
+    ///
+    /// If `label` is 0 or wider than 20 bits.  For a label of 0 the kernel
+    /// would pick one and write it back into the request.
+    pub fn get(dst: &Ipv6Addr, label: u32, share: FlowLabelShare) -> FlowLabelReq {
+        assert!(label != 0 && label <= 0xf_ffff, "invalid flow label {:#x}", label);
+        FlowLabelReq {
+            flr_dst: dst.0,
+            flr_share: share as u8,
+            flr_flags: IPV6_FL_F_CREATE,
+            ..FlowLabelReq::new(label, IPV6_FL_A_GET)
+        }
+    }
+
+    /// Release `label`, which was reserved by this socket
+    pub fn put(label: u32) -> FlowLabelReq {
+        FlowLabelReq::new(label, IPV6_FL_A_PUT)
+    }
+
+    /// Keep `label` reserved for at least `linger` seconds after it is last
+    /// used, and for at least `expires` seconds from now
+    pub fn renew(label: u32, linger: u16, expires: u16) -> FlowLabelReq {
+        FlowLabelReq {
+            flr_linger: linger,
+            flr_expires: expires,
+            ..FlowLabelReq::new(label, IPV6_FL_A_RENEW)
+        }
+    }
+
+    /// The flow label the request is about
+    pub fn label(&self) -> u32 {
+        u32::from_be(self.flr_label)
+    }
+}
+
+// From <linux/sctp.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCTP_SNDRCV: c_int = 1;
+
+// From <linux/socket.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCM_SECURITY: c_int = 3;
+
+// From <linux/socket.h> and <linux/rds.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SOL_RDS: c_int = 276;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_RDMA_ARGS: c_int = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_RDMA_DEST: c_int = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_RDMA_STATUS: c_int = 4;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_CONG_UPDATE: c_int = 5;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_ZCOPY_COOKIE: c_int = 12;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_CMSG_ZCOPY_COMPLETION: c_int = 13;
+
+/// Default parameters for new SCTP associations, as used by the
+/// [`SctpInitMsg`](sockopt/struct.SctpInitMsg.html) socket option.
+///
+/// This mirrors the kernel's `struct sctp_initmsg`.  Zero leaves a parameter
+/// at its default.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SctpInitMsg {
+    /// Number of outbound streams to request
+    pub num_ostreams: u16,
+    /// Maximum number of inbound streams to accept
+    pub max_instreams: u16,
+    /// Maximum number of `INIT` retransmissions
+    pub max_attempts: u16,
+    /// Maximum `INIT` retransmission timeout, in milliseconds
+    pub max_init_timeo: u16,
+}
+
+/// SCTP notifications and ancillary data to deliver to the application, as
+/// used by the [`SctpEvents`](sockopt/struct.SctpEvents.html) socket option.
+///
+/// This mirrors the kernel's `struct sctp_event_subscribe`.  A nonzero field
+/// enables the event.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SctpEventSubscribe {
+    /// Deliver a [`ControlMessageOwned::SctpSndRcv`](enum.ControlMessageOwned.html#variant.SctpSndRcv)
+    /// with every received message
+    pub data_io: u8,
+    /// Association change notifications
+    pub association: u8,
+    /// Peer address change notifications
+    pub address: u8,
+    /// Send failure notifications
+    pub send_failure: u8,
+    /// Peer error notifications
+    pub peer_error: u8,
+    /// Shutdown notifications
+    pub shutdown: u8,
+    /// Partial delivery notifications
+    pub partial_delivery: u8,
+    /// Adaptation layer indications
+    pub adaptation_layer: u8,
+    /// Authentication notifications
+    pub authentication: u8,
+    /// Sender dry notifications
+    pub sender_dry: u8,
+    /// Stream reset notifications
+    pub stream_reset: u8,
+    /// Association reset notifications
+    pub assoc_reset: u8,
+    /// Stream change notifications
+    pub stream_change: u8,
+    /// Send failure notifications in the RFC 6458 format
+    pub send_failure_event: u8,
+}
+
+/// Per-message SCTP parameters, sent with
+/// [`ControlMessage::SctpSndRcv`](enum.ControlMessage.html#variant.SctpSndRcv)
+/// and received as
+/// [`ControlMessageOwned::SctpSndRcv`](enum.ControlMessageOwned.html#variant.SctpSndRcv).
+///
+/// This mirrors the kernel's `struct sctp_sndrcvinfo`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SctpSndRcvInfo {
+    /// Stream number
+    pub stream: u16,
+    /// Stream sequence number of a received message
+    pub ssn: u16,
+    /// `SCTP_UNORDERED`, `SCTP_EOF` and other flags
+    pub flags: u16,
+    /// Payload protocol identifier, in network byte order
+    pub ppid: u32,
+    /// Opaque value returned in send failure notifications
+    pub context: u32,
+    /// Lifetime of the message in milliseconds, or zero for no limit
+    pub timetolive: u32,
+    /// Transmission sequence number of a received message
+    pub tsn: u32,
+    /// Cumulative transmission sequence number of a received message
+    pub cumtsn: u32,
+    /// Association identifier, for one-to-many style sockets
+    pub assoc_id: i32,
+}
+
+/// A region of memory taking part in an RDS RDMA operation.
+///
+/// This mirrors the kernel's `struct rds_iovec`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RdsIovec {
+    /// Start address of the region
+    pub addr: u64,
+    /// Length of the region in bytes
+    pub bytes: u64,
+}
+
+/// An RDMA operation to perform along with an RDS message, sent with
+/// [`ControlMessage::RdsRdmaArgs`](enum.ControlMessage.html#variant.RdsRdmaArgs).
+///
+/// This mirrors the kernel's `struct rds_rdma_args`.  The kernel reads and
+/// writes the local memory it describes, so it can only be created with the
+/// unsafe [`new`](#method.new).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RdsRdmaArgs {
+    cookie: u64,
+    remote_vec: RdsIovec,
+    local_vec_addr: u64,
+    nr_local: u64,
+    flags: u64,
+    user_token: u64,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl RdsRdmaArgs {
+    /// Describe an RDMA operation.
+    ///
+    /// - `cookie` identifies the remote memory region, as obtained by the
+    ///   peer with `RDS_GET_MR`.
+    /// - `remote_vec` is the remote memory to read or write.
+    /// - `local_vec_addr` is the address of an array of `nr_local`
+    ///   [`RdsIovec`](struct.RdsIovec.html) describing the local memory.
+    /// - `flags` are `RDS_RDMA_READWRITE`, `RDS_RDMA_NOTIFY_ME` and others.
+    /// - `user_token` is returned in the
+    ///   [`ControlMessageOwned::RdsRdmaStatus`](enum.ControlMessageOwned.html#variant.RdsRdmaStatus)
+    ///   notification of the operation's completion.
+    ///
+    /// # Safety
+    ///
+    /// `local_vec_addr` must point to `nr_local` valid `RdsIovec`s when the
+    /// message is sent.  Each of them must describe memory that stays valid,
+    /// and that nothing else accesses, until the operation completes: the
+    /// kernel writes to it when `RDS_RDMA_READWRITE` is not set, and reads
+    /// it otherwise.
+    pub unsafe fn new(cookie: u64, remote_vec: RdsIovec, local_vec_addr: u64, nr_local: u64,
+                      flags: u64, user_token: u64) -> RdsRdmaArgs
+    {
+        RdsRdmaArgs { cookie, remote_vec, local_vec_addr, nr_local, flags, user_token }
+    }
+
+    /// Cookie of the remote memory region
+    pub fn cookie(&self) -> u64 {
+        self.cookie
+    }
+
+    /// The remote memory to read or write
+    pub fn remote_vec(&self) -> RdsIovec {
+        self.remote_vec
+    }
+
+    /// Value returned in the notification of the operation's completion
+    pub fn user_token(&self) -> u64 {
+        self.user_token
+    }
+}
+
+/// Completion status of an RDS RDMA operation, received as
+/// [`ControlMessageOwned::RdsRdmaStatus`](enum.ControlMessageOwned.html#variant.RdsRdmaStatus).
+///
+/// This mirrors the kernel's `struct rds_rdma_notify`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RdsRdmaNotify {
+    /// The `user_token` of the operation's
+    /// [`RdsRdmaArgs`](struct.RdsRdmaArgs.html)
+    pub user_token: u64,
+    /// `RDS_RDMA_SUCCESS`, or the reason the operation failed
+    pub status: i32,
+}
+
+/// Cookies of completed RDS zero-copy sends; mirrors `struct rds_zcopy_cookies`
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RdsZcopyCookies {
+    num: u32,
+    cookies: [u32; 8],
+}
+
+/// Cipher, keys and initial record sequence number for kernel TLS, as used
+/// by the [`TlsTx`](sockopt/struct.TlsTx.html) and
+/// [`TlsRx`](sockopt/struct.TlsRx.html) socket options.
+///
+/// For further information, please refer to the kernel's
+/// [TLS documentation](https://www.kernel.org/doc/html/latest/networking/tls.html).
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TlsCryptoInfo {
+    /// AES-GCM with a 128-bit key (`TLS_CIPHER_AES_GCM_128`)
+    AesGcm128(libc::tls12_crypto_info_aes_gcm_128),
+    /// AES-GCM with a 256-bit key (`TLS_CIPHER_AES_GCM_256`)
+    AesGcm256(libc::tls12_crypto_info_aes_gcm_256),
+    /// AES-CCM with a 128-bit key (`TLS_CIPHER_AES_CCM_128`)
+    AesCcm128(libc::tls12_crypto_info_aes_ccm_128),
+    /// ChaCha20-Poly1305 (`TLS_CIPHER_CHACHA20_POLY1305`)
+    Chacha20Poly1305(libc::tls12_crypto_info_chacha20_poly1305),
+}
+
+#[cfg(target_os = "linux")]
+impl TlsCryptoInfo {
+    /// Pointer to and size of the wrapped structure
+    fn as_ffi_pair(&self) -> (*const c_void, socklen_t) {
+        match *self {
+            TlsCryptoInfo::AesGcm128(ref info) =>
+                (info as *const _ as *const c_void, mem::size_of_val(info) as socklen_t),
+            TlsCryptoInfo::AesGcm256(ref info) =>
+                (info as *const _ as *const c_void, mem::size_of_val(info) as socklen_t),
+            TlsCryptoInfo::AesCcm128(ref info) =>
+                (info as *const _ as *const c_void, mem::size_of_val(info) as socklen_t),
+            TlsCryptoInfo::Chacha20Poly1305(ref info) =>
+                (info as *const _ as *const c_void, mem::size_of_val(info) as socklen_t),
+        }
+    }
+}
+
+/// An RFC 2385 TCP MD5 signature key, as used by the
+/// [`TcpMd5Sig`](sockopt/struct.TcpMd5Sig.html) socket option.
+///
+/// This mirrors the kernel's `struct tcp_md5sig`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TcpMd5Sig {
+    tcpm_addr: sockaddr_storage,
+    tcpm_flags: u8,
+    tcpm_prefixlen: u8,
+    tcpm_keylen: u16,
+    tcpm_ifindex: c_int,
+    tcpm_key: [u8; libc::TCP_MD5SIG_MAXKEYLEN],
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl TcpMd5Sig {
+    /// Sign the segments exchanged with the peer at `addr` with `key`.  The
+    /// port of `addr` is ignored.  An empty `key` removes the peer's key.
+    ///
+    /// Fails with `EINVAL` if `key` is longer than
+    /// `libc::TCP_MD5SIG_MAXKEYLEN` bytes.
+    pub fn new(addr: &InetAddr, key: &[u8]) -> Result<TcpMd5Sig> {
+        if key.len() > libc::TCP_MD5SIG_MAXKEYLEN {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        let mut sig: TcpMd5Sig = unsafe { mem::zeroed() };
+        inet_addr_to_storage(addr, &mut sig.tcpm_addr);
+        sig.tcpm_keylen = key.len() as u16;
+        sig.tcpm_key[..key.len()].copy_from_slice(key);
+        Ok(sig)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl std::fmt::Debug for TcpMd5Sig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Don't print the key
+        f.debug_struct("TcpMd5Sig")
+            .field("keylen", &self.tcpm_keylen)
+            .finish()
+    }
+}
+
+/// Request for multicast socket operations
+///
+/// This is a wrapper type around `ip_mreq`.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpMembershipRequest(libc::ip_mreq);
+
+impl IpMembershipRequest {
+    /// Instantiate a new `IpMembershipRequest`
+    ///
+    /// If `interface` is `None`, then `Ipv4Addr::any()` will be used for the interface.
+    pub fn new(group: Ipv4Addr, interface: Option<Ipv4Addr>) -> Self {
+        IpMembershipRequest(libc::ip_mreq {
+            imr_multiaddr: group.0,
+            imr_interface: interface.unwrap_or_else(Ipv4Addr::any).0,
+        })
+    }
+}
+
+/// Request for ipv6 multicast socket operations
+///
+/// This is a wrapper type around `ipv6_mreq`.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ipv6MembershipRequest(libc::ipv6_mreq);
+
+impl Ipv6MembershipRequest {
+    /// Instantiate a new `Ipv6MembershipRequest`
+    pub fn new(group: Ipv6Addr) -> Self {
+        Ipv6MembershipRequest(libc::ipv6_mreq {
+            ipv6mr_multiaddr: group.0,
+            ipv6mr_interface: 0,
+        })
+    }
+}
+
+/// Request for source-specific multicast socket operations
+///
+/// This is a wrapper type around `ip_mreq_source`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpSourceMembershipRequest(libc::ip_mreq_source);
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl IpSourceMembershipRequest {
+    /// Instantiate a new `IpSourceMembershipRequest` for receiving the
+    /// traffic that `source` sends to `group`.
+    ///
+    /// If `interface` is `None`, then `Ipv4Addr::any()` will be used for the interface.
+    pub fn new(group: Ipv4Addr, source: Ipv4Addr, interface: Option<Ipv4Addr>)
+        -> Self
+    {
+        IpSourceMembershipRequest(libc::ip_mreq_source {
+            imr_multiaddr: group.0,
+            imr_interface: interface.unwrap_or_else(Ipv4Addr::any).0,
+            imr_sourceaddr: source.0,
+        })
+    }
+}
+
+/// Protocol-independent request for source-specific multicast socket
+/// operations
+///
+/// This is a wrapper type around `group_source_req`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GroupSourceRequest(libc::group_source_req);
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GroupSourceRequest {
+    /// Instantiate a new `GroupSourceRequest` for receiving the traffic that
+    /// `source` sends to `group`.
+    ///
+    /// The port numbers of the addresses are ignored.  An `interface` index
+    /// of 0 lets the kernel choose the interface.
+    ///
+    /// # Panics
+    ///
+    /// If `group` and `source` belong to different address families.
+    pub fn new(group: &InetAddr, source: &InetAddr, interface: u32) -> Self {
+        assert_eq!(mem::discriminant(group), mem::discriminant(source),
+                   "group and source must have the same address family");
+        unsafe {
+            let mut req: libc::group_source_req = mem::zeroed();
+            req.gsr_interface = interface;
+            inet_addr_to_storage(group, &mut req.gsr_group);
+            inet_addr_to_storage(source, &mut req.gsr_source);
+            GroupSourceRequest(req)
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn inet_addr_to_storage(addr: &InetAddr, storage: &mut sockaddr_storage) {
+    match *addr {
+        InetAddr::V4(sin) => unsafe {
+            *(storage as *mut sockaddr_storage as *mut sockaddr_in) = sin;
+        },
+        InetAddr::V6(sin6) => unsafe {
+            *(storage as *mut sockaddr_storage as *mut sockaddr_in6) = sin6;
+        },
+    }
+}
+
+/// Create a buffer large enough for storing some control messages as returned
+/// by [`recvmsg`](fn.recvmsg.html).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate nix;
+/// # use nix::sys::time::TimeVal;
+/// # use std::os::unix::io::RawFd;
+/// # fn main() {
+/// // Create a buffer for a `ControlMessageOwned::ScmTimestamp` message
+/// let _ = cmsg_space!(TimeVal);
+/// // Create a buffer big enough for a `ControlMessageOwned::ScmRights` message
+/// // with two file descriptors
+/// let _ = cmsg_space!([RawFd; 2]);
+/// // Create a buffer big enough for a `ControlMessageOwned::ScmRights` message
+/// // and a `ControlMessageOwned::ScmTimestamp` message
+/// let _ = cmsg_space!(RawFd, TimeVal);
+/// # }
+/// ```
+// Unfortunately, CMSG_SPACE isn't a const_fn, or else we could return a
+// stack-allocated array.
+#[macro_export]
+macro_rules! cmsg_space {
+    ( $( $x:ty ),* ) => {
+        {
+            use nix::sys::socket::{c_uint, CMSG_SPACE};
+            use std::mem;
+            let mut space = 0;
+            $(
+                // CMSG_SPACE is always safe
+                space += unsafe {
+                    CMSG_SPACE(mem::size_of::<$x>() as c_uint)
+                } as usize;
+            )*
+            Vec::<u8>::with_capacity(space)
+        }
+    }
+}
+
+/// Compute the buffer space needed by a single control message whose payload
+/// is `count` values of type `T`.
+///
+/// This is the runtime counterpart of [`cmsg_space!`](macro.cmsg_space.html),
+/// for use when the number of control message items is not known at compile
+/// time.  The results for several messages may be added together.
+///
+/// # Examples
+///
+/// ```
+/// # use nix::sys::socket::cmsg_space_len;
+/// # use nix::sys::time::TimeVal;
+/// # use std::os::unix::io::RawFd;
+/// let nfds = 5;
+/// // Create a buffer big enough for a `ControlMessageOwned::ScmRights` message
+/// // with `nfds` file descriptors and a `ControlMessageOwned::ScmTimestamp`
+/// // message
+/// let _ = Vec::<u8>::with_capacity(
+///     cmsg_space_len::<RawFd>(nfds) + cmsg_space_len::<TimeVal>(1));
+/// ```
+pub fn cmsg_space_len<T>(count: usize) -> usize {
+    let len = mem::size_of::<T>() * count;
+    // CMSG_SPACE is always safe
+    unsafe { CMSG_SPACE(len as c_uint) as usize }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RecvMsg<'a> {
+    pub bytes: usize,
+    cmsghdr: Option<&'a cmsghdr>,
+    /// Address of the sender, or `None` if it has none, as for connected
+    /// stream sockets and unbound UNIX sockets
+    pub address: Option<SockAddr>,
+    pub flags: MsgFlags,
+    mhdr: msghdr,
+}
+
+impl<'a> RecvMsg<'a> {
+    /// Iterate over the valid control messages pointed to by this
+    /// msghdr.
+    pub fn cmsgs(&self) -> CmsgIterator {
+        CmsgIterator {
+            cmsghdr: self.cmsghdr,
+            mhdr: &self.mhdr
+        }
+    }
+
+    /// Returns `true` if the datagram was larger than the supplied buffers,
+    /// so its tail was discarded (`MSG_TRUNC` is set in the returned flags).
+    ///
+    /// On Linux, if `MSG_TRUNC` was also passed to
+    /// [`recvmsg`](fn.recvmsg.html), `bytes` holds the real length of the
+    /// datagram, which may exceed the size of the buffers.
+    pub fn truncated(&self) -> bool {
+        self.flags.contains(MsgFlags::MSG_TRUNC)
+    }
+
+    /// Returns `true` if some control messages were discarded because the
+    /// control message buffer was too small (`MSG_CTRUNC` is set in the
+    /// returned flags).
+    pub fn cmsg_truncated(&self) -> bool {
+        self.flags.contains(MsgFlags::MSG_CTRUNC)
+    }
+
+    /// Returns `true` if the received data ends a record, as for sockets of
+    /// type [`SeqPacket`](enum.SockType.html) (`MSG_EOR` is set in the
+    /// returned flags).
+    pub fn end_of_record(&self) -> bool {
+        self.flags.contains(MsgFlags::MSG_EOR)
+    }
+
+    /// Returns `true` if expedited or out-of-band data was received
+    /// (`MSG_OOB` is set in the returned flags).
+    pub fn out_of_band(&self) -> bool {
+        self.flags.contains(MsgFlags::MSG_OOB)
+    }
+
+    /// Returns `true` if the message was taken from the socket error queue
+    /// (`MSG_ERRQUEUE` is set in the returned flags).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn from_error_queue(&self) -> bool {
+        self.flags.contains(MsgFlags::MSG_ERRQUEUE)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CmsgIterator<'a> {
+    /// Control message buffer to decode from. Must adhere to cmsg alignment.
+    cmsghdr: Option<&'a cmsghdr>,
+    mhdr: &'a msghdr
+}
+
+impl<'a> Iterator for CmsgIterator<'a> {
+    type Item = ControlMessageOwned;
+
+    fn next(&mut self) -> Option<ControlMessageOwned> {
+        match self.cmsghdr {
+            None => None,   // No more messages
+            Some(hdr) => {
+                // Get the data.
+                // Safe if cmsghdr points to valid data returned by recvmsg(2)
+                let cm = unsafe { Some(ControlMessageOwned::decode_from(hdr))};
+                // Advance the internal pointer.  Safe if mhdr and cmsghdr point
+                // to valid data returned by recvmsg(2)
+                self.cmsghdr = unsafe {
+                    let p = CMSG_NXTHDR(self.mhdr as *const _, hdr as *const _);
+                    p.as_ref()
+                };
+                cm
+            }
+        }
+    }
+}
+
+/// A type-safe wrapper around a single control message, as used with
+/// [`recvmsg`](#fn.recvmsg).
+///
+/// [Further reading](http://man7.org/linux/man-pages/man3/cmsg.3.html)
+//  Nix version 0.13.0 and earlier used ControlMessage for both recvmsg and
+//  sendmsg.  However, on some platforms the messages returned by recvmsg may be
+//  unaligned.  ControlMessageOwned takes those messages by copy, obviating any
+//  alignment issues.
+//
+//  See https://github.com/nix-rust/nix/issues/999
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ControlMessageOwned {
+    /// Received version of
     /// [`ControlMessage::ScmRights`][#enum.ControlMessage.html#variant.ScmRights]
     ScmRights(Vec<RawFd>),
     /// Received version of
@@ -427,6 +1670,10 @@ pub enum ControlMessageOwned {
     ScmCredentials(UnixCredentials),
     /// Received version of
     /// [`ControlMessage::ScmCreds`][#enum.ControlMessage.html#variant.ScmCreds]
+    ///
+    /// On FreeBSD, this is also received on sockets with the
+    /// [`LocalCredsPersistent`](sockopt/struct.LocalCredsPersistent.html)
+    /// socket option enabled.
     #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
     ScmCreds(UnixCredentials),
     /// A message of type `SCM_TIMESTAMP`, containing the time the
@@ -454,22 +1701,22 @@ pub enum ControlMessageOwned {
     ///     SockType::Datagram,
     ///     SockFlag::empty(),
     ///     None).unwrap();
-    /// setsockopt(in_socket, sockopt::ReceiveTimestamp, &true).unwrap();
+    /// setsockopt(&in_socket, sockopt::ReceiveTimestamp, &true).unwrap();
     /// let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
-    /// bind(in_socket, &SockAddr::new_inet(localhost)).unwrap();
-    /// let address = getsockname(in_socket).unwrap();
+    /// bind(&in_socket, &SockAddr::new_inet(localhost)).unwrap();
+    /// let address = getsockname(&in_socket).unwrap();
     /// // Get initial time
     /// let time0 = SystemTime::now();
     /// // Send the message
     /// let iov = [IoVec::from_slice(message)];
     /// let flags = MsgFlags::empty();
-    /// let l = sendmsg(in_socket, &iov, &[], flags, Some(&address)).unwrap();
+    /// let l = sendmsg(&in_socket, &iov, &[], flags, Some(&address)).unwrap();
     /// assert_eq!(message.len(), l);
     /// // Receive the message
     /// let mut buffer = vec![0u8; message.len()];
     /// let mut cmsgspace = cmsg_space!(TimeVal);
     /// let iov = [IoVec::from_mut_slice(&mut buffer)];
-    /// let r = recvmsg(in_socket, &iov, Some(&mut cmsgspace), flags).unwrap();
+    /// let r = recvmsg(&in_socket, &iov, Some(&mut cmsgspace), flags).unwrap();
     /// let rtime = match r.cmsgs().next() {
     ///     Some(ControlMessageOwned::ScmTimestamp(rtime)) => rtime,
     ///     Some(_) => panic!("Unexpected control message"),
@@ -488,6 +1735,24 @@ pub enum ControlMessageOwned {
     /// # }
     /// ```
     ScmTimestamp(TimeVal),
+    /// A message of type `SCM_TIMESTAMPNS`, containing the time the
+    /// packet was received by the kernel with nanosecond resolution.
+    ///
+    /// It is enabled with the
+    /// [`ReceiveTimestampns`](sockopt/struct.ReceiveTimestampns.html) socket
+    /// option.  See the kernel's explanation in "SO_TIMESTAMPNS" of
+    /// [networking/timestamping](https://www.kernel.org/doc/Documentation/networking/timestamping.txt).
+    #[cfg(target_os = "linux")]
+    ScmTimestampns(TimeSpec),
+    /// A message of type `SCM_TIMESTAMPING`, as requested by the
+    /// [`Timestamping`](sockopt/struct.Timestamping.html) socket option.
+    ///
+    /// The first element holds the software timestamp, the third one the raw
+    /// hardware timestamp.  The second one is deprecated and always zero.
+    /// Timestamps that were not requested are zero, too.  See "SO_TIMESTAMPING"
+    /// of [networking/timestamping](https://www.kernel.org/doc/Documentation/networking/timestamping.txt).
+    #[cfg(target_os = "linux")]
+    ScmTimestamping([TimeSpec; 3]),
     #[cfg(any(
         target_os = "android",
         target_os = "ios",
@@ -524,6 +1789,56 @@ pub enum ControlMessageOwned {
     ))]
     Ipv4RecvDstAddr(libc::in_addr),
 
+    /// Type of Service of a received IPv4 packet.
+    ///
+    /// The [`Ipv4RecvTos`](sockopt/struct.Ipv4RecvTos.html) socket option
+    /// must be enabled on the socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv4Tos(u8),
+
+    /// Traffic class of a received IPv6 packet.
+    ///
+    /// The [`Ipv6RecvTClass`](sockopt/struct.Ipv6RecvTClass.html) socket
+    /// option must be enabled on the socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv6TClass(i32),
+
+    /// Time To Live of a received IPv4 packet.
+    ///
+    /// The [`Ipv4RecvTtl`](sockopt/struct.Ipv4RecvTtl.html) socket option
+    /// must be enabled on the socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    IpTtl(i32),
+
+    /// Hop limit of a received IPv6 packet.  Neighbor Discovery messages,
+    /// for instance, must be dropped unless it is 255.
+    ///
+    /// The [`Ipv6RecvHopLimit`](sockopt/struct.Ipv6RecvHopLimit.html) socket
+    /// option must be enabled on the socket.
+    #[cfg(any(target_os = "android",
+              target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "ios",
+              target_os = "linux",
+              target_os = "macos"))]
+    Ipv6HopLimit(i32),
+
+    /// Flow information of a received IPv6 packet in host byte order: the
+    /// traffic class and the flow label, masked with `IPV6_FLOWINFO_TCLASS`
+    /// and `IPV6_FLOWINFO_FLOWLABEL` respectively.
+    ///
+    /// The [`Ipv6FlowInfo`](sockopt/struct.Ipv6FlowInfo.html) socket option
+    /// must be enabled on the socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv6FlowInfo(u32),
+
+    /// Multicast group on which a netlink message was received.
+    ///
+    /// `NetlinkPacketInfo` socket option should be enabled on a socket
+    /// to receive this message.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkPacketInfo(libc::nl_pktinfo),
+
     /// UDP Generic Receive Offload (GRO) allows receiving multiple UDP
     /// packets from a single sender.
     /// Fixed-size payloads are following one by one in a receive buffer.
@@ -535,6 +1850,69 @@ pub enum ControlMessageOwned {
     #[cfg(target_os = "linux")]
     UdpGroSegments(u16),
 
+    /// An extended error reported through the socket's error queue, together
+    /// with the address of the node that caused it, if any.
+    ///
+    /// The `Ipv4RecvErr` socket option must be enabled on the socket, and the
+    /// message is read with `MsgFlags::MSG_ERRQUEUE`.  See the `IP_RECVERR`
+    /// section of [`ip(7)`](http://man7.org/linux/man-pages/man7/ip.7.html).
+    #[cfg(target_os = "linux")]
+    Ipv4RecvErr(libc::sock_extended_err, Option<sockaddr_in>),
+
+    /// An extended error reported through the socket's error queue, together
+    /// with the address of the node that caused it, if any.
+    ///
+    /// The `Ipv6RecvErr` socket option must be enabled on the socket, and the
+    /// message is read with `MsgFlags::MSG_ERRQUEUE`.  See the `IPV6_RECVERR`
+    /// section of [`ipv6(7)`](http://man7.org/linux/man-pages/man7/ipv6.7.html).
+    #[cfg(target_os = "linux")]
+    Ipv6RecvErr(libc::sock_extended_err, Option<sockaddr_in6>),
+
+    /// Parameters of a received SCTP message.
+    ///
+    /// The `data_io` event must be enabled with the
+    /// [`SctpEvents`](sockopt/struct.SctpEvents.html) socket option.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    SctpSndRcv(SctpSndRcvInfo),
+
+    /// Content type of a TLS record received on a kernel TLS socket, such as
+    /// 21 for an alert.  Only records other than application data carry this
+    /// message.
+    #[cfg(target_os = "linux")]
+    TlsGetRecordType(u8),
+
+    /// Security label of the sending process, such as an SELinux context,
+    /// without any terminating null byte.
+    ///
+    /// The [`PassSec`](sockopt/struct.PassSec.html) socket option must be
+    /// enabled on the receiving UNIX socket, and a security module providing
+    /// labels must be active.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ScmSecurity(Vec<u8>),
+
+    /// Destination cookie of an RDMA operation requested by the peer with
+    /// `RDS_RDMA_DEST` on an RDS socket, to be passed back in
+    /// [`RdsRdmaArgs::new`](struct.RdsRdmaArgs.html#method.new).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsRdmaDest(u64),
+
+    /// Completion of an RDMA operation on an RDS socket, for operations
+    /// sent with the `RDS_RDMA_NOTIFY_ME` flag.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsRdmaStatus(RdsRdmaNotify),
+
+    /// Bitmap of the ports whose congestion state changed, received on RDS
+    /// sockets monitoring congestion with `RDS_CONG_MONITOR`.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsCongUpdate(u64),
+
+    /// Cookies of zero-copy sends on an RDS socket whose buffers may be
+    /// reused, as passed in
+    /// [`ControlMessage::RdsZcopyCookie`](enum.ControlMessage.html#variant.RdsZcopyCookie).
+    /// Read from the error queue with `MsgFlags::MSG_ERRQUEUE`.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsZcopyCompletion(Vec<u32>),
+
     /// Catch-all variant for unimplemented cmsg types.
     #[doc(hidden)]
     Unknown(UnknownCmsg),
@@ -575,10 +1953,46 @@ impl ControlMessageOwned {
                 let cred: libc::cmsgcred = ptr::read_unaligned(p as *const _);
                 ControlMessageOwned::ScmCreds(cred.into())
             }
+            #[cfg(target_os = "freebsd")]
+            (libc::SOL_SOCKET, libc::SCM_CREDS2) => {
+                // A struct sockcred2 with a variable-length group list
+                let sc: libc::sockcred2 = ptr::read_unaligned(p as *const _);
+                let groups_offset = mem::size_of::<libc::sockcred2>()
+                    - mem::size_of_val(&sc.sc_groups);
+                let mut cred: libc::cmsgcred = mem::zeroed();
+                cred.cmcred_pid = sc.sc_pid;
+                cred.cmcred_uid = sc.sc_uid;
+                cred.cmcred_euid = sc.sc_euid;
+                cred.cmcred_gid = sc.sc_gid;
+                let ngroups = (sc.sc_ngroups.max(0) as usize)
+                    .min(cred.cmcred_groups.len())
+                    .min(len.saturating_sub(groups_offset) / mem::size_of::<libc::gid_t>());
+                for i in 0..ngroups {
+                    let gp = p.add(groups_offset) as *const libc::gid_t;
+                    cred.cmcred_groups[i] = ptr::read_unaligned(gp.add(i));
+                }
+                cred.cmcred_ngroups = ngroups as libc::c_short;
+                ControlMessageOwned::ScmCreds(cred.into())
+            }
             (libc::SOL_SOCKET, libc::SCM_TIMESTAMP) => {
                 let tv: libc::timeval = ptr::read_unaligned(p as *const _);
                 ControlMessageOwned::ScmTimestamp(TimeVal::from(tv))
             },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, libc::SCM_TIMESTAMPNS) => {
+                let ts: libc::timespec = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::ScmTimestampns(TimeSpec::from(ts))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, libc::SCM_TIMESTAMPING) => {
+                let tss: [libc::timespec; 3] =
+                    ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::ScmTimestamping([
+                    TimeSpec::from(tss[0]),
+                    TimeSpec::from(tss[1]),
+                    TimeSpec::from(tss[2]),
+                ])
+            },
             #[cfg(any(
                 target_os = "android",
                 target_os = "freebsd",
@@ -623,10 +2037,92 @@ impl ControlMessageOwned {
                 let dl = ptr::read_unaligned(p as *const libc::in_addr);
                 ControlMessageOwned::Ipv4RecvDstAddr(dl)
             },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IP, libc::IP_TOS) => {
+                ControlMessageOwned::Ipv4Tos(*p)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IPV6, libc::IPV6_TCLASS) => {
+                let tclass: c_int = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::Ipv6TClass(tclass)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IP, libc::IP_TTL) => {
+                let ttl: c_int = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::IpTtl(ttl)
+            },
+            #[cfg(any(target_os = "android",
+                      target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "linux",
+                      target_os = "macos"))]
+            (libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT) => {
+                let hops: c_int = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::Ipv6HopLimit(hops)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO) => {
+                let flowinfo: u32 = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::Ipv6FlowInfo(u32::from_be(flowinfo))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::SOL_NETLINK, libc::NETLINK_PKTINFO) => {
+                let info = ptr::read_unaligned(p as *const libc::nl_pktinfo);
+                ControlMessageOwned::NetlinkPacketInfo(info)
+            },
             #[cfg(target_os = "linux")]
             (libc::SOL_UDP, libc::UDP_GRO) => {
-                let gso_size: u16 = ptr::read_unaligned(p as *const _);
-                ControlMessageOwned::UdpGroSegments(gso_size)
+                // The kernel sends the segment size as an int
+                let gso_size: c_int = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::UdpGroSegments(gso_size as u16)
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_IP, libc::IP_RECVERR) => {
+                let (err, addr) = Self::recv_err_helper::<sockaddr_in>(p, len);
+                ControlMessageOwned::Ipv4RecvErr(err, addr)
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_IPV6, libc::IPV6_RECVERR) => {
+                let (err, addr) = Self::recv_err_helper::<sockaddr_in6>(p, len);
+                ControlMessageOwned::Ipv6RecvErr(err, addr)
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_TLS, libc::TLS_GET_RECORD_TYPE) => {
+                ControlMessageOwned::TlsGetRecordType(*p)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::SOL_SOCKET, SCM_SECURITY) => {
+                let label = slice::from_raw_parts(p, len);
+                let end = label.iter().position(|&b| b == 0)
+                    .unwrap_or(len);
+                ControlMessageOwned::ScmSecurity(label[..end].to_vec())
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_SCTP, SCTP_SNDRCV) => {
+                let info: SctpSndRcvInfo = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::SctpSndRcv(info)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (SOL_RDS, RDS_CMSG_RDMA_DEST) => {
+                let cookie: u64 = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::RdsRdmaDest(cookie)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (SOL_RDS, RDS_CMSG_RDMA_STATUS) => {
+                let notify: RdsRdmaNotify = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::RdsRdmaStatus(notify)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (SOL_RDS, RDS_CMSG_CONG_UPDATE) => {
+                let mask: u64 = ptr::read_unaligned(p as *const _);
+                ControlMessageOwned::RdsCongUpdate(mask)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (SOL_RDS, RDS_CMSG_ZCOPY_COMPLETION) => {
+                let done: RdsZcopyCookies = ptr::read_unaligned(p as *const _);
+                let num = std::cmp::min(done.num as usize, done.cookies.len());
+                ControlMessageOwned::RdsZcopyCompletion(done.cookies[..num].to_vec())
             },
             (_, _) => {
                 let sl = slice::from_raw_parts(p, len);
@@ -635,6 +2131,24 @@ impl ControlMessageOwned {
             }
         }
     }
+
+    #[cfg(target_os = "linux")]
+    unsafe fn recv_err_helper<T>(p: *mut libc::c_uchar, len: usize)
+        -> (libc::sock_extended_err, Option<T>)
+    {
+        let ee = p as *const libc::sock_extended_err;
+        let err = ptr::read_unaligned(ee);
+
+        // The offender's address, if any, directly follows the error (this is
+        // what SO_EE_OFFENDER computes).  Errors generated locally carry no
+        // address, so check that it lies within the message before reading it.
+        let addrp = ee.add(1) as *const T;
+        if addrp.add(1) as usize - p as usize > len {
+            (err, None)
+        } else {
+            (err, Some(ptr::read_unaligned(addrp)))
+        }
+    }
 }
 
 /// A type-safe zero-copy wrapper around a single control message, as used wih
@@ -724,11 +2238,39 @@ pub enum ControlMessage<'a> {
     #[cfg(target_os = "linux")]
     UdpGsoSegments(&'a u16),
 
+    /// Set the stream, payload protocol identifier and other parameters of an
+    /// outgoing SCTP message.  See also [`sctp_sendmsg`](fn.sctp_sendmsg.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    SctpSndRcv(&'a SctpSndRcvInfo),
+
+    /// Send the data as a TLS record of the given content type, such as 21
+    /// for an alert, over a kernel TLS socket.
+    #[cfg(target_os = "linux")]
+    TlsSetRecordType(&'a u8),
+
+    /// Perform an RDMA operation along with an RDS message.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsRdmaArgs(&'a RdsRdmaArgs),
+
+    /// Reply to an RDMA operation requested by the peer, using the cookie
+    /// received in
+    /// [`ControlMessageOwned::RdsRdmaDest`](enum.ControlMessageOwned.html#variant.RdsRdmaDest).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsRdmaDest(&'a u64),
+
+    /// Tag a zero-copy (`MSG_ZEROCOPY`) send on an RDS socket with a cookie,
+    /// reported back in
+    /// [`ControlMessageOwned::RdsZcopyCompletion`](enum.ControlMessageOwned.html#variant.RdsZcopyCompletion)
+    /// once the buffer may be reused.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    RdsZcopyCookie(&'a u32),
+
     /// Configure the sending addressing and interface for v4
     ///
     /// For further information, please refer to the
     /// [`ip(7)`](http://man7.org/linux/man-pages/man7/ip.7.html) man page.
-    #[cfg(any(target_os = "linux",
+    #[cfg(any(target_os = "android",
+              target_os = "linux",
               target_os = "macos",
               target_os = "netbsd"))]
     Ipv4PacketInfo(&'a libc::in_pktinfo),
@@ -737,11 +2279,38 @@ pub enum ControlMessage<'a> {
     ///
     /// For further information, please refer to the
     /// [`ipv6(7)`](http://man7.org/linux/man-pages/man7/ipv6.7.html) man page.
-    #[cfg(any(target_os = "linux",
+    #[cfg(any(target_os = "android",
+              target_os = "freebsd",
+              target_os = "linux",
               target_os = "macos",
-              target_os = "netbsd",
-              target_os = "freebsd"))]
+              target_os = "netbsd"))]
     Ipv6PacketInfo(&'a libc::in6_pktinfo),
+
+    /// Set the Type of Service of an outgoing IPv4 packet, overriding the
+    /// [`Ipv4Tos`](sockopt/struct.Ipv4Tos.html) socket option.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv4Tos(&'a u8),
+
+    /// Set the traffic class of an outgoing IPv6 packet, overriding the
+    /// [`Ipv6TClass`](sockopt/struct.Ipv6TClass.html) socket option.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv6TClass(&'a i32),
+
+    /// Set the Time To Live of an outgoing IPv4 packet, overriding the
+    /// [`IpTtl`](sockopt/struct.IpTtl.html) socket option.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    IpTtl(&'a i32),
+
+    /// Set the hop limit of an outgoing IPv6 packet, overriding the
+    /// [`Ipv6UnicastHops`](sockopt/struct.Ipv6UnicastHops.html) socket option
+    /// and its multicast counterpart.
+    #[cfg(any(target_os = "android",
+              target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "ios",
+              target_os = "linux",
+              target_os = "macos"))]
+    Ipv6HopLimit(&'a i32),
 }
 
 // An opaque structure used to prevent cmsghdr from being a public type
@@ -823,12 +2392,36 @@ impl<'a> ControlMessage<'a> {
             ControlMessage::UdpGsoSegments(gso_size) => {
                 gso_size as *const _ as *const u8
             },
-            #[cfg(any(target_os = "linux", target_os = "macos",
-                      target_os = "netbsd"))]
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::SctpSndRcv(info) => info as *const _ as *const u8,
+            #[cfg(target_os = "linux")]
+            ControlMessage::TlsSetRecordType(ty) => ty as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaArgs(args) => args as *const _ as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaDest(cookie) => cookie as *const _ as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsZcopyCookie(cookie) => cookie as *const _ as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux",
+                      target_os = "macos", target_os = "netbsd"))]
             ControlMessage::Ipv4PacketInfo(info) => info as *const _ as *const u8,
-            #[cfg(any(target_os = "linux", target_os = "macos",
-                      target_os = "netbsd", target_os = "freebsd"))]
+            #[cfg(any(target_os = "android", target_os = "freebsd",
+                      target_os = "linux", target_os = "macos",
+                      target_os = "netbsd"))]
             ControlMessage::Ipv6PacketInfo(info) => info as *const _ as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4Tos(tos) => tos as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6TClass(tclass) => tclass as *const _ as *const u8,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::IpTtl(ttl) => ttl as *const _ as *const u8,
+            #[cfg(any(target_os = "android",
+                      target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "linux",
+                      target_os = "macos"))]
+            ControlMessage::Ipv6HopLimit(hops) => hops as *const _ as *const u8,
         };
         unsafe {
             ptr::copy_nonoverlapping(
@@ -869,12 +2462,36 @@ impl<'a> ControlMessage<'a> {
             ControlMessage::UdpGsoSegments(gso_size) => {
                 mem::size_of_val(gso_size)
             },
-            #[cfg(any(target_os = "linux", target_os = "macos",
-              target_os = "netbsd"))]
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::SctpSndRcv(info) => mem::size_of_val(info),
+            #[cfg(target_os = "linux")]
+            ControlMessage::TlsSetRecordType(ty) => mem::size_of_val(ty),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaArgs(args) => mem::size_of_val(args),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaDest(cookie) => mem::size_of_val(cookie),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsZcopyCookie(cookie) => mem::size_of_val(cookie),
+            #[cfg(any(target_os = "android", target_os = "linux",
+                      target_os = "macos", target_os = "netbsd"))]
             ControlMessage::Ipv4PacketInfo(info) => mem::size_of_val(info),
-            #[cfg(any(target_os = "linux", target_os = "macos",
-              target_os = "netbsd", target_os = "freebsd"))]
+            #[cfg(any(target_os = "android", target_os = "freebsd",
+                      target_os = "linux", target_os = "macos",
+                      target_os = "netbsd"))]
             ControlMessage::Ipv6PacketInfo(info) => mem::size_of_val(info),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4Tos(tos) => mem::size_of_val(tos),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6TClass(tclass) => mem::size_of_val(tclass),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::IpTtl(ttl) => mem::size_of_val(ttl),
+            #[cfg(any(target_os = "android",
+                      target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "linux",
+                      target_os = "macos"))]
+            ControlMessage::Ipv6HopLimit(hops) => mem::size_of_val(hops),
         }
     }
 
@@ -891,12 +2508,33 @@ impl<'a> ControlMessage<'a> {
                 ControlMessage::AlgSetAeadAssoclen(_) => libc::SOL_ALG,
             #[cfg(target_os = "linux")]
             ControlMessage::UdpGsoSegments(_) => libc::SOL_UDP,
-            #[cfg(any(target_os = "linux", target_os = "macos",
-                      target_os = "netbsd"))]
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::SctpSndRcv(_) => libc::IPPROTO_SCTP,
+            #[cfg(target_os = "linux")]
+            ControlMessage::TlsSetRecordType(_) => libc::SOL_TLS,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaArgs(_) | ControlMessage::RdsRdmaDest(_) |
+                ControlMessage::RdsZcopyCookie(_) => SOL_RDS,
+            #[cfg(any(target_os = "android", target_os = "linux",
+                      target_os = "macos", target_os = "netbsd"))]
             ControlMessage::Ipv4PacketInfo(_) => libc::IPPROTO_IP,
-            #[cfg(any(target_os = "linux", target_os = "macos",
-              target_os = "netbsd", target_os = "freebsd"))]
+            #[cfg(any(target_os = "android", target_os = "freebsd",
+                      target_os = "linux", target_os = "macos",
+                      target_os = "netbsd"))]
             ControlMessage::Ipv6PacketInfo(_) => libc::IPPROTO_IPV6,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4Tos(_) => libc::IPPROTO_IP,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6TClass(_) => libc::IPPROTO_IPV6,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::IpTtl(_) => libc::IPPROTO_IP,
+            #[cfg(any(target_os = "android",
+                      target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "linux",
+                      target_os = "macos"))]
+            ControlMessage::Ipv6HopLimit(_) => libc::IPPROTO_IPV6,
         }
     }
 
@@ -924,12 +2562,36 @@ impl<'a> ControlMessage<'a> {
             ControlMessage::UdpGsoSegments(_) => {
                 libc::UDP_SEGMENT
             },
-            #[cfg(any(target_os = "linux", target_os = "macos",
-                      target_os = "netbsd"))]
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::SctpSndRcv(_) => SCTP_SNDRCV,
+            #[cfg(target_os = "linux")]
+            ControlMessage::TlsSetRecordType(_) => libc::TLS_SET_RECORD_TYPE,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaArgs(_) => RDS_CMSG_RDMA_ARGS,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsRdmaDest(_) => RDS_CMSG_RDMA_DEST,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::RdsZcopyCookie(_) => RDS_CMSG_ZCOPY_COOKIE,
+            #[cfg(any(target_os = "android", target_os = "linux",
+                      target_os = "macos", target_os = "netbsd"))]
             ControlMessage::Ipv4PacketInfo(_) => libc::IP_PKTINFO,
-            #[cfg(any(target_os = "linux", target_os = "macos",
-                      target_os = "netbsd", target_os = "freebsd"))]
+            #[cfg(any(target_os = "android", target_os = "freebsd",
+                      target_os = "linux", target_os = "macos",
+                      target_os = "netbsd"))]
             ControlMessage::Ipv6PacketInfo(_) => libc::IPV6_PKTINFO,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4Tos(_) => libc::IP_TOS,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6TClass(_) => libc::IPV6_TCLASS,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::IpTtl(_) => libc::IP_TTL,
+            #[cfg(any(target_os = "android",
+                      target_os = "dragonfly",
+                      target_os = "freebsd",
+                      target_os = "ios",
+                      target_os = "linux",
+                      target_os = "macos"))]
+            ControlMessage::Ipv6HopLimit(_) => libc::IPV6_HOPLIMIT,
         }
     }
 
@@ -949,7 +2611,7 @@ impl<'a> ControlMessage<'a> {
 /// as with sendto.
 ///
 /// Allocates if cmsgs is nonempty.
-pub fn sendmsg(fd: RawFd, iov: &[IoVec<&[u8]>], cmsgs: &[ControlMessage],
+pub fn sendmsg<F: AsRawFd>(fd: &F, iov: &[IoVec<&[u8]>], cmsgs: &[ControlMessage],
                flags: MsgFlags, addr: Option<&SockAddr>) -> Result<usize>
 {
     let capacity = cmsgs.iter().map(|c| c.space()).sum();
@@ -960,11 +2622,26 @@ pub fn sendmsg(fd: RawFd, iov: &[IoVec<&[u8]>], cmsgs: &[ControlMessage],
 
     let mhdr = pack_mhdr_to_send(&mut cmsg_buffer[..], &iov, &cmsgs, addr);
 
-    let ret = unsafe { libc::sendmsg(fd, &mhdr, flags.bits()) };
+    let ret = unsafe { libc::sendmsg(fd.as_raw_fd(), &mhdr, flags.bits()) };
 
     Errno::result(ret).map(|r| r as usize)
 }
 
+/// Send a message on an SCTP socket with the given stream, payload protocol
+/// identifier and other parameters, like `sctp_sendmsg(3)` does.
+///
+/// `addr` selects the association on a one-to-many style socket, setting it
+/// up if needed.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn sctp_sendmsg<F: AsRawFd>(fd: &F, buf: &[u8], addr: Option<&SockAddr>,
+                    info: &SctpSndRcvInfo, flags: MsgFlags) -> Result<usize>
+{
+    let iov = [IoVec::from_slice(buf)];
+    sendmsg(fd, &iov, &[ControlMessage::SctpSndRcv(info)], flags, addr)
+}
+
+/// Description of a single message to be sent by
+/// [`sendmmsg`](fn.sendmmsg.html).
 #[cfg(any(
     target_os = "linux",
     target_os = "android",
@@ -977,8 +2654,11 @@ pub struct SendMmsgData<'a, I, C>
         I: AsRef<[IoVec<&'a [u8]>]>,
         C: AsRef<[ControlMessage<'a>]>
 {
+    /// Scatter-gather list of buffers holding the message's payload
     pub iov: I,
+    /// Control messages to send along with the payload
     pub cmsgs: C,
+    /// Destination address, for unconnected sockets
     pub addr: Option<SockAddr>,
     pub _lt: std::marker::PhantomData<&'a I>,
 }
@@ -996,7 +2676,8 @@ pub struct SendMmsgData<'a, I, C>
 /// * `flags`:          Optional flags passed directly to the operating system.
 ///
 /// # Returns
-/// `Vec` with numbers of sent bytes on each sent message.
+/// `Vec` with numbers of sent bytes on each sent message.  It may be shorter
+/// than `data` if the kernel could not send every message.
 ///
 /// # References
 /// [`sendmsg`](fn.sendmsg.html)
@@ -1006,12 +2687,13 @@ pub struct SendMmsgData<'a, I, C>
     target_os = "freebsd",
     target_os = "netbsd",
 ))]
-pub fn sendmmsg<'a, I, C>(
-    fd: RawFd,
+pub fn sendmmsg<'a, F, I, C>(
+    fd: &F,
     data: impl std::iter::IntoIterator<Item=&'a SendMmsgData<'a, I, C>>,
     flags: MsgFlags
 ) -> Result<Vec<usize>>
     where
+        F: AsRawFd,
         I: AsRef<[IoVec<&'a [u8]>]> + 'a,
         C: AsRef<[ControlMessage<'a>]> + 'a,
 {
@@ -1041,16 +2723,15 @@ pub fn sendmmsg<'a, I, C>(
         });
     };
 
-    let ret = unsafe { libc::sendmmsg(fd, output.as_mut_ptr(), output.len() as _, flags.bits() as _) };
+    let ret = unsafe { libc::sendmmsg(fd.as_raw_fd(), output.as_mut_ptr(), output.len() as _, flags.bits() as _) };
 
     let sent_messages = Errno::result(ret)? as usize;
-    let mut sent_bytes = Vec::with_capacity(sent_messages);
 
-    for item in &output {
-        sent_bytes.push(item.msg_len as usize);
-    }
-
-    Ok(sent_bytes)
+    Ok(output
+        .iter()
+        .take(sent_messages)
+        .map(|item| item.msg_len as usize)
+        .collect())
 }
 
 
@@ -1065,7 +2746,9 @@ pub struct RecvMmsgData<'a, I>
     where
         I: AsRef<[IoVec<&'a mut [u8]>]> + 'a,
 {
+    /// Scatter-gather list of buffers to receive the message's payload
     pub iov: I,
+    /// Space to receive ancillary data
     pub cmsg_buffer: Option<&'a mut Vec<u8>>,
 }
 
@@ -1089,8 +2772,14 @@ pub struct RecvMmsgData<'a, I>
 /// * `cmsg_buffer`:    Space to receive ancillary data.  Should be created by
 ///                     [`cmsg_space!`](macro.cmsg_space.html)
 ///
+/// * `timeout`:        Optional timeout for the whole batch.  Note that the
+///                     timeout is only checked after each datagram is
+///                     received; see the BUGS section of
+///                     [recvmmsg(2)](http://man7.org/linux/man-pages/man2/recvmmsg.2.html).
+///
 /// # Returns
-/// A `Vec` with multiple `RecvMsg`, one per received message
+/// A `Vec` with multiple `RecvMsg`, one per received message.  It may be
+/// shorter than `data` if fewer messages were available.
 ///
 /// # References
 /// - [`recvmsg`](fn.recvmsg.html)
@@ -1101,14 +2790,15 @@ pub struct RecvMmsgData<'a, I>
     target_os = "freebsd",
     target_os = "netbsd",
 ))]
-pub fn recvmmsg<'a, I>(
-    fd: RawFd,
+pub fn recvmmsg<'a, F, I>(
+    fd: &F,
     data: impl std::iter::IntoIterator<Item=&'a mut RecvMmsgData<'a, I>,
         IntoIter=impl ExactSizeIterator + Iterator<Item=&'a mut RecvMmsgData<'a, I>>>,
     flags: MsgFlags,
     timeout: Option<crate::sys::time::TimeSpec>
 ) -> Result<Vec<RecvMsg<'a>>>
     where
+        F: AsRawFd,
         I: AsRef<[IoVec<&'a mut [u8]>]> + 'a,
 {
     let iter = data.into_iter();
@@ -1120,7 +2810,7 @@ pub fn recvmmsg<'a, I>(
     // Addresses should be pre-allocated.  pack_mhdr_to_receive will store them
     // as raw pointers, so we may not move them.  Turn the vec into a boxed
     // slice so we won't inadvertently reallocate the vec.
-    let mut addresses = vec![mem::MaybeUninit::uninit(); num_messages]
+    let mut addresses = vec![mem::MaybeUninit::zeroed(); num_messages]
         .into_boxed_slice();
 
     let results: Vec<_> = iter.enumerate().map(|(i, d)| {
@@ -1142,25 +2832,27 @@ pub fn recvmmsg<'a, I>(
         (msg_controllen as usize, &mut d.cmsg_buffer)
     }).collect();
 
-    let timeout = if let Some(mut t) = timeout {
-        t.as_mut() as *mut libc::timespec
-    } else {
-        ptr::null_mut()
-    };
+    // The timeout must outlive the syscall, so keep it in a local variable
+    let mut timeout = timeout;
+    let timeout_ptr = timeout
+        .as_mut()
+        .map_or(ptr::null_mut(), |t| t.as_mut() as *mut libc::timespec);
 
-    let ret = unsafe { libc::recvmmsg(fd, output.as_mut_ptr(), output.len() as _, flags.bits() as _, timeout) };
+    let ret = unsafe { libc::recvmmsg(fd.as_raw_fd(), output.as_mut_ptr(), output.len() as _, flags.bits() as _, timeout_ptr) };
 
-    let r = Errno::result(ret)?;
+    let r = Errno::result(ret)? as usize;
 
+    // Only the first `r` headers and addresses were filled in by the kernel
     Ok(output
         .into_iter()
+        .take(r)
         .zip(addresses.iter().map(|addr| unsafe{addr.assume_init()}))
         .zip(results.into_iter())
         .map(|((mmsghdr, address), (msg_controllen, cmsg_buffer))| {
             unsafe {
                 read_mhdr(
                     mmsghdr.msg_hdr,
-                    r as isize,
+                    mmsghdr.msg_len as isize,
                     msg_controllen,
                     address,
                     cmsg_buffer
@@ -1192,10 +2884,12 @@ unsafe fn read_mhdr<'a, 'b>(
         }.as_ref()
     };
 
-    let address = sockaddr_storage_to_addr(
-        &address ,
-         mhdr.msg_namelen as usize
-    ).ok();
+    // msg_namelen is 0 if the sender has no address
+    let address = if mhdr.msg_namelen == 0 {
+        None
+    } else {
+        sockaddr_storage_to_addr(&address, mhdr.msg_namelen as usize).ok()
+    };
 
     RecvMsg {
         bytes: r as usize,
@@ -1311,23 +3005,147 @@ fn pack_mhdr_to_send<'a, I, C>(
 ///
 /// # References
 /// [recvmsg(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/recvmsg.html)
-pub fn recvmsg<'a>(fd: RawFd, iov: &[IoVec<&mut [u8]>],
+pub fn recvmsg<'a, F: AsRawFd>(fd: &F, iov: &[IoVec<&mut [u8]>],
                    mut cmsg_buffer: Option<&'a mut Vec<u8>>,
                    flags: MsgFlags) -> Result<RecvMsg<'a>>
 {
-    let mut address = mem::MaybeUninit::uninit();
+    // The kernel does not write the address if the sender has none
+    let mut address = mem::MaybeUninit::zeroed();
 
     let (msg_controllen, mut mhdr) = unsafe {
         pack_mhdr_to_receive(&iov, &mut cmsg_buffer, address.as_mut_ptr())
     };
 
-    let ret = unsafe { libc::recvmsg(fd, &mut mhdr, flags.bits()) };
+    let ret = unsafe { libc::recvmsg(fd.as_raw_fd(), &mut mhdr, flags.bits()) };
 
     let r = Errno::result(ret)?;
 
     Ok(unsafe { read_mhdr(mhdr, r, msg_controllen, address.assume_init(), &mut cmsg_buffer) })
 }
 
+/// Like [`recvmsg`](fn.recvmsg.html), but receive the data into a single,
+/// possibly uninitialized buffer.
+///
+/// Returns the part of `buf` that was filled in, together with the rest of
+/// the received message.  This saves zeroing large buffers before each
+/// call.
+///
+/// Fails with `EINVAL` if `flags` contains `MSG_TRUNC`: on stream sockets
+/// the kernel then discards the data without writing it to `buf`.
+pub fn recvmsg_uninit<'a, 'b, F: AsRawFd>(fd: &F, buf: &'b mut [mem::MaybeUninit<u8>],
+                                          mut cmsg_buffer: Option<&'a mut Vec<u8>>,
+                                          flags: MsgFlags) -> Result<(&'b mut [u8], RecvMsg<'a>)>
+{
+    check_uninit_flags(flags)?;
+    let mut address = mem::MaybeUninit::zeroed();
+    let iov = [IoVec::from_uninit_slice(buf)];
+
+    let (msg_controllen, mut mhdr) = unsafe {
+        pack_mhdr_to_receive(&iov, &mut cmsg_buffer, address.as_mut_ptr())
+    };
+
+    let ret = unsafe { libc::recvmsg(fd.as_raw_fd(), &mut mhdr, flags.bits()) };
+
+    let r = Errno::result(ret)?;
+
+    let msg = unsafe {
+        read_mhdr(mhdr, r, msg_controllen, address.assume_init(), &mut cmsg_buffer)
+    };
+    Ok((unsafe { assume_init_prefix(buf, r as usize) }, msg))
+}
+
+/// Reject `MSG_TRUNC`, with which the length returned by the kernel need not
+/// match what it wrote to the buffer.
+fn check_uninit_flags(flags: MsgFlags) -> Result<()> {
+    if flags.contains(MsgFlags::MSG_TRUNC) {
+        Err(Error::Sys(Errno::EINVAL))
+    } else {
+        Ok(())
+    }
+}
+
+/// The first `len` bytes of `buf`, but never more than all of it.
+///
+/// # Safety
+///
+/// Those bytes must have been initialized.
+unsafe fn assume_init_prefix(buf: &mut [mem::MaybeUninit<u8>], len: usize) -> &mut [u8] {
+    let len = std::cmp::min(len, buf.len());
+    slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, len)
+}
+
+/// Send file descriptors over a connected Unix domain socket, together with
+/// some regular data.
+///
+/// `payload` should not be empty: on stream sockets a message without any
+/// data can't be told apart from end-of-file by the receiver.  Returns the
+/// number of bytes of `payload` sent.  The descriptors are sent along with
+/// the first byte.
+///
+/// See also [`recv_fds`](fn.recv_fds.html).
+pub fn send_fds<F: AsRawFd>(sock: &F, fds: &[RawFd], payload: &[u8]) -> Result<usize> {
+    let iov = [IoVec::from_slice(payload)];
+    if fds.is_empty() {
+        sendmsg(sock, &iov, &[], MsgFlags::empty(), None)
+    } else {
+        let cmsg = [ControlMessage::ScmRights(fds)];
+        sendmsg(sock, &iov, &cmsg, MsgFlags::empty(), None)
+    }
+}
+
+/// Receive data and up to `max_fds` file descriptors from a Unix domain
+/// socket, as sent by [`send_fds`](fn.send_fds.html).
+///
+/// Returns the number of bytes stored in `buf` and the received descriptors,
+/// which the caller is responsible for closing.  Where supported, the
+/// descriptors are created with close-on-exec set.
+///
+/// At most `max_fds` descriptors are returned.  If the sender passed more,
+/// the surplus descriptors are closed, and the data is still returned; the
+/// truncation is not reported.  Use [`recvmsg`](fn.recvmsg.html) and check
+/// for `MSG_CTRUNC` to detect it.
+pub fn recv_fds<F: AsRawFd>(sock: &F, buf: &mut [u8], max_fds: usize)
+    -> Result<(usize, Vec<RawFd>)>
+{
+    let iov = [IoVec::from_mut_slice(buf)];
+    // Unlike CMSG_SPACE, CMSG_LEN leaves no padding at the end that the
+    // kernel could fill with another descriptor
+    let cmsg_len = unsafe {
+        CMSG_LEN((mem::size_of::<RawFd>() * max_fds) as c_uint)
+    } as usize;
+    let mut cmsg_buffer = Vec::with_capacity(cmsg_len);
+    cfg_if! {
+        if #[cfg(any(target_os = "android",
+                     target_os = "dragonfly",
+                     target_os = "freebsd",
+                     target_os = "linux",
+                     target_os = "netbsd",
+                     target_os = "openbsd"))] {
+            let flags = MsgFlags::MSG_CMSG_CLOEXEC;
+        } else {
+            let flags = MsgFlags::empty();
+        }
+    }
+    let msg = recvmsg(sock, &iov, Some(&mut cmsg_buffer), flags)?;
+
+    let mut fds = Vec::new();
+    for cmsg in msg.cmsgs() {
+        if let ControlMessageOwned::ScmRights(received) = cmsg {
+            fds.extend(received);
+        }
+    }
+
+    // The kernel already closed whatever did not fit in the buffer, but don't
+    // rely on every platform honouring the exact buffer size
+    if fds.len() > max_fds {
+        for fd in fds.split_off(max_fds) {
+            unsafe { libc::close(fd) };
+        }
+    }
+
+    Ok((msg.bytes, fds))
+}
+
 
 /// Create an endpoint for communication
 ///
@@ -1336,14 +3154,13 @@ pub fn recvmsg<'a>(fd: RawFd, iov: &[IoVec<&mut [u8]>],
 /// particular socket type within a given protocol family, in which case
 /// protocol can be specified as `None`.  However, it is possible that many
 /// protocols may exist, in which case a particular protocol must be
-/// specified in this manner.
+/// specified in this manner.  Each address family has its own protocol
+/// type, and protocols that those lack may be given by number; see
+/// [`RawSockProtocol`](struct.RawSockProtocol.html).
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/socket.html)
-pub fn socket<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType, flags: SockFlag, protocol: T) -> Result<RawFd> {
-    let protocol = match protocol.into() {
-        None => 0,
-        Some(p) => p as c_int,
-    };
+pub fn socket<T: Into<RawSockProtocol>>(domain: AddressFamily, ty: SockType, flags: SockFlag, protocol: T) -> Result<RawFd> {
+    let protocol = protocol.into().0;
 
     // SockFlags are usually embedded into `ty`, but we don't do that in `nix` because it's a
     // little easier to understand by separating it out. So we have to merge these bitfields
@@ -1359,12 +3176,9 @@ pub fn socket<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType
 /// Create a pair of connected sockets
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/socketpair.html)
-pub fn socketpair<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType, protocol: T,
+pub fn socketpair<T: Into<RawSockProtocol>>(domain: AddressFamily, ty: SockType, protocol: T,
                   flags: SockFlag) -> Result<(RawFd, RawFd)> {
-    let protocol = match protocol.into() {
-        None => 0,
-        Some(p) => p as c_int,
-    };
+    let protocol = protocol.into().0;
 
     // SockFlags are usually embedded into `ty`, but we don't do that in `nix` because it's a
     // little easier to understand by separating it out. So we have to merge these bitfields
@@ -1383,8 +3197,8 @@ pub fn socketpair<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: Sock
 /// Listen for connections on a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/listen.html)
-pub fn listen(sockfd: RawFd, backlog: usize) -> Result<()> {
-    let res = unsafe { libc::listen(sockfd, backlog as c_int) };
+pub fn listen<F: AsRawFd>(sockfd: &F, backlog: usize) -> Result<()> {
+    let res = unsafe { libc::listen(sockfd.as_raw_fd(), backlog as c_int) };
 
     Errno::result(res).map(drop)
 }
@@ -1392,10 +3206,9 @@ pub fn listen(sockfd: RawFd, backlog: usize) -> Result<()> {
 /// Bind a name to a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/bind.html)
-pub fn bind(fd: RawFd, addr: &SockAddr) -> Result<()> {
+pub fn bind<F: AsRawFd, A: SockaddrLike>(fd: &F, addr: &A) -> Result<()> {
     let res = unsafe {
-        let (ptr, len) = addr.as_ffi_pair();
-        libc::bind(fd, ptr, len)
+        libc::bind(fd.as_raw_fd(), addr.as_ptr(), addr.len())
     };
 
     Errno::result(res).map(drop)
@@ -1404,45 +3217,189 @@ pub fn bind(fd: RawFd, addr: &SockAddr) -> Result<()> {
 /// Accept a connection on a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/accept.html)
-pub fn accept(sockfd: RawFd) -> Result<RawFd> {
-    let res = unsafe { libc::accept(sockfd, ptr::null_mut(), ptr::null_mut()) };
+pub fn accept<F: AsRawFd>(sockfd: &F) -> Result<RawFd> {
+    let res = unsafe { libc::accept(sockfd.as_raw_fd(), ptr::null_mut(), ptr::null_mut()) };
 
     Errno::result(res)
 }
 
 /// Accept a connection on a socket
 ///
+/// Unlike calling [`accept`](fn.accept.html) followed by `fcntl`, the `flags`
+/// are applied atomically, so the new descriptor can't leak into a child
+/// process that calls `exec` in between.
+///
 /// [Further reading](http://man7.org/linux/man-pages/man2/accept.2.html)
 #[cfg(any(target_os = "android",
+          target_os = "dragonfly",
           target_os = "freebsd",
           target_os = "linux",
+          target_os = "netbsd",
           target_os = "openbsd"))]
-pub fn accept4(sockfd: RawFd, flags: SockFlag) -> Result<RawFd> {
-    let res = unsafe { libc::accept4(sockfd, ptr::null_mut(), ptr::null_mut(), flags.bits()) };
+pub fn accept4<F: AsRawFd>(sockfd: &F, flags: SockFlag) -> Result<RawFd> {
+    let res = unsafe { libc::accept4(sockfd.as_raw_fd(), ptr::null_mut(), ptr::null_mut(), flags.bits()) };
 
     Errno::result(res)
 }
 
+/// Accept a connection on a socket, returning the new descriptor together
+/// with the address of the connecting peer.
+///
+/// This saves a separate call to [`getpeername`](fn.getpeername.html).  On
+/// platforms without `accept4`, `SockFlag` has no members, and any flags that
+/// are set anyway fail with `EINVAL` rather than being silently dropped.
+///
+/// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/accept.html)
+pub fn accept_addr<F: AsRawFd>(sockfd: &F, flags: SockFlag) -> Result<(RawFd, SockAddr)> {
+    unsafe {
+        let mut addr: sockaddr_storage = mem::zeroed();
+        let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
+        let addrp = &mut addr as *mut libc::sockaddr_storage as *mut libc::sockaddr;
+
+        cfg_if! {
+            if #[cfg(any(target_os = "android",
+                         target_os = "dragonfly",
+                         target_os = "freebsd",
+                         target_os = "linux",
+                         target_os = "netbsd",
+                         target_os = "openbsd"))] {
+                let res = libc::accept4(sockfd.as_raw_fd(), addrp, &mut len, flags.bits());
+            } else {
+                if !flags.is_empty() {
+                    return Err(Error::Sys(Errno::EINVAL));
+                }
+                let res = libc::accept(sockfd.as_raw_fd(), addrp, &mut len);
+            }
+        }
+        let fd = Errno::result(res)?;
+
+        match sockaddr_storage_to_addr(&addr, len as usize) {
+            Ok(addr) => Ok((fd, addr)),
+            Err(e) => {
+                // Don't leak the new connection
+                libc::close(fd);
+                Err(e)
+            }
+        }
+    }
+}
+
 /// Initiate a connection on a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/connect.html)
-pub fn connect(fd: RawFd, addr: &SockAddr) -> Result<()> {
+pub fn connect<F: AsRawFd, A: SockaddrLike>(fd: &F, addr: &A) -> Result<()> {
     let res = unsafe {
-        let (ptr, len) = addr.as_ffi_pair();
-        libc::connect(fd, ptr, len)
+        libc::connect(fd.as_raw_fd(), addr.as_ptr(), addr.len())
     };
 
     Errno::result(res).map(drop)
 }
 
+/// Connect a `SysProtocol::KextControl` socket to unit `unit` of the kernel
+/// control named `name`, returning the address of the peer as reported by
+/// [`getpeername`](fn.getpeername.html).
+///
+/// A `unit` of 0 lets the kernel pick a free unit, numbered from 1, which is
+/// the unit of the returned address.  For `utun` interfaces, unit `n`
+/// creates interface `utun{n - 1}`.  Connecting to most controls requires
+/// root.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use nix::sys::socket::*;
+/// let fd = socket(AddressFamily::System, SockType::Datagram, SockFlag::empty(),
+///                 SysProtocol::KextControl).unwrap();
+/// let addr = connect_sys_control(&fd, "com.apple.net.utun_control", 0).unwrap();
+/// // The kernel picked a unit, at least 1
+/// println!("Created utun{}", addr.unit() - 1);
+/// ```
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub fn connect_sys_control<F: AsRawFd>(fd: &F, name: &str, unit: u32)
+    -> Result<SysControlAddr>
+{
+    let addr = SysControlAddr::from_name(fd.as_raw_fd(), name, unit)?;
+    connect(fd, &addr)?;
+    match getpeername(fd)? {
+        SockAddr::SysControl(addr) => Ok(addr),
+        _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
+    }
+}
+
+/// Progress of a connection started by
+/// [`connect_nonblock`](fn.connect_nonblock.html).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConnectStatus {
+    /// The connection was established immediately, as often happens for Unix
+    /// domain and loopback sockets.
+    Connected,
+    /// The connection is being established in the background.  Wait until the
+    /// socket becomes writable, for example with `poll`, and then call
+    /// [`finish_connect`](fn.finish_connect.html).
+    InProgress,
+}
+
+/// Start connecting a non-blocking socket.
+///
+/// Unlike [`connect`](fn.connect.html), `EINPROGRESS` is not an error, but
+/// reported as [`ConnectStatus::InProgress`](enum.ConnectStatus.html).  So is
+/// `EINTR`, after which the connection also continues in the background.
+///
+/// # Examples
+///
+/// ```
+/// # use nix::fcntl::{fcntl, FcntlArg, OFlag};
+/// # use nix::poll::{poll, PollFd, PollFlags};
+/// # use nix::sys::socket::*;
+/// # use std::os::unix::io::AsRawFd;
+/// let listener = Socket::new(AddressFamily::Inet, SockType::Stream,
+///                            SockFlag::empty(), None).unwrap();
+/// let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+/// listener.bind(&localhost).unwrap();
+/// listener.listen(1).unwrap();
+///
+/// let client = Socket::new(AddressFamily::Inet, SockType::Stream,
+///                          SockFlag::empty(), None).unwrap();
+/// fcntl(client.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK)).unwrap();
+/// let addr = listener.getsockname().unwrap();
+/// if connect_nonblock(&client, &addr).unwrap() == ConnectStatus::InProgress {
+///     let mut fds = [PollFd::new(client.as_raw_fd(), PollFlags::POLLOUT)];
+///     poll(&mut fds, -1).unwrap();
+///     finish_connect(&client).unwrap();
+/// }
+/// ```
+pub fn connect_nonblock<F: AsRawFd, A: SockaddrLike>(fd: &F, addr: &A)
+    -> Result<ConnectStatus>
+{
+    match connect(fd, addr) {
+        Ok(()) => Ok(ConnectStatus::Connected),
+        Err(Error::Sys(Errno::EINPROGRESS)) |
+        Err(Error::Sys(Errno::EINTR)) => Ok(ConnectStatus::InProgress),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get the outcome of a connection started by
+/// [`connect_nonblock`](fn.connect_nonblock.html), once the socket has become
+/// writable.
+///
+/// Reads and clears `SO_ERROR`, returning the error that made the connection
+/// fail, such as `ECONNREFUSED` or `ETIMEDOUT`.
+pub fn finish_connect<F: AsRawFd>(fd: &F) -> Result<()> {
+    match getsockopt(fd, sockopt::SocketError)? {
+        0 => Ok(()),
+        errno => Err(Error::Sys(Errno::from_i32(errno))),
+    }
+}
+
 /// Receive data from a connection-oriented socket. Returns the number of
 /// bytes read
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/recv.html)
-pub fn recv(sockfd: RawFd, buf: &mut [u8], flags: MsgFlags) -> Result<usize> {
+pub fn recv<F: AsRawFd>(sockfd: &F, buf: &mut [u8], flags: MsgFlags) -> Result<usize> {
     unsafe {
         let ret = libc::recv(
-            sockfd,
+            sockfd.as_raw_fd(),
             buf.as_ptr() as *mut c_void,
             buf.len() as size_t,
             flags.bits());
@@ -1451,12 +3408,55 @@ pub fn recv(sockfd: RawFd, buf: &mut [u8], flags: MsgFlags) -> Result<usize> {
     }
 }
 
+/// Receive a datagram, reporting both the number of bytes copied into `buf`
+/// and the real length of the datagram.
+///
+/// `MSG_TRUNC` is added to `flags`, so the kernel returns the full length of
+/// the datagram even if it didn't fit in `buf`.  The payload was truncated if
+/// the second value is larger than the first.
+///
+/// Only sockets that preserve message boundaries, of type `SOCK_DGRAM`,
+/// `SOCK_SEQPACKET`, `SOCK_RAW` (such as netlink sockets) or `SOCK_RDM`, are
+/// supported, and others fail with `EOPNOTSUPP`: on stream sockets
+/// `MSG_TRUNC` makes the kernel discard the data without copying any of it.
+///
+/// # Examples
+///
+/// ```
+/// # use nix::sys::socket::*;
+/// let (a, b) = socketpair(AddressFamily::Unix, SockType::Datagram, None,
+///                         SockFlag::empty()).unwrap();
+/// send(&a, b"hello world", MsgFlags::empty()).unwrap();
+/// let mut buf = [0u8; 5];
+/// let (copied, len) = recv_trunc(&b, &mut buf, MsgFlags::empty()).unwrap();
+/// assert_eq!(copied, 5);
+/// assert_eq!(len, 11);
+/// assert_eq!(&buf, b"hello");
+/// ```
+///
+/// [Further reading](https://man7.org/linux/man-pages/man2/recv.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn recv_trunc<F: AsRawFd>(sockfd: &F, buf: &mut [u8], flags: MsgFlags)
+    -> Result<(usize, usize)>
+{
+    match getsockopt(sockfd, sockopt::SockType)? {
+        SockType::Datagram | SockType::SeqPacket | SockType::Raw | SockType::Rdm => (),
+        _ => return Err(Error::Sys(Errno::EOPNOTSUPP)),
+    }
+    let len = recv(sockfd, buf, flags | MsgFlags::MSG_TRUNC)?;
+    Ok((std::cmp::min(len, buf.len()), len))
+}
+
 /// Receive data from a connectionless or connection-oriented socket. Returns
 /// the number of bytes read and, for connectionless sockets,  the socket
 /// address of the sender.
 ///
+/// The address is `None` if the sender is unnamed, if the socket is
+/// connection-oriented, or if the sender's address family is not supported by
+/// [`SockAddr`](enum.SockAddr.html).  The data is returned nonetheless.
+///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/recvfrom.html)
-pub fn recvfrom(sockfd: RawFd, buf: &mut [u8])
+pub fn recvfrom<F: AsRawFd>(sockfd: &F, buf: &mut [u8], flags: MsgFlags)
     -> Result<(usize, Option<SockAddr>)>
 {
     unsafe {
@@ -1464,28 +3464,99 @@ pub fn recvfrom(sockfd: RawFd, buf: &mut [u8])
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
 
         let ret = Errno::result(libc::recvfrom(
-            sockfd,
+            sockfd.as_raw_fd(),
             buf.as_ptr() as *mut c_void,
             buf.len() as size_t,
-            0,
+            flags.bits(),
             &mut addr as *mut libc::sockaddr_storage as *mut libc::sockaddr,
             &mut len as *mut socklen_t))? as usize;
 
         match sockaddr_storage_to_addr(&addr, len as usize) {
-            Err(Error::Sys(Errno::ENOTCONN)) => Ok((ret, None)),
+            Err(Error::Sys(Errno::ENOTCONN)) |
+            Err(Error::Sys(Errno::EAFNOSUPPORT)) => Ok((ret, None)),
             Ok(addr) => Ok((ret, Some(addr))),
             Err(e) => Err(e)
         }
     }
 }
 
+/// Like [`recv`](fn.recv.html), but receive into a possibly uninitialized
+/// buffer.
+///
+/// Returns the part of `buf` that was filled in.  This saves zeroing large
+/// buffers before each call.  Fails with `EINVAL` if `flags` contains
+/// `MSG_TRUNC`.
+///
+/// # Examples
+///
+/// ```
+/// # use nix::sys::socket::*;
+/// use std::mem::MaybeUninit;
+///
+/// let (a, b) = socketpair(AddressFamily::Unix, SockType::Datagram, None,
+///                         SockFlag::empty()).unwrap();
+/// send(&a, b"hello", MsgFlags::empty()).unwrap();
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 4096];
+/// let data = recv_uninit(&b, &mut buf, MsgFlags::empty()).unwrap();
+/// assert_eq!(data, b"hello");
+/// ```
+pub fn recv_uninit<'a, F: AsRawFd>(sockfd: &F, buf: &'a mut [mem::MaybeUninit<u8>],
+                                   flags: MsgFlags) -> Result<&'a mut [u8]>
+{
+    check_uninit_flags(flags)?;
+    unsafe {
+        let ret = libc::recv(
+            sockfd.as_raw_fd(),
+            buf.as_mut_ptr() as *mut c_void,
+            buf.len() as size_t,
+            flags.bits());
+
+        let len = Errno::result(ret)? as usize;
+        Ok(assume_init_prefix(buf, len))
+    }
+}
+
+/// Like [`recvfrom`](fn.recvfrom.html), but receive into a possibly
+/// uninitialized buffer.
+///
+/// Returns the part of `buf` that was filled in and the address of the
+/// sender, if known.  Fails with `EINVAL` if `flags` contains `MSG_TRUNC`.
+pub fn recvfrom_uninit<'a, F: AsRawFd>(sockfd: &F, buf: &'a mut [mem::MaybeUninit<u8>],
+                                       flags: MsgFlags)
+    -> Result<(&'a mut [u8], Option<SockAddr>)>
+{
+    check_uninit_flags(flags)?;
+    unsafe {
+        let mut addr: sockaddr_storage = mem::zeroed();
+        let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
+
+        let ret = Errno::result(libc::recvfrom(
+            sockfd.as_raw_fd(),
+            buf.as_mut_ptr() as *mut c_void,
+            buf.len() as size_t,
+            flags.bits(),
+            &mut addr as *mut libc::sockaddr_storage as *mut libc::sockaddr,
+            &mut len as *mut socklen_t))? as usize;
+
+        let addr = match sockaddr_storage_to_addr(&addr, len as usize) {
+            Err(Error::Sys(Errno::ENOTCONN)) |
+            Err(Error::Sys(Errno::EAFNOSUPPORT)) => None,
+            Ok(addr) => Some(addr),
+            Err(e) => return Err(e)
+        };
+        Ok((assume_init_prefix(buf, ret), addr))
+    }
+}
+
 /// Send a message to a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/sendto.html)
-pub fn sendto(fd: RawFd, buf: &[u8], addr: &SockAddr, flags: MsgFlags) -> Result<usize> {
+pub fn sendto<F: AsRawFd, A: SockaddrLike>(fd: &F, buf: &[u8], addr: &A, flags: MsgFlags)
+    -> Result<usize>
+{
     let ret = unsafe {
-        let (ptr, len) = addr.as_ffi_pair();
-        libc::sendto(fd, buf.as_ptr() as *const c_void, buf.len() as size_t, flags.bits(), ptr, len)
+        libc::sendto(fd.as_raw_fd(), buf.as_ptr() as *const c_void, buf.len() as size_t, flags.bits(),
+                     addr.as_ptr(), addr.len())
     };
 
     Errno::result(ret).map(|r| r as usize)
@@ -1494,9 +3565,9 @@ pub fn sendto(fd: RawFd, buf: &[u8], addr: &SockAddr, flags: MsgFlags) -> Result
 /// Send data to a connection-oriented socket. Returns the number of bytes read
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/send.html)
-pub fn send(fd: RawFd, buf: &[u8], flags: MsgFlags) -> Result<usize> {
+pub fn send<F: AsRawFd>(fd: &F, buf: &[u8], flags: MsgFlags) -> Result<usize> {
     let ret = unsafe {
-        libc::send(fd, buf.as_ptr() as *const c_void, buf.len() as size_t, flags.bits())
+        libc::send(fd.as_raw_fd(), buf.as_ptr() as *const c_void, buf.len() as size_t, flags.bits())
     };
 
     Errno::result(ret).map(|r| r as usize)
@@ -1544,11 +3615,37 @@ pub trait SetSockOpt : Clone {
     fn set(&self, fd: RawFd, val: &Self::Val) -> Result<()>;
 }
 
+/// Types of socket option values that
+/// [`custom_sockopt!`](../../macro.custom_sockopt.html) passes to and from the
+/// system as they are, such as the integer types.
+///
+/// # Safety
+///
+/// The type must have no padding, and every bit pattern of its size must be a
+/// valid value of it, so that it can be filled in by the system.
+pub unsafe trait PlainSockOptVal: Copy {}
+
+unsafe impl PlainSockOptVal for i8 {}
+unsafe impl PlainSockOptVal for u8 {}
+unsafe impl PlainSockOptVal for i16 {}
+unsafe impl PlainSockOptVal for u16 {}
+unsafe impl PlainSockOptVal for i32 {}
+unsafe impl PlainSockOptVal for u32 {}
+unsafe impl PlainSockOptVal for i64 {}
+unsafe impl PlainSockOptVal for u64 {}
+unsafe impl PlainSockOptVal for isize {}
+unsafe impl PlainSockOptVal for usize {}
+unsafe impl PlainSockOptVal for libc::linger {}
+unsafe impl PlainSockOptVal for libc::in_addr {}
+unsafe impl PlainSockOptVal for libc::in6_addr {}
+unsafe impl PlainSockOptVal for libc::ip_mreq {}
+unsafe impl PlainSockOptVal for libc::ipv6_mreq {}
+
 /// Get the current value for the requested socket option
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getsockopt.html)
-pub fn getsockopt<O: GetSockOpt>(fd: RawFd, opt: O) -> Result<O::Val> {
-    opt.get(fd)
+pub fn getsockopt<F: AsRawFd, O: GetSockOpt>(fd: &F, opt: O) -> Result<O::Val> {
+    opt.get(fd.as_raw_fd())
 }
 
 /// Sets the value for the requested socket option
@@ -1561,27 +3658,76 @@ pub fn getsockopt<O: GetSockOpt>(fd: RawFd, opt: O) -> Result<O::Val> {
 /// use nix::sys::socket::setsockopt;
 /// use nix::sys::socket::sockopt::KeepAlive;
 /// use std::net::TcpListener;
-/// use std::os::unix::io::AsRawFd;
 ///
 /// let listener = TcpListener::bind("0.0.0.0:0").unwrap();
-/// let fd = listener.as_raw_fd();
-/// let res = setsockopt(fd, KeepAlive, &true);
+/// let res = setsockopt(&listener, KeepAlive, &true);
 /// assert!(res.is_ok());
 /// ```
-pub fn setsockopt<O: SetSockOpt>(fd: RawFd, opt: O, val: &O::Val) -> Result<()> {
-    opt.set(fd, val)
+pub fn setsockopt<F: AsRawFd, O: SetSockOpt>(fd: &F, opt: O, val: &O::Val) -> Result<()> {
+    opt.set(fd.as_raw_fd(), val)
+}
+
+/// Get the value of a socket option as raw bytes, for options that have no
+/// typed wrapper in [`sockopt`](sockopt/index.html).
+///
+/// `level` and `name` are passed through unchanged.  Returns the number of
+/// bytes the system wrote into `val`.
+///
+/// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getsockopt.html)
+pub fn getsockopt_raw<F: AsRawFd>(fd: &F, level: c_int, name: c_int, val: &mut [u8])
+    -> Result<usize>
+{
+    let mut len = val.len() as socklen_t;
+    let res = unsafe {
+        libc::getsockopt(fd.as_raw_fd(), level, name, val.as_mut_ptr() as *mut c_void,
+                         &mut len)
+    };
+    Errno::result(res).map(|_| len as usize)
+}
+
+/// Set the value of a socket option from raw bytes, for options that have no
+/// typed wrapper in [`sockopt`](sockopt/index.html).
+///
+/// `val` must be laid out the way the system expects for `level` and `name`.
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::socket::{getsockopt_raw, setsockopt_raw};
+/// use std::net::TcpListener;
+///
+/// let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+/// let on: libc::c_int = 1;
+/// setsockopt_raw(&listener, libc::SOL_SOCKET, libc::SO_KEEPALIVE,
+///                &on.to_ne_bytes()).unwrap();
+/// let mut buf = [0u8; 4];
+/// let len = getsockopt_raw(&listener, libc::SOL_SOCKET, libc::SO_KEEPALIVE,
+///                          &mut buf).unwrap();
+/// assert_eq!(len, 4);
+/// assert_ne!(libc::c_int::from_ne_bytes(buf), 0);
+/// ```
+///
+/// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/setsockopt.html)
+pub fn setsockopt_raw<F: AsRawFd>(fd: &F, level: c_int, name: c_int, val: &[u8])
+    -> Result<()>
+{
+    let res = unsafe {
+        libc::setsockopt(fd.as_raw_fd(), level, name, val.as_ptr() as *const c_void,
+                         val.len() as socklen_t)
+    };
+    Errno::result(res).map(drop)
 }
 
 /// Get the address of the peer connected to the socket `fd`.
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getpeername.html)
-pub fn getpeername(fd: RawFd) -> Result<SockAddr> {
+pub fn getpeername<F: AsRawFd>(fd: &F) -> Result<SockAddr> {
     unsafe {
         let mut addr = mem::MaybeUninit::uninit();
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
 
         let ret = libc::getpeername(
-            fd,
+            fd.as_raw_fd(),
             addr.as_mut_ptr() as *mut libc::sockaddr,
             &mut len
         );
@@ -1595,13 +3741,13 @@ pub fn getpeername(fd: RawFd) -> Result<SockAddr> {
 /// Get the current address to which the socket `fd` is bound.
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getsockname.html)
-pub fn getsockname(fd: RawFd) -> Result<SockAddr> {
+pub fn getsockname<F: AsRawFd>(fd: &F) -> Result<SockAddr> {
     unsafe {
         let mut addr = mem::MaybeUninit::uninit();
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
 
         let ret = libc::getsockname(
-            fd,
+            fd.as_raw_fd(),
             addr.as_mut_ptr() as *mut libc::sockaddr,
             &mut len
         );
@@ -1620,63 +3766,22 @@ pub fn getsockname(fd: RawFd) -> Result<SockAddr> {
 /// allocated and valid.  It must be at least as large as all the useful parts
 /// of the structure.  Note that in the case of a `sockaddr_un`, `len` need not
 /// include the terminating null.
+///
+/// Returns `EAFNOSUPPORT` if the address family is not one that `SockAddr`
+/// can represent.
 pub fn sockaddr_storage_to_addr(
     addr: &sockaddr_storage,
     len: usize) -> Result<SockAddr> {
 
-    assert!(len <= mem::size_of::<sockaddr_un>());
+    assert!(len <= mem::size_of::<sockaddr_storage>());
     if len < mem::size_of_val(&addr.ss_family) {
         return Err(Error::Sys(Errno::ENOTCONN));
     }
 
-    match c_int::from(addr.ss_family) {
-        libc::AF_INET => {
-            assert_eq!(len as usize, mem::size_of::<sockaddr_in>());
-            let sin = unsafe {
-                *(addr as *const sockaddr_storage as *const sockaddr_in)
-            };
-            Ok(SockAddr::Inet(InetAddr::V4(sin)))
-        }
-        libc::AF_INET6 => {
-            assert_eq!(len as usize, mem::size_of::<sockaddr_in6>());
-            let sin6 = unsafe {
-                *(addr as *const _ as *const sockaddr_in6)
-            };
-            Ok(SockAddr::Inet(InetAddr::V6(sin6)))
-        }
-        libc::AF_UNIX => {
-            let pathlen = len - offset_of!(sockaddr_un, sun_path);
-            let sun = unsafe {
-                *(addr as *const _ as *const sockaddr_un)
-            };
-            Ok(SockAddr::Unix(UnixAddr(sun, pathlen)))
-        }
-        #[cfg(any(target_os = "android", target_os = "linux"))]
-        libc::AF_NETLINK => {
-            use libc::sockaddr_nl;
-            let snl = unsafe {
-                *(addr as *const _ as *const sockaddr_nl)
-            };
-            Ok(SockAddr::Netlink(NetlinkAddr(snl)))
-        }
-        #[cfg(any(target_os = "android", target_os = "linux"))]
-        libc::AF_ALG => {
-            use libc::sockaddr_alg;
-            let salg = unsafe {
-                *(addr as *const _ as *const sockaddr_alg)
-            };
-            Ok(SockAddr::Alg(AlgAddr(salg)))
-        }
-        #[cfg(target_os = "linux")]
-        libc::AF_VSOCK => {
-            use libc::sockaddr_vm;
-            let svm = unsafe {
-                *(addr as *const _ as *const sockaddr_vm)
-            };
-            Ok(SockAddr::Vsock(VsockAddr(svm)))
-        }
-        af => panic!("unexpected address family {}", af),
-    }
+    unsafe {
+        SockAddr::from_raw(addr as *const sockaddr_storage as *const sockaddr,
+                           Some(len as socklen_t))
+    }.ok_or(Error::Sys(Errno::EAFNOSUPPORT))
 }
 
 
@@ -1693,7 +3798,7 @@ pub enum Shutdown {
 /// Shut down part of a full-duplex connection.
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/shutdown.html)
-pub fn shutdown(df: RawFd, how: Shutdown) -> Result<()> {
+pub fn shutdown<F: AsRawFd>(df: &F, how: Shutdown) -> Result<()> {
     unsafe {
         use libc::shutdown;
 
@@ -1703,6 +3808,387 @@ pub fn shutdown(df: RawFd, how: Shutdown) -> Result<()> {
             Shutdown::Both  => libc::SHUT_RDWR,
         };
 
-        Errno::result(shutdown(df, how)).map(drop)
+        Errno::result(shutdown(df.as_raw_fd(), how)).map(drop)
+    }
+}
+
+/// An owned socket, closed when dropped.
+///
+/// This wraps the free functions of this module as methods, so that a socket
+/// can't be leaked or closed twice.  The underlying file descriptor remains
+/// available through [`AsRawFd`](https://doc.rust-lang.org/std/os/unix/io/trait.AsRawFd.html)
+/// for everything else.
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::socket::*;
+///
+/// let listener = Socket::new(AddressFamily::Inet, SockType::Stream,
+///                            SockFlag::empty(), None).unwrap();
+/// listener.setsockopt(sockopt::ReuseAddr, &true).unwrap();
+/// let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+/// listener.bind(&localhost).unwrap();
+/// listener.listen(1).unwrap();
+///
+/// let client = Socket::new(AddressFamily::Inet, SockType::Stream,
+///                          SockFlag::empty(), None).unwrap();
+/// client.connect(&listener.getsockname().unwrap()).unwrap();
+/// let server = listener.accept().unwrap();
+///
+/// client.send(b"hello", MsgFlags::empty()).unwrap();
+/// let mut buf = [0u8; 5];
+/// assert_eq!(server.recv(&mut buf, MsgFlags::MSG_WAITALL).unwrap(), 5);
+/// assert_eq!(&buf, b"hello");
+/// ```
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct Socket(RawFd);
+
+impl Socket {
+    /// Create a socket.  See [`socket`](fn.socket.html).
+    pub fn new<T: Into<RawSockProtocol>>(domain: AddressFamily, ty: SockType, flags: SockFlag,
+                                         protocol: T) -> Result<Socket> {
+        socket(domain, ty, flags, protocol).map(Socket)
+    }
+
+    /// Create a pair of connected sockets.  See
+    /// [`socketpair`](fn.socketpair.html).
+    pub fn pair<T: Into<RawSockProtocol>>(domain: AddressFamily, ty: SockType, protocol: T,
+                                          flags: SockFlag) -> Result<(Socket, Socket)> {
+        socketpair(domain, ty, protocol, flags).map(|(fd1, fd2)| (Socket(fd1), Socket(fd2)))
+    }
+
+    /// Bind a name to the socket.  See [`bind`](fn.bind.html).
+    pub fn bind<A: SockaddrLike>(&self, addr: &A) -> Result<()> {
+        bind(self, addr)
+    }
+
+    /// Listen for connections.  See [`listen`](fn.listen.html).
+    pub fn listen(&self, backlog: usize) -> Result<()> {
+        listen(self, backlog)
+    }
+
+    /// Accept a connection.  See [`accept`](fn.accept.html).
+    pub fn accept(&self) -> Result<Socket> {
+        accept(self).map(Socket)
+    }
+
+    /// Accept a connection, setting flags on the new socket.  See
+    /// [`accept4`](fn.accept4.html).
+    #[cfg(any(target_os = "android",
+              target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "linux",
+              target_os = "netbsd",
+              target_os = "openbsd"))]
+    pub fn accept4(&self, flags: SockFlag) -> Result<Socket> {
+        accept4(self, flags).map(Socket)
+    }
+
+    /// Connect the socket.  See [`connect`](fn.connect.html).
+    pub fn connect<A: SockaddrLike>(&self, addr: &A) -> Result<()> {
+        connect(self, addr)
+    }
+
+    /// Send data on a connected socket.  See [`send`](fn.send.html).
+    pub fn send(&self, buf: &[u8], flags: MsgFlags) -> Result<usize> {
+        send(self, buf, flags)
+    }
+
+    /// Send data to an address.  See [`sendto`](fn.sendto.html).
+    pub fn sendto<A: SockaddrLike>(&self, buf: &[u8], addr: &A, flags: MsgFlags)
+        -> Result<usize>
+    {
+        sendto(self, buf, addr, flags)
+    }
+
+    /// Receive data from a connected socket.  See [`recv`](fn.recv.html).
+    pub fn recv(&self, buf: &mut [u8], flags: MsgFlags) -> Result<usize> {
+        recv(self, buf, flags)
+    }
+
+    /// Receive data and the address of its sender.  See
+    /// [`recvfrom`](fn.recvfrom.html).
+    pub fn recvfrom(&self, buf: &mut [u8], flags: MsgFlags)
+        -> Result<(usize, Option<SockAddr>)>
+    {
+        recvfrom(self, buf, flags)
+    }
+
+    /// Get the value of a socket option.  See
+    /// [`getsockopt`](fn.getsockopt.html).
+    pub fn getsockopt<O: GetSockOpt>(&self, opt: O) -> Result<O::Val> {
+        getsockopt(self, opt)
+    }
+
+    /// Set the value of a socket option.  See
+    /// [`setsockopt`](fn.setsockopt.html).
+    pub fn setsockopt<O: SetSockOpt>(&self, opt: O, val: &O::Val) -> Result<()> {
+        setsockopt(self, opt, val)
+    }
+
+    /// The address the socket is bound to.  See
+    /// [`getsockname`](fn.getsockname.html).
+    pub fn getsockname(&self) -> Result<SockAddr> {
+        getsockname(self)
+    }
+
+    /// The address of the peer.  See [`getpeername`](fn.getpeername.html).
+    pub fn getpeername(&self) -> Result<SockAddr> {
+        getpeername(self)
+    }
+
+    /// Shut down part of a connection.  See [`shutdown`](fn.shutdown.html).
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        shutdown(self, how)
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.0;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Take ownership of `fd`, which must be an open socket not owned by
+    /// anything else.
+    unsafe fn from_raw_fd(fd: RawFd) -> Socket {
+        Socket(fd)
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        // Like PtyMaster, ignore errors other than EBADF, which means that
+        // the descriptor was closed behind our back
+        let e = crate::unistd::close(self.0);
+        if e == Err(Error::Sys(Errno::EBADF)) {
+            panic!("Closing an invalid file descriptor!");
+        };
+    }
+}
+
+/// Check that `fd` is a socket of one of `families`, of type `ty` and, where
+/// the system can tell, of `protocols`, that is listening or not.
+fn check_socket(fd: &Socket, families: &[AddressFamily], ty: SockType,
+                protocols: &[SockProtocol], listening: bool) -> Result<()>
+{
+    cfg_if! {
+        if #[cfg(any(target_os = "android", target_os = "linux"))] {
+            let family = fd.getsockopt(sockopt::SockDomain)?;
+        } else {
+            let family = fd.getsockname()?.family();
+        }
+    }
+    if !families.contains(&family) {
+        return Err(Error::Sys(Errno::EAFNOSUPPORT));
+    }
+    if fd.getsockopt(sockopt::SockType)? != ty {
+        return Err(Error::Sys(Errno::EPROTOTYPE));
+    }
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        if !protocols.is_empty() {
+            let protocol = fd.getsockopt(sockopt::SockProtocol)?;
+            if !protocols.iter().any(|&p| RawSockProtocol::from(p) == protocol) {
+                return Err(Error::Sys(Errno::EPROTONOSUPPORT));
+            }
+        }
+    }
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let _ = protocols;
+    if fd.getsockopt(sockopt::AcceptConn)? != listening {
+        return Err(Error::Sys(Errno::EINVAL));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+const TCP_PROTOCOLS: &[SockProtocol] = &[SockProtocol::Tcp, SockProtocol::MptcpTcp];
+#[cfg(not(target_os = "linux"))]
+const TCP_PROTOCOLS: &[SockProtocol] = &[SockProtocol::Tcp];
+
+/// The error from handing a [`Socket`](struct.Socket.html) over to the
+/// standard library, which gives the socket back rather than closing it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TryFromSocketError {
+    socket: Socket,
+    errno: Errno,
+}
+
+impl TryFromSocketError {
+    /// Why the socket couldn't be handed over.
+    pub fn errno(&self) -> Errno {
+        self.errno
+    }
+
+    /// Take back the socket that couldn't be handed over.
+    pub fn into_inner(self) -> Socket {
+        self.socket
+    }
+}
+
+impl std::fmt::Display for TryFromSocketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.errno, self.errno.desc())
+    }
+}
+
+impl std::error::Error for TryFromSocketError {}
+
+/// Close the socket, keeping only the reason it couldn't be handed over.
+impl From<TryFromSocketError> for Error {
+    fn from(e: TryFromSocketError) -> Error {
+        Error::Sys(e.errno)
+    }
+}
+
+/// Hand `sock` over as a `T` if [`check_socket`] accepts it, or give it back.
+fn try_from_socket<T: FromRawFd>(sock: Socket, families: &[AddressFamily], ty: SockType,
+                                 protocols: &[SockProtocol], listening: bool)
+    -> std::result::Result<T, TryFromSocketError>
+{
+    match check_socket(&sock, families, ty, protocols, listening) {
+        Ok(()) => Ok(unsafe { T::from_raw_fd(sock.into_raw_fd()) }),
+        Err(e) => Err(TryFromSocketError {
+            socket: sock,
+            errno: e.as_errno().unwrap_or(Errno::UnknownErrno),
+        }),
+    }
+}
+
+/// Hand a connected TCP socket over to the standard library.
+///
+/// Fails with `EAFNOSUPPORT`, `EPROTOTYPE` or `EPROTONOSUPPORT` if it isn't an
+/// IPv4 or IPv6 TCP socket, and with `EINVAL` if it is listening.
+impl std::convert::TryFrom<Socket> for std::net::TcpStream {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket) -> std::result::Result<std::net::TcpStream, TryFromSocketError> {
+        try_from_socket(sock, &[AddressFamily::Inet, AddressFamily::Inet6], SockType::Stream,
+                        TCP_PROTOCOLS, false)
+    }
+}
+
+/// Hand a listening TCP socket over to the standard library.
+///
+/// Fails with `EAFNOSUPPORT`, `EPROTOTYPE` or `EPROTONOSUPPORT` if it isn't an
+/// IPv4 or IPv6 TCP socket, and with `EINVAL` if it isn't listening.
+///
+/// # Examples
+///
+/// Set an option that the standard library doesn't offer before listening:
+///
+/// ```
+/// # use nix::sys::socket::*;
+/// use std::convert::TryFrom;
+/// use std::net::TcpListener;
+///
+/// let sock = Socket::new(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+///                        None).unwrap();
+/// sock.setsockopt(sockopt::ReuseAddr, &true).unwrap();
+/// let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+/// sock.bind(&localhost).unwrap();
+/// // Not listening yet, so the socket comes back
+/// let sock = TcpListener::try_from(sock).unwrap_err().into_inner();
+/// sock.listen(128).unwrap();
+/// let listener = TcpListener::try_from(sock).unwrap();
+/// ```
+impl std::convert::TryFrom<Socket> for std::net::TcpListener {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket) -> std::result::Result<std::net::TcpListener, TryFromSocketError> {
+        try_from_socket(sock, &[AddressFamily::Inet, AddressFamily::Inet6], SockType::Stream,
+                        TCP_PROTOCOLS, true)
+    }
+}
+
+/// Hand a UDP socket over to the standard library.
+///
+/// Fails with `EAFNOSUPPORT`, `EPROTOTYPE` or `EPROTONOSUPPORT` if it isn't an
+/// IPv4 or IPv6 UDP socket.
+impl std::convert::TryFrom<Socket> for std::net::UdpSocket {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket) -> std::result::Result<std::net::UdpSocket, TryFromSocketError> {
+        try_from_socket(sock, &[AddressFamily::Inet, AddressFamily::Inet6], SockType::Datagram,
+                        &[SockProtocol::Udp], false)
+    }
+}
+
+/// Hand a Unix stream socket that isn't listening over to the standard
+/// library.
+///
+/// Fails with `EAFNOSUPPORT` or `EPROTOTYPE` if it isn't a Unix stream socket,
+/// and with `EINVAL` if it is listening.
+impl std::convert::TryFrom<Socket> for std::os::unix::net::UnixStream {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket)
+        -> std::result::Result<std::os::unix::net::UnixStream, TryFromSocketError>
+    {
+        try_from_socket(sock, &[AddressFamily::Unix], SockType::Stream, &[], false)
+    }
+}
+
+/// Hand a listening Unix stream socket over to the standard library.
+///
+/// Fails with `EAFNOSUPPORT` or `EPROTOTYPE` if it isn't a Unix stream socket,
+/// and with `EINVAL` if it isn't listening.
+impl std::convert::TryFrom<Socket> for std::os::unix::net::UnixListener {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket)
+        -> std::result::Result<std::os::unix::net::UnixListener, TryFromSocketError>
+    {
+        try_from_socket(sock, &[AddressFamily::Unix], SockType::Stream, &[], true)
+    }
+}
+
+/// Hand a Unix datagram socket over to the standard library.
+///
+/// Fails with `EAFNOSUPPORT` or `EPROTOTYPE` if it isn't a Unix datagram
+/// socket.
+impl std::convert::TryFrom<Socket> for std::os::unix::net::UnixDatagram {
+    type Error = TryFromSocketError;
+
+    fn try_from(sock: Socket)
+        -> std::result::Result<std::os::unix::net::UnixDatagram, TryFromSocketError>
+    {
+        try_from_socket(sock, &[AddressFamily::Unix], SockType::Datagram, &[], false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    fn decode_scm_security() {
+        use super::*;
+
+        // Security modules may or may not count the terminating null byte
+        for label in &[&b"unconfined\0"[..], &b"unconfined"[..]] {
+            let mut buf = vec![0u64; cmsg_space_len::<u8>(label.len()) / 8 + 1];
+            let cmsg = buf.as_mut_ptr() as *mut cmsghdr;
+            let decoded = unsafe {
+                (*cmsg).cmsg_level = libc::SOL_SOCKET;
+                (*cmsg).cmsg_type = SCM_SECURITY;
+                (*cmsg).cmsg_len = CMSG_LEN(label.len() as c_uint) as _;
+                ptr::copy_nonoverlapping(label.as_ptr(), CMSG_DATA(cmsg),
+                                         label.len());
+                ControlMessageOwned::decode_from(&*cmsg)
+            };
+            assert_eq!(decoded,
+                       ControlMessageOwned::ScmSecurity(b"unconfined".to_vec()));
+        }
     }
 }
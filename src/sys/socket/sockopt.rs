@@ -9,14 +9,52 @@ use std::mem::{
     MaybeUninit
 };
 use std::os::unix::io::RawFd;
+use std::convert::TryFrom;
+use std::time::Duration;
 use std::ffi::{OsStr, OsString};
 #[cfg(target_family = "unix")]
 use std::os::unix::ffi::OsStrExt;
 
 // Constants
 // TCP_CA_NAME_MAX isn't defined in user space include files
-#[cfg(any(target_os = "freebsd", target_os = "linux"))] 
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
 const TCP_CA_NAME_MAX: usize = 16;
+// TCP_ULP_NAME_MAX isn't either
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_ULP_NAME_MAX: usize = 16;
+// From <linux/netfilter_ipv6/ip6_tables.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IP6T_SO_ORIGINAL_DST: c_int = 80;
+// From <linux/mptcp.h>; not yet in libc
+#[cfg(target_os = "linux")]
+const SOL_MPTCP: c_int = 284;
+#[cfg(target_os = "linux")]
+const MPTCP_INFO: c_int = 1;
+// From <linux/tcp.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_NO_QUEUE: c_int = 0;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_RECV_QUEUE: c_int = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_SEND_QUEUE: c_int = 2;
+// From <linux/sctp.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCTP_INITMSG: c_int = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCTP_NODELAY: c_int = 3;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCTP_EVENTS: c_int = 11;
+// From <linux/rds.h>; not yet in libc
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SO_RDS_TRANSPORT: c_int = 8;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_TRANS_IB: c_int = 0;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_TRANS_IWARP: c_int = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_TRANS_TCP: c_int = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const RDS_TRANS_NONE: c_int = !0;
 
 /// Helper for implementing `SetSockOpt` for a given socket option. See
 /// [`::sys::socket::SetSockOpt`](sys/socket/trait.SetSockOpt.html).
@@ -114,6 +152,7 @@ macro_rules! getsockopt_impl {
 ///
 /// # Arguments
 ///
+/// * Attributes, such as doc comments, to apply to the generated type; optional.
 /// * `GetOnly`, `SetOnly` or `Both`: whether you want to implement only getter, only setter or
 ///    both of them.
 /// * `$name:ident`: name of type `GetSockOpt`/`SetSockOpt` will be implemented for.
@@ -128,73 +167,76 @@ macro_rules! getsockopt_impl {
 /// * `$getter:ty`: `Get` implementation; optional; only for `GetOnly` and `Both`.
 /// * `$setter:ty`: `Set` implementation; optional; only for `SetOnly` and `Both`.
 macro_rules! sockopt_impl {
-    (GetOnly, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, bool, GetBool);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, bool, GetBool);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, u8, GetU8);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, u8, GetU8);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, usize, GetUsize);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, usize, GetUsize);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, bool, SetBool);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, bool, SetBool);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, u8, SetU8);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, u8, SetU8);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, usize, SetUsize);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, usize, SetUsize);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(Both, $name, $level, $flag, bool, GetBool, SetBool);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, bool, GetBool, SetBool);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(Both, $name, $level, $flag, u8, GetU8, SetU8);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, u8, GetU8, SetU8);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(Both, $name, $level, $flag, usize, GetUsize, SetUsize);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, usize, GetUsize, SetUsize);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, OsString<$array:ty>) => {
-        sockopt_impl!(Both, $name, $level, $flag, OsString, GetOsString<$array>, SetOsString);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, OsString<$array:ty>) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, OsString, GetOsString<$array>, SetOsString);
     };
 
     /*
      * Matchers with generic getter types must be placed at the end, so
      * they'll only match _after_ specialized matchers fail
      */
-    (GetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, $ty, GetStruct<$ty>);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, $ty, GetStruct<$ty>);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty) => {
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty) => {
+        $(#[$attr])*
         #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         pub struct $name;
 
         getsockopt_impl!($name, $level, $flag, $ty, $getter);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, $ty, SetStruct<$ty>);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, $ty, SetStruct<$ty>);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $setter:ty) => {
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $setter:ty) => {
+        $(#[$attr])*
         #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         pub struct $name;
 
         setsockopt_impl!($name, $level, $flag, $ty, $setter);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty, $setter:ty) => {
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty, $setter:ty) => {
+        $(#[$attr])*
         #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         pub struct $name;
 
@@ -202,8 +244,169 @@ macro_rules! sockopt_impl {
         getsockopt_impl!($name, $level, $flag, $ty, $getter);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(Both, $name, $level, $flag, $ty, GetStruct<$ty>, SetStruct<$ty>);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, $ty, GetStruct<$ty>, SetStruct<$ty>);
+    };
+}
+
+/// Define a socket option for use with
+/// [`getsockopt`](sys/socket/fn.getsockopt.html) and
+/// [`setsockopt`](sys/socket/fn.setsockopt.html), for options that nix does
+/// not provide, such as vendor-specific ones.
+///
+/// This declares a unit struct named `$name` and implements
+/// [`GetSockOpt`](sys/socket/trait.GetSockOpt.html),
+/// [`SetSockOpt`](sys/socket/trait.SetSockOpt.html) or both for it.
+///
+/// # Arguments
+///
+/// * Attributes, such as doc comments, to apply to the generated type; optional.
+/// * `GetOnly`, `SetOnly` or `Both`: which of the traits to implement.
+/// * `$name:ident`: name of the type to declare.
+/// * `$level:expr`: protocol level, such as `libc::SOL_SOCKET`.
+/// * `$flag:expr`: option name, such as `libc::SO_KEEPALIVE`.
+/// * `$ty:ty`: type of the value.  `bool` is passed to the system as a
+///   `c_int`.  Any other type is passed as is, so it must match the layout
+///   the system uses for the option and implement
+///   [`PlainSockOptVal`](sys/socket/trait.PlainSockOptVal.html), which the
+///   integer types and some `libc` structs do.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate nix;
+/// # fn main() {
+/// use nix::sys::socket::{getsockopt, setsockopt};
+/// use std::net::UdpSocket;
+///
+/// custom_sockopt!(
+///     /// Size of the receive buffer
+///     Both, MyRcvBuf, libc::SOL_SOCKET, libc::SO_RCVBUF, libc::c_int);
+/// custom_sockopt!(GetOnly, MyBroadcast, libc::SOL_SOCKET, libc::SO_BROADCAST,
+///                 bool);
+///
+/// let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+/// setsockopt(&sock, MyRcvBuf, &8192).unwrap();
+/// assert!(getsockopt(&sock, MyRcvBuf).unwrap() >= 8192);
+/// assert!(!getsockopt(&sock, MyBroadcast).unwrap());
+/// # }
+/// ```
+///
+/// A type that not every bit pattern is a valid value of is an error:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate nix;
+/// # fn main() {
+/// custom_sockopt!(GetOnly, MyType, libc::SOL_SOCKET, libc::SO_TYPE, char);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! custom_sockopt {
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:expr, $flag:expr, bool) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@get $name, $level, $flag, bool);
+    };
+
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:expr, $flag:expr, bool) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@set $name, $level, $flag, bool);
+    };
+
+    ($(#[$attr:meta])* Both, $name:ident, $level:expr, $flag:expr, bool) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@get $name, $level, $flag, bool);
+        custom_sockopt!(@set $name, $level, $flag, bool);
+    };
+
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:expr, $flag:expr, $ty:ty) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@get $name, $level, $flag, $ty);
+    };
+
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:expr, $flag:expr, $ty:ty) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@set $name, $level, $flag, $ty);
+    };
+
+    ($(#[$attr:meta])* Both, $name:ident, $level:expr, $flag:expr, $ty:ty) => {
+        custom_sockopt!(@struct $(#[$attr])* $name);
+        custom_sockopt!(@get $name, $level, $flag, $ty);
+        custom_sockopt!(@set $name, $level, $flag, $ty);
+    };
+
+    (@struct $(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct $name;
+    };
+
+    (@get $name:ident, $level:expr, $flag:expr, bool) => {
+        impl $crate::sys::socket::GetSockOpt for $name {
+            type Val = bool;
+
+            fn get(&self, fd: ::std::os::unix::io::RawFd) -> $crate::Result<bool> {
+                let mut buf = [0u8; ::std::mem::size_of::<$crate::libc::c_int>()];
+                $crate::sys::socket::getsockopt_raw(&fd, $level, $flag, &mut buf)?;
+                Ok($crate::libc::c_int::from_ne_bytes(buf) != 0)
+            }
+        }
+    };
+
+    (@get $name:ident, $level:expr, $flag:expr, $ty:ty) => {
+        impl $crate::sys::socket::GetSockOpt for $name
+            where $ty: $crate::sys::socket::PlainSockOptVal
+        {
+            type Val = $ty;
+
+            fn get(&self, fd: ::std::os::unix::io::RawFd) -> $crate::Result<$ty> {
+                let mut val = ::std::mem::MaybeUninit::<$ty>::uninit();
+                let mut len = ::std::mem::size_of::<$ty>() as $crate::libc::socklen_t;
+                let res = unsafe {
+                    $crate::libc::getsockopt(
+                        fd, $level, $flag,
+                        val.as_mut_ptr() as *mut $crate::libc::c_void, &mut len)
+                };
+                $crate::errno::Errno::result(res)?;
+                assert_eq!(len as usize, ::std::mem::size_of::<$ty>(),
+                           "invalid getsockopt implementation");
+                // Any value the system returns is a valid PlainSockOptVal
+                Ok(unsafe { val.assume_init() })
+            }
+        }
+    };
+
+    (@set $name:ident, $level:expr, $flag:expr, bool) => {
+        impl $crate::sys::socket::SetSockOpt for $name {
+            type Val = bool;
+
+            fn set(&self, fd: ::std::os::unix::io::RawFd, val: &bool)
+                -> $crate::Result<()>
+            {
+                let val = *val as $crate::libc::c_int;
+                $crate::sys::socket::setsockopt_raw(&fd, $level, $flag,
+                                                    &val.to_ne_bytes())
+            }
+        }
+    };
+
+    (@set $name:ident, $level:expr, $flag:expr, $ty:ty) => {
+        impl $crate::sys::socket::SetSockOpt for $name
+            where $ty: $crate::sys::socket::PlainSockOptVal
+        {
+            type Val = $ty;
+
+            fn set(&self, fd: ::std::os::unix::io::RawFd, val: &$ty)
+                -> $crate::Result<()>
+            {
+                let res = unsafe {
+                    $crate::libc::setsockopt(
+                        fd, $level, $flag,
+                        val as *const $ty as *const $crate::libc::c_void,
+                        ::std::mem::size_of::<$ty>() as $crate::libc::socklen_t)
+                };
+                $crate::errno::Errno::result(res).map(drop)
+            }
+        }
     };
 }
 
@@ -216,6 +419,25 @@ macro_rules! sockopt_impl {
 sockopt_impl!(Both, ReuseAddr, libc::SOL_SOCKET, libc::SO_REUSEADDR, bool);
 sockopt_impl!(Both, ReusePort, libc::SOL_SOCKET, libc::SO_REUSEPORT, bool);
 sockopt_impl!(Both, TcpNoDelay, libc::IPPROTO_TCP, libc::TCP_NODELAY, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Send ACKs immediately instead of delaying them.  The kernel may
+    /// leave quick ACK mode on its own, so this is not permanent and needs to
+    /// be set again after each receive to keep ACKs from being delayed.
+    Both, TcpQuickAck, libc::IPPROTO_TCP, libc::TCP_QUICKACK, bool);
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+sockopt_impl!(
+    /// Maximum segment size for outgoing TCP packets.  Reading it on a
+    /// connected socket returns the MSS in use; setting it before connecting
+    /// clamps the MSS announced to the peer.
+    Both, TcpMaxSeg, libc::IPPROTO_TCP, libc::TCP_MAXSEG, u32);
 sockopt_impl!(Both, Linger, libc::SOL_SOCKET, libc::SO_LINGER, libc::linger);
 sockopt_impl!(SetOnly, IpAddMembership, libc::IPPROTO_IP, libc::IP_ADD_MEMBERSHIP, super::IpMembershipRequest);
 sockopt_impl!(SetOnly, IpDropMembership, libc::IPPROTO_IP, libc::IP_DROP_MEMBERSHIP, super::IpMembershipRequest);
@@ -233,16 +455,87 @@ cfg_if! {
         sockopt_impl!(SetOnly, Ipv6DropMembership, libc::IPPROTO_IPV6, libc::IPV6_LEAVE_GROUP, super::Ipv6MembershipRequest);
     }
 }
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(SetOnly, IpAddSourceMembership, libc::IPPROTO_IP, libc::IP_ADD_SOURCE_MEMBERSHIP, super::IpSourceMembershipRequest);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(SetOnly, IpDropSourceMembership, libc::IPPROTO_IP, libc::IP_DROP_SOURCE_MEMBERSHIP, super::IpSourceMembershipRequest);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Join a source-specific multicast group on an IPv4 socket.
+    SetOnly, McastJoinSourceGroup, libc::IPPROTO_IP, libc::MCAST_JOIN_SOURCE_GROUP, super::GroupSourceRequest);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Leave a source-specific multicast group on an IPv4 socket.
+    SetOnly, McastLeaveSourceGroup, libc::IPPROTO_IP, libc::MCAST_LEAVE_SOURCE_GROUP, super::GroupSourceRequest);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Join a source-specific multicast group on an IPv6 socket.
+    SetOnly, Ipv6McastJoinSourceGroup, libc::IPPROTO_IPV6, libc::MCAST_JOIN_SOURCE_GROUP, super::GroupSourceRequest);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Leave a source-specific multicast group on an IPv6 socket.
+    SetOnly, Ipv6McastLeaveSourceGroup, libc::IPPROTO_IPV6, libc::MCAST_LEAVE_SOURCE_GROUP, super::GroupSourceRequest);
 sockopt_impl!(Both, IpMulticastTtl, libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, u8);
+sockopt_impl!(
+    /// Time To Live of outgoing unicast IPv4 packets.
+    Both, IpTtl, libc::IPPROTO_IP, libc::IP_TTL, libc::c_int);
+sockopt_impl!(
+    /// Hop limit of outgoing unicast IPv6 packets, or -1 for the route
+    /// default.
+    Both, Ipv6UnicastHops, libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS, libc::c_int);
 sockopt_impl!(Both, IpMulticastLoop, libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP, bool);
+sockopt_impl!(
+    /// Local address of the interface used for outgoing IPv4 multicast
+    /// packets.  `Ipv4Addr::any()` lets the system choose.
+    Both, IpMulticastIf, libc::IPPROTO_IP, libc::IP_MULTICAST_IF, super::Ipv4Addr);
+sockopt_impl!(
+    /// Hop limit for outgoing IPv6 multicast packets.  `-1` selects the
+    /// system default.
+    Both, Ipv6MulticastHops, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_HOPS, i32);
+sockopt_impl!(
+    /// Index of the interface used for outgoing IPv6 multicast packets, or 0
+    /// to let the system choose.
+    Both, Ipv6MulticastIf, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_IF, u32);
+sockopt_impl!(
+    /// Whether outgoing IPv6 multicast packets are looped back to local
+    /// sockets.
+    Both, Ipv6MulticastLoop, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP, bool);
 sockopt_impl!(Both, ReceiveTimeout, libc::SOL_SOCKET, libc::SO_RCVTIMEO, TimeVal);
 sockopt_impl!(Both, SendTimeout, libc::SOL_SOCKET, libc::SO_SNDTIMEO, TimeVal);
 sockopt_impl!(Both, Broadcast, libc::SOL_SOCKET, libc::SO_BROADCAST, bool);
 sockopt_impl!(Both, OobInline, libc::SOL_SOCKET, libc::SO_OOBINLINE, bool);
 sockopt_impl!(GetOnly, SocketError, libc::SOL_SOCKET, libc::SO_ERROR, i32);
 sockopt_impl!(Both, KeepAlive, libc::SOL_SOCKET, libc::SO_KEEPALIVE, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(
+    /// Time in microseconds to busy poll the device queue when receiving
+    /// with nothing to read.  Raising it requires `CAP_NET_ADMIN`.
+    Both, BusyPoll, libc::SOL_SOCKET, libc::SO_BUSY_POLL, u32);
+#[cfg(target_os = "linux")]
+sockopt_impl!(
+    /// Kernel-assigned identifier of the socket, unique for the lifetime of
+    /// the system.  It is the same cookie that eBPF programs and `sock_diag`
+    /// report.
+    GetOnly, SocketCookie, libc::SOL_SOCKET, libc::SO_COOKIE, u64);
+#[cfg(target_os = "linux")]
+sockopt_impl!(
+    /// CPU on which the socket's packets are processed, or -1 if unknown.
+    /// Setting it steers `SO_REUSEPORT` groups.
+    Both, IncomingCpu, libc::SOL_SOCKET, libc::SO_INCOMING_CPU, i32);
 #[cfg(any(target_os = "android", target_os = "linux"))]
 sockopt_impl!(GetOnly, PeerCredentials, libc::SOL_SOCKET, libc::SO_PEERCRED, super::UnixCredentials);
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos"))]
+sockopt_impl!(
+    /// Credentials of the peer of a connected UNIX stream socket, the BSD
+    /// counterpart of `SO_PEERCRED`.
+    GetOnly, LocalPeerCred, libc::SOL_LOCAL, libc::LOCAL_PEERCRED, super::XuCred);
+#[cfg(any(target_os = "ios",
+          target_os = "macos"))]
+sockopt_impl!(
+    /// Process ID of the peer of a connected UNIX socket.
+    GetOnly, LocalPeerPid, libc::SOL_LOCAL, libc::LOCAL_PEERPID, libc::pid_t);
 #[cfg(any(target_os = "ios",
           target_os = "macos"))]
 sockopt_impl!(Both, TcpKeepAlive, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, u32);
@@ -252,31 +545,127 @@ sockopt_impl!(Both, TcpKeepAlive, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, u32);
           target_os = "linux",
           target_os = "nacl"))]
 sockopt_impl!(Both, TcpKeepIdle, libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, u32);
+#[cfg(target_os = "linux")]
+sockopt_impl!(
+    /// Number of seconds a listening socket waits for data from a new
+    /// connection before completing `accept`.  The kernel rounds it up to a
+    /// whole number of SYN-ACK retransmissions.  0 disables it.
+    Both, TcpDeferAccept, libc::IPPROTO_TCP, libc::TCP_DEFER_ACCEPT, u32);
 sockopt_impl!(Both, RcvBuf, libc::SOL_SOCKET, libc::SO_RCVBUF, usize);
 sockopt_impl!(Both, SndBuf, libc::SOL_SOCKET, libc::SO_SNDBUF, usize);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(SetOnly, RcvBufForce, libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, usize);
+sockopt_impl!(
+    /// Like [`RcvBuf`](struct.RcvBuf.html), but ignoring the
+    /// `net.core.rmem_max` limit.  Requires `CAP_NET_ADMIN`.
+    SetOnly, RcvBufForce, libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, usize);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(SetOnly, SndBufForce, libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, usize);
-sockopt_impl!(GetOnly, SockType, libc::SOL_SOCKET, libc::SO_TYPE, super::SockType);
+sockopt_impl!(
+    /// Like [`SndBuf`](struct.SndBuf.html), but ignoring the
+    /// `net.core.wmem_max` limit.  Requires `CAP_NET_ADMIN`.
+    SetOnly, SndBufForce, libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, usize);
 sockopt_impl!(GetOnly, AcceptConn, libc::SOL_SOCKET, libc::SO_ACCEPTCONN, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
 sockopt_impl!(Both, BindToDevice, libc::SOL_SOCKET, libc::SO_BINDTODEVICE, OsString<[u8; libc::IFNAMSIZ]>);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(GetOnly, OriginalDst, libc::SOL_IP, libc::SO_ORIGINAL_DST, libc::sockaddr_in);
+sockopt_impl!(
+    /// The original destination address of a connection that was redirected
+    /// by netfilter NAT, e.g. by an iptables `REDIRECT` rule.  Wrap it in
+    /// [`InetAddr::V4`](../enum.InetAddr.html#variant.V4) for a typed address.
+    GetOnly, OriginalDst, libc::SOL_IP, libc::SO_ORIGINAL_DST, libc::sockaddr_in);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// The IPv6 equivalent of [`OriginalDst`](struct.OriginalDst.html).  Wrap
+    /// it in [`InetAddr::V6`](../enum.InetAddr.html#variant.V6) for a typed
+    /// address.
+    GetOnly, Ip6tOriginalDst, libc::SOL_IPV6, IP6T_SO_ORIGINAL_DST, libc::sockaddr_in6);
 sockopt_impl!(Both, ReceiveTimestamp, libc::SOL_SOCKET, libc::SO_TIMESTAMP, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, ReceiveTimestampns, libc::SOL_SOCKET, libc::SO_TIMESTAMPNS, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, Timestamping, libc::SOL_SOCKET, libc::SO_TIMESTAMPING, super::TimestampingFlag);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Allow binding to, and accepting connections for, non-local addresses,
+    /// as needed by transparent proxies.  Setting this option requires the
+    /// `CAP_NET_ADMIN` or `CAP_NET_RAW` capability.
+    Both, IpTransparent, libc::SOL_IP, libc::IP_TRANSPARENT, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(Both, IpTransparent, libc::SOL_IP, libc::IP_TRANSPARENT, bool);
+sockopt_impl!(
+    /// Allow binding to an IP address that is nonlocal or does not (yet)
+    /// exist.  Unlike `IpTransparent`, no special privileges are required.
+    Both, IpFreebind, libc::SOL_IP, libc::IP_FREEBIND, bool);
 #[cfg(target_os = "openbsd")]
 sockopt_impl!(Both, BindAny, libc::SOL_SOCKET, libc::SO_BINDANY, bool);
 #[cfg(target_os = "freebsd")]
 sockopt_impl!(Both, BindAny, libc::IPPROTO_IP, libc::IP_BINDANY, bool);
 #[cfg(target_os = "linux")]
-sockopt_impl!(Both, Mark, libc::SOL_SOCKET, libc::SO_MARK, u32);
+sockopt_impl!(
+    /// Set the mark for each packet sent through this socket, for use in
+    /// mark-based routing and firewalling.  Setting this option requires the
+    /// `CAP_NET_ADMIN` capability.
+    Both, Mark, libc::SOL_SOCKET, libc::SO_MARK, u32);
+#[cfg(target_os = "freebsd")]
+sockopt_impl!(Both, SetFib, libc::SOL_SOCKET, libc::SO_SETFIB, libc::c_int);
+#[cfg(target_os = "freebsd")]
+sockopt_impl!(Both, UserCookie, libc::SOL_SOCKET, libc::SO_USER_COOKIE, u32);
+#[cfg(target_os = "openbsd")]
+sockopt_impl!(Both, Rtable, libc::SOL_SOCKET, libc::SO_RTABLE, libc::c_int);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Enable receiving the credentials of the sender of each message on a
+    /// UNIX socket, as a `ControlMessageOwned::ScmCredentials` control
+    /// message.  The kernel fills in the sender's credentials if it did not
+    /// send any itself.
+    Both, PassCred, libc::SOL_SOCKET, libc::SO_PASSCRED, bool);
+#[cfg(target_os = "freebsd")]
+sockopt_impl!(
+    /// Enable receiving the credentials of the sender of each message on a
+    /// UNIX socket, as a `ControlMessageOwned::ScmCreds` control message,
+    /// without the sender's cooperation.  This is FreeBSD's counterpart of
+    /// Linux's `SO_PASSCRED`.
+    Both, LocalCredsPersistent, libc::SOL_LOCAL, libc::LOCAL_CREDS_PERSISTENT, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(Both, PassCred, libc::SOL_SOCKET, libc::SO_PASSCRED, bool);
-#[cfg(any(target_os = "freebsd", target_os = "linux"))] 
+sockopt_impl!(
+    /// Offset in the receive queue at which `MsgFlags::MSG_PEEK` starts
+    /// reading, or -1 to always peek from the start.  Once enabled, each peek
+    /// advances the offset by the number of bytes read, and normal reads
+    /// move it back.
+    Both, PeekOff, libc::SOL_SOCKET, libc::SO_PEEK_OFF, libc::c_int);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Receive the security label of the sender as an
+    /// `ControlMessageOwned::ScmSecurity` on UNIX sockets.
+    Both, PassSec, libc::SOL_SOCKET, libc::SO_PASSSEC, bool);
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
 sockopt_impl!(Both, TcpCongestion, libc::IPPROTO_TCP, libc::TCP_CONGESTION, OsString<[u8; TCP_CA_NAME_MAX]>);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Set or remove the RFC 2385 MD5 signature key used with a peer.  This
+    /// is mostly used by BGP.
+    SetOnly, TcpMd5Sig, libc::IPPROTO_TCP, libc::TCP_MD5SIG, super::TcpMd5Sig);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Put a TCP socket in repair mode, used to checkpoint and restore
+    /// established connections.  In this mode, the socket's state can be set
+    /// directly, and `connect`, `send` and `recv` no longer go to the network.
+    /// Requires `CAP_NET_ADMIN`.
+    Both, TcpRepair, libc::IPPROTO_TCP, libc::TCP_REPAIR, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Sequence number of the queue selected with
+    /// [`TcpRepairQueue`](struct.TcpRepairQueue.html), in repair mode.  It
+    /// can only be set before the connection is restored.
+    Both, TcpQueueSeq, libc::IPPROTO_TCP, libc::TCP_QUEUE_SEQ, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Restore the options negotiated by a connection, in repair mode.
+    SetOnly, TcpRepairOptions, libc::IPPROTO_TCP, libc::TCP_REPAIR_OPTIONS,
+    Vec<super::TcpRepairOpt>, SetRepairOptions);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Upper layer protocol attached to a TCP socket, such as `"tls"` for
+    /// kernel TLS.  Empty if there is none.
+    Both, TcpUlp, libc::IPPROTO_TCP, libc::TCP_ULP, OsString<[u8; TCP_ULP_NAME_MAX]>);
 #[cfg(any(
     target_os = "android",
     target_os = "ios",
@@ -295,6 +684,67 @@ sockopt_impl!(Both, Ipv4PacketInfo, libc::IPPROTO_IP, libc::IP_PKTINFO, bool);
     target_os = "openbsd",
 ))]
 sockopt_impl!(Both, Ipv6RecvPacketInfo, libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, Ipv4RecvErr, libc::IPPROTO_IP, libc::IP_RECVERR, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, Ipv6RecvErr, libc::IPPROTO_IPV6, libc::IPV6_RECVERR, bool);
+#[cfg(any(target_os = "android",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+sockopt_impl!(
+    /// Type of Service (DSCP and ECN bits) of outgoing IPv4 packets.
+    Both, Ipv4Tos, libc::IPPROTO_IP, libc::IP_TOS, libc::c_int);
+#[cfg(any(target_os = "android",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+sockopt_impl!(
+    /// Traffic class (DSCP and ECN bits) of outgoing IPv6 packets, or -1
+    /// for the kernel default.
+    Both, Ipv6TClass, libc::IPPROTO_IPV6, libc::IPV6_TCLASS, libc::c_int);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Pass the Type of Service of received IPv4 packets as a
+    /// `ControlMessageOwned::Ipv4Tos` control message.
+    Both, Ipv4RecvTos, libc::IPPROTO_IP, libc::IP_RECVTOS, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Pass the Time To Live of received IPv4 packets as a
+    /// `ControlMessageOwned::IpTtl` control message.
+    Both, Ipv4RecvTtl, libc::IPPROTO_IP, libc::IP_RECVTTL, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Pass the traffic class of received IPv6 packets as a
+    /// `ControlMessageOwned::Ipv6TClass` control message.
+    Both, Ipv6RecvTClass, libc::IPPROTO_IPV6, libc::IPV6_RECVTCLASS, bool);
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+sockopt_impl!(
+    /// Pass the hop limit of received IPv6 packets as a
+    /// `ControlMessageOwned::Ipv6HopLimit` control message.
+    Both, Ipv6RecvHopLimit, libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Pass the flow information of received IPv6 packets as a
+    /// `ControlMessageOwned::Ipv6FlowInfo` control message.
+    Both, Ipv6FlowInfo, libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Reserve, renew or release a flow label.  Only reserved labels may be
+    /// sent.
+    SetOnly, Ipv6FlowLabelMgr, libc::IPPROTO_IPV6, libc::IPV6_FLOWLABEL_MGR, super::FlowLabelReq);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Send the flow information from the `sin6_flowinfo` field of the
+    /// destination address instead of the one chosen by the kernel.
+    Both, Ipv6FlowInfoSend, libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO_SEND, bool);
 #[cfg(any(
     target_os = "freebsd",
     target_os = "ios",
@@ -311,10 +761,33 @@ sockopt_impl!(Both, Ipv4RecvIf, libc::IPPROTO_IP, libc::IP_RECVIF, bool);
     target_os = "openbsd",
 ))]
 sockopt_impl!(Both, Ipv4RecvDstAddr, libc::IPPROTO_IP, libc::IP_RECVDSTADDR, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(SetOnly, NetlinkAddMembership, libc::SOL_NETLINK, libc::NETLINK_ADD_MEMBERSHIP, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(SetOnly, NetlinkDropMembership, libc::SOL_NETLINK, libc::NETLINK_DROP_MEMBERSHIP, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, NetlinkPacketInfo, libc::SOL_NETLINK, libc::NETLINK_PKTINFO, bool);
 #[cfg(target_os = "linux")]
 sockopt_impl!(Both, UdpGsoSegment, libc::SOL_UDP, libc::UDP_SEGMENT, libc::c_int);
 #[cfg(target_os = "linux")]
-sockopt_impl!(Both, UdpGroSegment, libc::IPPROTO_UDP, libc::UDP_GRO, bool);
+sockopt_impl!(Both, UdpGroSegment, libc::SOL_UDP, libc::UDP_GRO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Disable the Nagle algorithm on an SCTP socket.
+    Both, SctpNodelay, libc::IPPROTO_SCTP, SCTP_NODELAY, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Default parameters for new SCTP associations.
+    Both, SctpInitMsg, libc::IPPROTO_SCTP, SCTP_INITMSG, super::SctpInitMsg);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Prefer the given CCID (congestion control algorithm) for both
+    /// directions of a DCCP socket.  Must be set before connecting.
+    SetOnly, DccpCcid, libc::SOL_DCCP, libc::DCCP_SOCKOPT_CCID, u8);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// The current maximum packet size of a DCCP socket.
+    GetOnly, DccpCurMps, libc::SOL_DCCP, libc::DCCP_SOCKOPT_GET_CUR_MPS, libc::c_int);
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
 #[derive(Copy, Clone, Debug)]
@@ -365,12 +838,597 @@ impl<T> SetSockOpt for AlgSetKey<T> where T: AsRef<[u8]> + Clone {
     }
 }
 
+/// Enable kernel TLS encryption of data sent on the socket.  The `TcpUlp`
+/// socket option must first be set to `"tls"`.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TlsTx;
+
+#[cfg(target_os = "linux")]
+impl SetSockOpt for TlsTx {
+    type Val = super::TlsCryptoInfo;
+
+    fn set(&self, fd: RawFd, val: &super::TlsCryptoInfo) -> Result<()> {
+        set_tls_crypto_info(fd, libc::TLS_TX, val)
+    }
+}
+
+/// Enable kernel TLS decryption of data received on the socket.  The `TcpUlp`
+/// socket option must first be set to `"tls"`.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TlsRx;
+
+#[cfg(target_os = "linux")]
+impl SetSockOpt for TlsRx {
+    type Val = super::TlsCryptoInfo;
+
+    fn set(&self, fd: RawFd, val: &super::TlsCryptoInfo) -> Result<()> {
+        set_tls_crypto_info(fd, libc::TLS_RX, val)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_tls_crypto_info(fd: RawFd, flag: c_int, val: &super::TlsCryptoInfo)
+    -> Result<()>
+{
+    let (ptr, len) = val.as_ffi_pair();
+    let res = unsafe {
+        libc::setsockopt(fd, libc::SOL_TLS, flag, ptr, len)
+    };
+    Errno::result(res).map(drop)
+}
+
+/// Select the interface used for outgoing IPv4 multicast packets by its
+/// index, or 0 to let the system choose.
+///
+/// Unlike [`IpMulticastIf`](struct.IpMulticastIf.html), this works for
+/// interfaces without an IPv4 address.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IpMulticastIfIndex;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl SetSockOpt for IpMulticastIfIndex {
+    type Val = u32;
+
+    fn set(&self, fd: RawFd, val: &u32) -> Result<()> {
+        let mreqn = libc::ip_mreqn {
+            imr_multiaddr: libc::in_addr { s_addr: libc::INADDR_ANY },
+            imr_address: libc::in_addr { s_addr: libc::INADDR_ANY },
+            imr_ifindex: *val as c_int,
+        };
+        unsafe {
+            let res = libc::setsockopt(fd,
+                                       libc::IPPROTO_IP,
+                                       libc::IP_MULTICAST_IF,
+                                       &mreqn as *const _ as *const c_void,
+                                       mem::size_of_val(&mreqn) as socklen_t);
+            Errno::result(res).map(drop)
+        }
+    }
+}
+
+/// Statistics of a TCP connection.  See [`TcpInfo`](../struct.TcpInfo.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TcpInfo;
+
+// Kernels copy as much of `tcp_info` as both sides know about, so the returned
+// length may be shorter than our structure.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for TcpInfo {
+    type Val = super::TcpInfo;
+
+    fn get(&self, fd: RawFd) -> Result<super::TcpInfo> {
+        let mut info = super::TcpInfo::default();
+        let mut len = mem::size_of::<super::TcpInfo>() as socklen_t;
+        unsafe {
+            let res = libc::getsockopt(fd,
+                                       libc::IPPROTO_TCP,
+                                       libc::TCP_INFO,
+                                       &mut info as *mut _ as *mut c_void,
+                                       &mut len);
+            Errno::result(res)?;
+        }
+        Ok(info)
+    }
+}
+
+/// State of a multipath TCP connection.  See
+/// [`MptcpInfo`](../struct.MptcpInfo.html).
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MptcpInfo;
+
+// As with TCP_INFO, the kernel may return less than our structure
+#[cfg(target_os = "linux")]
+impl GetSockOpt for MptcpInfo {
+    type Val = super::MptcpInfo;
+
+    fn get(&self, fd: RawFd) -> Result<super::MptcpInfo> {
+        let mut info = super::MptcpInfo::default();
+        let mut len = mem::size_of::<super::MptcpInfo>() as socklen_t;
+        unsafe {
+            let res = libc::getsockopt(fd,
+                                       SOL_MPTCP,
+                                       MPTCP_INFO,
+                                       &mut info as *mut _ as *mut c_void,
+                                       &mut len);
+            Errno::result(res)?;
+        }
+        Ok(info)
+    }
+}
+
+/// SCTP notifications and ancillary data to deliver.  See
+/// [`SctpEventSubscribe`](../struct.SctpEventSubscribe.html).
+///
+/// Kernels that predate some of the fields of `SctpEventSubscribe` reject it
+/// with `EINVAL`.  Setting the option then falls back to the shorter layout of
+/// such a kernel, as long as none of the events it lacks are enabled, and
+/// getting it leaves those events disabled.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SctpEvents;
+
+// Like TCP_INFO, the kernel returns only as much as it knows about
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for SctpEvents {
+    type Val = super::SctpEventSubscribe;
+
+    fn get(&self, fd: RawFd) -> Result<super::SctpEventSubscribe> {
+        let mut events = super::SctpEventSubscribe::default();
+        let mut len = mem::size_of::<super::SctpEventSubscribe>() as socklen_t;
+        unsafe {
+            let res = libc::getsockopt(fd,
+                                       libc::IPPROTO_SCTP,
+                                       SCTP_EVENTS,
+                                       &mut events as *mut _ as *mut c_void,
+                                       &mut len);
+            Errno::result(res)?;
+        }
+        Ok(events)
+    }
+}
+
+// Each event is a single byte, and a kernel accepts any length up to the size
+// of its own structure, so shorten the length one event at a time.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl SetSockOpt for SctpEvents {
+    type Val = super::SctpEventSubscribe;
+
+    fn set(&self, fd: RawFd, val: &super::SctpEventSubscribe) -> Result<()> {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(val as *const _ as *const u8,
+                                       mem::size_of::<super::SctpEventSubscribe>())
+        };
+        let min_len = bytes.iter().rposition(|&b| b != 0).map_or(1, |i| i + 1);
+        let mut len = bytes.len();
+        loop {
+            let res = unsafe {
+                libc::setsockopt(fd,
+                                 libc::IPPROTO_SCTP,
+                                 SCTP_EVENTS,
+                                 bytes.as_ptr() as *const c_void,
+                                 len as socklen_t)
+            };
+            match Errno::result(res) {
+                Err(crate::Error::Sys(Errno::EINVAL)) if len > min_len => len -= 1,
+                res => return res.map(drop),
+            }
+        }
+    }
+}
+
+/// Security label (such as an SELinux context) of the peer of a connected
+/// UNIX socket.  Fails with `ENOPROTOOPT` if no security module provides one.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PeerSec;
+
+// Labels have no fixed maximum length.  When the buffer is too small the kernel
+// fails with ERANGE and reports the length it needs, so retry once with that.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for PeerSec {
+    type Val = Vec<u8>;
+
+    fn get(&self, fd: RawFd) -> Result<Vec<u8>> {
+        let mut buf = vec![0u8; 256];
+        loop {
+            let mut len = buf.len() as socklen_t;
+            let res = unsafe {
+                libc::getsockopt(fd,
+                                 libc::SOL_SOCKET,
+                                 libc::SO_PEERSEC,
+                                 buf.as_mut_ptr() as *mut c_void,
+                                 &mut len)
+            };
+            match Errno::result(res) {
+                Err(crate::Error::Sys(Errno::ERANGE))
+                    if len as usize > buf.len() =>
+                {
+                    buf.resize(len as usize, 0);
+                }
+                Err(e) => return Err(e),
+                Ok(_) => {
+                    buf.truncate(len as usize);
+                    // Some security modules count the terminating null byte
+                    if buf.last() == Some(&0) {
+                        buf.pop();
+                    }
+                    return Ok(buf);
+                }
+            }
+        }
+    }
+}
+
+/// Receive timeout as a `Duration`, with `None` meaning that receive calls
+/// block indefinitely.  This is the same option as
+/// [`ReceiveTimeout`](struct.ReceiveTimeout.html).
+///
+/// Like `std::net::TcpStream::set_read_timeout`, setting a zero `Duration`
+/// fails with `EINVAL`, since the system would take it to mean no timeout.
+/// So does a `Duration` too long for `time_t`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ReceiveTimeoutDuration;
+
+impl GetSockOpt for ReceiveTimeoutDuration {
+    type Val = Option<Duration>;
+
+    fn get(&self, fd: RawFd) -> Result<Option<Duration>> {
+        get_timeout(fd, libc::SO_RCVTIMEO)
+    }
+}
+
+impl SetSockOpt for ReceiveTimeoutDuration {
+    type Val = Option<Duration>;
+
+    fn set(&self, fd: RawFd, val: &Option<Duration>) -> Result<()> {
+        set_timeout(fd, libc::SO_RCVTIMEO, *val)
+    }
+}
+
+/// Send timeout as a `Duration`, with `None` meaning that send calls block
+/// indefinitely.  This is the same option as
+/// [`SendTimeout`](struct.SendTimeout.html).
+///
+/// Setting a zero `Duration`, or one too long for `time_t`, fails with
+/// `EINVAL`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SendTimeoutDuration;
+
+impl GetSockOpt for SendTimeoutDuration {
+    type Val = Option<Duration>;
+
+    fn get(&self, fd: RawFd) -> Result<Option<Duration>> {
+        get_timeout(fd, libc::SO_SNDTIMEO)
+    }
+}
+
+impl SetSockOpt for SendTimeoutDuration {
+    type Val = Option<Duration>;
+
+    fn set(&self, fd: RawFd, val: &Option<Duration>) -> Result<()> {
+        set_timeout(fd, libc::SO_SNDTIMEO, *val)
+    }
+}
+
+fn get_timeout(fd: RawFd, flag: c_int) -> Result<Option<Duration>> {
+    let tv = unsafe { get_struct::<libc::timeval>(fd, libc::SOL_SOCKET, flag) }?;
+    if tv.tv_sec == 0 && tv.tv_usec == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000)))
+    }
+}
+
+fn set_timeout(fd: RawFd, flag: c_int, val: Option<Duration>) -> Result<()> {
+    let tv = match val {
+        None => libc::timeval { tv_sec: 0, tv_usec: 0 },
+        Some(d) if d.as_secs() == 0 && d.subsec_nanos() == 0 => {
+            return Err(crate::Error::Sys(Errno::EINVAL));
+        },
+        Some(d) => {
+            // Like std, round sub-microsecond timeouts up rather than
+            // letting them become no timeout
+            let usec = if d.as_secs() == 0 && d.subsec_micros() == 0 {
+                1
+            } else {
+                d.subsec_micros()
+            };
+            libc::timeval {
+                tv_sec: libc::time_t::try_from(d.as_secs())
+                    .map_err(|_| crate::Error::Sys(Errno::EINVAL))?,
+                tv_usec: usec as libc::suseconds_t,
+            }
+        }
+    };
+    set_struct(fd, libc::SOL_SOCKET, flag, &tv)
+}
+
+/// Queue of a TCP socket in repair mode that `send`, `recv` and
+/// [`TcpQueueSeq`](struct.TcpQueueSeq.html) act on.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TcpRepairQueueKind {
+    /// No queue is selected
+    None,
+    /// The receive queue
+    Recv,
+    /// The send queue
+    Send,
+}
+
+/// Selects the queue that a TCP socket in repair mode operates on.  See
+/// [`TcpRepairQueueKind`](enum.TcpRepairQueueKind.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TcpRepairQueue;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for TcpRepairQueue {
+    type Val = TcpRepairQueueKind;
+
+    fn get(&self, fd: RawFd) -> Result<TcpRepairQueueKind> {
+        let queue: c_int = unsafe {
+            get_struct(fd, libc::IPPROTO_TCP, libc::TCP_REPAIR_QUEUE)
+        }?;
+        match queue {
+            TCP_NO_QUEUE => Ok(TcpRepairQueueKind::None),
+            TCP_RECV_QUEUE => Ok(TcpRepairQueueKind::Recv),
+            TCP_SEND_QUEUE => Ok(TcpRepairQueueKind::Send),
+            _ => Err(crate::Error::Sys(Errno::EINVAL)),
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl SetSockOpt for TcpRepairQueue {
+    type Val = TcpRepairQueueKind;
+
+    fn set(&self, fd: RawFd, val: &TcpRepairQueueKind) -> Result<()> {
+        let queue = match *val {
+            TcpRepairQueueKind::None => TCP_NO_QUEUE,
+            TcpRepairQueueKind::Recv => TCP_RECV_QUEUE,
+            TcpRepairQueueKind::Send => TCP_SEND_QUEUE,
+        };
+        set_struct(fd, libc::IPPROTO_TCP, libc::TCP_REPAIR_QUEUE, &queue)
+    }
+}
+
+/// The service code of a DCCP socket, in host byte order.  A server
+/// must set it before listening, and a client before connecting.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DccpService;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for DccpService {
+    type Val = u32;
+
+    fn get(&self, fd: RawFd) -> Result<u32> {
+        let service: u32 = unsafe {
+            get_struct(fd, libc::SOL_DCCP, libc::DCCP_SOCKOPT_SERVICE)
+        }?;
+        Ok(u32::from_be(service))
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl SetSockOpt for DccpService {
+    type Val = u32;
+
+    fn set(&self, fd: RawFd, val: &u32) -> Result<()> {
+        set_struct(fd, libc::SOL_DCCP, libc::DCCP_SOCKOPT_SERVICE, &val.to_be())
+    }
+}
+
+/// Get the CCIDs (congestion control algorithms) that the kernel supports
+/// for DCCP.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DccpAvailableCcids;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for DccpAvailableCcids {
+    type Val = Vec<u8>;
+
+    fn get(&self, fd: RawFd) -> Result<Vec<u8>> {
+        // The kernel knows far fewer CCIDs than this
+        let mut buf = [0u8; 32];
+        let len = super::getsockopt_raw(&fd, libc::SOL_DCCP,
+                                        libc::DCCP_SOCKOPT_AVAILABLE_CCIDS, &mut buf)?;
+        Ok(buf[..len].to_vec())
+    }
+}
+
+macro_rules! dccp_ccid_sockopt {
+    ($(#[$attr:meta])* $name:ident, $flag:expr) => {
+        $(#[$attr])*
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct $name;
+
+        // The kernel reports the CCID as an int, but takes a list of u8
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        impl GetSockOpt for $name {
+            type Val = u8;
+
+            fn get(&self, fd: RawFd) -> Result<u8> {
+                let ccid: c_int = unsafe {
+                    get_struct(fd, libc::SOL_DCCP, $flag)
+                }?;
+                Ok(ccid as u8)
+            }
+        }
+
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        impl SetSockOpt for $name {
+            type Val = u8;
+
+            fn set(&self, fd: RawFd, val: &u8) -> Result<()> {
+                super::setsockopt_raw(&fd, libc::SOL_DCCP, $flag, &[*val])
+            }
+        }
+    }
+}
+
+dccp_ccid_sockopt!(
+    /// The CCID (congestion control algorithm) used for sending on a DCCP
+    /// socket.
+    DccpTxCcid, libc::DCCP_SOCKOPT_TX_CCID);
+dccp_ccid_sockopt!(
+    /// The CCID (congestion control algorithm) used for receiving on a DCCP
+    /// socket.
+    DccpRxCcid, libc::DCCP_SOCKOPT_RX_CCID);
+
+/// Transport underlying an RDS socket, as used by
+/// [`RdsTransport`](struct.RdsTransport.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RdsTransportKind {
+    /// No transport is bound yet.  Can't be set.
+    None,
+    /// InfiniBand
+    Ib,
+    /// iWARP, RDMA over TCP
+    Iwarp,
+    /// TCP
+    Tcp,
+}
+
+/// The transport of an RDS socket.  It must be set before the socket is
+/// bound, and can be set only once; otherwise binding picks the first
+/// transport able to reach the bound address.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RdsTransport;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for RdsTransport {
+    type Val = RdsTransportKind;
+
+    fn get(&self, fd: RawFd) -> Result<RdsTransportKind> {
+        let transport: c_int = unsafe {
+            get_struct(fd, super::SOL_RDS, SO_RDS_TRANSPORT)
+        }?;
+        match transport {
+            RDS_TRANS_NONE => Ok(RdsTransportKind::None),
+            RDS_TRANS_IB => Ok(RdsTransportKind::Ib),
+            RDS_TRANS_IWARP => Ok(RdsTransportKind::Iwarp),
+            RDS_TRANS_TCP => Ok(RdsTransportKind::Tcp),
+            _ => Err(crate::Error::Sys(Errno::EINVAL)),
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl SetSockOpt for RdsTransport {
+    type Val = RdsTransportKind;
+
+    fn set(&self, fd: RawFd, val: &RdsTransportKind) -> Result<()> {
+        let transport = match *val {
+            RdsTransportKind::None => RDS_TRANS_NONE,
+            RdsTransportKind::Ib => RDS_TRANS_IB,
+            RdsTransportKind::Iwarp => RDS_TRANS_IWARP,
+            RdsTransportKind::Tcp => RDS_TRANS_TCP,
+        };
+        set_struct(fd, super::SOL_RDS, SO_RDS_TRANSPORT, &transport)
+    }
+}
+
+/// Type of the socket, as given to [`socket`](../fn.socket.html).  Fails
+/// with `EINVAL` for types that `SockType` doesn't know.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SockType;
+
+impl GetSockOpt for SockType {
+    type Val = super::SockType;
+
+    fn get(&self, fd: RawFd) -> Result<super::SockType> {
+        let ty: c_int = unsafe {
+            get_struct(fd, libc::SOL_SOCKET, libc::SO_TYPE)
+        }?;
+        match ty {
+            libc::SOCK_STREAM => Ok(super::SockType::Stream),
+            libc::SOCK_DGRAM => Ok(super::SockType::Datagram),
+            libc::SOCK_SEQPACKET => Ok(super::SockType::SeqPacket),
+            libc::SOCK_RAW => Ok(super::SockType::Raw),
+            libc::SOCK_RDM => Ok(super::SockType::Rdm),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            libc::SOCK_DCCP => Ok(super::SockType::Dccp),
+            _ => Err(crate::Error::Sys(Errno::EINVAL)),
+        }
+    }
+}
+
+/// Address family of the socket, as given to [`socket`](../fn.socket.html).
+/// Fails with `EAFNOSUPPORT` for families that `AddressFamily` doesn't know.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SockDomain;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for SockDomain {
+    type Val = super::AddressFamily;
+
+    fn get(&self, fd: RawFd) -> Result<super::AddressFamily> {
+        let family: c_int = unsafe {
+            get_struct(fd, libc::SOL_SOCKET, libc::SO_DOMAIN)
+        }?;
+        super::AddressFamily::from_i32(family)
+            .ok_or(crate::Error::Sys(Errno::EAFNOSUPPORT))
+    }
+}
+
+/// Protocol number of the socket, as given to or chosen by
+/// [`socket`](../fn.socket.html).  It is only meaningful within the address
+/// family of the socket, so compare it with a protocol of that family, e.g.
+/// `RawSockProtocol::from(SockProtocol::Tcp)`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SockProtocol;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for SockProtocol {
+    type Val = super::RawSockProtocol;
+
+    fn get(&self, fd: RawFd) -> Result<super::RawSockProtocol> {
+        let protocol: c_int = unsafe {
+            get_struct(fd, libc::SOL_SOCKET, libc::SO_PROTOCOL)
+        }?;
+        Ok(super::RawSockProtocol(protocol))
+    }
+}
+
 /*
  *
  * ===== Accessor helpers =====
  *
  */
 
+// The caller must ensure that any value the system returns is a valid `T`
+unsafe fn get_struct<T>(fd: RawFd, level: c_int, flag: c_int) -> Result<T> {
+    let mut getter: GetStruct<T> = Get::uninit();
+    let res = libc::getsockopt(fd, level, flag, getter.ffi_ptr(),
+                               getter.ffi_len());
+    Errno::result(res)?;
+    Ok(getter.assume_init())
+}
+
+fn set_struct<T: 'static>(fd: RawFd, level: c_int, flag: c_int, val: &T)
+    -> Result<()>
+{
+    unsafe {
+        let setter: SetStruct<T> = Set::new(val);
+        let res = libc::setsockopt(fd, level, flag, setter.ffi_ptr(),
+                                   setter.ffi_len());
+        Errno::result(res).map(drop)
+    }
+}
+
 /// Helper trait that describes what is expected from a `GetSockOpt` getter.
 unsafe trait Get<T> {
     /// Returns an uninitialized value.
@@ -444,6 +1502,27 @@ unsafe impl<'a, T> Set<'a, T> for SetStruct<'a, T> {
     }
 }
 
+/// Setter for an array of `tcp_repair_opt`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+struct SetRepairOptions<'a> {
+    val: &'a [super::TcpRepairOpt],
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+unsafe impl<'a> Set<'a, Vec<super::TcpRepairOpt>> for SetRepairOptions<'a> {
+    fn new(val: &'a Vec<super::TcpRepairOpt>) -> SetRepairOptions<'a> {
+        SetRepairOptions { val }
+    }
+
+    fn ffi_ptr(&self) -> *const c_void {
+        self.val.as_ptr() as *const c_void
+    }
+
+    fn ffi_len(&self) -> socklen_t {
+        mem::size_of_val(self.val) as socklen_t
+    }
+}
+
 /// Getter for a boolean value.
 struct GetBool {
     len: socklen_t,
@@ -534,7 +1613,7 @@ unsafe impl<'a> Set<'a, u8> for SetU8 {
     }
 
     fn ffi_len(&self) -> socklen_t {
-        mem::size_of::<c_int>() as socklen_t
+        mem::size_of::<u8>() as socklen_t
     }
 }
 
@@ -610,7 +1689,11 @@ unsafe impl<T: AsMut<[u8]>> Get<OsString> for GetOsString<T> {
     unsafe fn assume_init(self) -> OsString {
         let len = self.len as usize;
         let mut v = self.val.assume_init();
-        OsStr::from_bytes(&v.as_mut()[0..len]).to_owned()
+        let v = &v.as_mut()[0..len];
+        // Depending on the option, the kernel may include the terminating
+        // null byte (and even trailing garbage) in the returned length.
+        let end = v.iter().position(|&b| b == 0).unwrap_or(len);
+        OsStr::from_bytes(&v[0..end]).to_owned()
     }
 }
 
@@ -642,10 +1725,12 @@ mod test {
         use super::super::*;
 
         let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty()).unwrap();
-        let a_cred = getsockopt(a, super::PeerCredentials).unwrap();
-        let b_cred = getsockopt(b, super::PeerCredentials).unwrap();
+        let a_cred = getsockopt(&a, super::PeerCredentials).unwrap();
+        let b_cred = getsockopt(&b, super::PeerCredentials).unwrap();
         assert_eq!(a_cred, b_cred);
         assert!(a_cred.pid() != 0);
+        // Both ends were created by this process
+        assert_eq!(a_cred, UnixCredentials::new());
     }
 
     #[test]
@@ -654,7 +1739,7 @@ mod test {
         use crate::unistd::close;
 
         let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty()).unwrap();
-        let a_type = getsockopt(a, super::SockType).unwrap();
+        let a_type = getsockopt(&a, super::SockType).unwrap();
         assert_eq!(a_type, SockType::Stream);
         close(a).unwrap();
         close(b).unwrap();
@@ -666,7 +1751,7 @@ mod test {
         use crate::unistd::close;
 
         let s = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
-        let s_type = getsockopt(s, super::SockType).unwrap();
+        let s_type = getsockopt(&s, super::SockType).unwrap();
         assert_eq!(s_type, SockType::Datagram);
         close(s).unwrap();
     }
@@ -680,10 +1765,10 @@ mod test {
         use crate::unistd::close;
 
         let s = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None).unwrap();
-        let s_listening = getsockopt(s, super::AcceptConn).unwrap();
+        let s_listening = getsockopt(&s, super::AcceptConn).unwrap();
         assert!(!s_listening);
-        listen(s, 10).unwrap();
-        let s_listening2 = getsockopt(s, super::AcceptConn).unwrap();
+        listen(&s, 10).unwrap();
+        let s_listening2 = getsockopt(&s, super::AcceptConn).unwrap();
         assert!(s_listening2);
         close(s).unwrap();
     }
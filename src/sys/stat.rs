@@ -111,6 +111,15 @@ pub fn fstat(fd: RawFd) -> Result<FileStat> {
     Ok(unsafe{dst.assume_init()})
 }
 
+/// Get the status of the file at `pathname`, relative to `dirfd`.
+///
+/// On Linux and Android, passing `AtFlags::AT_EMPTY_PATH` with an empty
+/// `pathname` returns the status of the file referred to by `dirfd` itself,
+/// which may have been opened with `O_PATH`.
+///
+/// # References
+///
+/// [fstatat(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fstatat.html).
 #[cfg(not(target_os = "redox"))]
 pub fn fstatat<P: ?Sized + NixPath>(dirfd: RawFd, pathname: &P, f: AtFlags) -> Result<FileStat> {
     let mut dst = mem::MaybeUninit::uninit();
@@ -0,0 +1,138 @@
+//! Kernel object (kobject) uevents.
+//!
+//! The kernel announces devices being added, removed or changed by
+//! multicasting uevents on `NETLINK_KOBJECT_UEVENT` sockets.  Each uevent is
+//! a single datagram holding an `action@devpath` header followed by
+//! `KEY=value` pairs, all separated by null bytes.  This is what udev and
+//! similar hotplug daemons listen to.
+//!
+//! [Further reading](https://www.kernel.org/doc/html/latest/core-api/kobject.html#uevents)
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use nix::sys::socket::SockFlag;
+//! use nix::sys::uevent;
+//!
+//! let fd = uevent::socket(SockFlag::SOCK_CLOEXEC).unwrap();
+//! loop {
+//!     let event = uevent::recv(fd).unwrap();
+//!     if event.subsystem() == Some("block") {
+//!         println!("{} {}", event.action(), event.devpath());
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::str;
+use crate::{Error, Result};
+use crate::errno::Errno;
+use crate::sys::socket::{self, AddressFamily, MsgFlags, NetlinkAddr, NetlinkProtocol,
+                         SockAddr, SockFlag, SockType};
+use crate::unistd::close;
+
+/// Multicast group of the uevents sent by the kernel.  Group 2 carries the
+/// messages that udev rebroadcasts, in its own format.
+const KERNEL_GROUP: u32 = 1;
+/// Size of the kernel's uevent buffer, and hence of the largest uevent
+const UEVENT_BUFFER_SIZE: usize = 2048;
+
+/// Open a `NETLINK_KOBJECT_UEVENT` socket subscribed to the uevents sent by
+/// the kernel.
+///
+/// The socket receives every uevent from now on; read them with
+/// [`recv`](fn.recv.html).
+pub fn socket(flags: SockFlag) -> Result<RawFd> {
+    let fd = socket::socket(AddressFamily::Netlink, SockType::Raw, flags,
+                            NetlinkProtocol::KObjectUEvent)?;
+    let addr = SockAddr::Netlink(NetlinkAddr::new(0, KERNEL_GROUP));
+    if let Err(e) = socket::bind(&fd, &addr) {
+        let _ = close(fd);
+        return Err(e);
+    }
+    Ok(fd)
+}
+
+/// Receive the next uevent sent by the kernel on `fd`.
+///
+/// Datagrams sent by other processes, which could forge events, are
+/// skipped.
+pub fn recv(fd: RawFd) -> Result<Uevent> {
+    let mut buf = [0u8; UEVENT_BUFFER_SIZE];
+    loop {
+        let (len, addr) = socket::recvfrom(&fd, &mut buf, MsgFlags::empty())?;
+        match addr {
+            Some(SockAddr::Netlink(addr)) if addr.pid() == 0 => {
+                return Uevent::parse(&buf[..len]);
+            },
+            _ => (),
+        }
+    }
+}
+
+/// A parsed uevent.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Uevent {
+    action: String,
+    devpath: String,
+    env: HashMap<String, String>,
+}
+
+impl Uevent {
+    /// Parse a uevent datagram, as sent by the kernel.
+    ///
+    /// Fails with `EINVAL` if `buf` doesn't start with an `action@devpath`
+    /// header, or isn't valid UTF-8.
+    pub fn parse(buf: &[u8]) -> Result<Uevent> {
+        let mut fields = buf.split(|&b| b == 0)
+            .map(|field| str::from_utf8(field).map_err(|_| Error::Sys(Errno::EINVAL)));
+        let header = fields.next().unwrap_or(Ok(""))?;
+        let at = header.find('@').ok_or(Error::Sys(Errno::EINVAL))?;
+
+        let mut env = HashMap::new();
+        for field in fields {
+            let field = field?;
+            if let Some(eq) = field.find('=') {
+                env.insert(field[..eq].to_owned(), field[eq + 1..].to_owned());
+            }
+        }
+        Ok(Uevent {
+            action: header[..at].to_owned(),
+            devpath: header[at + 1..].to_owned(),
+            env,
+        })
+    }
+
+    /// The action, such as `add`, `remove`, `change`, `move`, `online`,
+    /// `offline`, `bind` or `unbind`.
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// The path of the device below `/sys`.
+    pub fn devpath(&self) -> &str {
+        &self.devpath
+    }
+
+    /// The subsystem of the device, such as `block` or `net`.
+    pub fn subsystem(&self) -> Option<&str> {
+        self.get("SUBSYSTEM")
+    }
+
+    /// The sequence number of the uevent, which increases with every
+    /// uevent sent by the kernel.
+    pub fn seqnum(&self) -> Option<u64> {
+        self.get("SEQNUM").and_then(|seqnum| seqnum.parse().ok())
+    }
+
+    /// Look up the value of a key, such as `DEVNAME` or `MODALIAS`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.env.get(key).map(String::as_str)
+    }
+
+    /// All keys and values of the uevent, including `ACTION` and `DEVPATH`.
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+}
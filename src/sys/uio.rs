@@ -4,7 +4,9 @@
 use crate::Result;
 use crate::errno::Errno;
 use libc::{self, c_int, c_void, size_t, off_t};
+use std::io::{IoSlice, IoSliceMut};
 use std::marker::PhantomData;
+use std::{mem, slice};
 use std::os::unix::io::RawFd;
 
 pub fn writev(fd: RawFd, iov: &[IoVec<&[u8]>]) -> Result<usize> {
@@ -173,8 +175,6 @@ pub struct IoVec<T>(libc::iovec, PhantomData<T>);
 impl<T> IoVec<T> {
     #[inline]
     pub fn as_slice(&self) -> &[u8] {
-        use std::slice;
-
         unsafe {
             slice::from_raw_parts(
                 self.0.iov_base as *const u8,
@@ -190,6 +190,31 @@ impl<'a> IoVec<&'a [u8]> {
             iov_len: buf.len() as size_t,
         }, PhantomData)
     }
+
+    /// View a slice of `IoSlice`s as `IoVec`s, for passing to functions like
+    /// [`writev`](fn.writev.html) or
+    /// [`sendmsg`](../socket/fn.sendmsg.html) without copying.
+    pub fn from_io_slices<'b>(bufs: &'b [IoSlice<'a>]) -> &'b [IoVec<&'a [u8]>] {
+        // IoSlice is guaranteed to have the same layout as iovec on Unix
+        unsafe { slice::from_raw_parts(bufs.as_ptr() as *const IoVec<&'a [u8]>, bufs.len()) }
+    }
+}
+
+impl<'a> From<IoSlice<'a>> for IoVec<&'a [u8]> {
+    fn from(buf: IoSlice<'a>) -> Self {
+        IoVec(libc::iovec {
+            iov_base: buf.as_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }, PhantomData)
+    }
+}
+
+impl<'a> From<IoVec<&'a [u8]>> for IoSlice<'a> {
+    fn from(iov: IoVec<&'a [u8]>) -> Self {
+        IoSlice::new(unsafe {
+            slice::from_raw_parts(iov.0.iov_base as *const u8, iov.0.iov_len)
+        })
+    }
 }
 
 impl<'a> IoVec<&'a mut [u8]> {
@@ -199,4 +224,44 @@ impl<'a> IoVec<&'a mut [u8]> {
             iov_len: buf.len() as size_t,
         }, PhantomData)
     }
+
+    /// Refer to a buffer that the kernel will fill in.  Since `buf` may be
+    /// uninitialized, the result must not be read with `as_slice`.
+    pub(crate) fn from_uninit_slice(buf: &'a mut [mem::MaybeUninit<u8>])
+        -> IoVec<&'a mut [u8]>
+    {
+        IoVec(libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }, PhantomData)
+    }
+
+    /// View a slice of `IoSliceMut`s as `IoVec`s, for passing to functions
+    /// like [`readv`](fn.readv.html) or
+    /// [`recvmsg`](../socket/fn.recvmsg.html) without copying.
+    pub fn from_io_slices_mut<'b>(bufs: &'b mut [IoSliceMut<'a>])
+        -> &'b mut [IoVec<&'a mut [u8]>]
+    {
+        // IoSliceMut is guaranteed to have the same layout as iovec on Unix
+        unsafe {
+            slice::from_raw_parts_mut(bufs.as_mut_ptr() as *mut IoVec<&'a mut [u8]>, bufs.len())
+        }
+    }
+}
+
+impl<'a> From<IoSliceMut<'a>> for IoVec<&'a mut [u8]> {
+    fn from(mut buf: IoSliceMut<'a>) -> Self {
+        IoVec(libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len() as size_t,
+        }, PhantomData)
+    }
+}
+
+impl<'a> From<IoVec<&'a mut [u8]>> for IoSliceMut<'a> {
+    fn from(iov: IoVec<&'a mut [u8]>) -> Self {
+        IoSliceMut::new(unsafe {
+            slice::from_raw_parts_mut(iov.0.iov_base as *mut u8, iov.0.iov_len)
+        })
+    }
 }
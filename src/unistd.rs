@@ -6,8 +6,7 @@ use crate::errno::{self, Errno};
 use crate::{Error, Result, NixPath};
 #[cfg(not(target_os = "redox"))]
 use crate::fcntl::{AtFlags, at_rawfd};
-use crate::fcntl::{FdFlag, OFlag, fcntl};
-use crate::fcntl::FcntlArg::F_SETFD;
+use crate::fcntl::OFlag;
 use libc::{self, c_char, c_void, c_int, c_long, c_uint, size_t, pid_t, off_t,
            uid_t, gid_t, mode_t, PATH_MAX};
 use std::{fmt, mem, ptr};
@@ -310,11 +309,13 @@ pub fn tcgetpgrp(fd: c_int) -> Result<Pid> {
     let res = unsafe { libc::tcgetpgrp(fd) };
     Errno::result(res).map(Pid)
 }
+
 /// Set the terminal foreground process group (see
-/// [tcgetpgrp(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/tcsetpgrp.html)).
+/// [tcsetpgrp(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/tcsetpgrp.html)).
 ///
-/// Get the group process id (PGID) to the foreground process group on the
-/// terminal associated to file descriptor (FD).
+/// Set the group process id (PGID) of the foreground process group on the
+/// terminal associated to file descriptor (FD).  `pgrp` must belong to the
+/// same session as the calling process.
 #[inline]
 pub fn tcsetpgrp(fd: c_int, pgrp: Pid) -> Result<()> {
     let res = unsafe { libc::tcsetpgrp(fd, pgrp.into()) };
@@ -382,13 +383,36 @@ pub fn dup2(oldfd: RawFd, newfd: RawFd) -> Result<RawFd> {
 /// and flags (see [dup(2)](http://man7.org/linux/man-pages/man2/dup.2.html)).
 ///
 /// This function behaves similar to `dup2()` but allows for flags to be
-/// specified.
+/// specified.  Where the platform provides `dup3`, `O_CLOEXEC` is applied
+/// atomically, so the new descriptor cannot leak into a process forked and
+/// exec'd by another thread in between.  Elsewhere it falls back to `dup2()`
+/// followed by `fcntl()`.
 pub fn dup3(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
-    dup3_polyfill(oldfd, newfd, flags)
+    dup3_impl(oldfd, newfd, flags)
+}
+
+#[cfg(any(target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "linux",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[inline]
+fn dup3_impl(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
+    let res = unsafe { libc::dup3(oldfd, newfd, flags.bits()) };
+
+    Errno::result(res)
 }
 
+#[cfg(not(any(target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "linux",
+              target_os = "netbsd",
+              target_os = "openbsd")))]
 #[inline]
-fn dup3_polyfill(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
+fn dup3_impl(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
+    use crate::fcntl::{FdFlag, fcntl};
+    use crate::fcntl::FcntlArg::F_SETFD;
+
     if oldfd == newfd {
         return Err(Error::Sys(Errno::EINVAL));
     }
@@ -690,13 +714,19 @@ pub enum FchownatFlags {
 /// a call `libc::lchown(path, mode)`.  That's why `lchmod` is unimplemented in
 /// the `nix` crate.
 ///
+/// If `path` is `None`, then the ownership of the file referred to by `dirfd`
+/// itself is changed, using `AT_EMPTY_PATH`.  `dirfd` may then refer to any
+/// type of file, not just a directory, and may have been opened with `O_PATH`.
+/// This is only supported on Linux and Android; other platforms fail with
+/// `ENOENT`.
+///
 /// # References
 ///
 /// [fchownat(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fchownat.html).
 #[cfg(not(target_os = "redox"))]
 pub fn fchownat<P: ?Sized + NixPath>(
     dirfd: Option<RawFd>,
-    path: &P,
+    path: Option<&P>,
     owner: Option<Uid>,
     group: Option<Gid>,
     flag: FchownatFlags,
@@ -706,11 +736,18 @@ pub fn fchownat<P: ?Sized + NixPath>(
             FchownatFlags::FollowSymlink => AtFlags::empty(),
             FchownatFlags::NoFollowSymlink => AtFlags::AT_SYMLINK_NOFOLLOW,
         };
-    let res = path.with_nix_path(|cstr| unsafe {
-        let (uid, gid) = chown_raw_ids(owner, group);
-        libc::fchownat(at_rawfd(dirfd), cstr.as_ptr(), uid, gid,
-                       atflag.bits() as libc::c_int)
-    })?;
+    let (uid, gid) = chown_raw_ids(owner, group);
+    let res = match path {
+        Some(path) => path.with_nix_path(|cstr| unsafe {
+            libc::fchownat(at_rawfd(dirfd), cstr.as_ptr(), uid, gid,
+                           atflag.bits() as libc::c_int)
+        })?,
+        None => unsafe {
+            let atflag = empty_path(atflag);
+            libc::fchownat(at_rawfd(dirfd), b"\0".as_ptr() as *const c_char,
+                           uid, gid, atflag.bits() as libc::c_int)
+        },
+    };
 
     Errno::result(res).map(drop)
 }
@@ -840,7 +877,9 @@ pub fn fexecve(fd: RawFd, args: &[&CStr], env: &[&CStr]) -> Result<Infallible> {
 /// the new program will run until it exits.
 ///
 /// This function is similar to `execve`, except that the program to be executed
-/// is referenced as a file descriptor to the base directory plus a path.
+/// is referenced as a file descriptor to the base directory plus a path.  If
+/// `flags` contains `AtFlags::AT_EMPTY_PATH` and `pathname` is empty, the file
+/// referred to by `dirfd` is executed, as with `fexecve`.
 #[cfg(any(target_os = "android", target_os = "linux"))]
 #[inline]
 pub fn execveat(dirfd: RawFd, pathname: &CStr, args: &[&CStr],
@@ -850,7 +889,7 @@ pub fn execveat(dirfd: RawFd, pathname: &CStr, args: &[&CStr],
 
     unsafe {
         libc::syscall(libc::SYS_execveat, dirfd, pathname.as_ptr(),
-                      args_p.as_ptr(), env_p.as_ptr(), flags);
+                      args_p.as_ptr(), env_p.as_ptr(), flags.bits());
     };
 
     Err(Error::Sys(Errno::last()))
@@ -984,6 +1023,38 @@ pub fn close(fd: RawFd) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+#[cfg(target_os = "linux")]
+libc_bitflags! {
+    /// Flags for [`close_range`](fn.close_range.html).
+    pub struct CloseRangeFlags: c_uint {
+        /// Unshare the descriptor table first, so that other processes
+        /// sharing it, as after `clone(CLONE_FILES)`, are not affected.
+        CLOSE_RANGE_UNSHARE;
+        /// Set the close-on-exec flag on the descriptors instead of closing
+        /// them.
+        CLOSE_RANGE_CLOEXEC;
+    }
+}
+
+/// Close all open file descriptors from `first` to `last`, inclusive.
+///
+/// Descriptors in the range that are not open are skipped, so
+/// `close_range(3, RawFd::MAX, CloseRangeFlags::empty())` closes everything
+/// but the standard streams.  Requires Linux 5.9 or later, or 5.11 for
+/// `CLOSE_RANGE_CLOEXEC`; older kernels fail with `ENOSYS` or `EINVAL`.
+///
+/// The same caveats as for [`close`](fn.close.html) apply to descriptors
+/// owned by Rust objects.
+///
+/// See also [close_range(2)](http://man7.org/linux/man-pages/man2/close_range.2.html)
+#[cfg(target_os = "linux")]
+pub fn close_range(first: RawFd, last: RawFd, flags: CloseRangeFlags) -> Result<()> {
+    let res = unsafe {
+        libc::syscall(libc::SYS_close_range, first as c_uint, last as c_uint, flags.bits())
+    };
+    Errno::result(res).map(drop)
+}
+
 /// Read from a raw file descriptor.
 ///
 /// See also [read(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/read.html)
@@ -1153,12 +1224,17 @@ pub enum LinkatFlags {
 /// and/or `newpath` is then interpreted relative to the current working directory of the calling
 /// process. If either `oldpath` or `newpath` is absolute, then `dirfd` is ignored.
 ///
+/// In case `oldpath` is `None`, the new link refers to the file associated with `olddirfd` itself,
+/// which may be a file opened with `O_PATH` or `O_TMPFILE`, using `AT_EMPTY_PATH`. This is only
+/// supported on Linux and Android, where it requires the `CAP_DAC_READ_SEARCH` capability; other
+/// platforms fail with `ENOENT`.
+///
 /// # References
 /// See also [linkat(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/linkat.html)
 #[cfg(not(target_os = "redox"))] // RedoxFS does not support symlinks yet
 pub fn linkat<P: ?Sized + NixPath>(
     olddirfd: Option<RawFd>,
-    oldpath: &P,
+    oldpath: Option<&P>,
     newdirfd: Option<RawFd>,
     newpath: &P,
     flag: LinkatFlags,
@@ -1170,20 +1246,94 @@ pub fn linkat<P: ?Sized + NixPath>(
             LinkatFlags::NoSymlinkFollow => AtFlags::empty(),
         };
 
-    let res =
-        oldpath.with_nix_path(|oldcstr| {
+    let res = match oldpath {
+        Some(oldpath) =>
+            oldpath.with_nix_path(|oldcstr| {
+                newpath.with_nix_path(|newcstr| {
+                unsafe {
+                    libc::linkat(
+                        at_rawfd(olddirfd),
+                        oldcstr.as_ptr(),
+                        at_rawfd(newdirfd),
+                        newcstr.as_ptr(),
+                        atflag.bits() as libc::c_int
+                        )
+                    }
+                })
+            })??,
+        None =>
             newpath.with_nix_path(|newcstr| {
             unsafe {
                 libc::linkat(
                     at_rawfd(olddirfd),
-                    oldcstr.as_ptr(),
+                    b"\0".as_ptr() as *const c_char,
                     at_rawfd(newdirfd),
                     newcstr.as_ptr(),
-                    atflag.bits() as libc::c_int
+                    empty_path(atflag).bits() as libc::c_int
                     )
                 }
-            })
-        })??;
+            })?,
+    };
+    Errno::result(res).map(drop)
+}
+
+/// Add `AT_EMPTY_PATH` to `flags` where it is supported, for the `*at`
+/// functions that operate on their directory file descriptor when given no
+/// path.
+#[cfg(not(target_os = "redox"))]
+fn empty_path(flags: AtFlags) -> AtFlags {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        flags | AtFlags::AT_EMPTY_PATH
+    }
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    {
+        flags
+    }
+}
+
+/// Give a name to an anonymous file created with `O_TMPFILE`.
+///
+/// Creates a link at `newpath`, relative to `newdirfd` like in
+/// [`linkat`](fn.linkat.html), to the file open as `fd`.  This allows a file
+/// to be written completely before it appears in the filesystem.
+///
+/// Linking with `AT_EMPTY_PATH` is tried first, but it requires the
+/// `CAP_DAC_READ_SEARCH` capability; without it the file is linked through
+/// `/proc/self/fd` instead.  Files opened with `O_TMPFILE | O_EXCL` cannot be
+/// linked at all, and fail with `ENOENT`.
+///
+/// # References
+///
+/// [open(2)](http://man7.org/linux/man-pages/man2/open.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn link_tmpfile<P: ?Sized + NixPath>(
+    fd: RawFd,
+    newdirfd: Option<RawFd>,
+    newpath: &P,
+) -> Result<()> {
+    let proc_path = CString::new(format!("/proc/self/fd/{}", fd)).unwrap();
+
+    let res = newpath.with_nix_path(|newcstr| unsafe {
+        let res = libc::linkat(
+            fd,
+            b"\0".as_ptr() as *const c_char,
+            at_rawfd(newdirfd),
+            newcstr.as_ptr(),
+            libc::AT_EMPTY_PATH,
+        );
+        // ENOENT is also what a missing capability looks like
+        if res == 0 || Errno::last() != Errno::ENOENT {
+            return res;
+        }
+        libc::linkat(
+            libc::AT_FDCWD,
+            proc_path.as_ptr(),
+            at_rawfd(newdirfd),
+            newcstr.as_ptr(),
+            libc::AT_SYMLINK_FOLLOW,
+        )
+    })?;
     Errno::result(res).map(drop)
 }
 
@@ -1260,6 +1410,16 @@ pub fn sync() {
     unsafe { libc::sync() };
 }
 
+/// Commit the filesystem caches of the filesystem containing `fd` to disk
+///
+/// See also [syncfs(2)](http://man7.org/linux/man-pages/man2/syncfs.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn syncfs(fd: RawFd) -> Result<()> {
+    let res = unsafe { libc::syncfs(fd) };
+
+    Errno::result(res).map(drop)
+}
+
 /// Synchronize changes to a file
 ///
 /// See also [fsync(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fsync.html)
@@ -1274,11 +1434,16 @@ pub fn fsync(fd: RawFd) -> Result<()> {
 ///
 /// See also
 /// [fdatasync(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fdatasync.html)
-// `fdatasync(2) is in POSIX, but in libc it is only defined in `libc::notbsd`.
-// TODO: exclude only Apple systems after https://github.com/rust-lang/libc/pull/211
-#[cfg(any(target_os = "linux",
-          target_os = "android",
-          target_os = "emscripten"))]
+// `fdatasync(2)` is in POSIX, but Apple systems don't provide it.
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "emscripten",
+          target_os = "freebsd",
+          target_os = "illumos",
+          target_os = "linux",
+          target_os = "netbsd",
+          target_os = "openbsd",
+          target_os = "solaris"))]
 #[inline]
 pub fn fdatasync(fd: RawFd) -> Result<()> {
     let res = unsafe { libc::fdatasync(fd) };
@@ -1326,6 +1491,27 @@ pub fn getegid() -> Gid {
     Gid(unsafe { libc::getegid() })
 }
 
+/// Get the effective user and group IDs of the peer of a connected UNIX
+/// domain socket, as they were when the connection was established.
+///
+/// See also [getpeereid(3)](https://www.freebsd.org/cgi/man.cgi?query=getpeereid)
+#[cfg(any(target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub fn getpeereid(fd: RawFd) -> Result<(Uid, Gid)> {
+    let mut uid = mem::MaybeUninit::uninit();
+    let mut gid = mem::MaybeUninit::uninit();
+
+    let res = unsafe { libc::getpeereid(fd, uid.as_mut_ptr(), gid.as_mut_ptr()) };
+
+    Errno::result(res).map(|_| unsafe {
+        (Uid(uid.assume_init()), Gid(gid.assume_init()))
+    })
+}
+
 /// Set the effective user ID
 ///
 /// See also [seteuid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/seteuid.html)
@@ -1388,6 +1574,25 @@ pub fn setfsgid(gid: Gid) -> Gid {
     Gid::from_raw(prev_fsgid as gid_t)
 }
 
+/// Set the default routing table (FIB) of the calling process.
+///
+/// Sockets created afterwards by the process use routing table `fib`, unless
+/// overridden with the [`SetFib`](sys/socket/sockopt/struct.SetFib.html) socket
+/// option.
+///
+/// See also [setfib(2)](https://www.freebsd.org/cgi/man.cgi?query=setfib&sektion=2)
+#[cfg(target_os = "freebsd")]
+pub fn setfib(fib: c_int) -> Result<()> {
+    // FIXME: Move into `libc`
+    extern {
+        #[link_name = "setfib"]
+        fn libc_setfib(fib: c_int) -> c_int;
+    }
+
+    let res = unsafe { libc_setfib(fib) };
+    Errno::result(res).map(drop)
+}
+
 /// Get the list of supplementary group IDs of the calling process.
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/009695399/functions/getgroups.html)
@@ -20,17 +20,23 @@ mod test_sockopt;
 #[cfg(not(target_os = "redox"))]
 mod test_select;
 #[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_auxv;
+#[cfg(any(target_os = "android", target_os = "linux"))]
 mod test_sysinfo;
 #[cfg(not(target_os = "redox"))]
 mod test_termios;
 #[cfg(not(target_os = "redox"))]
 mod test_ioctl;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_uevent;
 mod test_wait;
 mod test_uio;
 
 #[cfg(target_os = "linux")]
 mod test_epoll;
 #[cfg(target_os = "linux")]
+mod test_fanotify;
+#[cfg(target_os = "linux")]
 mod test_inotify;
 mod test_pthread;
 #[cfg(any(target_os = "android",
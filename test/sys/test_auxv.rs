@@ -0,0 +1,20 @@
+use nix::sys::auxv::*;
+use nix::unistd::{sysconf, SysconfVar};
+
+#[test]
+fn test_getauxval_pagesz() {
+    let page_size = sysconf(SysconfVar::PAGE_SIZE).unwrap().unwrap();
+    assert_eq!(getauxval(AuxvType::AT_PAGESZ), Some(page_size as libc::c_ulong));
+}
+
+#[test]
+fn test_getauxval_secure() {
+    // The test binary is never set-user-ID, so it runs in normal mode
+    assert_eq!(getauxval(AuxvType::AT_SECURE), Some(0));
+}
+
+#[test]
+fn test_getauxval_random() {
+    let random = getauxval(AuxvType::AT_RANDOM).unwrap();
+    assert_ne!(random, 0);
+}
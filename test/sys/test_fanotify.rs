@@ -0,0 +1,74 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::sys::fanotify::{Fanotify, FanotifyResponse, InitFlags, MarkFlags, MaskFlags, Response};
+use nix::sys::stat::fstat;
+use nix::unistd::close;
+use std::os::unix::io::AsRawFd;
+use std::fs::{self, File};
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::thread;
+
+#[test]
+pub fn test_fanotify_notifications() {
+    require_capability!(CAP_SYS_ADMIN);
+
+    let group = Fanotify::init(InitFlags::FAN_CLASS_NOTIF | InitFlags::FAN_NONBLOCK,
+                               OFlag::O_RDONLY).unwrap();
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("test");
+    fs::write(&path, b"abc").unwrap();
+
+    group.mark(MarkFlags::FAN_MARK_ADD, MaskFlags::FAN_OPEN | MaskFlags::FAN_CLOSE_WRITE,
+               None, Some(&path)).unwrap();
+    assert_eq!(group.read_events().unwrap_err(), Error::Sys(Errno::EAGAIN));
+
+    fs::write(&path, b"def").unwrap();
+
+    let events = group.read_events().unwrap();
+    let mask = events.iter().fold(MaskFlags::empty(), |mask, e| mask | e.mask());
+    assert_eq!(mask, MaskFlags::FAN_OPEN | MaskFlags::FAN_CLOSE_WRITE);
+    for event in &events {
+        assert!(event.check_version());
+        assert_eq!(event.pid() as u32, std::process::id());
+        let stat = fstat(event.fd().unwrap()).unwrap();
+        assert_eq!(stat.st_ino, fs::metadata(&path).unwrap().ino());
+    }
+
+    close(group.as_raw_fd()).unwrap();
+}
+
+#[test]
+pub fn test_fanotify_permissions() {
+    require_capability!(CAP_SYS_ADMIN);
+
+    let group = Fanotify::init(InitFlags::FAN_CLASS_CONTENT, OFlag::O_RDONLY).unwrap();
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("test");
+    File::create(&path).unwrap();
+
+    group.mark(MarkFlags::FAN_MARK_ADD, MaskFlags::FAN_OPEN_PERM, None, Some(&path)).unwrap();
+
+    for &response in &[Response::Deny, Response::Allow] {
+        let opener = {
+            let path = path.clone();
+            thread::spawn(move || File::open(path).map(drop))
+        };
+
+        let events = group.read_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].mask(), MaskFlags::FAN_OPEN_PERM);
+        let fd = events[0].fd().unwrap();
+        group.write_response(FanotifyResponse::new(fd, response)).unwrap();
+
+        let res = opener.join().unwrap();
+        if response == Response::Deny {
+            assert_eq!(res.unwrap_err().kind(), ErrorKind::PermissionDenied);
+        } else {
+            res.unwrap();
+        }
+    }
+
+    close(group.as_raw_fd()).unwrap();
+}
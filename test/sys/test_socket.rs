@@ -55,6 +55,211 @@ pub fn test_inetv6_addr_to_sock_addr() {
     assert_eq!(actual, addr.to_std());
 }
 
+#[test]
+pub fn test_std_conversions() {
+    use nix::sys::socket::{IpAddr, SockAddr};
+    use std::convert::TryFrom;
+    use std::net::SocketAddrV4;
+
+    let v6: SocketAddrV6 = SocketAddrV6::new("fe80::1".parse().unwrap(), 443, 7, 3);
+    let inet = InetAddr::from(v6);
+    assert_eq!(SocketAddrV6::try_from(inet).unwrap(), v6);
+    assert!(SocketAddrV4::try_from(inet).is_err());
+    assert_eq!(SocketAddr::from(inet), SocketAddr::V6(v6));
+
+    let v4: SocketAddrV4 = "10.1.2.3:80".parse().unwrap();
+    let sa = SockAddr::from(v4);
+    assert_eq!(sa, SockAddr::new_inet(InetAddr::from(SocketAddr::V4(v4))));
+    assert_eq!(SocketAddrV4::try_from(sa).unwrap(), v4);
+    assert_eq!(SocketAddr::try_from(sa).unwrap(), SocketAddr::V4(v4));
+    assert!(SocketAddrV6::try_from(sa).is_err());
+    let unix = SockAddr::new_unix("/tmp/sock").unwrap();
+    assert!(SocketAddr::try_from(unix).is_err());
+
+    let ip: net::IpAddr = "::1".parse().unwrap();
+    assert_eq!(net::IpAddr::from(IpAddr::from(ip)), ip);
+    let ip4: net::Ipv4Addr = "192.168.0.1".parse().unwrap();
+    assert_eq!(net::Ipv4Addr::from(nix::sys::socket::Ipv4Addr::from(ip4)), ip4);
+    let ip6: Ipv6Addr = "2001:db8::5".parse().unwrap();
+    assert_eq!(Ipv6Addr::from(nix::sys::socket::Ipv6Addr::from(ip6)), ip6);
+}
+
+#[test]
+pub fn test_addr_from_str() {
+    use nix::sys::socket::{IpAddr, Ipv4Addr, SockAddr};
+
+    let ip: Ipv4Addr = "1.2.3.4".parse().unwrap();
+    assert_eq!(ip, Ipv4Addr::new(1, 2, 3, 4));
+    let ip: IpAddr = "fe80::1".parse().unwrap();
+    assert_eq!(ip.to_string(), "fe80::1");
+    assert!("1.2.3".parse::<IpAddr>().is_err());
+    let ip6: nix::sys::socket::Ipv6Addr = "::1".parse().unwrap();
+    assert_eq!(ip6.to_string(), "::1");
+
+    for s in &["1.2.3.4:80", "[::1]:443"] {
+        let inet: InetAddr = s.parse().unwrap();
+        assert_eq!(inet.to_string(), *s);
+        let sa: SockAddr = s.parse().unwrap();
+        assert_eq!(sa, SockAddr::Inet(inet));
+    }
+
+    let sa: SockAddr = "/tmp/nix.sock".parse().unwrap();
+    assert_eq!(sa, SockAddr::new_unix("/tmp/nix.sock").unwrap());
+    assert_eq!(sa.to_string(), "/tmp/nix.sock");
+    assert!("localhost:80".parse::<SockAddr>().is_err());
+}
+
+#[test]
+pub fn test_recvmsg_no_address() {
+    use nix::sys::socket::{recvmsg, send, socketpair, MsgFlags, SockFlag, SockType};
+    use nix::sys::uio::IoVec;
+
+    for &ty in &[SockType::Stream, SockType::Datagram] {
+        let (a, b) = socketpair(AddressFamily::Unix, ty, None, SockFlag::empty()).unwrap();
+        send(&a, b"hello", MsgFlags::empty()).unwrap();
+        let mut buf = [0u8; 5];
+        let iov = [IoVec::from_mut_slice(&mut buf)];
+        let msg = recvmsg(&b, &iov, None, MsgFlags::empty()).unwrap();
+        assert_eq!(msg.bytes, 5);
+        assert_eq!(msg.address, None);
+    }
+}
+
+#[test]
+pub fn test_addr_ord() {
+    use nix::sys::socket::IpAddr;
+    use std::collections::BTreeSet;
+
+    let mut inets: Vec<InetAddr> = ["[::1]:80", "10.0.0.1:443", "10.0.0.1:80", "9.0.0.1:8080"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    inets.sort();
+    let sorted: Vec<String> = inets.iter().map(|inet| inet.to_string()).collect();
+    assert_eq!(sorted, ["9.0.0.1:8080", "10.0.0.1:80", "10.0.0.1:443", "[::1]:80"]);
+
+    let a: IpAddr = "10.0.0.2".parse().unwrap();
+    let b: IpAddr = "10.0.0.10".parse().unwrap();
+    assert!(a < b);
+
+    // Equality and hashing agree with the ordering, and ignore padding
+    let inet: InetAddr = "10.0.0.1:80".parse().unwrap();
+    let mut padded = inet;
+    if let InetAddr::V4(ref mut sin) = padded {
+        sin.sin_zero = [1; 8];
+    }
+    assert_eq!(inet.cmp(&padded), std::cmp::Ordering::Equal);
+    assert_eq!(inet, padded);
+    assert_eq!(calculate_hash(&inet), calculate_hash(&padded));
+
+    let set: BTreeSet<UnixAddr> = ["/tmp/b", "/tmp/a", "/tmp/b"].iter()
+        .map(|p| UnixAddr::new(*p).unwrap())
+        .collect();
+    let paths: Vec<_> = set.iter().map(|addr| addr.path().unwrap()).collect();
+    assert_eq!(paths, [Path::new("/tmp/a"), Path::new("/tmp/b")]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+pub fn test_addr_serde() {
+    use nix::sys::socket::{IpAddr, SockAddr};
+    use serde_test::{assert_tokens, Token};
+
+    let v4: InetAddr = "10.0.0.1:80".parse().unwrap();
+    assert_tokens(&v4, &[Token::Str("10.0.0.1:80")]);
+    assert_tokens(&SockAddr::Inet(v4), &[Token::Str("10.0.0.1:80")]);
+    let v6: InetAddr = "[::1]:443".parse().unwrap();
+    assert_tokens(&v6, &[Token::Str("[::1]:443")]);
+    assert_tokens(&SockAddr::Inet(v6), &[Token::Str("[::1]:443")]);
+    let ip: IpAddr = "fe80::1".parse().unwrap();
+    assert_tokens(&ip, &[Token::Str("fe80::1")]);
+
+    let path = UnixAddr::new("/tmp/sock").unwrap();
+    assert_tokens(&path, &[Token::Str("/tmp/sock")]);
+    assert_tokens(&SockAddr::Unix(path), &[Token::Str("/tmp/sock")]);
+
+    // SockAddr can't tell relative paths and unnamed addresses apart from
+    // other families
+    let relative = UnixAddr::new("sock").unwrap();
+    assert_tokens(&relative, &[Token::Str("sock")]);
+    assert_tokens(&SockAddr::Unix(relative), &[
+        Token::Map { len: Some(1) },
+        Token::Str("path"),
+        Token::Seq { len: Some(4) },
+        Token::U8(b's'), Token::U8(b'o'), Token::U8(b'c'), Token::U8(b'k'),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ]);
+    let unnamed = UnixAddr::new_unnamed();
+    assert_tokens(&unnamed, &[Token::Str("")]);
+    assert_tokens(&SockAddr::Unix(unnamed), &[
+        Token::Map { len: Some(1) },
+        Token::Str("path"),
+        Token::Seq { len: Some(0) },
+        Token::SeqEnd,
+        Token::MapEnd,
+    ]);
+}
+
+#[cfg(all(feature = "serde", any(target_os = "android", target_os = "linux")))]
+#[test]
+pub fn test_abstract_addr_serde() {
+    use nix::sys::socket::SockAddr;
+    use serde_test::{assert_tokens, Token};
+
+    let abstract_addr = UnixAddr::new_abstract(b"nix").unwrap();
+    assert_tokens(&abstract_addr, &[Token::Str("@nix")]);
+    assert_tokens(&SockAddr::Unix(abstract_addr), &[Token::Str("@nix")]);
+
+    // "@nix" would parse as the abstract address
+    let at_path = UnixAddr::new("@nix").unwrap();
+    let tokens = [
+        Token::Map { len: Some(1) },
+        Token::Str("path"),
+        Token::Seq { len: Some(4) },
+        Token::U8(b'@'), Token::U8(b'n'), Token::U8(b'i'), Token::U8(b'x'),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ];
+    assert_tokens(&at_path, &tokens);
+    assert_tokens(&SockAddr::Unix(at_path), &tokens);
+
+    let non_utf8 = UnixAddr::new_abstract(b"\xff").unwrap();
+    assert_tokens(&non_utf8, &[
+        Token::Map { len: Some(1) },
+        Token::Str("abstract"),
+        Token::Seq { len: Some(1) },
+        Token::U8(0xff),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ]);
+}
+
+#[test]
+pub fn test_mac_addr() {
+    use nix::sys::socket::MacAddr;
+
+    let mac: MacAddr = "00:1B:63:84:45:e6".parse().unwrap();
+    assert_eq!(mac.octets(), [0x00, 0x1b, 0x63, 0x84, 0x45, 0xe6]);
+    assert_eq!(mac.to_string(), "00:1b:63:84:45:e6");
+    assert_eq!("00-1b-63-84-45-e6".parse::<MacAddr>().unwrap(), mac);
+    for bad in &["", "00:1b:63:84:45", "00:1b:63:84:45:e6:01", "00:1b:63:84:45:g6",
+                 "00:1b:63:84:45:0e6", "00:1b:63:84::e6", "00-1b:63:84:45:e6"] {
+        assert!(bad.parse::<MacAddr>().is_err(), "parsed {:?}", bad);
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_abstract_addr_from_str() {
+    use nix::sys::socket::SockAddr;
+
+    let sa: SockAddr = "@nix-abstract".parse().unwrap();
+    let expected = UnixAddr::new_abstract(b"nix-abstract").unwrap();
+    assert_eq!(sa, SockAddr::Unix(expected));
+    assert_eq!(sa.to_string(), "@nix-abstract");
+}
+
 #[test]
 pub fn test_path_to_sock_addr() {
     let path = "/foo/bar";
@@ -132,6 +337,114 @@ pub fn test_abstract_uds_addr() {
     assert_eq!(addr.0.sun_path[0], 0);
 }
 
+// Names with interior null bytes survive bind and getsockname
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_abstract_uds_bind() {
+    use nix::sys::socket::{bind, socket, SockAddr, SockFlag, SockType};
+    use nix::unistd::close;
+
+    let name = format!("nix\0abstract\0{}\0", std::process::id());
+    let addr = UnixAddr::new_abstract(name.as_bytes()).unwrap();
+    let sock = socket(AddressFamily::Unix, SockType::Datagram, SockFlag::empty(), None)
+               .unwrap();
+    bind(&sock, &addr).unwrap();
+    match getsockname(&sock).unwrap() {
+        SockAddr::Unix(bound) => {
+            assert_eq!(bound, addr);
+            assert_eq!(bound.as_abstract(), Some(name.as_bytes()));
+        },
+        other => panic!("Unexpected address {:?}", other),
+    }
+    close(sock).unwrap();
+}
+
+#[test]
+pub fn test_unnamed_uds_addr() {
+    use nix::sys::socket::{socketpair, SockAddr, SockFlag, SockType};
+    use nix::unistd::close;
+
+    let addr = UnixAddr::new_unnamed();
+    assert!(addr.is_unnamed());
+    assert_eq!(addr.path(), None);
+    assert!(!UnixAddr::new("/tmp/sock").unwrap().is_unnamed());
+
+    let (s1, s2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                   .unwrap();
+    assert_eq!(getsockname(&s1).unwrap(), SockAddr::Unix(addr));
+    close(s1).unwrap();
+    close(s2).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_unix_autobind() {
+    use nix::sys::socket::{bind, socket, SockAddr, SockFlag, SockType};
+    use nix::unistd::close;
+
+    let sock = socket(AddressFamily::Unix, SockType::Datagram, SockFlag::empty(), None)
+               .unwrap();
+    bind(&sock, &UnixAddr::new_unnamed()).unwrap();
+    match getsockname(&sock).unwrap() {
+        SockAddr::Unix(bound) => {
+            let name = bound.as_abstract().expect("not an abstract address");
+            assert_eq!(name.len(), 5);
+            assert!(name.iter().all(|c| c.is_ascii_hexdigit()));
+        },
+        other => panic!("Unexpected address {:?}", other),
+    }
+    close(sock).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_bluetooth_addr() {
+    use nix::sys::socket::{L2capAddr, MacAddr, RfcommAddr, SockAddr, SockaddrLike,
+                           SockaddrStorage};
+    use std::mem;
+
+    let bdaddr: MacAddr = "00:11:22:33:44:55".parse().unwrap();
+    let rc = RfcommAddr::new(bdaddr, 3);
+    assert_eq!(rc.len(), 10);
+    assert_eq!(rc.bdaddr(), bdaddr);
+    assert_eq!(rc.channel(), 3);
+    assert_eq!(rc.family(), Some(AddressFamily::Bluetooth));
+    assert_eq!(rc.to_string(), "00:11:22:33:44:55 channel 3");
+    let sa = unsafe { SockAddr::from_raw(rc.as_ptr(), Some(rc.len())) };
+    assert_eq!(sa, Some(SockAddr::Rfcomm(rc)));
+    // The kernel stores the address least significant octet first
+    let mut ss: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    ss.ss_family = libc::AF_BLUETOOTH as libc::sa_family_t;
+    let raw = unsafe {
+        slice::from_raw_parts_mut(&mut ss as *mut _ as *mut u8, mem::size_of_val(&ss))
+    };
+    raw[2..8].copy_from_slice(&[0x55, 0x44, 0x33, 0x22, 0x11, 0x00]);
+    raw[8] = 3;
+    let sa = unsafe {
+        SockAddr::from_raw(&ss as *const _ as *const libc::sockaddr, Some(rc.len()))
+    };
+    assert_eq!(sa, Some(SockAddr::Rfcomm(rc)));
+
+    let l2 = L2capAddr::new(bdaddr, 0x1001);
+    assert_eq!(l2.len(), 14);
+    assert_eq!(l2.bdaddr(), bdaddr);
+    assert_eq!(l2.psm(), 0x1001);
+    assert_eq!(l2.cid(), 0);
+    let sa = unsafe { SockAddr::from_raw(l2.as_ptr(), Some(l2.len())) };
+    assert_eq!(sa, Some(SockAddr::L2cap(l2)));
+    assert_eq!(sa.unwrap().family(), AddressFamily::Bluetooth);
+
+    // Both protocols share AF_BLUETOOTH, so only the length tells them apart
+    let storage = unsafe { SockaddrStorage::from_raw(l2.as_ptr(), Some(l2.len())) }
+        .unwrap();
+    assert_eq!(storage.as_l2cap_addr(), Some(&l2));
+    assert!(storage.as_rfcomm_addr().is_none());
+    let storage = unsafe { SockaddrStorage::from_raw(rc.as_ptr(), Some(rc.len())) }
+        .unwrap();
+    assert_eq!(storage.as_rfcomm_addr(), Some(&rc));
+    assert!(storage.as_l2cap_addr().is_none());
+}
+
 #[test]
 pub fn test_getsockname() {
     use nix::sys::socket::{socket, AddressFamily, SockType, SockFlag};
@@ -142,9 +455,137 @@ pub fn test_getsockname() {
     let sock = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None)
                .expect("socket failed");
     let sockaddr = SockAddr::new_unix(&sockname).unwrap();
-    bind(sock, &sockaddr).expect("bind failed");
+    bind(&sock, &sockaddr).expect("bind failed");
     assert_eq!(sockaddr.to_str(),
-               getsockname(sock).expect("getsockname failed").to_str());
+               getsockname(&sock).expect("getsockname failed").to_str());
+}
+
+#[test]
+pub fn test_bind_unix_addr() {
+    use nix::sys::socket::{bind, socket, SockAddr, SockaddrLike, SockFlag, SockType};
+    use nix::unistd::close;
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let sockname = tempdir.path().join("sock");
+    let sock = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None)
+               .expect("socket failed");
+    // Any address type may be used, without wrapping it in a SockAddr
+    let addr = UnixAddr::new(&sockname).unwrap();
+    let path_offset = addr.len() as usize - sockname.as_os_str().len();
+    bind(&sock, &addr).expect("bind failed");
+
+    // The kernel includes the terminating null byte, but it isn't part of
+    // the path
+    match getsockname(&sock).unwrap() {
+        SockAddr::Unix(bound) => {
+            assert_eq!(bound, addr);
+            assert_eq!(bound.len() as usize, path_offset + sockname.as_os_str().len());
+        },
+        bound => panic!("unexpected address {:?}", bound),
+    }
+    close(sock).unwrap();
+}
+
+#[test]
+pub fn test_sockaddr_storage() {
+    use nix::sys::socket::{SockAddr, SockaddrLike, SockaddrStorage};
+
+    let inet = InetAddr::from_std(&"127.0.0.1:8080".parse().unwrap());
+    let storage = unsafe {
+        SockaddrStorage::from_raw(inet.as_ptr(), Some(inet.len()))
+    }.unwrap();
+    assert_eq!(storage.family(), Some(AddressFamily::Inet));
+    assert_eq!(storage.len(), inet.len());
+    assert_eq!(storage.as_sockaddr_in().unwrap().sin_port, 8080u16.to_be());
+    assert!(storage.as_sockaddr_in6().is_none());
+    assert!(storage.to_unix_addr().is_none());
+    assert_eq!(storage.to_sock_addr(), Some(SockAddr::Inet(inet)));
+
+    // Too short for the family
+    let truncated = unsafe {
+        SockaddrStorage::from_raw(inet.as_ptr(), Some(inet.len() - 1))
+    }.unwrap();
+    assert!(truncated.as_sockaddr_in().is_none());
+    assert!(truncated.to_sock_addr().is_none());
+}
+
+#[test]
+pub fn test_sockaddr_storage_to_addr_unknown_family() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::sockaddr_storage_to_addr;
+    use std::mem;
+
+    let mut ss: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    // AF_UNSPEC is never a valid address family for a bound socket
+    ss.ss_family = libc::AF_UNSPEC as libc::sa_family_t;
+    let len = mem::size_of::<libc::sockaddr_storage>();
+    assert_eq!(sockaddr_storage_to_addr(&ss, len).err(),
+               Some(Error::Sys(Errno::EAFNOSUPPORT)));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_tipc_addr() {
+    use nix::sys::socket::{sockaddr_storage_to_addr, SockAddr, TipcAddr,
+                           TipcAddrKind, TipcScope};
+    use std::{mem, ptr};
+
+    let addrs = [
+        (TipcAddr::new_socket(1234, 0x1001),
+         TipcAddrKind::Socket { port: 1234, node: 0x1001 }),
+        (TipcAddr::new_service(18888, 17, TipcScope::Cluster),
+         TipcAddrKind::Service { service_type: 18888, instance: 17, domain: 0 }),
+        (TipcAddr::new_service_range(18888, 10, 20, TipcScope::Node),
+         TipcAddrKind::ServiceRange { service_type: 18888, lower: 10, upper: 20 }),
+    ];
+    for &(addr, kind) in &addrs {
+        assert_eq!(addr.kind(), Some(kind));
+
+        let sockaddr = SockAddr::Tipc(addr);
+        assert_eq!(sockaddr.family(), AddressFamily::Tipc);
+        let (ptr, len) = sockaddr.as_ffi_pair();
+        let mut ss: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        unsafe {
+            ptr::copy_nonoverlapping(ptr as *const _ as *const u8,
+                                     &mut ss as *mut _ as *mut u8,
+                                     len as usize);
+        }
+        assert_eq!(sockaddr_storage_to_addr(&ss, len as usize).unwrap(),
+                   sockaddr);
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_tipc_bind() {
+    use nix::sys::socket::{bind, getsockname, socket, SockAddr, SockFlag,
+                           SockType, TipcAddr, TipcAddrKind, TipcScope};
+    use nix::unistd::close;
+    use std::io::Write;
+
+    let fd = match socket(AddressFamily::Tipc, SockType::Rdm,
+                          SockFlag::empty(), None) {
+        Ok(fd) => fd,
+        Err(_) => {
+            writeln!(std::io::stderr(),
+                     "TIPC is not supported. Skipping test.").unwrap();
+            return;
+        },
+    };
+
+    // Every TIPC socket has a socket address
+    match getsockname(&fd).unwrap() {
+        SockAddr::Tipc(addr) => match addr.kind() {
+            Some(TipcAddrKind::Socket { .. }) => (),
+            kind => panic!("unexpected address kind {:?}", kind),
+        },
+        addr => panic!("unexpected address {}", addr),
+    }
+
+    let service = TipcAddr::new_service_range(18888, 10, 20, TipcScope::Node);
+    bind(&fd, &SockAddr::Tipc(service)).unwrap();
+    close(fd).unwrap();
 }
 
 #[test]
@@ -161,6 +602,281 @@ pub fn test_socketpair() {
     assert_eq!(&buf[..], b"hello");
 }
 
+#[test]
+pub fn test_socket_owned() {
+    use nix::sys::socket::{AddressFamily, MsgFlags, Socket, SockFlag, SockType, sockopt};
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    let (s1, s2) = Socket::pair(AddressFamily::Unix, SockType::Stream, None,
+                                SockFlag::empty()).unwrap();
+    assert!(s1.getsockopt(sockopt::AcceptConn).is_ok());
+    s1.send(b"hello", MsgFlags::empty()).unwrap();
+    let mut buf = [0;5];
+    s2.recv(&mut buf, MsgFlags::empty()).unwrap();
+    assert_eq!(&buf[..], b"hello");
+
+    drop(s1);
+    assert_eq!(s2.recv(&mut buf, MsgFlags::empty()).unwrap(), 0);
+
+    let fd = s2.into_raw_fd();
+    let s2 = unsafe { Socket::from_raw_fd(fd) };
+    assert_eq!(s2.as_raw_fd(), fd);
+}
+
+#[test]
+pub fn test_recv_uninit() {
+    use nix::sys::socket::{bind, recvfrom_uninit, recvmsg_uninit, sendto, socket,
+                           IpAddr, MsgFlags, SockAddr, SockFlag, SockType};
+    use nix::unistd::close;
+    use std::mem::MaybeUninit;
+
+    let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let rsock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    bind(&rsock, &localhost).unwrap();
+    let raddr = getsockname(&rsock).unwrap();
+    let ssock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    bind(&ssock, &localhost).unwrap();
+    let saddr = getsockname(&ssock).unwrap();
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 1024];
+    sendto(&ssock, b"first", &raddr, MsgFlags::empty()).unwrap();
+    let (data, from) = recvfrom_uninit(&rsock, &mut buf, MsgFlags::empty()).unwrap();
+    assert_eq!(data, b"first");
+    assert_eq!(from, Some(saddr));
+
+    sendto(&ssock, b"second", &raddr, MsgFlags::empty()).unwrap();
+    let (data, msg) = recvmsg_uninit(&rsock, &mut buf, None, MsgFlags::empty()).unwrap();
+    assert_eq!(data, b"second");
+    assert_eq!(msg.bytes, 6);
+    assert_eq!(msg.address, Some(saddr));
+
+    // A truncated datagram only fills the buffer
+    sendto(&ssock, b"truncated", &raddr, MsgFlags::empty()).unwrap();
+    let mut small = [MaybeUninit::<u8>::uninit(); 5];
+    let (data, msg) = recvmsg_uninit(&rsock, &mut small, None, MsgFlags::empty())
+        .unwrap();
+    assert_eq!(data, b"trunc");
+    assert_eq!(msg.bytes, 5);
+    assert!(msg.truncated());
+
+    close(rsock).unwrap();
+    close(ssock).unwrap();
+}
+
+// With MSG_TRUNC, stream sockets discard data without writing it to the buffer
+#[test]
+pub fn test_recv_uninit_msg_trunc() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{recv, recv_uninit, recvfrom_uninit, recvmsg_uninit, send,
+                           socketpair, MsgFlags, SockFlag, SockType};
+    use nix::unistd::close;
+    use std::mem::MaybeUninit;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None,
+                                SockFlag::empty()).unwrap();
+    send(&fd1, b"hello", MsgFlags::empty()).unwrap();
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 16];
+    let einval = Err(Error::Sys(Errno::EINVAL));
+    assert_eq!(recv_uninit(&fd2, &mut buf, MsgFlags::MSG_TRUNC).map(drop), einval);
+    assert_eq!(recvfrom_uninit(&fd2, &mut buf, MsgFlags::MSG_TRUNC).map(drop), einval);
+    assert_eq!(recvmsg_uninit(&fd2, &mut buf, None, MsgFlags::MSG_TRUNC).map(drop),
+               einval);
+
+    // Nothing was consumed
+    let mut buf = [0u8; 16];
+    assert_eq!(recv(&fd2, &mut buf, MsgFlags::empty()).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+#[test]
+pub fn test_connect_nonblock_refused() {
+    use nix::errno::Errno;
+    use nix::fcntl::{fcntl, FcntlArg, OFlag};
+    use nix::poll::{poll, PollFd, PollFlags};
+    use nix::sys::socket::{connect_nonblock, finish_connect, ConnectStatus, IpAddr,
+                           SockAddr, Socket, SockFlag, SockType};
+    use std::os::unix::io::AsRawFd;
+
+    // Find a port that nobody listens on
+    let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let addr = {
+        let sock = Socket::new(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+            .unwrap();
+        sock.bind(&localhost).unwrap();
+        sock.getsockname().unwrap()
+    };
+
+    let sock = Socket::new(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    fcntl(sock.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK)).unwrap();
+    let res = match connect_nonblock(&sock, &addr) {
+        Ok(ConnectStatus::InProgress) => {
+            let mut fds = [PollFd::new(sock.as_raw_fd(), PollFlags::POLLOUT)];
+            poll(&mut fds, 10_000).unwrap();
+            finish_connect(&sock)
+        },
+        Ok(ConnectStatus::Connected) => panic!("Connected to a closed port"),
+        Err(e) => Err(e),
+    };
+    assert_eq!(res, Err(nix::Error::Sys(Errno::ECONNREFUSED)));
+    // SO_ERROR is cleared once read
+    assert_eq!(finish_connect(&sock), Ok(()));
+}
+
+#[test]
+pub fn test_socket_into_std() {
+    use nix::errno::Errno;
+    use nix::sys::socket::{IpAddr, SockAddr, Socket, SockFlag, SockType};
+    use std::convert::TryFrom;
+    use std::io::{Read, Write};
+    use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+
+    let localhost = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let tcp = || Socket::new(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+
+    let sock = tcp();
+    sock.bind(&localhost).unwrap();
+    // Not listening yet
+    let err = net::TcpListener::try_from(sock).unwrap_err();
+    assert_eq!(err.errno(), Errno::EINVAL);
+    // A failed conversion gives the socket back, still bound
+    let sock = err.into_inner();
+    assert_ne!(sock.getsockname().unwrap(), localhost);
+    drop(sock);
+
+    let sock = tcp();
+    sock.bind(&localhost).unwrap();
+    sock.listen(1).unwrap();
+    let addr = sock.getsockname().unwrap();
+    let listener = net::TcpListener::try_from(sock).unwrap();
+    let sock = tcp();
+    sock.connect(&addr).unwrap();
+    let mut client = net::TcpStream::try_from(sock).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    client.write_all(b"hello").unwrap();
+    let mut buf = [0u8; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    let sock = Socket::new(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    sock.bind(&localhost).unwrap();
+    let addr = sock.getsockname().unwrap();
+    let udp = net::UdpSocket::try_from(sock).unwrap();
+    assert_eq!(SockAddr::from(udp.local_addr().unwrap()), addr);
+
+    let sock = Socket::new(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(net::TcpStream::try_from(sock).unwrap_err().errno(), Errno::EPROTOTYPE);
+    let sock = Socket::new(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(net::TcpStream::try_from(sock).unwrap_err().errno(), Errno::EAFNOSUPPORT);
+
+    let (s1, s2) = Socket::pair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+        .unwrap();
+    let mut s1 = UnixStream::try_from(s1).unwrap();
+    let err = UnixListener::try_from(s2).unwrap_err();
+    assert_eq!(err.errno(), Errno::EINVAL);
+    // The socket that failed to convert is still open and connected
+    let mut s2 = UnixStream::try_from(err.into_inner()).unwrap();
+    s1.write_all(b"hello").unwrap();
+    s2.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+    drop(s2);
+    assert_eq!(s1.read(&mut buf).unwrap(), 0);
+
+    let (d1, d2) = Socket::pair(AddressFamily::Unix, SockType::Datagram, None,
+                                SockFlag::empty()).unwrap();
+    let d1 = UnixDatagram::try_from(d1).unwrap();
+    let d2 = UnixDatagram::try_from(d2).unwrap();
+    d1.send(b"hello").unwrap();
+    assert_eq!(d2.recv(&mut buf).unwrap(), 5);
+}
+
+#[test]
+pub fn test_socket_raw_protocol() {
+    use nix::sys::socket::{socket, socketpair, AddressFamily, SockType, SockFlag};
+    use nix::unistd::close;
+
+    // Zero selects the default protocol, like None
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, 0, SockFlag::empty())
+                     .unwrap();
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+                    libc::IPPROTO_UDP).unwrap();
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_socket_raw_protocol_gre() {
+    use nix::sys::socket::{getsockopt, socket, sockopt, AddressFamily, RawSockProtocol,
+                           SockType, SockFlag};
+    use nix::unistd::close;
+
+    require_capability!(CAP_NET_RAW);
+    let fd = socket(AddressFamily::Inet, SockType::Raw, SockFlag::empty(),
+                    libc::IPPROTO_GRE).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::SockProtocol).unwrap(),
+               RawSockProtocol(libc::IPPROTO_GRE));
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "linux",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[test]
+pub fn test_send_nosignal() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{send, socketpair, AddressFamily, MsgFlags, SockType, SockFlag};
+    use nix::unistd::close;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    close(fd2).unwrap();
+    // Without MSG_NOSIGNAL this would kill the test process with SIGPIPE
+    assert_eq!(send(&fd1, b"hello", MsgFlags::MSG_NOSIGNAL),
+               Err(Error::Sys(Errno::EPIPE)));
+    close(fd1).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_send_more_recv_waitall() {
+    use nix::sys::socket::{recv, send, socketpair, AddressFamily, MsgFlags, SockType,
+                           SockFlag};
+    use nix::unistd::close;
+    use std::thread;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let sender = thread::spawn(move || {
+        send(&fd1, b"hel", MsgFlags::MSG_MORE | MsgFlags::MSG_NOSIGNAL).unwrap();
+        thread::sleep(std::time::Duration::from_millis(10));
+        send(&fd1, b"lo", MsgFlags::MSG_NOSIGNAL).unwrap();
+        fd1
+    });
+    let mut buf = [0u8; 5];
+    assert_eq!(recv(&fd2, &mut buf, MsgFlags::MSG_WAITALL).unwrap(), 5);
+    assert_eq!(&buf[..], b"hello");
+    close(sender.join().unwrap()).unwrap();
+    close(fd2).unwrap();
+}
+
 mod recvfrom {
     use nix::Result;
     use nix::sys::socket::*;
@@ -186,7 +902,7 @@ mod recvfrom {
         });
 
         while l < std::mem::size_of_val(MSG) {
-            let (len, from_) = recvfrom(rsock, &mut buf[l..]).unwrap();
+            let (len, from_) = recvfrom(&rsock, &mut buf[l..], MsgFlags::empty()).unwrap();
             f_recv(len, from_);
             from = from_;
             l += len;
@@ -202,7 +918,7 @@ mod recvfrom {
                                     None, SockFlag::empty()).unwrap();
         // Ignore from for stream sockets
         let _ = sendrecv(fd1, fd2, |s, m, flags| {
-            send(s, m, flags)
+            send(&s, m, flags)
         }, |_, _| {});
     }
 
@@ -216,7 +932,7 @@ mod recvfrom {
             SockFlag::empty(),
             None
         ).unwrap();
-        bind(rsock, &sock_addr).unwrap();
+        bind(&rsock, &sock_addr).unwrap();
         let ssock = socket(
             AddressFamily::Inet,
             SockType::Datagram,
@@ -224,12 +940,51 @@ mod recvfrom {
             None,
         ).expect("send socket failed");
         let from = sendrecv(rsock, ssock, move |s, m, flags| {
-            sendto(s, m, &sock_addr, flags)
+            sendto(&s, m, &sock_addr, flags)
         },|_, _| {});
         // UDP sockets should set the from address
         assert_eq!(AddressFamily::Inet, from.unwrap().family());
     }
 
+    #[test]
+    pub fn udp_flags() {
+        use nix::Error;
+        use nix::errno::Errno;
+
+        let std_sa = SocketAddr::from_str("127.0.0.1:6800").unwrap();
+        let inet_addr = InetAddr::from_std(&std_sa);
+        let sock_addr = SockAddr::new_inet(inet_addr);
+        let rsock = socket(AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::empty(),
+            None
+        ).unwrap();
+        bind(&rsock, &sock_addr).unwrap();
+        let ssock = socket(
+            AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::empty(),
+            None,
+        ).expect("send socket failed");
+
+        let mut buf = [0u8; 13];
+        assert_eq!(recvfrom(&rsock, &mut buf, MsgFlags::MSG_DONTWAIT).err(),
+                   Some(Error::Sys(Errno::EAGAIN)));
+
+        sendto(&ssock, MSG, &sock_addr, MsgFlags::empty()).unwrap();
+
+        // Peeking leaves the datagram in the receive queue
+        let (len, from) = recvfrom(&rsock, &mut buf, MsgFlags::MSG_PEEK).unwrap();
+        assert_eq!(&buf[..len], MSG);
+        assert_eq!(AddressFamily::Inet, from.unwrap().family());
+
+        let mut buf = [0u8; 13];
+        let (len, from) = recvfrom(&rsock, &mut buf, MsgFlags::MSG_DONTWAIT)
+            .unwrap();
+        assert_eq!(&buf[..len], MSG);
+        assert_eq!(AddressFamily::Inet, from.unwrap().family());
+    }
+
     #[cfg(target_os = "linux")]
     mod udp_offload {
         use super::*;
@@ -254,10 +1009,10 @@ mod recvfrom {
                                None
             ).unwrap();
 
-            setsockopt(rsock, UdpGsoSegment, &(segment_size as _))
+            setsockopt(&rsock, UdpGsoSegment, &(segment_size as _))
                 .expect("setsockopt UDP_SEGMENT failed");
 
-            bind(rsock, &sock_addr).unwrap();
+            bind(&rsock, &sock_addr).unwrap();
             let ssock = socket(
                 AddressFamily::Inet,
                 SockType::Datagram,
@@ -270,7 +1025,7 @@ mod recvfrom {
             sendrecv(rsock, ssock, move |s, m, flags| {
                 let iov = [IoVec::from_slice(m)];
                 let cmsg = ControlMessage::UdpGsoSegments(&segment_size);
-                sendmsg(s, &iov, &[cmsg], flags, Some(&sock_addr))
+                sendmsg(&s, &iov, &[cmsg], flags, Some(&sock_addr))
             }, {
                 let num_packets_received_ref = &mut num_packets_received;
 
@@ -300,8 +1055,51 @@ mod recvfrom {
                                None
             ).unwrap();
 
-            setsockopt(rsock, UdpGroSegment, &true)
+            setsockopt(&rsock, UdpGroSegment, &true)
                 .expect("setsockopt UDP_GRO failed");
+            assert!(getsockopt(&rsock, UdpGroSegment).unwrap());
+        }
+
+        #[test]
+        pub fn gro_cmsg() {
+            require_kernel_version!(udp_offload::gro_cmsg, ">= 5.3");
+
+            let segment_size: u16 = 2;
+            let std_sa = SocketAddr::from_str("127.0.0.1:6792").unwrap();
+            let inet_addr = InetAddr::from_std(&std_sa);
+            let sock_addr = SockAddr::new_inet(inet_addr);
+            let rsock = socket(AddressFamily::Inet,
+                               SockType::Datagram,
+                               SockFlag::empty(),
+                               None
+            ).unwrap();
+            setsockopt(&rsock, UdpGroSegment, &true)
+                .expect("setsockopt UDP_GRO failed");
+            bind(&rsock, &sock_addr).unwrap();
+            let ssock = socket(AddressFamily::Inet,
+                               SockType::Datagram,
+                               SockFlag::empty(),
+                               None
+            ).unwrap();
+
+            let iov = [IoVec::from_slice(b"abcdefgh")];
+            let cmsg = ControlMessage::UdpGsoSegments(&segment_size);
+            sendmsg(&ssock, &iov, &[cmsg], MsgFlags::empty(), Some(&sock_addr))
+                .unwrap();
+
+            let mut buf = [0u8; 8];
+            let iov = [IoVec::from_mut_slice(&mut buf)];
+            let mut cmsgspace = cmsg_space!(libc::c_int);
+            let msg = recvmsg(&rsock, &iov, Some(&mut cmsgspace), MsgFlags::empty())
+                .unwrap();
+            // Over loopback, the GSO segments are delivered as a single
+            // coalesced datagram
+            assert_eq!(msg.bytes, 8);
+            match msg.cmsgs().next() {
+                Some(ControlMessageOwned::UdpGroSegments(size)) =>
+                    assert_eq!(size, segment_size),
+                cmsg => panic!("unexpected control message {:?}", cmsg),
+            }
         }
     }
 
@@ -327,7 +1125,7 @@ mod recvfrom {
             SockFlag::empty(),
             None
         ).unwrap();
-        bind(rsock, &sock_addr).unwrap();
+        bind(&rsock, &sock_addr).unwrap();
         let ssock = socket(
             AddressFamily::Inet,
             SockType::Datagram,
@@ -358,7 +1156,7 @@ mod recvfrom {
                     }
                 );
             }
-            sendmmsg(s, msgs.iter(), flags)
+            sendmmsg(&s, msgs.iter(), flags)
                 .map(move |sent_bytes| {
                     assert!(sent_bytes.len() >= 1);
                     for sent in &sent_bytes {
@@ -394,7 +1192,7 @@ mod recvfrom {
             SockFlag::empty(),
             None
         ).unwrap();
-        bind(rsock, &sock_addr).unwrap();
+        bind(&rsock, &sock_addr).unwrap();
         let ssock = socket(
             AddressFamily::Inet,
             SockType::Datagram,
@@ -404,7 +1202,7 @@ mod recvfrom {
 
         let send_thread = thread::spawn(move || {
             for _ in 0..NUM_MESSAGES_SENT {
-                sendto(ssock, &DATA[..], &sock_addr, MsgFlags::empty()).unwrap();
+                sendto(&ssock, &DATA[..], &sock_addr, MsgFlags::empty()).unwrap();
             }
         });
 
@@ -423,7 +1221,7 @@ mod recvfrom {
             })
         };
 
-        let res = recvmmsg(rsock, &mut msgs, MsgFlags::empty(), None).expect("recvmmsg");
+        let res = recvmmsg(&rsock, &mut msgs, MsgFlags::empty(), None).expect("recvmmsg");
         assert_eq!(res.len(), DATA.len());
 
         for RecvMsg { address, bytes, .. } in res.into_iter() {
@@ -437,6 +1235,160 @@ mod recvfrom {
 
         send_thread.join().unwrap();
     }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "netbsd",
+    ))]
+    #[test]
+    pub fn udp_recvmmsg_dontwait_short_read() {
+        use nix::sys::uio::IoVec;
+        use nix::sys::socket::{MsgFlags, recvmmsg};
+
+        const NUM_MESSAGES_SENT: usize = 2;
+        const DATA: [u8; 4] = [1,2,3,5];
+
+        let std_sa = SocketAddr::from_str("127.0.0.1:6799").unwrap();
+        let inet_addr = InetAddr::from_std(&std_sa);
+        let sock_addr = SockAddr::new_inet(inet_addr);
+
+        let rsock = socket(AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::empty(),
+            None
+        ).unwrap();
+        bind(&rsock, &sock_addr).unwrap();
+        let ssock = socket(
+            AddressFamily::Inet,
+            SockType::Datagram,
+            SockFlag::empty(),
+            None,
+        ).expect("send socket failed");
+
+        let send_thread = thread::spawn(move || {
+            for _ in 0..NUM_MESSAGES_SENT {
+                sendto(&ssock, &DATA[..], &sock_addr, MsgFlags::empty()).unwrap();
+            }
+        });
+        // Ensure we've sent all the messages before continuing so `recvmmsg`
+        // will return right away
+        send_thread.join().unwrap();
+
+        let mut msgs = std::collections::LinkedList::new();
+
+        // Buffers to receive >`NUM_MESSAGES_SENT` messages to ensure `recvmmsg`
+        // will return when there are fewer than requested messages in the
+        // kernel buffers when using `MSG_DONTWAIT`.
+        let mut receive_buffers = [[0u8; 32]; NUM_MESSAGES_SENT + 2];
+        let iovs: Vec<_> = receive_buffers.iter_mut().map(|buf| {
+            [IoVec::from_mut_slice(&mut buf[..])]
+        }).collect();
+
+        for iov in &iovs {
+            msgs.push_back(RecvMmsgData {
+                iov: iov,
+                cmsg_buffer: None,
+            })
+        };
+
+        let res = recvmmsg(&rsock, &mut msgs, MsgFlags::MSG_DONTWAIT, None).expect("recvmmsg");
+        assert_eq!(res.len(), NUM_MESSAGES_SENT);
+
+        for RecvMsg { address, bytes, .. } in res.into_iter() {
+            assert_eq!(AddressFamily::Inet, address.unwrap().family());
+            assert_eq!(DATA.len(), bytes);
+        }
+
+        for buf in &receive_buffers[..NUM_MESSAGES_SENT] {
+            assert_eq!(&buf[..DATA.len()], DATA);
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_recv_truncated() {
+    use nix::sys::socket::{recv_trunc, recvmsg, send, socketpair, AddressFamily, MsgFlags,
+                           SockFlag, SockType};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Datagram, None,
+                                SockFlag::empty()).unwrap();
+
+    send(&fd1, b"0123456789", MsgFlags::empty()).unwrap();
+    let mut buf = [0u8; 4];
+    assert_eq!(recv_trunc(&fd2, &mut buf, MsgFlags::empty()).unwrap(), (4, 10));
+    assert_eq!(&buf, b"0123");
+
+    send(&fd1, b"0123456789", MsgFlags::empty()).unwrap();
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    let msg = recvmsg(&fd2, &iov, None, MsgFlags::MSG_TRUNC).unwrap();
+    assert!(msg.truncated());
+    assert_eq!(msg.bytes, 10);
+
+    send(&fd1, b"01", MsgFlags::empty()).unwrap();
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    let msg = recvmsg(&fd2, &iov, None, MsgFlags::empty()).unwrap();
+    assert!(!msg.truncated());
+    assert_eq!(msg.bytes, 2);
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+// Stream sockets would discard the data without copying it
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_recv_trunc_stream() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{recv, recv_trunc, send, socketpair, AddressFamily, MsgFlags,
+                           SockFlag, SockType};
+    use nix::unistd::close;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None,
+                                SockFlag::empty()).unwrap();
+
+    send(&fd1, b"0123456789", MsgFlags::empty()).unwrap();
+    let mut buf = [0u8; 4];
+    assert_eq!(recv_trunc(&fd2, &mut buf, MsgFlags::empty()),
+               Err(Error::Sys(Errno::EOPNOTSUPP)));
+    let mut buf = [0u8; 16];
+    assert_eq!(recv(&fd2, &mut buf, MsgFlags::empty()).unwrap(), 10);
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+#[test]
+pub fn test_recvmsg_cmsg_truncated() {
+    use nix::sys::socket::{recvmsg, sendmsg, socketpair, AddressFamily, ControlMessage,
+                           MsgFlags, SockFlag, SockType};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Datagram, None,
+                                SockFlag::empty()).unwrap();
+
+    let iov = [IoVec::from_slice(b"x")];
+    let fds = [fd1];
+    let cmsg = [ControlMessage::ScmRights(&fds)];
+    sendmsg(&fd1, &iov, &cmsg, MsgFlags::empty(), None).unwrap();
+
+    let mut buf = [0u8; 1];
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    // No room for the control message, so the kernel must drop it
+    let msg = recvmsg(&fd2, &iov, None, MsgFlags::empty()).unwrap();
+    assert!(msg.cmsg_truncated());
+    assert!(!msg.truncated());
+    assert!(!msg.out_of_band());
+    assert_eq!(msg.cmsgs().next(), None);
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
 }
 
 // Test error handling of our recvmsg wrapper
@@ -450,15 +1402,107 @@ pub fn test_recvmsg_ebadf() {
     let mut buf = [0u8; 5];
     let iov = [IoVec::from_mut_slice(&mut buf[..])];
     let fd = -1;    // Bad file descriptor
-    let r = recvmsg(fd, &iov, None, MsgFlags::empty());
+    let r = recvmsg(&fd, &iov, None, MsgFlags::empty());
     assert_eq!(r.err().unwrap(), Error::Sys(Errno::EBADF));
 }
 
-// Disable the test on emulated platforms due to a bug in QEMU versions <
-// 2.12.0.  https://bugs.launchpad.net/qemu/+bug/1701808
-#[cfg_attr(not(any(target_arch = "x86_64", target_arch="i686")), ignore)]
+// Disable the test on emulated platforms due to a bug in QEMU versions <
+// 2.12.0.  https://bugs.launchpad.net/qemu/+bug/1701808
+#[cfg_attr(not(any(target_arch = "x86_64", target_arch="i686")), ignore)]
+#[test]
+pub fn test_scm_rights() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{pipe, read, write, close};
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, ControlMessageOwned, MsgFlags};
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let (r, w) = pipe().unwrap();
+    let mut received_r: Option<RawFd> = None;
+
+    {
+        let iov = [IoVec::from_slice(b"hello")];
+        let fds = [r];
+        let cmsg = ControlMessage::ScmRights(&fds);
+        assert_eq!(sendmsg(&fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+        close(r).unwrap();
+        close(fd1).unwrap();
+    }
+
+    {
+        let mut buf = [0u8; 5];
+        let iov = [IoVec::from_mut_slice(&mut buf[..])];
+        let mut cmsgspace = cmsg_space!([RawFd; 1]);
+        let msg = recvmsg(&fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+
+        for cmsg in msg.cmsgs() {
+            if let ControlMessageOwned::ScmRights(fd) = cmsg {
+                assert_eq!(received_r, None);
+                assert_eq!(fd.len(), 1);
+                received_r = Some(fd[0]);
+            } else {
+                panic!("unexpected cmsg");
+            }
+        }
+        assert_eq!(msg.bytes, 5);
+        assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+        close(fd2).unwrap();
+    }
+
+    let received_r = received_r.expect("Did not receive passed fd");
+    // Ensure that the received file descriptor works
+    write(w, b"world").unwrap();
+    let mut buf = [0u8; 5];
+    read(received_r, &mut buf).unwrap();
+    assert_eq!(&buf[..], b"world");
+    close(received_r).unwrap();
+    close(w).unwrap();
+}
+
+// Receive a number of file descriptors that is only known at runtime
+#[test]
+pub fn test_scm_rights_runtime_space() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{pipe, close};
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg, cmsg_space_len,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, ControlMessageOwned, MsgFlags};
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let (r, w) = pipe().unwrap();
+    let fds: Vec<RawFd> = vec![r, w, r];
+
+    let iov = [IoVec::from_slice(b"hello")];
+    let cmsg = ControlMessage::ScmRights(&fds);
+    assert_eq!(sendmsg(&fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace = Vec::with_capacity(cmsg_space_len::<RawFd>(fds.len()));
+    let msg = recvmsg(&fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+    assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+
+    let mut received = Vec::new();
+    for cmsg in msg.cmsgs() {
+        if let ControlMessageOwned::ScmRights(fd) = cmsg {
+            received.extend(fd);
+        } else {
+            panic!("unexpected cmsg");
+        }
+    }
+    assert_eq!(received.len(), fds.len());
+
+    for fd in received.into_iter().chain(vec![r, w, fd1, fd2]) {
+        close(fd).unwrap();
+    }
+}
+
+// Every descriptor of a single SCM_RIGHTS message should arrive, in order
 #[test]
-pub fn test_scm_rights() {
+pub fn test_scm_rights_many_fds() {
     use nix::sys::uio::IoVec;
     use nix::unistd::{pipe, read, write, close};
     use nix::sys::socket::{socketpair, sendmsg, recvmsg,
@@ -467,46 +1511,73 @@ pub fn test_scm_rights() {
 
     let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
                      .unwrap();
-    let (r, w) = pipe().unwrap();
-    let mut received_r: Option<RawFd> = None;
+    let pipes: Vec<(RawFd, RawFd)> = (0..4).map(|_| pipe().unwrap()).collect();
+    let wfds: Vec<RawFd> = pipes.iter().map(|&(_, w)| w).collect();
 
-    {
-        let iov = [IoVec::from_slice(b"hello")];
-        let fds = [r];
-        let cmsg = ControlMessage::ScmRights(&fds);
-        assert_eq!(sendmsg(fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+    let iov = [IoVec::from_slice(b"hello")];
+    let cmsg = ControlMessage::ScmRights(&wfds);
+    sendmsg(&fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap();
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace = cmsg_space!([RawFd; 4]);
+    let msg = recvmsg(&fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+    let received = match msg.cmsgs().next() {
+        Some(ControlMessageOwned::ScmRights(fds)) => fds,
+        other => panic!("unexpected cmsg {:?}", other),
+    };
+    assert_eq!(received.len(), 4);
+
+    // Each received fd is the write end of the corresponding pipe
+    for (i, (&(r, w), &fd)) in pipes.iter().zip(received.iter()).enumerate() {
+        write(fd, &[i as u8]).unwrap();
+        let mut byte = [0u8; 1];
+        read(r, &mut byte).unwrap();
+        assert_eq!(byte[0], i as u8);
+        close(fd).unwrap();
         close(r).unwrap();
-        close(fd1).unwrap();
+        close(w).unwrap();
     }
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
 
-    {
-        let mut buf = [0u8; 5];
-        let iov = [IoVec::from_mut_slice(&mut buf[..])];
-        let mut cmsgspace = cmsg_space!([RawFd; 1]);
-        let msg = recvmsg(fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+#[test]
+pub fn test_send_recv_fds() {
+    use nix::unistd::{pipe, read, write, close};
+    use nix::sys::socket::{socketpair, send_fds, recv_fds,
+                           AddressFamily, SockType, SockFlag};
 
-        for cmsg in msg.cmsgs() {
-            if let ControlMessageOwned::ScmRights(fd) = cmsg {
-                assert_eq!(received_r, None);
-                assert_eq!(fd.len(), 1);
-                received_r = Some(fd[0]);
-            } else {
-                panic!("unexpected cmsg");
-            }
-        }
-        assert_eq!(msg.bytes, 5);
-        assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
-        close(fd2).unwrap();
-    }
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let (r, w) = pipe().unwrap();
 
-    let received_r = received_r.expect("Did not receive passed fd");
-    // Ensure that the received file descriptor works
-    write(w, b"world").unwrap();
+    assert_eq!(send_fds(&fd1, &[r, w], b"hello").unwrap(), 5);
     let mut buf = [0u8; 5];
-    read(received_r, &mut buf).unwrap();
+    let (len, received) = recv_fds(&fd2, &mut buf, 2).unwrap();
+    assert_eq!(&buf[..len], b"hello");
+    assert_eq!(received.len(), 2);
+
+    // Ensure that the received file descriptors work
+    write(received[1], b"world").unwrap();
+    read(received[0], &mut buf).unwrap();
     assert_eq!(&buf[..], b"world");
-    close(received_r).unwrap();
-    close(w).unwrap();
+
+    // Plain data arrives without any descriptors
+    assert_eq!(send_fds(&fd1, &[], b"hello").unwrap(), 5);
+    let (len, none) = recv_fds(&fd2, &mut buf, 2).unwrap();
+    assert_eq!(len, 5);
+    assert!(none.is_empty());
+
+    // Descriptors beyond max_fds are dropped, but the data still arrives
+    send_fds(&fd1, &[r, w, r], b"hello").unwrap();
+    let (len, truncated) = recv_fds(&fd2, &mut buf, 1).unwrap();
+    assert_eq!(&buf[..len], b"hello");
+    assert_eq!(truncated.len(), 1);
+
+    for fd in received.into_iter().chain(truncated).chain(vec![r, w, fd1, fd2]) {
+        close(fd).unwrap();
+    }
 }
 
 // Disable the test on emulated platforms due to not enabled support of AF_ALG in QEMU from rust cross
@@ -541,7 +1612,7 @@ pub fn test_af_alg_cipher() {
         .expect("socket failed");
 
     let sockaddr = SockAddr::new_alg(alg_type, alg_name);
-    bind(sock, &sockaddr).expect("bind failed");
+    bind(&sock, &sockaddr).expect("bind failed");
 
     if let SockAddr::Alg(alg) = sockaddr {
         assert_eq!(alg.alg_name().to_string_lossy(), alg_name);
@@ -550,12 +1621,12 @@ pub fn test_af_alg_cipher() {
         panic!("unexpected SockAddr");
     }
 
-    setsockopt(sock, AlgSetKey::default(), &key).expect("setsockopt");
-    let session_socket = accept(sock).expect("accept failed");
+    setsockopt(&sock, AlgSetKey::default(), &key).expect("setsockopt");
+    let session_socket = accept(&sock).expect("accept failed");
 
     let msgs = [ControlMessage::AlgSetOp(&libc::ALG_OP_ENCRYPT), ControlMessage::AlgSetIv(iv.as_slice())];
     let iov = IoVec::from_slice(&payload);
-    sendmsg(session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg encrypt");
+    sendmsg(&session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg encrypt");
 
     // allocate buffer for encrypted data
     let mut encrypted = vec![0u8; payload_len];
@@ -567,7 +1638,7 @@ pub fn test_af_alg_cipher() {
     let iv = vec![1u8; iv_len];
 
     let msgs = [ControlMessage::AlgSetOp(&libc::ALG_OP_DECRYPT), ControlMessage::AlgSetIv(iv.as_slice())];
-    sendmsg(session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg decrypt");
+    sendmsg(&session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg decrypt");
 
     // allocate buffer for decrypted data
     let mut decrypted = vec![0u8; payload_len];
@@ -622,18 +1693,18 @@ pub fn test_af_alg_aead() {
         .expect("socket failed");
 
     let sockaddr = SockAddr::new_alg(alg_type, alg_name);
-    bind(sock, &sockaddr).expect("bind failed");
+    bind(&sock, &sockaddr).expect("bind failed");
 
-    setsockopt(sock, AlgSetAeadAuthSize, &auth_size).expect("setsockopt AlgSetAeadAuthSize");
-    setsockopt(sock, AlgSetKey::default(), &key).expect("setsockopt AlgSetKey");
-    let session_socket = accept(sock).expect("accept failed");
+    setsockopt(&sock, AlgSetAeadAuthSize, &auth_size).expect("setsockopt AlgSetAeadAuthSize");
+    setsockopt(&sock, AlgSetKey::default(), &key).expect("setsockopt AlgSetKey");
+    let session_socket = accept(&sock).expect("accept failed");
 
     let msgs = [
         ControlMessage::AlgSetOp(&ALG_OP_ENCRYPT),
         ControlMessage::AlgSetIv(iv.as_slice()),
         ControlMessage::AlgSetAeadAssoclen(&assoc_size)];
     let iov = IoVec::from_slice(&payload);
-    sendmsg(session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg encrypt");
+    sendmsg(&session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg encrypt");
 
     // allocate buffer for encrypted data
     let mut encrypted = vec![0u8; (assoc_size as usize) + payload_len + auth_size];
@@ -649,14 +1720,14 @@ pub fn test_af_alg_aead() {
 
     let iv = vec![1u8; iv_len];
 
-    let session_socket = accept(sock).expect("accept failed");
+    let session_socket = accept(&sock).expect("accept failed");
 
     let msgs = [
         ControlMessage::AlgSetOp(&ALG_OP_DECRYPT),
         ControlMessage::AlgSetIv(iv.as_slice()),
         ControlMessage::AlgSetAeadAssoclen(&assoc_size),
     ];
-    sendmsg(session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg decrypt");
+    sendmsg(&session_socket, &[iov], &msgs, MsgFlags::empty(), None).expect("sendmsg decrypt");
 
     // allocate buffer for decrypted data
     let mut decrypted = vec![0u8; payload_len + (assoc_size as usize) + auth_size];
@@ -673,7 +1744,8 @@ pub fn test_af_alg_aead() {
 // This would be a more interesting test if we could assume that the test host
 // has more than one IP address (since we could select a different address to
 // test from).
-#[cfg(any(target_os = "linux",
+#[cfg(any(target_os = "android",
+        target_os = "linux",
         target_os = "macos",
         target_os = "netbsd"))]
 #[test]
@@ -693,7 +1765,7 @@ pub fn test_sendmsg_ipv4packetinfo() {
     let inet_addr = InetAddr::from_std(&std_sa);
     let sock_addr = SockAddr::new_inet(inet_addr);
 
-    bind(sock, &sock_addr).expect("bind failed");
+    bind(&sock, &sock_addr).expect("bind failed");
 
     let slice = [1u8, 2, 3, 4, 5, 6, 7, 8];
     let iov = [IoVec::from_slice(&slice)];
@@ -707,7 +1779,7 @@ pub fn test_sendmsg_ipv4packetinfo() {
 
         let cmsg = [ControlMessage::Ipv4PacketInfo(&pi)];
 
-        sendmsg(sock, &iov, &cmsg, MsgFlags::empty(), Some(&sock_addr))
+        sendmsg(&sock, &iov, &cmsg, MsgFlags::empty(), Some(&sock_addr))
             .expect("sendmsg");
     } else {
         panic!("No IPv4 addresses available for testing?");
@@ -722,7 +1794,8 @@ pub fn test_sendmsg_ipv4packetinfo() {
 // This would be a more interesting test if we could assume that the test host
 // has more than one IP address (since we could select a different address to
 // test from).
-#[cfg(any(target_os = "linux",
+#[cfg(any(target_os = "android",
+        target_os = "linux",
         target_os = "macos",
         target_os = "netbsd",
         target_os = "freebsd"))]
@@ -745,7 +1818,7 @@ pub fn test_sendmsg_ipv6packetinfo() {
     let inet_addr = InetAddr::from_std(&std_sa);
     let sock_addr = SockAddr::new_inet(inet_addr);
 
-    match bind(sock, &sock_addr) {
+    match bind(&sock, &sock_addr) {
         Err(Error::Sys(Errno::EADDRNOTAVAIL)) => {
             println!("IPv6 not available, skipping test.");
             return;
@@ -764,7 +1837,7 @@ pub fn test_sendmsg_ipv6packetinfo() {
 
         let cmsg = [ControlMessage::Ipv6PacketInfo(&pi)];
 
-        sendmsg(sock, &iov, &cmsg, MsgFlags::empty(), Some(&sock_addr))
+        sendmsg(&sock, &iov, &cmsg, MsgFlags::empty(), Some(&sock_addr))
             .expect("sendmsg");
     } else {
         println!("No IPv6 addresses available for testing: skipping testing Ipv6PacketInfo");
@@ -778,7 +1851,7 @@ pub fn test_sendmsg_ipv6packetinfo() {
 #[test]
 fn test_scm_rights_single_cmsg_multiple_fds() {
     use std::os::unix::net::UnixDatagram;
-    use std::os::unix::io::{RawFd, AsRawFd};
+    use std::os::unix::io::RawFd;
     use std::thread;
     use nix::sys::socket::{ControlMessage, ControlMessageOwned, MsgFlags,
         sendmsg, recvmsg};
@@ -791,7 +1864,7 @@ fn test_scm_rights_single_cmsg_multiple_fds() {
         let iovec = [IoVec::from_mut_slice(&mut buf)];
         let mut space = cmsg_space!([RawFd; 2]);
         let msg = recvmsg(
-            receive.as_raw_fd(),
+            &receive,
             &iovec,
             Some(&mut space),
             MsgFlags::empty()
@@ -817,7 +1890,7 @@ fn test_scm_rights_single_cmsg_multiple_fds() {
     let iov = [IoVec::from_slice(&slice)];
     let fds = [libc::STDIN_FILENO, libc::STDOUT_FILENO];    // pass stdin and stdout
     let cmsg = [ControlMessage::ScmRights(&fds)];
-    sendmsg(send.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None).unwrap();
+    sendmsg(&send, &iov, &cmsg, MsgFlags::empty(), None).unwrap();
     thread.join().unwrap();
 }
 
@@ -837,7 +1910,7 @@ pub fn test_sendmsg_empty_cmsgs() {
 
     {
         let iov = [IoVec::from_slice(b"hello")];
-        assert_eq!(sendmsg(fd1, &iov, &[], MsgFlags::empty(), None).unwrap(), 5);
+        assert_eq!(sendmsg(&fd1, &iov, &[], MsgFlags::empty(), None).unwrap(), 5);
         close(fd1).unwrap();
     }
 
@@ -845,11 +1918,9 @@ pub fn test_sendmsg_empty_cmsgs() {
         let mut buf = [0u8; 5];
         let iov = [IoVec::from_mut_slice(&mut buf[..])];
         let mut cmsgspace = cmsg_space!([RawFd; 1]);
-        let msg = recvmsg(fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+        let msg = recvmsg(&fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
 
-        for _ in msg.cmsgs() {
-            panic!("unexpected cmsg");
-        }
+        assert!(msg.cmsgs().next().is_none(), "unexpected cmsg");
         assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
         assert_eq!(msg.bytes, 5);
         close(fd2).unwrap();
@@ -876,7 +1947,7 @@ fn test_scm_credentials() {
     let (send, recv) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
         .unwrap();
     #[cfg(any(target_os = "android", target_os = "linux"))]
-    setsockopt(recv, PassCred, &true).unwrap();
+    setsockopt(&recv, PassCred, &true).unwrap();
 
     {
         let iov = [IoVec::from_slice(b"hello")];
@@ -886,7 +1957,7 @@ fn test_scm_credentials() {
         let cmsg = ControlMessage::ScmCredentials(&cred);
         #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
         let cmsg = ControlMessage::ScmCreds;
-        assert_eq!(sendmsg(send, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+        assert_eq!(sendmsg(&send, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
         close(send).unwrap();
     }
 
@@ -894,7 +1965,7 @@ fn test_scm_credentials() {
         let mut buf = [0u8; 5];
         let iov = [IoVec::from_mut_slice(&mut buf[..])];
         let mut cmsgspace = cmsg_space!(UnixCredentials);
-        let msg = recvmsg(recv, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+        let msg = recvmsg(&recv, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
         let mut received_cred = None;
 
         for cmsg in msg.cmsgs() {
@@ -918,6 +1989,48 @@ fn test_scm_credentials() {
     }
 }
 
+/// Credentials are received without the sender's cooperation once the
+/// receiver asks for them.
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+#[test]
+fn test_scm_credentials_passive() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{getpid, getuid};
+    use nix::sys::socket::{socketpair, send, recvmsg, setsockopt, getsockopt,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessageOwned, MsgFlags, UnixCredentials};
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    use nix::sys::socket::sockopt::PassCred as PassOpt;
+    #[cfg(target_os = "freebsd")]
+    use nix::sys::socket::sockopt::LocalCredsPersistent as PassOpt;
+
+    let (send_fd, recv) = socketpair(AddressFamily::Unix, SockType::Datagram, None,
+                                     SockFlag::empty()).unwrap();
+    assert!(!getsockopt(&recv, PassOpt).unwrap());
+    setsockopt(&recv, PassOpt, &true).unwrap();
+    assert!(getsockopt(&recv, PassOpt).unwrap());
+
+    assert_eq!(send(&send_fd, b"hello", MsgFlags::empty()).unwrap(), 5);
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    // FreeBSD's sockcred2 is larger than cmsgcred
+    let mut cmsgspace = cmsg_space!(UnixCredentials, [libc::gid_t; 16]);
+    let msg = recvmsg(&recv, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+    let cred = match msg.cmsgs().next() {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        Some(ControlMessageOwned::ScmCredentials(cred)) => cred,
+        #[cfg(target_os = "freebsd")]
+        Some(ControlMessageOwned::ScmCreds(cred)) => {
+            assert_eq!(cred.groups()[0], nix::unistd::getegid().as_raw());
+            cred
+        }
+        other => panic!("unexpected cmsg {:?}", other),
+    };
+    assert_eq!(cred.pid(), getpid().as_raw());
+    assert_eq!(cred.uid(), getuid().as_raw());
+}
+
 /// Ensure that we can send `SCM_CREDENTIALS` and `SCM_RIGHTS` with a single
 /// `sendmsg` call.
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -956,7 +2069,7 @@ fn test_impl_scm_credentials_and_rights(mut space: Vec<u8>) {
 
     let (send, recv) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
         .unwrap();
-    setsockopt(recv, PassCred, &true).unwrap();
+    setsockopt(&recv, PassCred, &true).unwrap();
 
     let (r, w) = pipe().unwrap();
     let mut received_r: Option<RawFd> = None;
@@ -973,7 +2086,7 @@ fn test_impl_scm_credentials_and_rights(mut space: Vec<u8>) {
             ControlMessage::ScmCredentials(&cred),
             ControlMessage::ScmRights(&fds),
         ];
-        assert_eq!(sendmsg(send, &iov, &cmsgs, MsgFlags::empty(), None).unwrap(), 5);
+        assert_eq!(sendmsg(&send, &iov, &cmsgs, MsgFlags::empty(), None).unwrap(), 5);
         close(r).unwrap();
         close(send).unwrap();
     }
@@ -981,7 +2094,7 @@ fn test_impl_scm_credentials_and_rights(mut space: Vec<u8>) {
     {
         let mut buf = [0u8; 5];
         let iov = [IoVec::from_mut_slice(&mut buf[..])];
-        let msg = recvmsg(recv, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
+        let msg = recvmsg(&recv, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
         let mut received_cred = None;
 
         assert_eq!(msg.cmsgs().count(), 2, "expected 2 cmsgs");
@@ -1032,18 +2145,18 @@ pub fn test_unixdomain() {
     let s1 = socket(AddressFamily::Unix, SockType::Stream,
                     SockFlag::empty(), None).expect("socket failed");
     let sockaddr = SockAddr::new_unix(&sockname).unwrap();
-    bind(s1, &sockaddr).expect("bind failed");
-    listen(s1, 10).expect("listen failed");
+    bind(&s1, &sockaddr).expect("bind failed");
+    listen(&s1, 10).expect("listen failed");
 
     let thr = thread::spawn(move || {
         let s2 = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None)
                  .expect("socket failed");
-        connect(s2, &sockaddr).expect("connect failed");
+        connect(&s2, &sockaddr).expect("connect failed");
         write(s2, b"hello").expect("write failed");
         close(s2).unwrap();
     });
 
-    let s3 = accept(s1).expect("accept failed");
+    let s3 = accept(&s1).expect("accept failed");
 
     let mut buf = [0;5];
     read(s3, &mut buf).unwrap();
@@ -1054,22 +2167,108 @@ pub fn test_unixdomain() {
     assert_eq!(&buf[..], b"hello");
 }
 
+#[test]
+pub fn test_accept_addr() {
+    use nix::sys::socket::{SockType, SockFlag};
+    use nix::sys::socket::{accept_addr, bind, connect, getpeername, listen,
+                           socket, IpAddr, SockAddr};
+    use nix::unistd::close;
+
+    let listener = socket(AddressFamily::Inet, SockType::Stream,
+                          SockFlag::empty(), None).expect("socket failed");
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&listener, &SockAddr::new_inet(localhost)).expect("bind failed");
+    listen(&listener, 1).expect("listen failed");
+
+    let client = socket(AddressFamily::Inet, SockType::Stream,
+                        SockFlag::empty(), None).expect("socket failed");
+    connect(&client, &getsockname(&listener).unwrap()).expect("connect failed");
+
+    let (server, peer) = accept_addr(&listener, SockFlag::empty())
+        .expect("accept_addr failed");
+    assert_eq!(peer, getsockname(&client).unwrap());
+    assert_eq!(peer, getpeername(&server).unwrap());
+
+    close(server).unwrap();
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "linux",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[test]
+pub fn test_accept4_flags() {
+    use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+    use nix::sys::socket::{SockType, SockFlag};
+    use nix::sys::socket::{accept4, bind, connect, listen, socket, IpAddr,
+                           SockAddr};
+    use nix::unistd::close;
+
+    let listener = socket(AddressFamily::Inet, SockType::Stream,
+                          SockFlag::empty(), None).expect("socket failed");
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&listener, &SockAddr::new_inet(localhost)).expect("bind failed");
+    listen(&listener, 1).expect("listen failed");
+
+    let client = socket(AddressFamily::Inet, SockType::Stream,
+                        SockFlag::empty(), None).expect("socket failed");
+    connect(&client, &getsockname(&listener).unwrap()).expect("connect failed");
+
+    let server = accept4(&listener, SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK)
+        .expect("accept4 failed");
+    let fdflags = FdFlag::from_bits_truncate(
+        fcntl(server, FcntlArg::F_GETFD).unwrap());
+    assert!(fdflags.contains(FdFlag::FD_CLOEXEC));
+    let oflags = OFlag::from_bits_truncate(
+        fcntl(server, FcntlArg::F_GETFL).unwrap());
+    assert!(oflags.contains(OFlag::O_NONBLOCK));
+
+    close(server).unwrap();
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
 // Test creating and using named system control sockets
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 #[test]
 pub fn test_syscontrol() {
     use nix::Error;
     use nix::errno::Errno;
-    use nix::sys::socket::{socket, SockAddr, SockType, SockFlag, SockProtocol};
+    use nix::sys::socket::{connect_sys_control, socket, SockAddr, SockType, SockFlag,
+                           SysControlAddr, SysProtocol};
 
     let fd = socket(AddressFamily::System, SockType::Datagram,
-                    SockFlag::empty(), SockProtocol::KextControl)
+                    SockFlag::empty(), SysProtocol::KextControl)
              .expect("socket failed");
     let _sockaddr = SockAddr::new_sys_control(fd, "com.apple.net.utun_control", 0).expect("resolving sys_control name failed");
     assert_eq!(SockAddr::new_sys_control(fd, "foo.bar.lol", 0).err(), Some(Error::Sys(Errno::ENOENT)));
+    let addr = SysControlAddr::from_name(fd, "com.apple.net.utun_control", 3).unwrap();
+    assert_eq!(addr.unit(), 3);
+    assert_eq!(connect_sys_control(&fd, "foo.bar.lol", 0).err(),
+               Some(Error::Sys(Errno::ENOENT)));
 
     // requires root privileges
-    // connect(fd, &sockaddr).expect("connect failed");
+    // connect(&fd, &sockaddr).expect("connect failed");
+}
+
+// Connecting with unit 0 returns the unit picked by the kernel
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[test]
+pub fn test_connect_sys_control() {
+    use nix::sys::socket::{connect_sys_control, socket, SockType, SockFlag, SysProtocol};
+    use nix::unistd::close;
+
+    skip_if_not_root!("test_connect_sys_control");
+
+    let fd = socket(AddressFamily::System, SockType::Datagram,
+                    SockFlag::empty(), SysProtocol::KextControl).unwrap();
+    let addr = connect_sys_control(&fd, "com.apple.net.utun_control", 0).unwrap();
+    assert!(addr.unit() >= 1);
+    close(fd).unwrap();
 }
 
 #[cfg(any(
@@ -1155,9 +2354,9 @@ pub fn test_recv_ipv4pktinfo() {
             SockFlag::empty(),
             None,
         ).expect("receive socket failed");
-    bind(receive, &lo).expect("bind failed");
-    let sa = getsockname(receive).expect("getsockname failed");
-    setsockopt(receive, Ipv4PacketInfo, &true).expect("setsockopt failed");
+    bind(&receive, &lo).expect("bind failed");
+    let sa = getsockname(&receive).expect("getsockname failed");
+    setsockopt(&receive, Ipv4PacketInfo, &true).expect("setsockopt failed");
 
     {
         let slice = [1u8, 2, 3, 4, 5, 6, 7, 8];
@@ -1169,7 +2368,7 @@ pub fn test_recv_ipv4pktinfo() {
             SockFlag::empty(),
             None,
         ).expect("send socket failed");
-        sendmsg(send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
+        sendmsg(&send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
     }
 
     {
@@ -1177,7 +2376,7 @@ pub fn test_recv_ipv4pktinfo() {
         let iovec = [IoVec::from_mut_slice(&mut buf)];
         let mut space = cmsg_space!(libc::in_pktinfo);
         let msg = recvmsg(
-            receive,
+            &receive,
             &iovec,
             Some(&mut space),
             MsgFlags::empty(),
@@ -1245,10 +2444,10 @@ pub fn test_recvif() {
         SockFlag::empty(),
         None,
     ).expect("receive socket failed");
-    bind(receive, &lo).expect("bind failed");
-    let sa = getsockname(receive).expect("getsockname failed");
-    setsockopt(receive, Ipv4RecvIf, &true).expect("setsockopt IP_RECVIF failed");
-    setsockopt(receive, Ipv4RecvDstAddr, &true).expect("setsockopt IP_RECVDSTADDR failed");
+    bind(&receive, &lo).expect("bind failed");
+    let sa = getsockname(&receive).expect("getsockname failed");
+    setsockopt(&receive, Ipv4RecvIf, &true).expect("setsockopt IP_RECVIF failed");
+    setsockopt(&receive, Ipv4RecvDstAddr, &true).expect("setsockopt IP_RECVDSTADDR failed");
 
     {
         let slice = [1u8, 2, 3, 4, 5, 6, 7, 8];
@@ -1260,7 +2459,7 @@ pub fn test_recvif() {
             SockFlag::empty(),
             None,
         ).expect("send socket failed");
-        sendmsg(send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
+        sendmsg(&send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
     }
 
     {
@@ -1268,7 +2467,7 @@ pub fn test_recvif() {
         let iovec = [IoVec::from_mut_slice(&mut buf)];
         let mut space = cmsg_space!(libc::sockaddr_dl, libc::in_addr);
         let msg = recvmsg(
-            receive,
+            &receive,
             &iovec,
             Some(&mut space),
             MsgFlags::empty(),
@@ -1356,9 +2555,9 @@ pub fn test_recv_ipv6pktinfo() {
         SockFlag::empty(),
         None,
     ).expect("receive socket failed");
-    bind(receive, &lo).expect("bind failed");
-    let sa = getsockname(receive).expect("getsockname failed");
-    setsockopt(receive, Ipv6RecvPacketInfo, &true).expect("setsockopt failed");
+    bind(&receive, &lo).expect("bind failed");
+    let sa = getsockname(&receive).expect("getsockname failed");
+    setsockopt(&receive, Ipv6RecvPacketInfo, &true).expect("setsockopt failed");
 
     {
         let slice = [1u8, 2, 3, 4, 5, 6, 7, 8];
@@ -1370,7 +2569,7 @@ pub fn test_recv_ipv6pktinfo() {
             SockFlag::empty(),
             None,
         ).expect("send socket failed");
-        sendmsg(send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
+        sendmsg(&send, &iov, &[], MsgFlags::empty(), Some(&sa)).expect("sendmsg failed");
     }
 
     {
@@ -1378,7 +2577,7 @@ pub fn test_recv_ipv6pktinfo() {
         let iovec = [IoVec::from_mut_slice(&mut buf)];
         let mut space = cmsg_space!(libc::in6_pktinfo);
         let msg = recvmsg(
-            receive,
+            &receive,
             &iovec,
             Some(&mut space),
             MsgFlags::empty(),
@@ -1411,6 +2610,152 @@ pub fn test_recv_ipv6pktinfo() {
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_tos_tclass() {
+    use nix::sys::socket::sockopt::{Ipv4RecvTos, Ipv4Tos, Ipv6RecvTClass, Ipv6TClass};
+    use nix::sys::socket::{getsockopt, recvmsg, sendmsg, setsockopt};
+    use nix::sys::socket::{ControlMessage, ControlMessageOwned, MsgFlags, SockAddr};
+    use nix::sys::uio::IoVec;
+    use std::net::UdpSocket;
+
+    fn check(local: &str) {
+        let receive = match UdpSocket::bind(local) {
+            Ok(sock) => sock,
+            // No IPv6 support
+            Err(_) => return,
+        };
+        let sa = SockAddr::new_inet(InetAddr::from_std(&receive.local_addr().unwrap()));
+        let send = UdpSocket::bind(local).unwrap();
+        let v4 = receive.local_addr().unwrap().is_ipv4();
+        if v4 {
+            setsockopt(&send, Ipv4Tos, &0x28).unwrap();
+            assert_eq!(getsockopt(&send, Ipv4Tos).unwrap(), 0x28);
+            setsockopt(&receive, Ipv4RecvTos, &true).unwrap();
+            assert!(getsockopt(&receive, Ipv4RecvTos).unwrap());
+        } else {
+            setsockopt(&send, Ipv6TClass, &0x28).unwrap();
+            assert_eq!(getsockopt(&send, Ipv6TClass).unwrap(), 0x28);
+            setsockopt(&receive, Ipv6RecvTClass, &true).unwrap();
+            assert!(getsockopt(&receive, Ipv6RecvTClass).unwrap());
+        }
+
+        let (tos, tclass) = (0x48u8, 0x48i32);
+        for &with_cmsg in &[false, true] {
+            let iov = [IoVec::from_slice(b"hi")];
+            let cmsgs = match (with_cmsg, v4) {
+                (false, _) => vec![],
+                (true, true) => vec![ControlMessage::Ipv4Tos(&tos)],
+                (true, false) => vec![ControlMessage::Ipv6TClass(&tclass)],
+            };
+            sendmsg(&send, &iov, &cmsgs, MsgFlags::empty(), Some(&sa)).unwrap();
+
+            let mut buf = [0u8; 2];
+            let iov = [IoVec::from_mut_slice(&mut buf)];
+            let mut space = cmsg_space!(libc::c_int);
+            let msg = recvmsg(&receive, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
+            let expected = if with_cmsg { 0x48 } else { 0x28 };
+            match msg.cmsgs().next() {
+                Some(ControlMessageOwned::Ipv4Tos(t)) if v4 => assert_eq!(t, expected),
+                Some(ControlMessageOwned::Ipv6TClass(t)) if !v4 => {
+                    assert_eq!(t, i32::from(expected))
+                }
+                cmsg => panic!("Unexpected control message {:?}", cmsg),
+            }
+        }
+    }
+
+    check("127.0.0.1:0");
+    check("[::1]:0");
+}
+
+#[test]
+pub fn test_ttl_hops() {
+    use nix::sys::socket::sockopt::{IpTtl, Ipv6UnicastHops};
+    use nix::sys::socket::{getsockopt, setsockopt};
+    use std::net::UdpSocket;
+
+    let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+    setsockopt(&sock, IpTtl, &3).unwrap();
+    assert_eq!(getsockopt(&sock, IpTtl).unwrap(), 3);
+
+    if let Ok(sock) = UdpSocket::bind("[::1]:0") {
+        setsockopt(&sock, Ipv6UnicastHops, &7).unwrap();
+        assert_eq!(getsockopt(&sock, Ipv6UnicastHops).unwrap(), 7);
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_recv_ttl() {
+    use nix::sys::socket::sockopt::{IpTtl, Ipv4RecvTtl};
+    use nix::sys::socket::{recvmsg, sendmsg, setsockopt};
+    use nix::sys::socket::{ControlMessage, ControlMessageOwned, MsgFlags, SockAddr};
+    use nix::sys::uio::IoVec;
+    use std::net::UdpSocket;
+
+    let receive = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sa = SockAddr::new_inet(InetAddr::from_std(&receive.local_addr().unwrap()));
+    setsockopt(&receive, Ipv4RecvTtl, &true).unwrap();
+    let send = UdpSocket::bind("127.0.0.1:0").unwrap();
+    setsockopt(&send, IpTtl, &5).unwrap();
+
+    let ttl = 9;
+    for &(cmsgs, expected) in &[(&[][..], 5), (&[ControlMessage::IpTtl(&ttl)][..], 9)] {
+        let iov = [IoVec::from_slice(b"hi")];
+        sendmsg(&send, &iov, cmsgs, MsgFlags::empty(), Some(&sa)).unwrap();
+
+        let mut buf = [0u8; 2];
+        let iov = [IoVec::from_mut_slice(&mut buf)];
+        let mut space = cmsg_space!(libc::c_int);
+        let msg = recvmsg(&receive, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
+        match msg.cmsgs().next() {
+            Some(ControlMessageOwned::IpTtl(t)) => assert_eq!(t, expected),
+            cmsg => panic!("Unexpected control message {:?}", cmsg),
+        }
+    }
+}
+
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+#[test]
+pub fn test_recv_hoplimit() {
+    use nix::sys::socket::sockopt::{Ipv6RecvHopLimit, Ipv6UnicastHops};
+    use nix::sys::socket::{recvmsg, sendmsg, setsockopt};
+    use nix::sys::socket::{ControlMessage, ControlMessageOwned, MsgFlags, SockAddr};
+    use nix::sys::uio::IoVec;
+    use std::net::UdpSocket;
+
+    let receive = match UdpSocket::bind("[::1]:0") {
+        Ok(sock) => sock,
+        // No IPv6 support
+        Err(_) => return,
+    };
+    let sa = SockAddr::new_inet(InetAddr::from_std(&receive.local_addr().unwrap()));
+    setsockopt(&receive, Ipv6RecvHopLimit, &true).unwrap();
+    let send = UdpSocket::bind("[::1]:0").unwrap();
+    setsockopt(&send, Ipv6UnicastHops, &5).unwrap();
+
+    let hops = 255;
+    for &(cmsgs, expected) in &[(&[][..], 5), (&[ControlMessage::Ipv6HopLimit(&hops)][..], 255)] {
+        let iov = [IoVec::from_slice(b"hi")];
+        sendmsg(&send, &iov, cmsgs, MsgFlags::empty(), Some(&sa)).unwrap();
+
+        let mut buf = [0u8; 2];
+        let iov = [IoVec::from_mut_slice(&mut buf)];
+        let mut space = cmsg_space!(libc::c_int);
+        let msg = recvmsg(&receive, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
+        match msg.cmsgs().next() {
+            Some(ControlMessageOwned::Ipv6HopLimit(h)) => assert_eq!(h, expected),
+            cmsg => panic!("Unexpected control message {:?}", cmsg),
+        }
+    }
+}
+
 #[cfg(target_os = "linux")]
 #[test]
 pub fn test_vsock() {
@@ -1431,17 +2776,18 @@ pub fn test_vsock() {
     // VMADDR_CID_HYPERVISOR and VMADDR_CID_RESERVED are reserved, so we expect
     // an EADDRNOTAVAIL error.
     let sockaddr = SockAddr::new_vsock(libc::VMADDR_CID_HYPERVISOR, port);
-    assert_eq!(bind(s1, &sockaddr).err(),
+    assert_eq!(bind(&s1, &sockaddr).err(),
                Some(Error::Sys(Errno::EADDRNOTAVAIL)));
 
     let sockaddr = SockAddr::new_vsock(libc::VMADDR_CID_RESERVED, port);
-    assert_eq!(bind(s1, &sockaddr).err(),
+    assert_eq!(bind(&s1, &sockaddr).err(),
                Some(Error::Sys(Errno::EADDRNOTAVAIL)));
 
 
     let sockaddr = SockAddr::new_vsock(libc::VMADDR_CID_ANY, port);
-    assert_eq!(bind(s1, &sockaddr), Ok(()));
-    listen(s1, 10).expect("listen failed");
+    assert_eq!(bind(&s1, &sockaddr), Ok(()));
+    assert_eq!(getsockname(&s1).unwrap(), sockaddr);
+    listen(&s1, 10).expect("listen failed");
 
     let thr = thread::spawn(move || {
         let cid: u32 = libc::VMADDR_CID_HOST;
@@ -1454,7 +2800,7 @@ pub fn test_vsock() {
 
         // The current implementation does not support loopback devices, so,
         // for now, we expect a failure on the connect.
-        assert_ne!(connect(s2, &sockaddr), Ok(()));
+        assert_ne!(connect(&s2, &sockaddr), Ok(()));
 
         close(s2).unwrap();
     });
@@ -1462,3 +2808,407 @@ pub fn test_vsock() {
     close(s1).unwrap();
     thr.join().unwrap();
 }
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_netlink_addr_groups() {
+    use nix::sys::socket::NetlinkAddr;
+
+    let addr = NetlinkAddr::with_groups(0, &[1, 3, 32]);
+    assert_eq!(addr.groups(), 0x8000_0005);
+    assert!(addr.has_group(1));
+    assert!(!addr.has_group(2));
+    assert!(addr.has_group(32));
+    assert!(!addr.has_group(33));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_netlink_membership() {
+    use nix::sys::socket::{bind, getsockopt, setsockopt, socket, sockopt};
+    use nix::sys::socket::{NetlinkAddr, NetlinkProtocol, SockAddr, SockFlag, SockType};
+    use nix::unistd::close;
+
+    // Unprivileged users may join NETLINK_ROUTE's groups
+    let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                    NetlinkProtocol::Route)
+        .unwrap();
+    let group = libc::RTNLGRP_LINK as u32;
+    bind(&fd, &SockAddr::Netlink(NetlinkAddr::with_groups(0, &[group]))).unwrap();
+    setsockopt(&fd, sockopt::NetlinkAddMembership, &(libc::RTNLGRP_IPV4_IFADDR as u32)).unwrap();
+    setsockopt(&fd, sockopt::NetlinkDropMembership, &group).unwrap();
+
+    setsockopt(&fd, sockopt::NetlinkPacketInfo, &true).unwrap();
+    assert!(getsockopt(&fd, sockopt::NetlinkPacketInfo).unwrap());
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_af_packet_bind() {
+    use nix::net::if_::if_nametoindex;
+    use nix::sys::socket::{bind, socket, EthProtocol, LinkAddr, SockAddr, SockFlag,
+                           SockType};
+    use nix::unistd::close;
+
+    require_capability!(CAP_NET_RAW);
+
+    let fd = socket(AddressFamily::Packet, SockType::Raw, SockFlag::empty(),
+                    EthProtocol::All)
+        .expect("socket failed");
+    let ifindex = if_nametoindex("lo").unwrap() as usize;
+    let protocol = EthProtocol::All as u16;
+    bind(&fd, &SockAddr::Link(LinkAddr::new(protocol, ifindex))).unwrap();
+
+    match getsockname(&fd).unwrap() {
+        SockAddr::Link(addr) => {
+            assert_eq!(addr.family(), AddressFamily::Packet);
+            assert_eq!(addr.protocol(), protocol);
+            assert_eq!(addr.ifindex(), ifindex);
+        },
+        addr => panic!("unexpected address {:?}", addr),
+    }
+    close(fd).unwrap();
+}
+
+// The kernel returns AF_PACKET addresses shorter than sockaddr_ll
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_af_packet_sockaddr_storage() {
+    use nix::net::if_::if_nametoindex;
+    use nix::sys::socket::{bind, socket, EthProtocol, LinkAddr, SockAddr, SockaddrLike,
+                           SockaddrStorage, SockFlag, SockType};
+    use nix::unistd::close;
+    use std::mem;
+
+    require_capability!(CAP_NET_RAW);
+
+    let fd = socket(AddressFamily::Packet, SockType::Raw, SockFlag::empty(),
+                    EthProtocol::All)
+        .expect("socket failed");
+    let ifindex = if_nametoindex("lo").unwrap() as usize;
+    let protocol = EthProtocol::All as u16;
+    bind(&fd, &SockAddr::Link(LinkAddr::new(protocol, ifindex))).unwrap();
+
+    let mut ss: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of_val(&ss) as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockname(fd, &mut ss as *mut _ as *mut libc::sockaddr, &mut len)
+    };
+    assert_eq!(res, 0);
+    assert!((len as usize) < mem::size_of::<libc::sockaddr_ll>());
+
+    let storage = unsafe {
+        SockaddrStorage::from_raw(&ss as *const _ as *const libc::sockaddr, Some(len))
+    }.unwrap();
+    let addr = storage.as_link_addr().unwrap();
+    assert_eq!(addr.protocol(), protocol);
+    assert_eq!(addr.ifindex(), ifindex);
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_netlink_protocols() {
+    use nix::sys::socket::{socket, NetlinkProtocol, SockFlag, SockType};
+    use nix::unistd::close;
+
+    for &proto in &[NetlinkProtocol::Route,
+                    NetlinkProtocol::KObjectUEvent,
+                    NetlinkProtocol::SockDiag,
+                    NetlinkProtocol::Generic] {
+        let fd = socket(AddressFamily::Netlink, SockType::Raw,
+                        SockFlag::empty(), proto)
+            .unwrap();
+        close(fd).unwrap();
+    }
+}
+
+// Send an echo request over an unprivileged ICMP socket
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_icmp_ping() {
+    use nix::errno::Errno;
+    use nix::Error;
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+    use std::io::Write;
+
+    let sock = match socket(AddressFamily::Inet, SockType::Datagram,
+                            SockFlag::empty(), SockProtocol::Icmp) {
+        Ok(fd) => fd,
+        // Our group is outside of net.ipv4.ping_group_range
+        Err(Error::Sys(Errno::EACCES)) => {
+            writeln!(std::io::stderr(),
+                     "Ping sockets are disabled. Skipping test.").unwrap();
+            return;
+        },
+        Err(e) => panic!("socket failed: {}", e),
+    };
+    let localhost = SockAddr::new_inet(
+        InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+
+    // ICMP echo request with sequence number 1.  The kernel fills in the
+    // identifier and the checksum.
+    let request = [8u8, 0, 0, 0, 0, 0, 0, 1, b'n', b'i', b'x'];
+    assert_eq!(sendto(&sock, &request, &localhost, MsgFlags::empty()).unwrap(),
+               request.len());
+
+    let mut reply = [0u8; 64];
+    let (len, from) = recvfrom(&sock, &mut reply, MsgFlags::empty()).unwrap();
+    assert_eq!(len, request.len());
+    // Echo reply
+    assert_eq!(reply[0], 0);
+    assert_eq!(&reply[6..len], &request[6..]);
+    assert_eq!(from, Some(localhost));
+
+    close(sock).unwrap();
+}
+
+// Exchange a message over one-to-many style SCTP sockets
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_sctp_sndrcv() {
+    use nix::sys::socket::*;
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use std::io::Write;
+
+    let server = match socket(AddressFamily::Inet, SockType::SeqPacket,
+                              SockFlag::empty(), SockProtocol::Sctp) {
+        Ok(fd) => fd,
+        Err(_) => {
+            writeln!(std::io::stderr(),
+                     "SCTP is not supported. Skipping test.").unwrap();
+            return;
+        },
+    };
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&server, &SockAddr::new_inet(localhost)).unwrap();
+    listen(&server, 1).unwrap();
+    let server_addr = getsockname(&server).unwrap();
+
+    let events = SctpEventSubscribe { data_io: 1, ..Default::default() };
+    setsockopt(&server, sockopt::SctpEvents, &events).unwrap();
+    assert_eq!(getsockopt(&server, sockopt::SctpEvents).unwrap(), events);
+
+    let client = socket(AddressFamily::Inet, SockType::SeqPacket,
+                        SockFlag::empty(), SockProtocol::Sctp)
+        .unwrap();
+    setsockopt(&client, sockopt::SctpNodelay, &true).unwrap();
+    assert!(getsockopt(&client, sockopt::SctpNodelay).unwrap());
+    let initmsg = SctpInitMsg { num_ostreams: 2, max_instreams: 2,
+                                ..Default::default() };
+    setsockopt(&client, sockopt::SctpInitMsg, &initmsg).unwrap();
+
+    let info = SctpSndRcvInfo { stream: 1, ppid: 42u32.to_be(),
+                                ..Default::default() };
+    assert_eq!(sctp_sendmsg(&client, b"hello", Some(&server_addr), &info,
+                            MsgFlags::empty()).unwrap(), 5);
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace = cmsg_space!(SctpSndRcvInfo);
+    let msg = recvmsg(&server, &iov, Some(&mut cmsgspace), MsgFlags::empty())
+        .unwrap();
+    assert_eq!(msg.bytes, 5);
+    match msg.cmsgs().next() {
+        Some(ControlMessageOwned::SctpSndRcv(received)) => {
+            assert_eq!(received.stream, 1);
+            assert_eq!(u32::from_be(received.ppid), 42);
+        },
+        other => panic!("unexpected cmsg {:?}", other),
+    }
+    assert_eq!(&buf, b"hello");
+
+    close(client).unwrap();
+    close(server).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_recv_timestampns() {
+    use nix::sys::socket::*;
+    use nix::sys::socket::sockopt::ReceiveTimestampns;
+    use nix::sys::time::TimeSpec;
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram,
+                      SockFlag::empty(), None)
+        .expect("socket failed");
+    setsockopt(&sock, ReceiveTimestampns, &true).unwrap();
+    assert!(getsockopt(&sock, ReceiveTimestampns).unwrap());
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&sock, &SockAddr::new_inet(localhost)).unwrap();
+    let address = getsockname(&sock).unwrap();
+
+    let time0 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let iov = [IoVec::from_slice(b"hello")];
+    sendmsg(&sock, &iov, &[], MsgFlags::empty(), Some(&address)).unwrap();
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    let mut cmsgspace = cmsg_space!(TimeSpec);
+    let msg = recvmsg(&sock, &iov, Some(&mut cmsgspace), MsgFlags::empty())
+        .unwrap();
+    let rtime = match msg.cmsgs().next() {
+        Some(ControlMessageOwned::ScmTimestampns(rtime)) => rtime,
+        Some(_) => panic!("Unexpected control message"),
+        None => panic!("No control message")
+    };
+    let time1 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    // The packet's received timestamp should lie in-between the two system
+    // times, unless the system clock was adjusted in the meantime.
+    let rduration = Duration::new(rtime.tv_sec() as u64,
+                                  rtime.tv_nsec() as u32);
+    assert!(time0 <= rduration);
+    assert!(rduration <= time1);
+    close(sock).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_timestamping() {
+    use nix::sys::socket::*;
+    use nix::sys::socket::sockopt::Timestamping;
+    use nix::sys::time::{TimeSpec, TimeValLike};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use std::thread;
+    use std::time::Duration;
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram,
+                      SockFlag::empty(), None)
+        .expect("socket failed");
+    let flags = TimestampingFlag::SOF_TIMESTAMPING_SOFTWARE |
+                TimestampingFlag::SOF_TIMESTAMPING_RX_SOFTWARE;
+    setsockopt(&sock, Timestamping, &flags).unwrap();
+    assert_eq!(getsockopt(&sock, Timestamping).unwrap(), flags);
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&sock, &SockAddr::new_inet(localhost)).unwrap();
+    let address = getsockname(&sock).unwrap();
+
+    // The kernel turns on receive timestamps asynchronously, so the first
+    // datagrams may still arrive without one.
+    let mut timestamps = None;
+    for _ in 0..100 {
+        let iov = [IoVec::from_slice(b"hello")];
+        sendmsg(&sock, &iov, &[], MsgFlags::empty(), Some(&address)).unwrap();
+
+        let mut buf = [0u8; 5];
+        let iov = [IoVec::from_mut_slice(&mut buf)];
+        let mut cmsgspace = cmsg_space!([TimeSpec; 3]);
+        let msg = recvmsg(&sock, &iov, Some(&mut cmsgspace), MsgFlags::empty())
+            .unwrap();
+        match msg.cmsgs().next() {
+            Some(ControlMessageOwned::ScmTimestamping(ts)) => {
+                timestamps = Some(ts);
+                break;
+            }
+            Some(_) => panic!("Unexpected control message"),
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    let timestamps = timestamps.expect("No control message");
+    // Only a software timestamp was requested
+    assert!(timestamps[0].num_nanoseconds() > 0);
+    assert_eq!(timestamps[2].num_nanoseconds(), 0);
+    close(sock).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_ipv4_recverr() {
+    use nix::errno::Errno;
+    use nix::sys::socket::*;
+    use nix::sys::socket::sockopt::Ipv4RecvErr;
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    // Find a port that nobody is listening on
+    let probe = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), None)
+        .unwrap();
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&probe, &SockAddr::new_inet(localhost)).unwrap();
+    let closed_addr = getsockname(&probe).unwrap();
+    close(probe).unwrap();
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram,
+                      SockFlag::empty(), None)
+        .unwrap();
+    setsockopt(&sock, Ipv4RecvErr, &true).unwrap();
+    assert!(getsockopt(&sock, Ipv4RecvErr).unwrap());
+    sendto(&sock, b"hello", &closed_addr, MsgFlags::empty()).unwrap();
+
+    let mut buf = [0u8; 8];
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    let mut cmsgspace = cmsg_space!(libc::sock_extended_err, libc::sockaddr_in);
+    let msg = recvmsg(&sock, &iov, Some(&mut cmsgspace),
+                      MsgFlags::MSG_ERRQUEUE | MsgFlags::MSG_DONTWAIT)
+        .unwrap();
+    match msg.cmsgs().next() {
+        Some(ControlMessageOwned::Ipv4RecvErr(err, addr)) => {
+            assert_eq!(err.ee_errno as i32, Errno::ECONNREFUSED as i32);
+            assert_eq!(err.ee_origin, libc::SO_EE_ORIGIN_ICMP);
+            // ICMP destination/port unreachable
+            assert_eq!(err.ee_type, 3);
+            assert_eq!(err.ee_code, 3);
+            let addr = addr.expect("no offender address");
+            assert_eq!(u32::from_be(addr.sin_addr.s_addr), 0x7f00_0001);
+        },
+        cmsg => panic!("unexpected control message {:?}", cmsg),
+    }
+    close(sock).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_ipv6_flowlabel() {
+    use nix::sys::socket::sockopt::{Ipv6FlowInfo, Ipv6FlowInfoSend, Ipv6FlowLabelMgr};
+    use nix::sys::socket::{recvmsg, sendmsg, setsockopt};
+    use nix::sys::socket::{ControlMessageOwned, FlowLabelReq, FlowLabelShare, MsgFlags,
+                           SockAddr};
+    use nix::sys::uio::IoVec;
+    use std::net::UdpSocket;
+
+    let receive = match UdpSocket::bind("[::1]:0") {
+        Ok(sock) => sock,
+        // No IPv6 support
+        Err(_) => return,
+    };
+    setsockopt(&receive, Ipv6FlowInfo, &true).unwrap();
+    let send = UdpSocket::bind("[::1]:0").unwrap();
+
+    let label = 0x1_2345;
+    let dst = match receive.local_addr().unwrap() {
+        SocketAddr::V6(sa) => sa,
+        _ => unreachable!(),
+    };
+    let req = FlowLabelReq::get(&nix::sys::socket::Ipv6Addr::from_std(dst.ip()), label,
+                                FlowLabelShare::Exclusive);
+    assert_eq!(req.label(), label);
+    setsockopt(&send, Ipv6FlowLabelMgr, &req).unwrap();
+    setsockopt(&send, Ipv6FlowInfoSend, &true).unwrap();
+
+    // sin6_flowinfo is in network byte order
+    let dst = SocketAddrV6::new(*dst.ip(), dst.port(), label.to_be(), 0);
+    let sa = SockAddr::new_inet(InetAddr::from_std(&SocketAddr::V6(dst)));
+    let iov = [IoVec::from_slice(b"hi")];
+    sendmsg(&send, &iov, &[], MsgFlags::empty(), Some(&sa)).unwrap();
+
+    let mut buf = [0u8; 2];
+    let iov = [IoVec::from_mut_slice(&mut buf)];
+    let mut space = cmsg_space!(u32);
+    let msg = recvmsg(&receive, &iov, Some(&mut space), MsgFlags::empty()).unwrap();
+    match msg.cmsgs().next() {
+        Some(ControlMessageOwned::Ipv6FlowInfo(info)) => {
+            assert_eq!(info & libc::IPV6_FLOWINFO_FLOWLABEL as u32, label)
+        }
+        cmsg => panic!("Unexpected control message {:?}", cmsg),
+    }
+
+    setsockopt(&send, Ipv6FlowLabelMgr, &FlowLabelReq::put(label)).unwrap();
+}
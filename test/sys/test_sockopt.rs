@@ -9,8 +9,8 @@ fn is_so_mark_functional() {
     require_capability!(CAP_NET_ADMIN);
 
     let s = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None).unwrap();
-    setsockopt(s, sockopt::Mark, &1337).unwrap();
-    let mark = getsockopt(s, sockopt::Mark).unwrap();
+    setsockopt(&s, sockopt::Mark, &1337).unwrap();
+    let mark = getsockopt(&s, sockopt::Mark).unwrap();
     assert_eq!(mark, 1337);
 }
 
@@ -19,14 +19,29 @@ fn test_so_buf() {
     let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
              .unwrap();
     let bufsize: usize = thread_rng().gen_range(4096, 131_072);
-    setsockopt(fd, sockopt::SndBuf, &bufsize).unwrap();
-    let actual = getsockopt(fd, sockopt::SndBuf).unwrap();
+    setsockopt(&fd, sockopt::SndBuf, &bufsize).unwrap();
+    let actual = getsockopt(&fd, sockopt::SndBuf).unwrap();
     assert!(actual >= bufsize);
-    setsockopt(fd, sockopt::RcvBuf, &bufsize).unwrap();
-    let actual = getsockopt(fd, sockopt::RcvBuf).unwrap();
+    setsockopt(&fd, sockopt::RcvBuf, &bufsize).unwrap();
+    let actual = getsockopt(&fd, sockopt::RcvBuf).unwrap();
     assert!(actual >= bufsize);
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_so_buf_force() {
+    require_capability!(CAP_NET_ADMIN);
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
+             .unwrap();
+    // Larger than any sensible rmem_max and wmem_max
+    let bufsize: usize = 64 << 20;
+    setsockopt(&fd, sockopt::SndBufForce, &bufsize).unwrap();
+    assert!(getsockopt(&fd, sockopt::SndBuf).unwrap() >= bufsize);
+    setsockopt(&fd, sockopt::RcvBufForce, &bufsize).unwrap();
+    assert!(getsockopt(&fd, sockopt::RcvBuf).unwrap() >= bufsize);
+}
+
 // The CI doesn't supported getsockopt and setsockopt on emulated processors.
 // It's beleived that a QEMU issue, the tests run ok on a fully emulated system.
 // Current CI just run the binary with QEMU but the Kernel remains the same as the host.
@@ -34,36 +49,694 @@ fn test_so_buf() {
 #[test]
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    any(target_os = "freebsd", target_os = "linux")
+    any(target_os = "android", target_os = "freebsd", target_os = "linux")
 ))]
 fn test_tcp_congestion() {
     use std::ffi::OsString;
 
     let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None).unwrap();
 
-    let val = getsockopt(fd, sockopt::TcpCongestion).unwrap();
-    setsockopt(fd, sockopt::TcpCongestion, &val).unwrap();
+    let val = getsockopt(&fd, sockopt::TcpCongestion).unwrap();
+    setsockopt(&fd, sockopt::TcpCongestion, &val).unwrap();
 
-    setsockopt(fd, sockopt::TcpCongestion, &OsString::from("tcp_congestion_does_not_exist")).unwrap_err();
+    setsockopt(&fd, sockopt::TcpCongestion, &OsString::from("tcp_congestion_does_not_exist")).unwrap_err();
 
     assert_eq!(
-        getsockopt(fd, sockopt::TcpCongestion).unwrap(),
+        getsockopt(&fd, sockopt::TcpCongestion).unwrap(),
         val
     );
+
+    // The name must not include the kernel's null padding
+    let reno = OsString::from("reno");
+    setsockopt(&fd, sockopt::TcpCongestion, &reno).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::TcpCongestion).unwrap(), reno);
 }
 
 #[test]
 #[cfg(any(target_os = "android", target_os = "linux"))]
 fn test_bindtodevice() {
+    use std::ffi::OsString;
+
     skip_if_not_root!("test_bindtodevice");
 
     let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None).unwrap();
 
-    let val = getsockopt(fd, sockopt::BindToDevice).unwrap();
-    setsockopt(fd, sockopt::BindToDevice, &val).unwrap();
+    let val = getsockopt(&fd, sockopt::BindToDevice).unwrap();
+    setsockopt(&fd, sockopt::BindToDevice, &val).unwrap();
 
     assert_eq!(
-        getsockopt(fd, sockopt::BindToDevice).unwrap(),
+        getsockopt(&fd, sockopt::BindToDevice).unwrap(),
         val
     );
+
+    let lo = OsString::from("lo");
+    setsockopt(&fd, sockopt::BindToDevice, &lo).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::BindToDevice).unwrap(), lo);
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+fn test_setfib() {
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+
+    // The default FIB always exists
+    setsockopt(&fd, sockopt::SetFib, &0).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::SetFib).unwrap(), 0);
+    nix::unistd::close(fd).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+fn test_user_cookie() {
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+
+    setsockopt(&fd, sockopt::UserCookie, &0xdead_beef).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::UserCookie).unwrap(), 0xdead_beef);
+    nix::unistd::close(fd).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_tcp_info() {
+    use nix::sys::socket::{accept, bind, connect, getsockname, listen,
+                           InetAddr, IpAddr, SockAddr};
+    use nix::unistd::close;
+
+    let listener = socket(AddressFamily::Inet, SockType::Stream,
+                          SockFlag::empty(), None)
+        .unwrap();
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&listener, &SockAddr::new_inet(localhost)).unwrap();
+    listen(&listener, 1).unwrap();
+    let addr = getsockname(&listener).unwrap();
+
+    let client = socket(AddressFamily::Inet, SockType::Stream,
+                        SockFlag::empty(), None)
+        .unwrap();
+    // TCP_CLOSE
+    let info = getsockopt(&client, sockopt::TcpInfo).unwrap();
+    assert_eq!(info.state(), 7);
+
+    connect(&client, &addr).unwrap();
+    let server = accept(&listener).unwrap();
+    // TCP_ESTABLISHED
+    let info = getsockopt(&client, sockopt::TcpInfo).unwrap();
+    assert_eq!(info.state(), 1);
+    assert!(info.snd_mss() > 0);
+    assert!(info.snd_cwnd() > 0);
+    assert_eq!(info.total_retrans(), 0);
+
+    close(server).unwrap();
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_ip_freebind() {
+    use nix::sys::socket::{bind, InetAddr, IpAddr, SockAddr};
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    assert!(!getsockopt(&fd, sockopt::IpFreebind).unwrap());
+    setsockopt(&fd, sockopt::IpFreebind, &true).unwrap();
+    assert!(getsockopt(&fd, sockopt::IpFreebind).unwrap());
+
+    // A TEST-NET-3 address, which is not assigned to any local interface
+    let addr = InetAddr::new(IpAddr::new_v4(203, 0, 113, 1), 0);
+    bind(&fd, &SockAddr::new_inet(addr)).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_ip_transparent() {
+    require_capability!(CAP_NET_ADMIN);
+
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    setsockopt(&fd, sockopt::IpTransparent, &true).unwrap();
+    assert!(getsockopt(&fd, sockopt::IpTransparent).unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_source_specific_multicast() {
+    use nix::net::if_::if_nametoindex;
+    use nix::sys::socket::{GroupSourceRequest, InetAddr, IpAddr, Ipv4Addr,
+                           IpSourceMembershipRequest};
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    let group = Ipv4Addr::new(232, 1, 1, 1);
+    let source = Ipv4Addr::new(127, 0, 0, 1);
+    let lo = Some(Ipv4Addr::new(127, 0, 0, 1));
+
+    let req = IpSourceMembershipRequest::new(group, source, lo);
+    setsockopt(&fd, sockopt::IpAddSourceMembership, &req).unwrap();
+    setsockopt(&fd, sockopt::IpDropSourceMembership, &req).unwrap();
+
+    let group = InetAddr::new(IpAddr::V4(group), 0);
+    let source = InetAddr::new(IpAddr::V4(source), 0);
+    let lo_index = if_nametoindex("lo").unwrap();
+    let req = GroupSourceRequest::new(&group, &source, lo_index);
+    setsockopt(&fd, sockopt::McastJoinSourceGroup, &req).unwrap();
+    setsockopt(&fd, sockopt::McastLeaveSourceGroup, &req).unwrap();
+}
+
+#[test]
+fn test_ipv6_multicast() {
+    use nix::net::if_::if_nametoindex;
+
+    let fd = match socket(AddressFamily::Inet6, SockType::Datagram, SockFlag::empty(), None) {
+        Ok(fd) => fd,
+        // IPv6 is disabled on this host
+        Err(_) => return,
+    };
+
+    setsockopt(&fd, sockopt::Ipv6MulticastHops, &5).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::Ipv6MulticastHops).unwrap(), 5);
+
+    setsockopt(&fd, sockopt::Ipv6MulticastLoop, &false).unwrap();
+    assert!(!getsockopt(&fd, sockopt::Ipv6MulticastLoop).unwrap());
+
+    let lo_index = if_nametoindex(if cfg!(any(target_os = "android",
+                                              target_os = "linux")) {
+        "lo"
+    } else {
+        "lo0"
+    }).unwrap();
+    setsockopt(&fd, sockopt::Ipv6MulticastIf, &lo_index).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::Ipv6MulticastIf).unwrap(), lo_index);
+}
+
+#[test]
+fn test_ip_multicast() {
+    use nix::sys::socket::Ipv4Addr;
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+
+    setsockopt(&fd, sockopt::IpMulticastTtl, &5).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::IpMulticastTtl).unwrap(), 5);
+
+    setsockopt(&fd, sockopt::IpMulticastLoop, &false).unwrap();
+    assert!(!getsockopt(&fd, sockopt::IpMulticastLoop).unwrap());
+
+    let lo = Ipv4Addr::new(127, 0, 0, 1);
+    setsockopt(&fd, sockopt::IpMulticastIf, &lo).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::IpMulticastIf).unwrap(), lo);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_ip_multicast_if_index() {
+    use nix::net::if_::if_nametoindex;
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    let lo_index = if_nametoindex("lo").unwrap();
+    setsockopt(&fd, sockopt::IpMulticastIfIndex, &lo_index).unwrap();
+    setsockopt(&fd, sockopt::IpMulticastIfIndex, &0).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_mptcp_info() {
+    use nix::sys::socket::{accept, bind, connect, getsockname, listen,
+                           InetAddr, IpAddr, SockAddr};
+    use nix::unistd::close;
+
+    require_kernel_version!(test_mptcp_info, ">= 5.16");
+
+    let listener = match socket(AddressFamily::Inet, SockType::Stream,
+                                SockFlag::empty(), SockProtocol::MptcpTcp) {
+        Ok(fd) => fd,
+        // MPTCP is disabled by the net.mptcp.enabled sysctl
+        Err(_) => return,
+    };
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&listener, &SockAddr::new_inet(localhost)).unwrap();
+    listen(&listener, 1).unwrap();
+
+    let client = socket(AddressFamily::Inet, SockType::Stream,
+                        SockFlag::empty(), SockProtocol::MptcpTcp)
+        .unwrap();
+    connect(&client, &getsockname(&listener).unwrap()).unwrap();
+    let server = accept(&listener).unwrap();
+
+    let info = getsockopt(&client, sockopt::MptcpInfo).unwrap();
+    assert!(!info.fallback());
+    assert_ne!(info.token(), 0);
+
+    close(server).unwrap();
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_ktls() {
+    use nix::errno::Errno;
+    use nix::Error;
+    use nix::sys::socket::{accept, bind, connect, getsockname, listen, recv,
+                           send, InetAddr, IpAddr, MsgFlags, SockAddr,
+                           TlsCryptoInfo};
+    use nix::unistd::close;
+    use std::ffi::OsString;
+    use std::mem;
+
+    let listener = socket(AddressFamily::Inet, SockType::Stream,
+                          SockFlag::empty(), None)
+        .unwrap();
+    let localhost = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+    bind(&listener, &SockAddr::new_inet(localhost)).unwrap();
+    listen(&listener, 1).unwrap();
+    let client = socket(AddressFamily::Inet, SockType::Stream,
+                        SockFlag::empty(), None)
+        .unwrap();
+    connect(&client, &getsockname(&listener).unwrap()).unwrap();
+    let server = accept(&listener).unwrap();
+
+    assert_eq!(getsockopt(&client, sockopt::TcpUlp).unwrap(), OsString::new());
+    match setsockopt(&client, sockopt::TcpUlp, &OsString::from("tls")) {
+        Ok(()) => (),
+        // The tls module isn't available
+        Err(Error::Sys(Errno::ENOENT)) => return,
+        Err(e) => panic!("setsockopt failed: {}", e),
+    }
+    assert_eq!(getsockopt(&client, sockopt::TcpUlp).unwrap(), OsString::from("tls"));
+    setsockopt(&server, sockopt::TcpUlp, &OsString::from("tls")).unwrap();
+
+    let mut info: libc::tls12_crypto_info_aes_gcm_128 = unsafe { mem::zeroed() };
+    info.info.version = libc::TLS_1_2_VERSION;
+    info.info.cipher_type = libc::TLS_CIPHER_AES_GCM_128;
+    info.key = [0x42; libc::TLS_CIPHER_AES_GCM_128_KEY_SIZE];
+    let info = TlsCryptoInfo::AesGcm128(info);
+    setsockopt(&client, sockopt::TlsTx, &info).unwrap();
+    setsockopt(&server, sockopt::TlsRx, &info).unwrap();
+
+    assert_eq!(send(&client, b"hello", MsgFlags::empty()).unwrap(), 5);
+    let mut buf = [0u8; 5];
+    assert_eq!(recv(&server, &mut buf, MsgFlags::empty()).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    close(server).unwrap();
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_tcp_md5sig() {
+    use nix::errno::Errno;
+    use nix::Error;
+    use nix::sys::socket::{InetAddr, IpAddr, TcpMd5Sig};
+
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    let peer = InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0);
+
+    assert_eq!(TcpMd5Sig::new(&peer, &[0; 81]).err(),
+               Some(Error::Sys(Errno::EINVAL)));
+
+    let sig = TcpMd5Sig::new(&peer, b"secret").unwrap();
+    match setsockopt(&fd, sockopt::TcpMd5Sig, &sig) {
+        Ok(()) => (),
+        // The kernel lacks CONFIG_TCP_MD5SIG
+        Err(Error::Sys(Errno::ENOPROTOOPT)) => return,
+        Err(e) => panic!("setsockopt failed: {}", e),
+    }
+    let sig = TcpMd5Sig::new(&peer, b"").unwrap();
+    setsockopt(&fd, sockopt::TcpMd5Sig, &sig).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_busy_poll() {
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::BusyPoll).unwrap(), 0);
+    // Lowering the value is always allowed
+    setsockopt(&fd, sockopt::BusyPoll, &0).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_busy_poll_cap() {
+    require_capability!(CAP_NET_ADMIN);
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    setsockopt(&fd, sockopt::BusyPoll, &50).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::BusyPoll).unwrap(), 50);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_incoming_cpu() {
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    setsockopt(&fd, sockopt::IncomingCpu, &0).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::IncomingCpu).unwrap(), 0);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_peer_sec() {
+    use nix::sys::socket::socketpair;
+    use std::io::Write;
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None,
+                            SockFlag::empty()).unwrap();
+    setsockopt(&b, sockopt::PassSec, &true).unwrap();
+    assert!(getsockopt(&b, sockopt::PassSec).unwrap());
+    match getsockopt(&a, sockopt::PeerSec) {
+        Ok(label) => assert!(!label.contains(&0)),
+        Err(nix::Error::Sys(nix::errno::Errno::ENOPROTOOPT)) => {
+            writeln!(std::io::stderr(),
+                     "No security module provides socket labels. Skipping test.")
+                .unwrap();
+        },
+        Err(e) => panic!("getsockopt: {}", e),
+    }
+    nix::unistd::close(a).unwrap();
+    nix::unistd::close(b).unwrap();
+}
+
+#[test]
+fn test_sockopt_raw() {
+    use nix::sys::socket::{getsockopt_raw, setsockopt_raw};
+
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    let on: libc::c_int = 1;
+    setsockopt_raw(&fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, &on.to_ne_bytes())
+        .unwrap();
+    assert!(getsockopt(&fd, sockopt::KeepAlive).unwrap());
+
+    setsockopt(&fd, sockopt::KeepAlive, &false).unwrap();
+    let mut buf = [0xffu8; 8];
+    let len = getsockopt_raw(&fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, &mut buf)
+        .unwrap();
+    assert_eq!(len, std::mem::size_of::<libc::c_int>());
+    assert!(buf[..len].iter().all(|&b| b == 0));
+
+    setsockopt_raw(&fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, &[]).unwrap_err();
+}
+
+mod custom {
+    use nix::sys::socket::{getsockopt, setsockopt, socket, AddressFamily,
+                           SockFlag, SockType};
+
+    custom_sockopt!(Both, KeepAlive, libc::SOL_SOCKET, libc::SO_KEEPALIVE, bool);
+    custom_sockopt!(
+        /// The socket type, as a raw integer
+        GetOnly, Type, libc::SOL_SOCKET, libc::SO_TYPE, libc::c_int);
+    custom_sockopt!(SetOnly, Linger, libc::SOL_SOCKET, libc::SO_LINGER,
+                    libc::linger);
+
+    #[test]
+    fn test_custom_sockopt() {
+        let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+                        None).unwrap();
+        assert!(!getsockopt(&fd, KeepAlive).unwrap());
+        setsockopt(&fd, KeepAlive, &true).unwrap();
+        assert!(getsockopt(&fd, KeepAlive).unwrap());
+        assert_eq!(getsockopt(&fd, Type).unwrap(), libc::SOCK_STREAM);
+        let linger = libc::linger { l_onoff: 1, l_linger: 5 };
+        setsockopt(&fd, Linger, &linger).unwrap();
+        nix::unistd::close(fd).unwrap();
+    }
+}
+
+#[test]
+fn test_timeout_duration() {
+    use std::time::Duration;
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::ReceiveTimeoutDuration).unwrap(), None);
+    let timeout = Duration::from_millis(1500);
+    setsockopt(&fd, sockopt::ReceiveTimeoutDuration, &Some(timeout)).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::ReceiveTimeoutDuration).unwrap(),
+               Some(timeout));
+    let tv = getsockopt(&fd, sockopt::ReceiveTimeout).unwrap();
+    assert_eq!((tv.tv_sec(), tv.tv_usec()), (1, 500_000));
+    setsockopt(&fd, sockopt::ReceiveTimeoutDuration, &None).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::ReceiveTimeoutDuration).unwrap(), None);
+
+    // Sub-microsecond timeouts are rounded up rather than disabling the timeout
+    setsockopt(&fd, sockopt::SendTimeoutDuration, &Some(Duration::new(0, 1)))
+        .unwrap();
+    assert!(getsockopt(&fd, sockopt::SendTimeoutDuration).unwrap().is_some());
+    setsockopt(&fd, sockopt::SendTimeoutDuration, &Some(Duration::new(0, 0)))
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_peek_off() {
+    use nix::sys::socket::{recv, send, socketpair, MsgFlags};
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None,
+                            SockFlag::empty()).unwrap();
+    assert_eq!(getsockopt(&b, sockopt::PeekOff).unwrap(), -1);
+    setsockopt(&b, sockopt::PeekOff, &0).unwrap();
+    send(&a, b"hello world", MsgFlags::empty()).unwrap();
+
+    let mut buf = [0u8; 6];
+    assert_eq!(recv(&b, &mut buf[..5], MsgFlags::MSG_PEEK).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(recv(&b, &mut buf, MsgFlags::MSG_PEEK).unwrap(), 6);
+    assert_eq!(&buf, b" world");
+    assert_eq!(getsockopt(&b, sockopt::PeekOff).unwrap(), 11);
+
+    nix::unistd::close(a).unwrap();
+    nix::unistd::close(b).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_socket_cookie() {
+    let a = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    let b = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    let cookie = getsockopt(&a, sockopt::SocketCookie).unwrap();
+    assert_ne!(cookie, 0);
+    assert_eq!(getsockopt(&a, sockopt::SocketCookie).unwrap(), cookie);
+    assert_ne!(getsockopt(&b, sockopt::SocketCookie).unwrap(), cookie);
+    nix::unistd::close(a).unwrap();
+    nix::unistd::close(b).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_tcp_repair() {
+    use nix::sys::socket::{accept, bind, connect, getsockname, listen, SockAddr,
+                           InetAddr, IpAddr, TcpRepairOpt};
+    use nix::sys::socket::sockopt::TcpRepairQueueKind;
+    use nix::unistd::close;
+
+    require_capability!(CAP_NET_ADMIN);
+
+    // Sequence numbers can only be set before the connection is restored
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    setsockopt(&fd, sockopt::TcpRepair, &true).unwrap();
+    assert!(getsockopt(&fd, sockopt::TcpRepair).unwrap());
+    setsockopt(&fd, sockopt::TcpRepairQueue, &TcpRepairQueueKind::Recv).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::TcpRepairQueue).unwrap(),
+               TcpRepairQueueKind::Recv);
+    setsockopt(&fd, sockopt::TcpQueueSeq, &1000).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::TcpQueueSeq).unwrap(), 1000);
+    close(fd).unwrap();
+
+    // Options can be restored on a connection that hasn't sent data yet
+    let listener = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+                          None).unwrap();
+    let lo = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    bind(&listener, &lo).unwrap();
+    listen(&listener, 1).unwrap();
+    let client = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+                        None).unwrap();
+    connect(&client, &getsockname(&listener).unwrap()).unwrap();
+    let server = accept(&listener).unwrap();
+
+    setsockopt(&client, sockopt::TcpRepair, &true).unwrap();
+    setsockopt(&client, sockopt::TcpRepairQueue, &TcpRepairQueueKind::Send).unwrap();
+    getsockopt(&client, sockopt::TcpQueueSeq).unwrap();
+    let opts = vec![TcpRepairOpt::mss(1460), TcpRepairOpt::sack_permitted()];
+    setsockopt(&client, sockopt::TcpRepairOptions, &opts).unwrap();
+    setsockopt(&client, sockopt::TcpRepair, &false).unwrap();
+
+    for fd in &[client, server, listener] {
+        close(*fd).unwrap();
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_sock_domain_type_protocol() {
+    use nix::sys::socket::{bind, listen, socketpair, InetAddr, IpAddr, NetlinkProtocol,
+                           RawSockProtocol, SockAddr};
+    use nix::unistd::close;
+
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::SockDomain).unwrap(), AddressFamily::Inet);
+    assert_eq!(getsockopt(&fd, sockopt::SockType).unwrap(), SockType::Stream);
+    // The kernel fills in the default protocol
+    assert_eq!(getsockopt(&fd, sockopt::SockProtocol).unwrap(), SockProtocol::Tcp.into());
+    assert!(!getsockopt(&fd, sockopt::AcceptConn).unwrap());
+    let lo = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    bind(&fd, &lo).unwrap();
+    listen(&fd, 1).unwrap();
+    assert!(getsockopt(&fd, sockopt::AcceptConn).unwrap());
+    close(fd).unwrap();
+
+    let fd = socket(AddressFamily::Inet6, SockType::Datagram, SockFlag::empty(), None)
+        .unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::SockDomain).unwrap(), AddressFamily::Inet6);
+    assert_eq!(getsockopt(&fd, sockopt::SockType).unwrap(), SockType::Datagram);
+    assert_eq!(getsockopt(&fd, sockopt::SockProtocol).unwrap(), SockProtocol::Udp.into());
+    close(fd).unwrap();
+
+    let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                    NetlinkProtocol::Route).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::SockDomain).unwrap(), AddressFamily::Netlink);
+    assert_eq!(getsockopt(&fd, sockopt::SockProtocol).unwrap(),
+               NetlinkProtocol::Route.into());
+    close(fd).unwrap();
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::SeqPacket, None,
+                            SockFlag::empty()).unwrap();
+    assert_eq!(getsockopt(&a, sockopt::SockDomain).unwrap(), AddressFamily::Unix);
+    assert_eq!(getsockopt(&a, sockopt::SockType).unwrap(), SockType::SeqPacket);
+    assert_eq!(getsockopt(&a, sockopt::SockProtocol).unwrap(), RawSockProtocol(0));
+    close(a).unwrap();
+    close(b).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_dccp_sockopts() {
+    use nix::unistd::close;
+    use std::io::Write;
+
+    let fd = match socket(AddressFamily::Inet, SockType::Dccp, SockFlag::empty(),
+                          SockProtocol::Dccp) {
+        Ok(fd) => fd,
+        Err(_) => {
+            writeln!(std::io::stderr(), "DCCP is not supported. Skipping test.").unwrap();
+            return;
+        },
+    };
+    assert_eq!(getsockopt(&fd, sockopt::SockType).unwrap(), SockType::Dccp);
+    assert_eq!(getsockopt(&fd, sockopt::SockProtocol).unwrap(), SockProtocol::Dccp.into());
+
+    setsockopt(&fd, sockopt::DccpService, &0x4e49_5800).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::DccpService).unwrap(), 0x4e49_5800);
+
+    let ccids = getsockopt(&fd, sockopt::DccpAvailableCcids).unwrap();
+    assert!(!ccids.is_empty());
+    setsockopt(&fd, sockopt::DccpCcid, &ccids[0]).unwrap();
+    close(fd).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_rds_transport() {
+    use nix::sys::socket::sockopt::RdsTransportKind;
+    use nix::unistd::close;
+    use std::io::Write;
+
+    let fd = match socket(AddressFamily::Rds, SockType::SeqPacket, SockFlag::empty(),
+                          None) {
+        Ok(fd) => fd,
+        Err(_) => {
+            writeln!(std::io::stderr(), "RDS is not supported. Skipping test.").unwrap();
+            return;
+        },
+    };
+    assert_eq!(getsockopt(&fd, sockopt::RdsTransport).unwrap(), RdsTransportKind::None);
+    setsockopt(&fd, sockopt::RdsTransport, &RdsTransportKind::Tcp).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::RdsTransport).unwrap(), RdsTransportKind::Tcp);
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos"))]
+#[test]
+fn test_local_peercred() {
+    use nix::sys::socket::socketpair;
+    use nix::unistd::{getegid, geteuid};
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty()).unwrap();
+    let cred = getsockopt(&a, sockopt::LocalPeerCred).unwrap();
+    assert_eq!(cred.uid(), geteuid().as_raw());
+    assert_eq!(cred.groups()[0], getegid().as_raw());
+    assert_eq!(getsockopt(&b, sockopt::LocalPeerCred).unwrap(), cred);
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    assert_eq!(getsockopt(&a, sockopt::LocalPeerPid).unwrap(),
+               nix::unistd::getpid().as_raw());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_tcp_defer_accept() {
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+             .unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::TcpDeferAccept).unwrap(), 0);
+    setsockopt(&fd, sockopt::TcpDeferAccept, &5).unwrap();
+    assert!(getsockopt(&fd, sockopt::TcpDeferAccept).unwrap() >= 5);
+    setsockopt(&fd, sockopt::TcpDeferAccept, &0).unwrap();
+    assert_eq!(getsockopt(&fd, sockopt::TcpDeferAccept).unwrap(), 0);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_tcp_quickack() {
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+             .unwrap();
+    setsockopt(&fd, sockopt::TcpQuickAck, &false).unwrap();
+    assert!(!getsockopt(&fd, sockopt::TcpQuickAck).unwrap());
+    setsockopt(&fd, sockopt::TcpQuickAck, &true).unwrap();
+    assert!(getsockopt(&fd, sockopt::TcpQuickAck).unwrap());
+}
+
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[test]
+fn test_tcp_maxseg() {
+    use nix::sys::socket::{accept, bind, connect, listen, InetAddr, SockAddr};
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    let std_sa = SocketAddr::from_str("127.0.0.1:4005").unwrap();
+    let inet_addr = InetAddr::from_std(&std_sa);
+    let sock_addr = SockAddr::new_inet(inet_addr);
+
+    let rsock = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+                .unwrap();
+    // The port may still be in TIME_WAIT from a previous run
+    setsockopt(&rsock, sockopt::ReuseAddr, &true).unwrap();
+    bind(&rsock, &sock_addr).unwrap();
+    listen(&rsock, 10).unwrap();
+
+    let ssock = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+                .unwrap();
+    setsockopt(&ssock, sockopt::TcpMaxSeg, &1024).unwrap();
+    connect(&ssock, &sock_addr).unwrap();
+    let _rconn = accept(&rsock).unwrap();
+
+    let mss = getsockopt(&ssock, sockopt::TcpMaxSeg).unwrap();
+    assert!(mss > 0 && mss <= 1024, "unexpected MSS {}", mss);
 }
@@ -0,0 +1,63 @@
+use rand::{thread_rng, Rng};
+use nix::sys::socket::{setsockopt, sockopt, SockFlag};
+use nix::sys::time::{TimeVal, TimeValLike};
+use nix::sys::uevent::{self, Uevent};
+use nix::unistd::close;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[test]
+fn test_uevent_parse() {
+    let buf = b"change@/devices/virtual/net/lo\0ACTION=change\0\
+                DEVPATH=/devices/virtual/net/lo\0SUBSYSTEM=net\0\
+                INTERFACE=lo\0IFINDEX=1\0SEQNUM=652\0";
+    let event = Uevent::parse(buf).unwrap();
+    assert_eq!(event.action(), "change");
+    assert_eq!(event.devpath(), "/devices/virtual/net/lo");
+    assert_eq!(event.subsystem(), Some("net"));
+    assert_eq!(event.seqnum(), Some(652));
+    assert_eq!(event.get("INTERFACE"), Some("lo"));
+    assert_eq!(event.get("DEVNAME"), None);
+    assert_eq!(event.env().len(), 6);
+
+    // udev's rebroadcast format has no action@devpath header
+    assert!(Uevent::parse(b"libudev\0\xfe\xed\xca\xfe").is_err());
+}
+
+/// Run with `cargo test -- --ignored test_uevent_recv`.
+///
+/// FIXME: This test is disabled by default, because the uevent it triggers
+/// on the loopback device reaches every udev listener on the host.  A fresh
+/// network namespace doesn't help, as sysfs still shows the host's devices.
+#[test]
+#[ignore]
+fn test_uevent_recv() {
+    let fd = uevent::socket(SockFlag::SOCK_CLOEXEC).unwrap();
+    setsockopt(&fd, sockopt::ReceiveTimeout, &TimeVal::seconds(5)).unwrap();
+
+    // Since Linux 4.13, writing an action and a UUID to a device's uevent
+    // file makes the kernel send a synthetic uevent tagged with that UUID,
+    // which tells udev that it isn't a real change to the device
+    let mut rng = thread_rng();
+    let uuid = format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+                       rng.gen::<u32>(), rng.gen::<u16>(), rng.gen::<u16>(),
+                       rng.gen::<u16>(), rng.gen::<u64>() & 0xffff_ffff_ffff);
+    let trigger = OpenOptions::new().write(true).open("/sys/class/net/lo/uevent")
+        .and_then(|mut f| f.write_all(format!("change {}", uuid).as_bytes()));
+    if trigger.is_err() {
+        writeln!(std::io::stderr(),
+                 "Can't trigger synthetic uevents. Skipping test.").unwrap();
+        close(fd).unwrap();
+        return;
+    }
+    loop {
+        let event = uevent::recv(fd).unwrap();
+        if event.get("SYNTH_UUID") == Some(uuid.as_str()) {
+            assert_eq!(event.action(), "change");
+            assert_eq!(event.subsystem(), Some("net"));
+            assert_eq!(event.get("INTERFACE"), Some("lo"));
+            break;
+        }
+    }
+    close(fd).unwrap();
+}
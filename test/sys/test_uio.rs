@@ -54,6 +54,34 @@ fn test_writev() {
     assert!(close_res.is_ok());
 }
 
+#[test]
+fn test_io_slices() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    let (reader, writer) = pipe().unwrap();
+    let bufs = [IoSlice::new(b"hello "), IoSlice::new(b"world")];
+    assert_eq!(writev(writer, IoVec::from_io_slices(&bufs)).unwrap(), 11);
+
+    let mut hello = [0u8; 6];
+    let mut world = [0u8; 5];
+    {
+        let mut bufs = [IoSliceMut::new(&mut hello), IoSliceMut::new(&mut world)];
+        assert_eq!(readv(reader, IoVec::from_io_slices_mut(&mut bufs)).unwrap(), 11);
+    }
+    assert_eq!(&hello, b"hello ");
+    assert_eq!(&world, b"world");
+
+    let iov = IoVec::from(IoSlice::new(b"round trip"));
+    assert_eq!(iov.as_slice(), b"round trip");
+    assert_eq!(&*IoSlice::from(iov), b"round trip");
+    let mut buf = [1u8, 2, 3];
+    let iov = IoVec::from(IoSliceMut::new(&mut buf));
+    assert_eq!(&*IoSliceMut::from(iov), &[1, 2, 3]);
+
+    close(reader).unwrap();
+    close(writer).unwrap();
+}
+
 #[test]
 #[cfg(not(target_os = "redox"))]
 fn test_readv() {
@@ -59,6 +59,84 @@ fn test_renameat() {
     assert!(new_dir.path().join("new").exists());
 }
 
+#[test]
+#[cfg(target_os = "linux")]
+fn test_renameat2() {
+    use nix::fcntl::{renameat2, RenameFlags};
+
+    let tempdir = tempfile::tempdir().unwrap();
+    std::fs::write(tempdir.path().join("a"), b"a").unwrap();
+    std::fs::write(tempdir.path().join("b"), b"b").unwrap();
+    let dirfd = open(tempdir.path(), OFlag::empty(), Mode::empty()).unwrap();
+
+    match renameat2(Some(dirfd), "a", Some(dirfd), "b", RenameFlags::RENAME_NOREPLACE) {
+        Err(Error::Sys(Errno::EEXIST)) => (),
+        // Old kernel or a filesystem without support for the flags
+        Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EINVAL)) => return,
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    renameat2(Some(dirfd), "a", Some(dirfd), "b", RenameFlags::RENAME_EXCHANGE).unwrap();
+    assert_eq!(std::fs::read(tempdir.path().join("a")).unwrap(), b"b");
+    assert_eq!(std::fs::read(tempdir.path().join("b")).unwrap(), b"a");
+
+    renameat2(Some(dirfd), "a", Some(dirfd), "c", RenameFlags::RENAME_NOREPLACE).unwrap();
+    assert!(!tempdir.path().join("a").exists());
+    assert_eq!(std::fs::read(tempdir.path().join("c")).unwrap(), b"b");
+
+    close(dirfd).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_clone_file() {
+    use nix::fcntl::{clone_file, clone_file_range};
+    use std::os::unix::io::AsRawFd;
+
+    let mut src = tempfile::tempfile().unwrap();
+    src.write_all(&[b'a'; 8192]).unwrap();
+    let dest = tempfile::tempfile().unwrap();
+
+    match clone_file(src.as_raw_fd(), dest.as_raw_fd()) {
+        Ok(()) => (),
+        // Most filesystems can't share extents
+        Err(Error::Sys(Errno::EOPNOTSUPP)) | Err(Error::Sys(Errno::EXDEV)) |
+            Err(Error::Sys(Errno::EINVAL)) => return,
+        Err(e) => panic!("clone_file failed: {}", e),
+    }
+    assert_eq!(dest.metadata().unwrap().len(), 8192);
+
+    clone_file_range(src.as_raw_fd(), 0, 4096, dest.as_raw_fd(), 8192).unwrap();
+    assert_eq!(dest.metadata().unwrap().len(), 12288);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_dedupe_file_range() {
+    use nix::fcntl::{dedupe_file_range, DedupeResult};
+    use std::os::unix::io::AsRawFd;
+
+    let mut src = tempfile::tempfile().unwrap();
+    src.write_all(&[b'a'; 4096]).unwrap();
+    let mut same = tempfile::tempfile().unwrap();
+    same.write_all(&[b'a'; 4096]).unwrap();
+    let mut differs = tempfile::tempfile().unwrap();
+    differs.write_all(&[b'b'; 4096]).unwrap();
+
+    let dests = [(same.as_raw_fd(), 0), (differs.as_raw_fd(), 0)];
+    let results = match dedupe_file_range(src.as_raw_fd(), 0, 4096, &dests) {
+        Ok(results) => results,
+        // Most filesystems can't share extents
+        Err(Error::Sys(Errno::EOPNOTSUPP)) | Err(Error::Sys(Errno::EINVAL)) => return,
+        Err(e) => panic!("dedupe_file_range failed: {}", e),
+    };
+    assert_eq!(results.len(), 2);
+    if let DedupeResult::Error(Errno::EOPNOTSUPP) = results[0] {
+        return;
+    }
+    assert_eq!(results, vec![DedupeResult::Same(4096), DedupeResult::Differs]);
+}
+
 #[test]
 #[cfg(not(target_os = "redox"))]
 fn test_readlink() {
@@ -77,6 +155,64 @@ fn test_readlink() {
 
 }
 
+#[test]
+#[cfg(not(target_os = "redox"))]
+fn test_flock() {
+    use nix::fcntl::{flock, FlockArg};
+
+    let tmp = NamedTempFile::new().unwrap();
+    let fd1 = open(tmp.path(), OFlag::O_RDONLY, Mode::empty()).unwrap();
+    let fd2 = open(tmp.path(), OFlag::O_RDONLY, Mode::empty()).unwrap();
+
+    flock(fd1, FlockArg::LockExclusive).unwrap();
+    assert_eq!(flock(fd2, FlockArg::LockSharedNonblock),
+               Err(Error::Sys(Errno::EAGAIN)));
+
+    flock(fd1, FlockArg::Unlock).unwrap();
+    flock(fd2, FlockArg::LockSharedNonblock).unwrap();
+    flock(fd1, FlockArg::LockSharedNonblock).unwrap();
+    assert_eq!(flock(fd1, FlockArg::LockExclusiveNonblock),
+               Err(Error::Sys(Errno::EAGAIN)));
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_openat2() {
+    use nix::fcntl::{openat2, OpenHow, ResolveFlag};
+
+    let tempdir = tempfile::tempdir().unwrap();
+    File::create(tempdir.path().join("file")).unwrap();
+    fs::symlink("/", tempdir.path().join("root")).unwrap();
+    let dirfd = open(tempdir.path(), OFlag::O_DIRECTORY, Mode::empty()).unwrap();
+
+    let how = OpenHow::new(OFlag::O_RDONLY, Mode::empty());
+    let fd = match openat2(dirfd, "file", how) {
+        Ok(fd) => fd,
+        // openat2 needs Linux 5.6 or later
+        Err(Error::Sys(Errno::ENOSYS)) => return,
+        Err(e) => panic!("openat2 failed: {}", e),
+    };
+    close(fd).unwrap();
+    close(openat2(dirfd, "root", how).unwrap()).unwrap();
+
+    let beneath = how.resolve(ResolveFlag::RESOLVE_BENEATH);
+    assert_eq!(openat2(dirfd, "root", beneath), Err(Error::Sys(Errno::EXDEV)));
+    assert_eq!(openat2(dirfd, "../", beneath), Err(Error::Sys(Errno::EXDEV)));
+    close(openat2(dirfd, "file", beneath).unwrap()).unwrap();
+
+    let no_symlinks = how.resolve(ResolveFlag::RESOLVE_NO_SYMLINKS);
+    assert_eq!(openat2(dirfd, "root", no_symlinks), Err(Error::Sys(Errno::ELOOP)));
+
+    // A mode without O_CREAT is rejected
+    let bad_mode = OpenHow::new(OFlag::O_RDONLY, Mode::S_IRUSR);
+    assert_eq!(openat2(dirfd, "file", bad_mode), Err(Error::Sys(Errno::EINVAL)));
+
+    close(dirfd).unwrap();
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod linux_android {
     use std::fs::File;
@@ -153,6 +289,86 @@ mod linux_android {
         close(wr).unwrap();
     }
 
+    #[test]
+    fn test_splice_socket() {
+        use nix::sys::socket::{socketpair, AddressFamily, SockFlag, SockType};
+
+        let (src, proxy_in) = socketpair(AddressFamily::Unix, SockType::Stream,
+                                         None, SockFlag::empty()).unwrap();
+        let (proxy_out, dst) = socketpair(AddressFamily::Unix, SockType::Stream,
+                                          None, SockFlag::empty()).unwrap();
+        let (rd, wr) = pipe().unwrap();
+
+        write(src, b"hello").unwrap();
+        assert_eq!(5, splice(proxy_in, None, wr, None, 5, SpliceFFlags::empty()).unwrap());
+        assert_eq!(5, splice(rd, None, proxy_out, None, 5, SpliceFFlags::empty()).unwrap());
+
+        let mut buf = [0u8; 1024];
+        assert_eq!(5, read(dst, &mut buf).unwrap());
+        assert_eq!(b"hello", &buf[0..5]);
+
+        for fd in &[src, proxy_in, proxy_out, dst, rd, wr] {
+            close(*fd).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_memfd_seals() {
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+        use std::ffi::CString;
+
+        let name = CString::new("nix-test").unwrap();
+        let fd = memfd_create(&name, MemFdCreateFlag::MFD_ALLOW_SEALING).unwrap();
+        write(fd, b"abc").unwrap();
+
+        let seals = SealFlag::F_SEAL_WRITE | SealFlag::F_SEAL_SHRINK |
+            SealFlag::F_SEAL_GROW | SealFlag::F_SEAL_SEAL;
+        fcntl(fd, FcntlArg::F_ADD_SEALS(seals)).unwrap();
+        let got = fcntl(fd, FcntlArg::F_GET_SEALS).unwrap();
+        assert_eq!(seals, SealFlag::from_bits_truncate(got));
+
+        assert_eq!(write(fd, b"d"), Err(Error::Sys(Errno::EPERM)));
+        assert_eq!(fcntl(fd, FcntlArg::F_ADD_SEALS(SealFlag::empty())),
+                   Err(Error::Sys(Errno::EPERM)));
+
+        close(fd).unwrap();
+    }
+
+    #[test]
+    fn test_pipe_size() {
+        let (rd, wr) = pipe().unwrap();
+
+        // Stays below the default /proc/sys/fs/pipe-max-size of 1 MiB
+        let size = fcntl(wr, FcntlArg::F_SETPIPE_SZ(256 * 1024)).unwrap();
+        assert!(size >= 256 * 1024);
+        assert_eq!(size, fcntl(rd, FcntlArg::F_GETPIPE_SZ).unwrap());
+
+        close(rd).unwrap();
+        close(wr).unwrap();
+    }
+
+    #[test]
+    fn test_o_path() {
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::sys::stat::{fstatat, stat, Mode};
+
+        let tmp = NamedTempFile::new().unwrap();
+        let fd = open(tmp.path(), OFlag::O_PATH, Mode::empty()).unwrap();
+
+        let st = fstatat(fd, "", AtFlags::AT_EMPTY_PATH).unwrap();
+        assert_eq!(stat(tmp.path()).unwrap().st_ino, st.st_ino);
+
+        // The descriptor only names the file; it cannot be used for I/O
+        let mut buf = [0u8; 1];
+        assert_eq!(read(fd, &mut buf), Err(Error::Sys(Errno::EBADF)));
+
+        close(fd).unwrap();
+    }
+
     #[test]
     fn test_tee() {
         let (rd1, wr1) = pipe().unwrap();
@@ -214,6 +430,28 @@ mod linux_android {
         assert_eq!(100, read(fd, &mut buf).unwrap());
     }
 
+    #[test]
+    fn test_fallocate_punch_hole() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        tmp.write_all(&[b'a'; 8192]).unwrap();
+
+        let fd = tmp.as_raw_fd();
+        let mode = FallocateFlags::FALLOC_FL_PUNCH_HOLE | FallocateFlags::FALLOC_FL_KEEP_SIZE;
+        match fallocate(fd, mode, 0, 4096) {
+            Ok(()) => (),
+            // Not every filesystem can punch holes
+            Err(nix::Error::Sys(nix::errno::Errno::EOPNOTSUPP)) => return,
+            Err(e) => panic!("fallocate failed: {}", e),
+        }
+
+        assert_eq!(8192, fstat(fd).unwrap().st_size);
+        let mut buf = [0u8; 8192];
+        tmp.seek(SeekFrom::Start(0)).unwrap();
+        tmp.read_exact(&mut buf).unwrap();
+        assert!(buf[..4096].iter().all(|&b| b == 0));
+        assert!(buf[4096..].iter().all(|&b| b == b'a'));
+    }
+
     // The tests below are disabled for the listed targets
     // due to OFD locks not being available in the kernel/libc
     // versions used in the CI environment, probably because
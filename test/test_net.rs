@@ -10,3 +10,462 @@ const LOOPBACK: &[u8] = b"lo0";
 fn test_if_nametoindex() {
     assert!(if_nametoindex(&LOOPBACK[..]).is_ok());
 }
+
+#[test]
+fn test_if_indextoname() {
+    let index = if_nametoindex(LOOPBACK).unwrap();
+    let name = if_indextoname(index).unwrap();
+    assert_eq!(name.as_bytes(), LOOPBACK);
+    assert!(if_indextoname(0).is_err());
+}
+
+#[test]
+fn test_if_nameindex() {
+    let index = if_nametoindex(LOOPBACK).unwrap();
+    let interfaces = if_nameindex().unwrap();
+    let lo = interfaces.iter()
+        .find(|iface| iface.name().to_bytes() == LOOPBACK)
+        .expect("loopback interface not listed");
+    assert_eq!(lo.index(), index);
+    assert_eq!(interfaces.to_slice().len(), interfaces.iter().count());
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod ioctl {
+    use nix::net::if_::InterfaceFlags;
+    use nix::net::if_::ioctl::*;
+    use nix::sys::socket::{socket, AddressFamily, Ipv4Addr, SockFlag, SockType};
+    use nix::unistd::close;
+    use super::LOOPBACK;
+
+    fn inet_socket() -> std::os::unix::io::RawFd {
+        socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ifreq() {
+        let req = IfReq::new(LOOPBACK).unwrap()
+            .with_mtu(1500);
+        assert_eq!(req.name().to_bytes(), LOOPBACK);
+        assert_eq!(req.mtu(), 1500);
+        let req = req.with_addr(Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(req.addr(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+        let req = req.with_flags(InterfaceFlags::IFF_UP | InterfaceFlags::IFF_DYNAMIC);
+        assert_eq!(req.flags(), InterfaceFlags::IFF_UP | InterfaceFlags::IFF_DYNAMIC);
+
+        assert!(IfReq::new("0123456789abcdef").is_err());
+    }
+
+    #[test]
+    fn test_get_loopback() {
+        let fd = inet_socket();
+        let flags = get_flags(fd, LOOPBACK).unwrap();
+        assert!(flags.contains(InterfaceFlags::IFF_UP | InterfaceFlags::IFF_LOOPBACK));
+        assert!(get_mtu(fd, LOOPBACK).unwrap() > 0);
+        assert_eq!(get_addr(fd, LOOPBACK).unwrap(), Ipv4Addr::new(127, 0, 0, 1));
+        assert_eq!(get_netmask(fd, LOOPBACK).unwrap(), Ipv4Addr::new(255, 0, 0, 0));
+        assert!(get_flags(fd, "nix-no-such-if").is_err());
+        close(fd).unwrap();
+    }
+
+    #[test]
+    fn test_get_hwaddr() {
+        use nix::ifaddrs::getifaddrs;
+        use nix::sys::socket::{MacAddr, SockAddr};
+
+        let fd = inet_socket();
+        let mac = get_hwaddr(fd, LOOPBACK).unwrap();
+        assert_eq!(mac, MacAddr::default());
+        close(fd).unwrap();
+
+        // getifaddrs reports the same address through LinkAddr
+        let link = getifaddrs().unwrap()
+            .filter(|ifaddr| ifaddr.interface_name.as_bytes() == LOOPBACK)
+            .find_map(|ifaddr| match ifaddr.address {
+                Some(SockAddr::Link(link)) => Some(link),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(link.mac_addr(), Some(mac));
+    }
+
+    #[test]
+    fn test_set_loopback() {
+        require_capability!(CAP_NET_ADMIN);
+
+        // Write back the current configuration, so as not to disturb the host
+        let fd = inet_socket();
+        let mtu = get_mtu(fd, LOOPBACK).unwrap();
+        set_mtu(fd, LOOPBACK, mtu).unwrap();
+        let flags = get_flags(fd, LOOPBACK).unwrap();
+        set_flags(fd, LOOPBACK, flags).unwrap();
+        assert_eq!(get_flags(fd, LOOPBACK).unwrap(), flags);
+        close(fd).unwrap();
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod ethtool {
+    use nix::errno::Errno;
+    use nix::net::if_::ethtool::*;
+    use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+    use nix::unistd::close;
+    use nix::Error;
+    use super::LOOPBACK;
+
+    #[test]
+    fn test_ethtool_loopback() {
+        let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+                        None).unwrap();
+        assert!(get_link(fd, LOOPBACK).unwrap());
+        get_offload(fd, LOOPBACK, Offload::ScatterGather).unwrap();
+
+        // The loopback driver doesn't implement these, but the kernel may
+        // answer some generically
+        match get_driver_info(fd, LOOPBACK) {
+            Ok(info) => assert!(info.driver().to_bytes().len() < 32),
+            Err(Error::Sys(Errno::EOPNOTSUPP)) => (),
+            Err(e) => panic!("get_driver_info: {}", e),
+        }
+        for res in &[get_link_settings(fd, LOOPBACK).map(drop),
+                     get_ring_params(fd, LOOPBACK).map(drop),
+                     get_channels(fd, LOOPBACK).map(drop)] {
+            match *res {
+                Ok(()) | Err(Error::Sys(Errno::EOPNOTSUPP)) => (),
+                Err(e) => panic!("ethtool: {}", e),
+            }
+        }
+        assert!(get_link(fd, "nix-no-such-if").is_err());
+        close(fd).unwrap();
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod netlink {
+    use nix::errno::Errno;
+    use nix::net::netlink::genl::*;
+    use nix::net::netlink::*;
+    use nix::sys::socket::{socket, AddressFamily, NetlinkProtocol, SockFlag, SockType};
+    use nix::unistd::close;
+    use nix::Error;
+
+    #[test]
+    fn test_nlmsg_roundtrip() {
+        let mut msg = NlMsgBuilder::new(0x20, NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_ACK);
+        msg.seq(7).pid(9).push(&[1, 2, 3]).attr_u8(1, 0xab);
+        let nest = msg.begin_nested(2);
+        msg.attr_u16(3, 0x1234).attr_u64(4, 1 << 40);
+        msg.end_nested(nest).attr_str(5, "abc");
+        let bytes = msg.into_bytes();
+        assert_eq!(bytes.len() % 4, 0);
+
+        let mut msgs = messages(&bytes);
+        let parsed = msgs.next().unwrap();
+        assert!(msgs.next().is_none());
+        assert_eq!(parsed.msg_type(), 0x20);
+        assert_eq!(parsed.flags(), NlMsgFlags::NLM_F_REQUEST | NlMsgFlags::NLM_F_ACK);
+        assert_eq!(parsed.seq(), 7);
+        assert_eq!(parsed.pid(), 9);
+        assert_eq!(&parsed.payload()[..3], &[1, 2, 3]);
+        assert_eq!(parsed.error(), None);
+
+        let attrs: Vec<_> = parsed.attrs(3).collect();
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].kind(), 1);
+        assert_eq!(attrs[0].get_u8(), Some(0xab));
+        assert_eq!(attrs[0].get_u32(), None);
+        assert_eq!(attrs[1].kind(), 2);
+        assert!(attrs[1].is_nested());
+        let nested: Vec<_> = attrs[1].nested().collect();
+        assert_eq!(nested[0].get_u16(), Some(0x1234));
+        assert_eq!(nested[1].get_u64(), Some(1 << 40));
+        assert_eq!(attrs[2].get_str(), Some("abc"));
+
+        // Truncated input stops the iteration
+        assert!(messages(&bytes[..bytes.len() - 4]).next().is_none());
+    }
+
+    #[test]
+    fn test_genl_get_family() {
+        let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                        NetlinkProtocol::Generic).unwrap();
+        // The controller describes itself like any other family
+        let family = get_family(fd, "nlctrl").unwrap();
+        assert_eq!(family.id(), libc::GENL_ID_CTRL as u16);
+        assert_eq!(family.name(), "nlctrl");
+        assert!(family.version() >= 1);
+        assert!(family.mcast_group("notify").is_some());
+
+        assert_eq!(get_family(fd, "nix-nofamily"),
+                   Err(Error::Sys(Errno::ENOENT)));
+        close(fd).unwrap();
+    }
+
+    #[test]
+    fn test_dump_single_reply() {
+        let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                        NetlinkProtocol::Generic).unwrap();
+        // Without NLM_F_DUMP the kernel replies with a single message and no
+        // NLMSG_DONE
+        let mut request = GenlMsg::builder(libc::GENL_ID_CTRL as u16,
+                                           libc::CTRL_CMD_GETFAMILY as u8, 1,
+                                           NlMsgFlags::NLM_F_REQUEST);
+        request.seq(1).attr_str(libc::CTRL_ATTR_FAMILY_NAME as u16, "nlctrl");
+        let mut replies = 0;
+        dump(fd, &request, |msg| {
+            assert!(!msg.flags().contains(NlMsgFlags::NLM_F_MULTI));
+            replies += 1;
+            Ok(())
+        }).unwrap();
+        assert_eq!(replies, 1);
+        close(fd).unwrap();
+    }
+
+    #[test]
+    fn test_transact_timeout() {
+        use nix::sys::socket::{setsockopt, sockopt};
+        use nix::sys::time::{TimeVal, TimeValLike};
+        use std::time::Duration;
+
+        let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                        NetlinkProtocol::Generic).unwrap();
+        // The kernel doesn't answer messages without NLM_F_REQUEST
+        let mut request = GenlMsg::builder(libc::GENL_ID_CTRL as u16,
+                                           libc::CTRL_CMD_GETFAMILY as u8, 1,
+                                           NlMsgFlags::empty());
+        request.seq(1).attr_str(libc::CTRL_ATTR_FAMILY_NAME as u16, "nlctrl");
+        let res: nix::Result<()> = transact(fd, &request, Some(Duration::from_millis(100)),
+                                            |_| Ok(Some(())));
+        assert_eq!(res, Err(Error::Sys(Errno::ETIMEDOUT)));
+
+        // Without a timeout, the socket's own one applies
+        setsockopt(&fd, sockopt::ReceiveTimeout, &TimeVal::milliseconds(100)).unwrap();
+        assert_eq!(dump(fd, &request, |_| Ok(())), Err(Error::Sys(Errno::EAGAIN)));
+        close(fd).unwrap();
+    }
+
+    mod audit {
+        use nix::errno::Errno;
+        use nix::net::netlink::audit::*;
+        use nix::net::netlink::{messages, NlMsgBuilder, NlMsgFlags};
+        use nix::sys::socket::*;
+        use nix::unistd::close;
+        use nix::Error;
+
+        #[test]
+        fn test_audit_record_parse() {
+            let mut msg = NlMsgBuilder::new(1300, NlMsgFlags::empty());
+            msg.push(b"audit(1600000000.123:42): arch=c000003e syscall=59\0");
+            let bytes = msg.into_bytes();
+            let record = AuditRecord::parse(&messages(&bytes).next().unwrap());
+            assert_eq!(record.record_type(), 1300);
+            assert_eq!(record.text(), &b"audit(1600000000.123:42): arch=c000003e syscall=59"[..]);
+        }
+
+        #[test]
+        fn test_audit_status() {
+            require_capability!(CAP_AUDIT_CONTROL);
+            let fd = socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                            NetlinkProtocol::Audit).unwrap();
+            let status = match get_status(fd) {
+                Ok(status) => status,
+                Err(Error::Sys(Errno::ECONNREFUSED)) => {
+                    writeln!(io::stderr(),
+                             "Audit is unavailable in this namespace. Skipping test.").unwrap();
+                    close(fd).unwrap();
+                    return;
+                },
+                Err(e) => panic!("get_status failed: {}", e),
+            };
+            assert!(status.enabled <= 2);
+            assert_ne!(status.feature_bitmap, 0);
+
+            // Set the backlog limit to its current value, to leave it unchanged
+            set_status(fd, AuditStatusMask::AUDIT_STATUS_BACKLOG_LIMIT, &status).unwrap();
+            assert_eq!(get_status(fd).unwrap().backlog_limit, status.backlog_limit);
+
+            // Records of the kernel's own types can't be forged
+            assert_eq!(log_user_record(fd, 1300, "forged"),
+                       Err(Error::Sys(Errno::EINVAL)));
+            close(fd).unwrap();
+        }
+    }
+
+    mod sock_diag {
+        use nix::net::netlink::dump;
+        use nix::net::netlink::sock_diag::*;
+        use nix::sys::socket::*;
+        use nix::sys::stat::fstat;
+        use nix::unistd::close;
+        use std::os::unix::io::RawFd;
+
+        fn diag_socket() -> RawFd {
+            socket(AddressFamily::Netlink, SockType::Raw, SockFlag::empty(),
+                   NetlinkProtocol::SockDiag).unwrap()
+        }
+
+        #[test]
+        fn test_inet_diag() {
+            let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+                            None).unwrap();
+            bind(&fd, &SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0)))
+                .unwrap();
+            listen(&fd, 1).unwrap();
+            let port = match getsockname(&fd).unwrap() {
+                SockAddr::Inet(addr) => addr.port(),
+                addr => panic!("unexpected address {}", addr),
+            };
+            let inode = fstat(fd).unwrap().st_ino as u32;
+
+            let diag = diag_socket();
+            let req = InetDiagReq::new(AddressFamily::Inet, SockProtocol::Tcp,
+                                       TcpStates::LISTEN)
+                .unwrap()
+                .extensions(InetDiagExt::CONG);
+            let mut found = false;
+            dump(diag, &req.message(), |msg| {
+                let (msg, mut attrs) = InetDiagMsg::parse(&msg)?;
+                assert_eq!(msg.state(), Some(TcpState::Listen));
+                if msg.id().src_port() == port {
+                    found = true;
+                    assert_eq!(msg.inode(), inode);
+                    assert_eq!(msg.family(), Some(AddressFamily::Inet));
+                    assert_eq!(msg.src_addr(), "127.0.0.1".parse::<std::net::IpAddr>().unwrap());
+                    assert!(msg.id().cookie().is_some());
+                    let cong = attrs.find(|a| a.kind() == InetDiagAttr::Cong as u16)
+                        .and_then(|a| a.get_str().map(str::to_owned));
+                    assert!(cong.is_some());
+                }
+                Ok(())
+            }).unwrap();
+            assert!(found);
+            close(diag).unwrap();
+            close(fd).unwrap();
+        }
+
+        #[test]
+        fn test_inet_diag_req_invalid() {
+            use nix::Error;
+            use nix::errno::Errno;
+
+            let einval = Err(Error::Sys(Errno::EINVAL));
+            // IPPROTO_MPTCP doesn't fit in the request
+            assert_eq!(InetDiagReq::new(AddressFamily::Inet, SockProtocol::MptcpTcp,
+                                        TcpStates::all()), einval);
+            assert_eq!(InetDiagReq::new(AddressFamily::Unix, SockProtocol::Tcp,
+                                        TcpStates::all()), einval);
+        }
+
+        #[test]
+        fn test_unix_diag() {
+            let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None,
+                                        SockFlag::empty()).unwrap();
+            let inode1 = fstat(fd1).unwrap().st_ino as u32;
+            let inode2 = fstat(fd2).unwrap().st_ino as u32;
+
+            let diag = diag_socket();
+            let req = UnixDiagReq::new(TcpStates::ESTABLISHED, UnixDiagShow::PEER);
+            let mut found = false;
+            dump(diag, &req.message(), |msg| {
+                let (msg, mut attrs) = UnixDiagMsg::parse(&msg)?;
+                if msg.inode() == inode1 {
+                    found = true;
+                    assert_eq!(msg.state(), Some(TcpState::Established));
+                    assert_eq!(i32::from(msg.sock_type()), libc::SOCK_STREAM);
+                    let peer = attrs.find(|a| a.kind() == UnixDiagAttr::Peer as u16).unwrap();
+                    assert_eq!(peer.get_u32(), Some(inode2));
+                }
+                Ok(())
+            }).unwrap();
+            assert!(found);
+            close(diag).unwrap();
+            close(fd1).unwrap();
+            close(fd2).unwrap();
+        }
+
+        #[test]
+        fn test_packet_diag() {
+            require_capability!(CAP_NET_RAW);
+            let fd = socket(AddressFamily::Packet, SockType::Datagram, SockFlag::empty(),
+                            None).unwrap();
+            let inode = fstat(fd).unwrap().st_ino as u32;
+
+            let diag = diag_socket();
+            let req = PacketDiagReq::new(PacketDiagShow::INFO);
+            let mut found = false;
+            dump(diag, &req.message(), |msg| {
+                let (msg, _) = PacketDiagMsg::parse(&msg)?;
+                if msg.inode() == inode {
+                    found = true;
+                    assert_eq!(i32::from(msg.sock_type()), libc::SOCK_DGRAM);
+                }
+                Ok(())
+            }).unwrap();
+            assert!(found);
+            close(diag).unwrap();
+            close(fd).unwrap();
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod pfkey {
+    use nix::net::pfkey::*;
+    use nix::sys::socket::{recv, send, InetAddr, IpAddr, MsgFlags, SockAddr, SockFlag};
+    use nix::unistd::close;
+
+    #[test]
+    fn test_sadb_msg_roundtrip() {
+        // struct sadb_sa: SPI 0x1234 in network order, replay window 32
+        let mut sa = [0u8; 12];
+        sa[..4].copy_from_slice(&0x1234u32.to_be_bytes());
+        sa[4] = 32;
+        let addr = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(192, 0, 2, 1), 0));
+
+        let mut msg = SadbMsgBuilder::new(SadbMsgType::Add, SaType::Esp, 42);
+        msg.ext(SadbExtType::Sa, &sa).address(SadbExtType::AddressDst, 0, 32, &addr);
+        let bytes = msg.into_bytes();
+        assert_eq!(bytes.len() % 8, 0);
+
+        let (header, exts) = SadbMsg::parse(&bytes).unwrap();
+        assert_eq!(header.version(), 2);
+        assert_eq!(header.msg_type(), Some(SadbMsgType::Add));
+        assert_eq!(header.sa_type(), SaType::Esp as u8);
+        assert_eq!(header.seq(), 42);
+        assert_eq!(header.error(), None);
+        let exts: Vec<_> = exts.collect();
+        assert_eq!(exts.len(), 2);
+        let sa = exts[0].sa().unwrap();
+        assert_eq!(sa.spi(), 0x1234);
+        assert_eq!(sa.replay_window(), 32);
+        assert!(exts[0].address().is_none());
+        assert_eq!(exts[1].ext_type(), SadbExtType::AddressDst as u16);
+        assert_eq!(exts[1].address(), Some(addr));
+
+        assert!(SadbMsg::parse(&bytes[..bytes.len() - 8]).is_err());
+    }
+
+    #[test]
+    fn test_pfkey_register() {
+        require_capability!(CAP_NET_ADMIN);
+        let fd = match socket(SockFlag::SOCK_CLOEXEC) {
+            Ok(fd) => fd,
+            Err(_) => {
+                writeln!(std::io::stderr(),
+                         "PF_KEY is not supported. Skipping test.").unwrap();
+                return;
+            },
+        };
+        let request = SadbMsgBuilder::new(SadbMsgType::Register, SaType::Esp, 7);
+        send(&fd, request.as_bytes(), MsgFlags::empty()).unwrap();
+        let mut buf = vec![0u8; 64 * 1024];
+        let len = recv(&fd, &mut buf, MsgFlags::empty()).unwrap();
+        let (reply, exts) = SadbMsg::parse(&buf[..len]).unwrap();
+        assert_eq!(reply.msg_type(), Some(SadbMsgType::Register));
+        assert_eq!(reply.seq(), 7);
+        assert_eq!(reply.error(), None);
+        assert!(exts.filter_map(|ext| ext.algorithms()).count() > 0);
+        close(fd).unwrap();
+    }
+}
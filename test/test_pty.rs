@@ -273,3 +273,33 @@ fn test_forkpty() {
         },
     }
 }
+
+#[test]
+fn test_tcgetpgrp_tcsetpgrp() {
+    use libc::STDIN_FILENO;
+    use nix::unistd::{getpgrp, getsid, tcgetpgrp, tcsetpgrp};
+    use nix::unistd::ForkResult::*;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    // forkpty calls openpty which uses ptname(3) internally.
+    let _m0 = crate::PTSNAME_MTX.lock().expect("Mutex got poisoned by another test");
+    // forkpty spawns a child process
+    let _m1 = crate::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let pty = forkpty(None, None).unwrap();
+    match pty.fork_result {
+        Child => {
+            // The child is a session leader and the pty is its controlling
+            // terminal, so its own process group is in the foreground.
+            let pgrp = getpgrp();
+            let ok = tcgetpgrp(STDIN_FILENO) == Ok(pgrp) &&
+                tcgetsid(STDIN_FILENO) == getsid(None) &&
+                tcsetpgrp(STDIN_FILENO, pgrp).is_ok() &&
+                tcgetpgrp(STDIN_FILENO) == Ok(pgrp);
+            unsafe { _exit(if ok { 0 } else { 1 }); }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            close(pty.master).unwrap();
+        },
+    }
+}
@@ -122,6 +122,19 @@ fn test_fstatat() {
     assert_stat_results(result);
 }
 
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_fstatat_empty_path() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let filename = tempdir.path().join("foo.txt");
+    let file = File::create(&filename).unwrap();
+
+    let result = stat::fstatat(file.as_raw_fd(), "", fcntl::AtFlags::AT_EMPTY_PATH);
+    let fstat_result = stat::fstat(file.as_raw_fd());
+    assert_eq!(result.unwrap().st_ino, fstat_result.unwrap().st_ino);
+    assert_stat_results(result);
+}
+
 #[test]
 #[cfg(not(any(target_os = "netbsd", target_os = "redox")))]
 fn test_stat_fstat_lstat() {
@@ -447,13 +447,37 @@ fn test_fchownat() {
 
     let dirfd = open(tempdir.path(), OFlag::empty(), Mode::empty()).unwrap();
 
-    fchownat(Some(dirfd), "file", uid, gid, FchownatFlags::FollowSymlink).unwrap();
+    fchownat(Some(dirfd), Some("file"), uid, gid, FchownatFlags::FollowSymlink).unwrap();
 
     chdir(tempdir.path()).unwrap();
-    fchownat(None, "file", uid, gid, FchownatFlags::FollowSymlink).unwrap();
+    fchownat(None, Some("file"), uid, gid, FchownatFlags::FollowSymlink).unwrap();
 
     fs::remove_file(&path).unwrap();
-    fchownat(None, "file", uid, gid, FchownatFlags::FollowSymlink).unwrap_err();
+    fchownat(None, Some("file"), uid, gid, FchownatFlags::FollowSymlink).unwrap_err();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_fchownat_empty_path() {
+    let uid = Some(getuid());
+    let gid = Some(getgid());
+
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join("file");
+    let file = File::create(&path).unwrap();
+
+    fchownat(Some(file.as_raw_fd()), None::<&str>, uid, gid, FchownatFlags::FollowSymlink)
+        .unwrap();
+    // Without AT_EMPTY_PATH an empty path is an error
+    fchownat(Some(file.as_raw_fd()), Some(""), uid, gid, FchownatFlags::FollowSymlink)
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+fn test_setfib() {
+    // The default FIB always exists
+    setfib(0).unwrap();
 }
 
 #[test]
@@ -734,8 +758,49 @@ fn test_linkat_file() {
     let dirfd = fcntl::open(tempdir.path(), fcntl::OFlag::empty(), stat::Mode::empty()).unwrap();
 
     // Attempt hard link file at relative path
-    linkat(Some(dirfd), oldfilename, Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
+    linkat(Some(dirfd), Some(oldfilename), Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
+    assert!(newfilepath.exists());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_linkat_empty_path() {
+    require_capability!(CAP_DAC_READ_SEARCH);
+
+    let tempdir = tempdir().unwrap();
+    let oldfilepath = tempdir.path().join("foo.txt");
+    let newfilename = "bar.txt";
+    let newfilepath = tempdir.path().join(newfilename);
+
+    let file = File::create(&oldfilepath).unwrap();
+    let dirfd = fcntl::open(tempdir.path(), fcntl::OFlag::empty(), stat::Mode::empty()).unwrap();
+
+    // Link the open file itself, rather than a path
+    linkat(Some(file.as_raw_fd()), None, Some(dirfd), newfilename, LinkatFlags::SymlinkFollow)
+        .unwrap();
     assert!(newfilepath.exists());
+    close(dirfd).unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_link_tmpfile() {
+    let tempdir = tempdir().unwrap();
+    let newfilepath = tempdir.path().join("foo.txt");
+
+    let fd = match fcntl::open(tempdir.path(), fcntl::OFlag::O_TMPFILE | fcntl::OFlag::O_RDWR,
+                               stat::Mode::S_IRUSR | stat::Mode::S_IWUSR) {
+        Ok(fd) => fd,
+        // Not every filesystem supports O_TMPFILE
+        Err(Error::Sys(Errno::EOPNOTSUPP)) => return,
+        Err(e) => panic!("open failed: {}", e),
+    };
+    write(fd, b"hello").unwrap();
+    assert!(!newfilepath.exists());
+
+    link_tmpfile(fd, None, &newfilepath).unwrap();
+    assert_eq!(fs::read(&newfilepath).unwrap(), b"hello");
+    close(fd).unwrap();
 }
 
 #[test]
@@ -759,7 +824,7 @@ fn test_linkat_olddirfd_none() {
 
     // Attempt hard link file using curent working directory as relative path for old file path
     chdir(tempdir_oldfile.path()).unwrap();
-    linkat(None, oldfilename, Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
+    linkat(None, Some(oldfilename), Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
     assert!(newfilepath.exists());
 }
 
@@ -784,7 +849,7 @@ fn test_linkat_newdirfd_none() {
 
     // Attempt hard link file using current working directory as relative path for new file path
     chdir(tempdir_newfile.path()).unwrap();
-    linkat(Some(dirfd), oldfilename, None, newfilename, LinkatFlags::SymlinkFollow).unwrap();
+    linkat(Some(dirfd), Some(oldfilename), None, newfilename, LinkatFlags::SymlinkFollow).unwrap();
     assert!(newfilepath.exists());
 }
 
@@ -813,7 +878,7 @@ fn test_linkat_no_follow_symlink() {
     let dirfd = fcntl::open(tempdir.path(), fcntl::OFlag::empty(), stat::Mode::empty()).unwrap();
 
     // Attempt link symlink of file at relative path
-    linkat(Some(dirfd), symoldfilename, Some(dirfd), newfilename, LinkatFlags::NoSymlinkFollow).unwrap();
+    linkat(Some(dirfd), Some(symoldfilename), Some(dirfd), newfilename, LinkatFlags::NoSymlinkFollow).unwrap();
 
     // Assert newfile is actually a symlink to oldfile.
     assert_eq!(
@@ -850,7 +915,7 @@ fn test_linkat_follow_symlink() {
     let dirfd = fcntl::open(tempdir.path(), fcntl::OFlag::empty(), stat::Mode::empty()).unwrap();
 
     // Attempt link target of symlink of file at relative path
-    linkat(Some(dirfd), symoldfilename, Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
+    linkat(Some(dirfd), Some(symoldfilename), Some(dirfd), newfilename, LinkatFlags::SymlinkFollow).unwrap();
 
     let newfilestat = stat::stat(&newfilepath).unwrap();
 
@@ -1011,3 +1076,87 @@ fn test_ttyname_invalid_fd() {
 fn test_ttyname_invalid_fd() {
     assert_eq!(ttyname(-1), Err(Error::Sys(Errno::ENOTTY)));
 }
+
+#[test]
+#[cfg(any(target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+fn test_getpeereid() {
+    use std::os::unix::net::UnixStream;
+
+    let (a, b) = UnixStream::pair().unwrap();
+    let (uid, gid) = getpeereid(a.as_raw_fd()).unwrap();
+    assert_eq!(uid, geteuid());
+    assert_eq!(gid, getegid());
+    assert_eq!(getpeereid(b.as_raw_fd()).unwrap(), (uid, gid));
+
+    // Not a socket
+    let f = tempfile().unwrap();
+    assert_eq!(getpeereid(f.as_raw_fd()).err(), Some(Error::Sys(Errno::ENOTSOCK)));
+}
+
+#[test]
+fn test_dup_cloexec() {
+    let (rd, wr) = pipe().unwrap();
+    let is_cloexec = |fd| {
+        let flags = fcntl(fd, FcntlArg::F_GETFD).unwrap();
+        FdFlag::from_bits_truncate(flags).contains(FdFlag::FD_CLOEXEC)
+    };
+
+    let fd = fcntl(rd, FcntlArg::F_DUPFD_CLOEXEC(100)).unwrap();
+    assert!(fd >= 100);
+    assert!(is_cloexec(fd));
+
+    // Reserve the target descriptor, without close-on-exec, so that dup3
+    // atomically replaces a descriptor this test owns
+    let target = fcntl(wr, FcntlArg::F_DUPFD(100)).unwrap();
+    assert!(!is_cloexec(target));
+    let fd2 = dup3(rd, target, OFlag::O_CLOEXEC).unwrap();
+    assert_eq!(target, fd2);
+    assert!(is_cloexec(fd2));
+    assert_eq!(dup3(rd, rd, OFlag::O_CLOEXEC), Err(Error::Sys(Errno::EINVAL)));
+
+    for fd in &[rd, wr, fd, fd2] {
+        close(*fd).unwrap();
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_syncfs() {
+    let file = tempfile().unwrap();
+    syncfs(file.as_raw_fd()).unwrap();
+    fdatasync(file.as_raw_fd()).unwrap();
+
+    assert_eq!(syncfs(-1), Err(Error::Sys(Errno::EBADF)));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_close_range() {
+    let (rd, wr) = pipe().unwrap();
+    // Use a descriptor well above those other tests have open
+    let fd = fcntl(rd, FcntlArg::F_DUPFD(500)).unwrap();
+
+    match close_range(fd, fd, CloseRangeFlags::CLOSE_RANGE_CLOEXEC) {
+        Ok(()) => {
+            let flags = FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+            assert!(flags.contains(FdFlag::FD_CLOEXEC));
+        },
+        // CLOSE_RANGE_CLOEXEC needs Linux 5.11, close_range itself 5.9
+        Err(Error::Sys(Errno::EINVAL)) | Err(Error::Sys(Errno::ENOSYS)) => (),
+        Err(e) => panic!("close_range failed: {}", e),
+    }
+
+    match close_range(fd, fd, CloseRangeFlags::empty()) {
+        Ok(()) => assert_eq!(fcntl(fd, FcntlArg::F_GETFD), Err(Error::Sys(Errno::EBADF))),
+        Err(Error::Sys(Errno::ENOSYS)) => close(fd).unwrap(),
+        Err(e) => panic!("close_range failed: {}", e),
+    }
+
+    close(rd).unwrap();
+    close(wr).unwrap();
+}